@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::str::FromStr;
+use solana_cli_config::{Config, CONFIG_FILE};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+// Connection settings read from a `config/config.*.yml` file, with command line overrides
+pub struct CliConfig {
+    pub json_rpc_url: String,
+    pub keypair_path: String,
+    pub commitment: CommitmentConfig,
+}
+
+impl CliConfig {
+    pub fn load(config_file: Option<&str>, url: Option<String>, keypair: Option<String>) -> Result<Self, Box<dyn Error>> {
+        let config_file = config_file
+            .or(CONFIG_FILE.as_deref())
+            .ok_or("No config file given and no default Solana CLI config found")?;
+        let config = Config::load(config_file)
+            .map_err(|e| format!("Failed to load config file {}: {}", config_file, e))?;
+
+        Ok(CliConfig {
+            json_rpc_url: url.unwrap_or(config.json_rpc_url),
+            keypair_path: keypair.unwrap_or(config.keypair_path),
+            commitment: CommitmentConfig::from_str(&config.commitment)
+                .map_err(|_| format!("Invalid commitment in config file: {}", config.commitment))?,
+        })
+    }
+
+    pub fn rpc_client(&self) -> RpcClient {
+        RpcClient::new_with_commitment(self.json_rpc_url.clone(), self.commitment)
+    }
+
+    pub fn signer(&self) -> Result<Keypair, Box<dyn Error>> {
+        read_signer(&self.keypair_path)
+    }
+}
+
+pub fn read_signer(keypair_path: &str) -> Result<Keypair, Box<dyn Error>> {
+    read_keypair_file(keypair_path)
+        .map_err(|e| format!("Failed to read keypair {}: {}", keypair_path, e).into())
+}
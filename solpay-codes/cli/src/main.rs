@@ -0,0 +1,666 @@
+mod config;
+mod offline;
+mod output;
+
+use std::error::Error;
+use std::process::exit;
+use candy_machine_affiliates_client::offline::DetachedSignature;
+use candy_machine_affiliates_client::program::{affiliate_index_page, FeeDiscountTier, AFFILIATE_INDEX_PAGE_SIZE};
+use candy_machine_affiliates_client::{
+    affiliate_address,
+    affiliate_profile_address,
+    fee_discount_config_address,
+    fetch_affiliate,
+    fetch_affiliate_profile,
+    fetch_fee_discount_config,
+    fetch_indexed_affiliates,
+    fetch_project,
+    fetch_promo_code,
+    fetch_protocol_stats,
+    fetch_referral_code,
+    instruction,
+    list_affiliates,
+    list_projects,
+    lookup_candy_machine_project,
+    project_address,
+    promo_code_address,
+    protocol_stats_address,
+    referral_code_address,
+};
+use clap::{Args, Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+use crate::config::CliConfig;
+use crate::offline::TransactionArgs;
+use crate::output::{
+    AffiliateProfileView,
+    AffiliateView,
+    FeeDiscountConfigView,
+    OutputFormat,
+    ProjectView,
+    PromoCodeView,
+    ProtocolStatsView,
+    ReferralCodeView,
+};
+
+// Affiliate index pages `close-project` closes at most, keeping the transaction under the size limit
+const MAX_CLOSED_AFFILIATE_INDEX_PAGES: u32 = 16;
+
+#[derive(Parser)]
+#[command(name = "solpay-affiliates", version, about = "Manage solpay.codes candy machine affiliate projects")]
+struct Cli {
+    /// Configuration file, e.g. config/config.dev.yml. Defaults to the Solana CLI config
+    #[arg(short = 'C', long, global = true)]
+    config: Option<String>,
+
+    /// JSON RPC URL overriding the config file
+    #[arg(short = 'u', long, global = true)]
+    url: Option<String>,
+
+    /// Signer keypair overriding the config file
+    #[arg(short = 'k', long, global = true)]
+    keypair: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "human", global = true)]
+    output: OutputFormat,
+
+    #[command(flatten)]
+    transaction: TransactionArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+// Parses a fee discount tier given as MIN_SPAF_BALANCE:DISCOUNT_BASIS_POINTS
+fn parse_fee_discount_tier(tier: &str) -> Result<FeeDiscountTier, String> {
+    let (min_spaf_balance, discount_basis_points) = tier.split_once(':')
+        .ok_or_else(|| format!("Expected MIN_SPAF_BALANCE:DISCOUNT_BASIS_POINTS, got {}", tier))?;
+
+    Ok(FeeDiscountTier {
+        min_spaf_balance: min_spaf_balance.parse().map_err(|e| format!("Invalid SPAF balance {}: {}", min_spaf_balance, e))?,
+        discount_basis_points: discount_basis_points.parse().map_err(|e| format!("Invalid discount {}: {}", discount_basis_points, e))?,
+    })
+}
+
+#[derive(Args)]
+struct ProjectArgs {
+    /// Wallet that registered the project
+    #[arg(long)]
+    owner: Pubkey,
+
+    #[arg(long)]
+    candy_machine: Pubkey,
+}
+
+#[derive(Args)]
+struct ProjectSettingsArgs {
+    #[arg(long)]
+    affiliate_fee_percentage: f64,
+
+    #[arg(long)]
+    affiliate_target_in_sol: u8,
+
+    #[arg(long)]
+    max_affiliate_count: u32,
+
+    #[arg(long)]
+    title: String,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Register a project for a candy machine, owned by the signer
+    RegisterProject {
+        #[arg(long)]
+        candy_machine: Pubkey,
+
+        #[command(flatten)]
+        settings: ProjectSettingsArgs,
+    },
+    /// Update the settings of a project (admin only)
+    UpdateProject {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        #[command(flatten)]
+        settings: ProjectSettingsArgs,
+    },
+    /// Close a project and its affiliate index pages and refund their rent to the owner (admin only)
+    CloseProject {
+        #[command(flatten)]
+        project: ProjectArgs,
+    },
+    /// Link a candy machine to a project registered before candy machine lookups, paid by the signer
+    LinkCandyMachineProject {
+        #[command(flatten)]
+        project: ProjectArgs,
+    },
+    /// Register the signer as an affiliate of a project
+    RegisterAffiliate {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Token account of the signer holding the gating mint, required by gated projects
+        #[arg(long)]
+        gating_token_account: Option<Pubkey>,
+
+        /// Gating mint, required by projects burning the gating tokens
+        #[arg(long, requires = "gating_token_account")]
+        gating_mint: Option<Pubkey>,
+
+        /// SPAF token account of the signer, discounting the registration fee by its balance
+        #[arg(long)]
+        spaf_token_account: Option<Pubkey>,
+    },
+    /// Redeem the reward of the signer's affiliate account
+    RedeemReward {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// SPAF token account of the signer receiving the milestone rewards
+        #[arg(long)]
+        reward_token_account: Option<Pubkey>,
+    },
+    /// Close an affiliate account and move its balance to the treasury (admin only)
+    CloseAffiliate {
+        #[arg(long)]
+        affiliate: Pubkey,
+
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Referral code claimed by the affiliate before codes were recorded on affiliate accounts,
+        /// the recorded code is released along with the account otherwise
+        #[arg(long)]
+        referral_code: Option<String>,
+    },
+    /// Add an affiliate account registered before the affiliate index to it, paid by the signer
+    IndexAffiliate {
+        #[arg(long)]
+        affiliate: Pubkey,
+
+        #[command(flatten)]
+        project: ProjectArgs,
+    },
+    /// Add a project an affiliate registered to before affiliate profiles to the profile of its wallet, paid by the signer
+    AddAffiliateToProfile {
+        #[arg(long)]
+        affiliate: Pubkey,
+
+        #[command(flatten)]
+        project: ProjectArgs,
+    },
+    /// Claim a referral code for the signer's affiliate account
+    ClaimReferralCode {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        #[arg(long)]
+        code: String,
+
+        /// Wallet of the closed affiliate account still holding the code
+        #[arg(long)]
+        previous_affiliate: Option<Pubkey>,
+    },
+    /// Require affiliates of a project owned by the signer to hold or burn a token to register
+    SetProjectGating {
+        #[arg(long)]
+        candy_machine: Pubkey,
+
+        /// Gating mint, leave out to open registration to everyone
+        #[arg(long, requires = "min_amount")]
+        mint: Option<Pubkey>,
+
+        /// Minimum balance in base units of the mint
+        #[arg(long, requires = "mint")]
+        min_amount: Option<u64>,
+
+        /// Burn the minimum balance on registration instead of only checking it
+        #[arg(long, requires = "mint")]
+        burn: bool,
+    },
+    /// List projects, optionally of a single owner
+    ListProjects {
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// Show a project
+    ShowProject {
+        #[command(flatten)]
+        project: ProjectArgs,
+    },
+    /// Show the project registered for a candy machine, whatever its owner
+    FindProject {
+        #[arg(long)]
+        candy_machine: Pubkey,
+    },
+    /// List affiliate accounts, optionally filtered by wallet and project
+    ListAffiliates {
+        #[arg(long)]
+        affiliate: Option<Pubkey>,
+
+        #[arg(long)]
+        owner: Option<Pubkey>,
+
+        #[arg(long)]
+        candy_machine: Option<Pubkey>,
+    },
+    /// List the affiliate accounts of a project from its affiliate index, in registration order
+    ListIndexedAffiliates {
+        #[command(flatten)]
+        project: ProjectArgs,
+    },
+    /// Show an affiliate account
+    ShowAffiliate {
+        #[arg(long)]
+        affiliate: Pubkey,
+
+        #[command(flatten)]
+        project: ProjectArgs,
+    },
+    /// Show the projects an affiliate wallet is registered to and its commission across them
+    ShowAffiliateProfile {
+        #[arg(long)]
+        affiliate: Pubkey,
+    },
+    /// Create a buyer promo code for a project owned by the signer
+    CreatePromoCode {
+        #[arg(long)]
+        candy_machine: Pubkey,
+
+        #[arg(long)]
+        code: String,
+
+        /// Discount in basis points, 100 is 1%
+        #[arg(long)]
+        discount_basis_points: u16,
+
+        /// Affiliate wallet the code is attached to
+        #[arg(long)]
+        affiliate: Option<Pubkey>,
+
+        /// Total number of uses, 0 for unlimited
+        #[arg(long, default_value_t = 0)]
+        max_uses: u32,
+
+        /// Number of uses per buyer wallet, 0 for unlimited
+        #[arg(long, default_value_t = 0)]
+        max_uses_per_wallet: u16,
+
+        /// Unix timestamp the code becomes usable at
+        #[arg(long, default_value_t = 0)]
+        starts_at: i64,
+
+        /// Unix timestamp the code expires at, 0 for never
+        #[arg(long, default_value_t = 0)]
+        ends_at: i64,
+    },
+    /// Show a promo code and its usage
+    ShowPromoCode {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        #[arg(long)]
+        code: String,
+    },
+    /// Show a referral code and the affiliate account holding it
+    ShowReferralCode {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        #[arg(long)]
+        code: String,
+    },
+    /// Create the protocol stats account with starting totals (admin only)
+    InitializeProtocolStats {
+        /// Projects open when the stats account is created
+        #[arg(long, default_value_t = 0)]
+        live_project_count: u32,
+
+        /// Affiliate accounts open when the stats account is created
+        #[arg(long, default_value_t = 0)]
+        live_affiliate_count: u32,
+
+        #[arg(long, default_value_t = 0)]
+        registration_fees_collected_lamports: u64,
+
+        #[arg(long, default_value_t = 0)]
+        total_rewards_redeemed_lamports: u64,
+
+        #[arg(long, default_value_t = 0)]
+        total_referred_volume_lamports: u64,
+    },
+    /// Show the protocol-wide stats
+    ShowStats,
+    /// Replace the SPAF holder discount tiers of the registration fee (admin only)
+    SetFeeDiscountTiers {
+        /// Tier as MIN_SPAF_BALANCE:DISCOUNT_BASIS_POINTS, by increasing balance. Leave out to remove all discounts
+        #[arg(long = "tier", value_parser = parse_fee_discount_tier)]
+        tiers: Vec<FeeDiscountTier>,
+    },
+    /// Show the SPAF holder discount tiers of the registration fee
+    ShowFeeDiscountTiers,
+    /// Sign a transaction exported with --export, without network access
+    Sign {
+        /// File written by --export
+        file: String,
+    },
+    /// Submit a transaction exported with --export once it is signed
+    Submit {
+        /// File written by --export
+        file: String,
+
+        /// Detached signature printed by the sign command, as PUBKEY=SIGNATURE
+        #[arg(long = "signature", required = true)]
+        signatures: Vec<DetachedSignature>,
+    },
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let output = cli.output;
+
+    // Signing happens on an air-gapped host, which may only have the keypair
+    if let Command::Sign { file } = &cli.command {
+        let signer = match &cli.keypair {
+            Some(keypair) => config::read_signer(keypair)?,
+            None => CliConfig::load(cli.config.as_deref(), None, None)?.signer()?,
+        };
+
+        return offline::sign(output, file, &signer);
+    }
+
+    let config = CliConfig::load(cli.config.as_deref(), cli.url, cli.keypair)?;
+    let mut rpc_client = config.rpc_client();
+    let transaction_args = cli.transaction;
+
+    match cli.command {
+        Command::RegisterProject { candy_machine, settings } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::register_project(
+                &signer,
+                &candy_machine,
+                settings.affiliate_fee_percentage,
+                settings.affiliate_target_in_sol,
+                settings.max_affiliate_count,
+                settings.title,
+            );
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::UpdateProject { project, settings } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::update_project(
+                &signer,
+                &project.owner,
+                &project.candy_machine,
+                settings.affiliate_fee_percentage,
+                settings.affiliate_target_in_sol,
+                settings.max_affiliate_count,
+                settings.title,
+            );
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::CloseProject { project } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let project_state = fetch_project(&mut rpc_client, &project_address(&project.owner, &project.candy_machine)).await?;
+            // Pages left out to fit the transaction are cleared when the project is registered again
+            let affiliate_index_page_count = project_state.data.affiliate_index_length
+                .div_ceil(AFFILIATE_INDEX_PAGE_SIZE as u32)
+                .min(MAX_CLOSED_AFFILIATE_INDEX_PAGES);
+            let instruction = instruction::close_project_with_affiliate_index(
+                &signer,
+                &project.owner,
+                &project.candy_machine,
+                affiliate_index_page_count,
+            );
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::LinkCandyMachineProject { project } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::link_candy_machine_project(&signer, &project.owner, &project.candy_machine);
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::RegisterAffiliate { project, gating_token_account, gating_mint, spaf_token_account } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let project_state = fetch_project(&mut rpc_client, &project_address(&project.owner, &project.candy_machine)).await?;
+            let index_page = affiliate_index_page(project_state.data.affiliate_index_length);
+            let instruction = match (gating_token_account, spaf_token_account) {
+                (_, Some(spaf_token_account)) => instruction::register_affiliate_with_spaf_discount(
+                    &signer,
+                    &project.owner,
+                    &project.candy_machine,
+                    index_page,
+                    gating_token_account.as_ref(),
+                    gating_mint.as_ref(),
+                    &spaf_token_account,
+                ),
+                (Some(gating_token_account), None) => instruction::register_affiliate_with_gating_token(
+                    &signer,
+                    &project.owner,
+                    &project.candy_machine,
+                    index_page,
+                    &gating_token_account,
+                    gating_mint.as_ref(),
+                ),
+                (None, None) => instruction::register_affiliate(&signer, &project.owner, &project.candy_machine, index_page),
+            };
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::RedeemReward { project, reward_token_account } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = match reward_token_account {
+                Some(reward_token_account) => instruction::redeem_reward_with_milestone_reward(
+                    &signer,
+                    &project.owner,
+                    &project.candy_machine,
+                    &reward_token_account,
+                )?,
+                None => instruction::redeem_reward(&signer, &project.owner, &project.candy_machine),
+            };
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::CloseAffiliate { affiliate, project, referral_code } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let affiliate_state = fetch_affiliate(&mut rpc_client, &affiliate_address(&affiliate, &project.owner, &project.candy_machine)).await?;
+            let index_page = affiliate_state.data.affiliate_index_position.map(affiliate_index_page);
+            let instruction = match referral_code.or(affiliate_state.data.referral_code) {
+                Some(code) => instruction::close_affiliate_account_with_referral_code(
+                    &signer,
+                    &affiliate,
+                    &project.owner,
+                    &project.candy_machine,
+                    index_page,
+                    &code,
+                )?,
+                None => instruction::close_affiliate_account(&signer, &affiliate, &project.owner, &project.candy_machine, index_page),
+            };
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::IndexAffiliate { affiliate, project } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let project_state = fetch_project(&mut rpc_client, &project_address(&project.owner, &project.candy_machine)).await?;
+            let instruction = instruction::index_affiliate(
+                &signer,
+                &affiliate,
+                &project.owner,
+                &project.candy_machine,
+                affiliate_index_page(project_state.data.affiliate_index_length),
+            );
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::AddAffiliateToProfile { affiliate, project } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::add_affiliate_to_profile(&signer, &affiliate, &project.owner, &project.candy_machine);
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::ClaimReferralCode { project, code, previous_affiliate } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::claim_referral_code(
+                &signer,
+                &project.owner,
+                &project.candy_machine,
+                &code,
+                previous_affiliate.as_ref(),
+            )?;
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::SetProjectGating { candy_machine, mint, min_amount, burn } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::set_project_gating(&signer, &candy_machine, mint, min_amount.unwrap_or(0), burn);
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::ListProjects { owner } => {
+            let projects = list_projects(&rpc_client, owner.as_ref()).await?;
+
+            output::print_projects(
+                output,
+                &projects.iter().map(|(address, state)| ProjectView::new(address, state)).collect::<Vec<_>>(),
+            );
+        }
+        Command::ShowProject { project } => {
+            let address = project_address(&project.owner, &project.candy_machine);
+            let state = fetch_project(&mut rpc_client, &address).await?;
+
+            output::print_project(output, &ProjectView::new(&address, &state));
+        }
+        Command::FindProject { candy_machine } => {
+            let (address, state) = lookup_candy_machine_project(&mut rpc_client, &candy_machine).await?;
+
+            output::print_project(output, &ProjectView::new(&address, &state));
+        }
+        Command::ListAffiliates { affiliate, owner, candy_machine } => {
+            let affiliates = list_affiliates(&rpc_client, affiliate.as_ref(), owner.as_ref(), candy_machine.as_ref()).await?;
+
+            output::print_affiliates(
+                output,
+                &affiliates.iter().map(|(address, state)| AffiliateView::new(address, state, None)).collect::<Vec<_>>(),
+            );
+        }
+        Command::ListIndexedAffiliates { project } => {
+            let mut affiliates = Vec::new();
+
+            for address in fetch_indexed_affiliates(&mut rpc_client, &project.owner, &project.candy_machine).await? {
+                let state = fetch_affiliate(&mut rpc_client, &address).await?;
+
+                affiliates.push(AffiliateView::new(&address, &state, None));
+            }
+
+            output::print_affiliates(output, &affiliates);
+        }
+        Command::ShowAffiliate { affiliate, project } => {
+            let address = affiliate_address(&affiliate, &project.owner, &project.candy_machine);
+            let state = fetch_affiliate(&mut rpc_client, &address).await?;
+            let balance_lamports = rpc_client.get_balance(&address).await?;
+
+            output::print_affiliate(output, &AffiliateView::new(&address, &state, Some(balance_lamports)));
+        }
+        Command::ShowAffiliateProfile { affiliate } => {
+            let address = affiliate_profile_address(&affiliate);
+            let state = fetch_affiliate_profile(&mut rpc_client, &affiliate).await?;
+
+            output::print_affiliate_profile(output, &AffiliateProfileView::new(&address, &state));
+        }
+        Command::CreatePromoCode {
+            candy_machine,
+            code,
+            discount_basis_points,
+            affiliate,
+            max_uses,
+            max_uses_per_wallet,
+            starts_at,
+            ends_at,
+        } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::create_promo_code(
+                &signer,
+                &candy_machine,
+                &code,
+                affiliate.as_ref(),
+                discount_basis_points,
+                max_uses,
+                max_uses_per_wallet,
+                starts_at,
+                ends_at,
+            )?;
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::ShowPromoCode { project, code } => {
+            let address = promo_code_address(&project.owner, &project.candy_machine, &code)?;
+            let state = fetch_promo_code(&mut rpc_client, &address).await?;
+
+            output::print_promo_code(output, &PromoCodeView::new(&address, &state));
+        }
+        Command::ShowReferralCode { project, code } => {
+            let address = referral_code_address(&project.owner, &project.candy_machine, &code)?;
+            let state = fetch_referral_code(&mut rpc_client, &address).await?;
+            let affiliate_is_open = rpc_client.get_account_with_commitment(&state.data.affiliate_account, rpc_client.commitment())
+                .await?
+                .value
+                .is_some();
+
+            output::print_referral_code(output, &ReferralCodeView::new(&address, &state, affiliate_is_open));
+        }
+        Command::InitializeProtocolStats {
+            live_project_count,
+            live_affiliate_count,
+            registration_fees_collected_lamports,
+            total_rewards_redeemed_lamports,
+            total_referred_volume_lamports,
+        } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::initialize_protocol_stats(
+                &signer,
+                live_project_count,
+                live_affiliate_count,
+                registration_fees_collected_lamports,
+                total_rewards_redeemed_lamports,
+                total_referred_volume_lamports,
+            );
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::ShowStats => {
+            let address = protocol_stats_address();
+            let state = fetch_protocol_stats(&mut rpc_client).await?;
+
+            output::print_protocol_stats(output, &ProtocolStatsView::new(&address, &state));
+        }
+        Command::SetFeeDiscountTiers { tiers } => {
+            let signer = transaction_args.signer_pubkey(&config)?;
+            let instruction = instruction::set_fee_discount_tiers(&signer, tiers);
+
+            offline::execute(output, &config, &mut rpc_client, &transaction_args, instruction, &signer).await?;
+        }
+        Command::ShowFeeDiscountTiers => {
+            let address = fee_discount_config_address();
+            let state = fetch_fee_discount_config(&mut rpc_client).await?;
+
+            output::print_fee_discount_config(output, &FeeDiscountConfigView::new(&address, &state));
+        }
+        Command::Submit { file, signatures } => {
+            offline::submit(output, &rpc_client, &file, &signatures).await?;
+        }
+        Command::Sign { .. } => unreachable!(),
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("Error: {}", e);
+        exit(1);
+    }
+}
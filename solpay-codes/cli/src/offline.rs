@@ -0,0 +1,147 @@
+// Durable nonce transactions and the export, sign and submit steps of offline signing
+use std::error::Error;
+use std::fs;
+use candy_machine_affiliates_client::offline::{
+    attach_signatures,
+    decode_transaction,
+    encode_transaction,
+    fetch_nonce_hash,
+    nonce_transaction,
+    required_signers,
+    sign_offline,
+    DetachedSignature,
+};
+use candy_machine_affiliates_client::program::{id, CandyMachineAffiliatesInstruction};
+use clap::Args;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use crate::config::CliConfig;
+use crate::output::{self, ExportView, OutputFormat};
+
+#[derive(Args)]
+pub struct TransactionArgs {
+    /// Durable nonce account used instead of a recent blockhash
+    #[arg(long, global = true, value_name = "ACCOUNT")]
+    nonce: Option<Pubkey>,
+
+    /// Authority of the nonce account. Defaults to the signer
+    #[arg(long, global = true, value_name = "PUBKEY", requires = "nonce")]
+    nonce_authority: Option<Pubkey>,
+
+    /// Public key of the signer when its keypair is not available, e.g. kept offline
+    #[arg(long, global = true, value_name = "PUBKEY", requires = "export")]
+    signer_pubkey: Option<Pubkey>,
+
+    /// Write the unsigned transaction to a file instead of sending it
+    #[arg(long, global = true, value_name = "FILE", requires = "nonce")]
+    export: Option<String>,
+}
+
+impl TransactionArgs {
+    pub fn signer_pubkey(&self, config: &CliConfig) -> Result<Pubkey, Box<dyn Error>> {
+        match self.signer_pubkey {
+            Some(pubkey) => Ok(pubkey),
+            None => Ok(config.signer()?.pubkey()),
+        }
+    }
+}
+
+// Sends the instruction signed by the config keypair or, with --export, writes it unsigned to a file
+pub async fn execute(
+    format: OutputFormat,
+    config: &CliConfig,
+    rpc_client: &mut RpcClient,
+    args: &TransactionArgs,
+    instruction: Instruction,
+    signer: &Pubkey,
+) -> Result<(), Box<dyn Error>> {
+    let (transaction, blockhash) = match args.nonce {
+        Some(nonce_account) => {
+            let nonce_authority = args.nonce_authority.unwrap_or(*signer);
+            let nonce_hash = fetch_nonce_hash(rpc_client, &nonce_account).await?;
+
+            (nonce_transaction(&[instruction], signer, &nonce_account, &nonce_authority, nonce_hash), nonce_hash)
+        }
+        None => (Transaction::new_with_payer(&[instruction], Some(signer)), rpc_client.get_latest_blockhash().await?),
+    };
+
+    if let Some(file) = &args.export {
+        fs::write(file, encode_transaction(&transaction))
+            .map_err(|e| format!("Failed to write {}: {}", file, e))?;
+
+        output::print_export(format, &ExportView {
+            file: file.clone(),
+            required_signers: required_signers(&transaction).iter().map(Pubkey::to_string).collect(),
+        });
+
+        return Ok(());
+    }
+
+    if required_signers(&transaction).len() > 1 {
+        return Err("The nonce authority must sign separately, use --export".into());
+    }
+
+    let mut transaction = transaction;
+    transaction.try_sign(&[&config.signer()?], blockhash)?;
+
+    output::print_signature(format, &rpc_client.send_and_confirm_transaction(&transaction).await?);
+
+    Ok(())
+}
+
+fn read_transaction(file: &str) -> Result<Transaction, Box<dyn Error>> {
+    let encoded = fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+
+    Ok(decode_transaction(&encoded)?)
+}
+
+// Describes the transaction on stderr so it can be reviewed before signing
+fn print_summary(transaction: &Transaction) {
+    let message = &transaction.message;
+
+    eprintln!("Fee payer:     {}", message.account_keys[0]);
+    eprintln!("Blockhash:     {}", message.recent_blockhash);
+    eprintln!("Signers:       {}", required_signers(transaction).iter().map(Pubkey::to_string).collect::<Vec<_>>().join(", "));
+
+    for instruction in &message.instructions {
+        let program_id = message.account_keys[instruction.program_id_index as usize];
+
+        if program_id == id() {
+            match CandyMachineAffiliatesInstruction::unpack(&instruction.data) {
+                Ok(decoded) => eprintln!("Instruction:   {:?}", decoded),
+                Err(_) => eprintln!("Instruction:   invalid data for {}", program_id),
+            }
+        } else {
+            eprintln!("Instruction:   program {}", program_id);
+        }
+    }
+}
+
+// Signs an exported transaction, needing only the file and the keypair
+pub fn sign(format: OutputFormat, file: &str, signer: &Keypair) -> Result<(), Box<dyn Error>> {
+    let transaction = read_transaction(file)?;
+
+    print_summary(&transaction);
+    output::print_detached_signature(format, &sign_offline(&transaction, signer)?);
+
+    Ok(())
+}
+
+// Attaches the detached signatures to an exported transaction and sends it
+pub async fn submit(
+    format: OutputFormat,
+    rpc_client: &RpcClient,
+    file: &str,
+    signatures: &[DetachedSignature],
+) -> Result<(), Box<dyn Error>> {
+    let mut transaction = read_transaction(file)?;
+
+    attach_signatures(&mut transaction, signatures)?;
+
+    output::print_signature(format, &rpc_client.send_and_confirm_transaction(&transaction).await?);
+
+    Ok(())
+}
@@ -0,0 +1,490 @@
+use candy_machine_affiliates_client::offline::DetachedSignature;
+use candy_machine_affiliates_client::program::{
+    AffiliateAccountState,
+    AffiliateProfileAccountState,
+    FeeDiscountConfigAccountState,
+    ProjectAccountState,
+    PromoCodeAccountState,
+    ProtocolStatsAccountState,
+    ReferralCodeAccountState,
+};
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectView {
+    pub address: String,
+    pub project_owner: String,
+    pub candy_machine_id: String,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u32,
+    pub affiliate_count: u32,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub gating_mint: Option<String>,
+    pub gating_min_amount: u64,
+    pub gating_burn: bool,
+    pub total_mint_count: u32,
+    pub total_referred_volume_lamports: u64,
+    pub total_commission_lamports: u64,
+    pub total_redemption_count: u32,
+    pub peak_affiliate_count: u32,
+}
+
+impl ProjectView {
+    pub fn new(address: &Pubkey, state: &ProjectAccountState) -> Self {
+        ProjectView {
+            address: address.to_string(),
+            project_owner: state.data.project_owner_pubkey.to_string(),
+            candy_machine_id: state.data.candy_machine_id.to_string(),
+            affiliate_fee_percentage: state.data.affiliate_fee_percentage,
+            affiliate_target_in_sol: state.data.affiliate_target_in_sol,
+            max_affiliate_count: state.data.max_affiliate_count,
+            affiliate_count: state.data.affiliate_count,
+            title: state.data.title.clone(),
+            created_at: state.data.created_at,
+            updated_at: state.data.updated_at,
+            gating_mint: state.data.gating_mint.map(|mint| mint.to_string()),
+            gating_min_amount: state.data.gating_min_amount,
+            gating_burn: state.data.gating_burn,
+            total_mint_count: state.data.total_mint_count,
+            total_referred_volume_lamports: state.data.total_referred_volume_lamports,
+            total_commission_lamports: state.data.total_commission_lamports,
+            total_redemption_count: state.data.total_redemption_count,
+            peak_affiliate_count: state.data.peak_affiliate_count,
+        }
+    }
+
+    fn print_human(&self) {
+        println!("Project:            {}", self.address);
+        println!("Title:              {}", self.title);
+        println!("Owner:              {}", self.project_owner);
+        println!("Candy machine:      {}", self.candy_machine_id);
+        println!("Affiliate fee:      {}%", self.affiliate_fee_percentage);
+        println!("Affiliate target:   {} SOL", self.affiliate_target_in_sol);
+        println!("Affiliates:         {}/{} (peak {})", self.affiliate_count, self.max_affiliate_count, self.peak_affiliate_count);
+        println!("Referred mints:     {} for {} lamports", self.total_mint_count, self.total_referred_volume_lamports);
+        println!("Commission paid:    {} lamports", self.total_commission_lamports);
+        println!("Redemptions:        {}", self.total_redemption_count);
+        match &self.gating_mint {
+            Some(mint) => println!(
+                "Gating:             {} {} of {}",
+                if self.gating_burn { "burn" } else { "hold" },
+                self.gating_min_amount,
+                mint,
+            ),
+            None => println!("Gating:             none"),
+        }
+        println!("Created at:         {}", self.created_at);
+        println!("Updated at:         {}", self.updated_at);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AffiliateView {
+    pub address: String,
+    pub affiliate: String,
+    pub project_owner: String,
+    pub candy_machine_id: String,
+    pub total_redeemed_amount_in_sol: u32,
+    pub created_at: i64,
+    pub mint_count: u32,
+    pub referred_volume_lamports: u64,
+    pub total_commission_lamports: u64,
+    pub redemption_count: u32,
+    pub last_activity_at: i64,
+    pub pending_milestone_reward: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referral_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance_lamports: Option<u64>,
+}
+
+impl AffiliateView {
+    pub fn new(address: &Pubkey, state: &AffiliateAccountState, balance_lamports: Option<u64>) -> Self {
+        AffiliateView {
+            address: address.to_string(),
+            affiliate: state.data.affiliate_pubkey.to_string(),
+            project_owner: state.data.project_owner_pubkey.to_string(),
+            candy_machine_id: state.data.candy_machine_id.to_string(),
+            total_redeemed_amount_in_sol: state.data.total_redeemed_amount_in_sol,
+            created_at: state.data.created_at,
+            mint_count: state.data.mint_count,
+            referred_volume_lamports: state.data.referred_volume_lamports,
+            total_commission_lamports: state.data.total_commission_lamports,
+            redemption_count: state.data.redemption_count,
+            last_activity_at: state.data.last_activity_at,
+            pending_milestone_reward: state.data.pending_milestone_reward,
+            referral_code: state.data.referral_code.clone(),
+            balance_lamports,
+        }
+    }
+
+    fn print_human(&self) {
+        println!("Affiliate account:  {}", self.address);
+        println!("Affiliate:          {}", self.affiliate);
+        println!("Project owner:      {}", self.project_owner);
+        println!("Candy machine:      {}", self.candy_machine_id);
+        println!("Total redeemed:     {} SOL in {} redemptions", self.total_redeemed_amount_in_sol, self.redemption_count);
+        println!("Referred mints:     {} for {} lamports", self.mint_count, self.referred_volume_lamports);
+        println!("Commission earned:  {} lamports", self.total_commission_lamports);
+        println!("Pending milestones: {} SPAF", self.pending_milestone_reward);
+        if let Some(referral_code) = &self.referral_code {
+            println!("Referral code:      {}", referral_code);
+        }
+        if let Some(balance_lamports) = self.balance_lamports {
+            println!("Balance:            {} lamports", balance_lamports);
+        }
+        println!("Created at:         {}", self.created_at);
+        println!("Last activity at:   {}", self.last_activity_at);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralCodeView {
+    pub address: String,
+    pub code: String,
+    pub affiliate_account: String,
+    pub affiliate: String,
+    pub project_owner: String,
+    pub candy_machine_id: String,
+    pub created_at: i64,
+    // False once the affiliate account is closed, the code can then be claimed again
+    pub affiliate_is_open: bool,
+}
+
+impl ReferralCodeView {
+    pub fn new(address: &Pubkey, state: &ReferralCodeAccountState, affiliate_is_open: bool) -> Self {
+        ReferralCodeView {
+            address: address.to_string(),
+            code: state.data.code.clone(),
+            affiliate_account: state.data.affiliate_account.to_string(),
+            affiliate: state.data.affiliate_pubkey.to_string(),
+            project_owner: state.data.project_owner_pubkey.to_string(),
+            candy_machine_id: state.data.candy_machine_id.to_string(),
+            created_at: state.data.created_at,
+            affiliate_is_open,
+        }
+    }
+
+    fn print_human(&self) {
+        println!("Referral code:      {}", self.address);
+        println!("Code:               {}", self.code);
+        println!("Affiliate account:  {}{}", self.affiliate_account, if self.affiliate_is_open { "" } else { " (closed)" });
+        println!("Affiliate:          {}", self.affiliate);
+        println!("Project owner:      {}", self.project_owner);
+        println!("Candy machine:      {}", self.candy_machine_id);
+        println!("Created at:         {}", self.created_at);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromoCodeView {
+    pub address: String,
+    pub code: String,
+    pub project_owner: String,
+    pub candy_machine_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub affiliate_account: Option<String>,
+    pub discount_basis_points: u16,
+    pub max_uses: u32,
+    pub max_uses_per_wallet: u16,
+    pub use_count: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub created_at: i64,
+}
+
+impl PromoCodeView {
+    pub fn new(address: &Pubkey, state: &PromoCodeAccountState) -> Self {
+        PromoCodeView {
+            address: address.to_string(),
+            code: state.data.code.clone(),
+            project_owner: state.data.project_owner_pubkey.to_string(),
+            candy_machine_id: state.data.candy_machine_id.to_string(),
+            affiliate_account: state.data.affiliate_account.map(|account| account.to_string()),
+            discount_basis_points: state.data.discount_basis_points,
+            max_uses: state.data.max_uses,
+            max_uses_per_wallet: state.data.max_uses_per_wallet,
+            use_count: state.data.use_count,
+            starts_at: state.data.starts_at,
+            ends_at: state.data.ends_at,
+            created_at: state.data.created_at,
+        }
+    }
+
+    fn print_human(&self) {
+        println!("Promo code:         {}", self.address);
+        println!("Code:               {}", self.code);
+        println!("Project owner:      {}", self.project_owner);
+        println!("Candy machine:      {}", self.candy_machine_id);
+        if let Some(affiliate_account) = &self.affiliate_account {
+            println!("Affiliate account:  {}", affiliate_account);
+        }
+        println!("Discount:           {} bps", self.discount_basis_points);
+        println!("Uses:               {}/{}", self.use_count, limit(self.max_uses as u64));
+        println!("Uses per wallet:    {}", limit(self.max_uses_per_wallet as u64));
+        println!("Starts at:          {}", self.starts_at);
+        println!("Ends at:            {}", if self.ends_at == 0 { "never".to_string() } else { self.ends_at.to_string() });
+        println!("Created at:         {}", self.created_at);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolStatsView {
+    pub address: String,
+    pub live_project_count: u32,
+    pub live_affiliate_count: u32,
+    pub registration_fees_collected_lamports: u64,
+    pub total_rewards_redeemed_lamports: u64,
+    pub total_referred_volume_lamports: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ProtocolStatsView {
+    pub fn new(address: &Pubkey, state: &ProtocolStatsAccountState) -> Self {
+        ProtocolStatsView {
+            address: address.to_string(),
+            live_project_count: state.data.live_project_count,
+            live_affiliate_count: state.data.live_affiliate_count,
+            registration_fees_collected_lamports: state.data.registration_fees_collected_lamports,
+            total_rewards_redeemed_lamports: state.data.total_rewards_redeemed_lamports,
+            total_referred_volume_lamports: state.data.total_referred_volume_lamports,
+            created_at: state.data.created_at,
+            updated_at: state.data.updated_at,
+        }
+    }
+
+    fn print_human(&self) {
+        println!("Protocol stats:     {}", self.address);
+        println!("Live projects:      {}", self.live_project_count);
+        println!("Live affiliates:    {}", self.live_affiliate_count);
+        println!("Registration fees:  {} lamports", self.registration_fees_collected_lamports);
+        println!("Rewards redeemed:   {} lamports", self.total_rewards_redeemed_lamports);
+        println!("Referred volume:    {} lamports", self.total_referred_volume_lamports);
+        println!("Created at:         {}", self.created_at);
+        println!("Updated at:         {}", self.updated_at);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeDiscountTierView {
+    pub min_spaf_balance: u64,
+    pub discount_basis_points: u16,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeDiscountConfigView {
+    pub address: String,
+    pub tiers: Vec<FeeDiscountTierView>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl FeeDiscountConfigView {
+    pub fn new(address: &Pubkey, state: &FeeDiscountConfigAccountState) -> Self {
+        FeeDiscountConfigView {
+            address: address.to_string(),
+            tiers: state.data.tiers.iter().map(|tier| FeeDiscountTierView {
+                min_spaf_balance: tier.min_spaf_balance,
+                discount_basis_points: tier.discount_basis_points,
+            }).collect(),
+            created_at: state.data.created_at,
+            updated_at: state.data.updated_at,
+        }
+    }
+
+    fn print_human(&self) {
+        println!("Fee discount config: {}", self.address);
+
+        for tier in &self.tiers {
+            println!("  From {} SPAF base units: {} bps off", tier.min_spaf_balance, tier.discount_basis_points);
+        }
+
+        println!("Created at:          {}", self.created_at);
+        println!("Updated at:          {}", self.updated_at);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AffiliateProfileView {
+    pub address: String,
+    pub affiliate: String,
+    pub total_commission_lamports: u64,
+    pub projects: Vec<String>,
+}
+
+impl AffiliateProfileView {
+    pub fn new(address: &Pubkey, state: &AffiliateProfileAccountState) -> Self {
+        AffiliateProfileView {
+            address: address.to_string(),
+            affiliate: state.data.affiliate_pubkey.to_string(),
+            total_commission_lamports: state.data.total_commission_lamports,
+            projects: state.data.project_accounts.iter().map(|project| project.to_string()).collect(),
+        }
+    }
+
+    fn print_human(&self) {
+        println!("Affiliate profile:  {}", self.address);
+        println!("Affiliate:          {}", self.affiliate);
+        println!("Total commission:   {} lamports", self.total_commission_lamports);
+        println!("Projects:");
+        for project in &self.projects {
+            println!("  {}", project);
+        }
+    }
+}
+
+fn limit(max: u64) -> String {
+    if max == 0 { "unlimited".to_string() } else { max.to_string() }
+}
+
+#[derive(Serialize)]
+pub struct SignatureView {
+    pub signature: String,
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    println!("{}", serde_json::to_string_pretty(value).expect("JSON serialization failed"));
+}
+
+pub fn print_signature(format: OutputFormat, signature: &Signature) {
+    match format {
+        OutputFormat::Human => println!("Signature: {}", signature),
+        OutputFormat::Json => print_json(&SignatureView { signature: signature.to_string() }),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportView {
+    pub file: String,
+    pub required_signers: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct DetachedSignatureView {
+    pub pubkey: String,
+    pub signature: String,
+}
+
+pub fn print_export(format: OutputFormat, export: &ExportView) {
+    match format {
+        OutputFormat::Human => {
+            println!("Unsigned transaction written to {}", export.file);
+            println!("Required signers:");
+            for signer in &export.required_signers {
+                println!("  {}", signer);
+            }
+        }
+        OutputFormat::Json => print_json(export),
+    }
+}
+
+pub fn print_detached_signature(format: OutputFormat, signature: &DetachedSignature) {
+    match format {
+        OutputFormat::Human => println!("{}", signature),
+        OutputFormat::Json => print_json(&DetachedSignatureView {
+            pubkey: signature.pubkey.to_string(),
+            signature: signature.signature.to_string(),
+        }),
+    }
+}
+
+pub fn print_project(format: OutputFormat, project: &ProjectView) {
+    match format {
+        OutputFormat::Human => project.print_human(),
+        OutputFormat::Json => print_json(project),
+    }
+}
+
+pub fn print_projects(format: OutputFormat, projects: &[ProjectView]) {
+    match format {
+        OutputFormat::Human => {
+            for (i, project) in projects.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                project.print_human();
+            }
+            println!("{} project(s)", projects.len());
+        }
+        OutputFormat::Json => print_json(&projects),
+    }
+}
+
+pub fn print_affiliate(format: OutputFormat, affiliate: &AffiliateView) {
+    match format {
+        OutputFormat::Human => affiliate.print_human(),
+        OutputFormat::Json => print_json(affiliate),
+    }
+}
+
+pub fn print_affiliates(format: OutputFormat, affiliates: &[AffiliateView]) {
+    match format {
+        OutputFormat::Human => {
+            for (i, affiliate) in affiliates.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                affiliate.print_human();
+            }
+            println!("{} affiliate account(s)", affiliates.len());
+        }
+        OutputFormat::Json => print_json(&affiliates),
+    }
+}
+
+pub fn print_referral_code(format: OutputFormat, referral_code: &ReferralCodeView) {
+    match format {
+        OutputFormat::Human => referral_code.print_human(),
+        OutputFormat::Json => print_json(referral_code),
+    }
+}
+
+pub fn print_promo_code(format: OutputFormat, promo_code: &PromoCodeView) {
+    match format {
+        OutputFormat::Human => promo_code.print_human(),
+        OutputFormat::Json => print_json(promo_code),
+    }
+}
+
+pub fn print_protocol_stats(format: OutputFormat, protocol_stats: &ProtocolStatsView) {
+    match format {
+        OutputFormat::Human => protocol_stats.print_human(),
+        OutputFormat::Json => print_json(protocol_stats),
+    }
+}
+
+pub fn print_fee_discount_config(format: OutputFormat, fee_discount_config: &FeeDiscountConfigView) {
+    match format {
+        OutputFormat::Human => fee_discount_config.print_human(),
+        OutputFormat::Json => print_json(fee_discount_config),
+    }
+}
+
+pub fn print_affiliate_profile(format: OutputFormat, affiliate_profile: &AffiliateProfileView) {
+    match format {
+        OutputFormat::Human => affiliate_profile.print_human(),
+        OutputFormat::Json => print_json(affiliate_profile),
+    }
+}
@@ -0,0 +1,158 @@
+use std::process::{Command, Output};
+use std::str::FromStr;
+use candy_machine_affiliates_client::instruction::register_affiliate;
+use candy_machine_affiliates_client::offline::{attach_signatures, decode_transaction, encode_transaction, nonce_transaction, DetachedSignature};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{write_keypair_file, Keypair, Signer};
+
+fn solpay_affiliates(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_solpay-affiliates"))
+        .args(args)
+        .output()
+        .expect("Failed to run solpay-affiliates")
+}
+
+fn config_file(name: &str) -> String {
+    format!("{}/../config/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn help_lists_every_command() {
+    let output = solpay_affiliates(&["--help"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+
+    for command in [
+        "register-project",
+        "update-project",
+        "close-project",
+        "link-candy-machine-project",
+        "register-affiliate",
+        "redeem-reward",
+        "close-affiliate",
+        "claim-referral-code",
+        "set-project-gating",
+        "create-promo-code",
+        "list-projects",
+        "show-project",
+        "find-project",
+        "list-affiliates",
+        "show-affiliate",
+        "show-referral-code",
+        "show-promo-code",
+        "sign",
+        "submit",
+    ] {
+        assert!(stdout.contains(command), "{} missing from help", command);
+    }
+}
+
+#[test]
+fn invalid_pubkey_is_rejected() {
+    let output = solpay_affiliates(&["show-project", "--owner", "not-a-pubkey", "--candy-machine", "11111111111111111111111111111111"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("--owner"));
+}
+
+#[test]
+fn missing_config_file_is_reported() {
+    let output = solpay_affiliates(&["-C", &config_file("config.missing.yml"), "list-projects"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("Error: Failed to load config file"));
+}
+
+#[test]
+fn repo_config_files_are_loaded() {
+    for name in ["config.local.yml", "config.dev.yml", "config.main.yml"] {
+        // The RPC URL is overridden with a closed port so the request fails after the config is loaded
+        let output = solpay_affiliates(&["-C", &config_file(name), "-u", "http://127.0.0.1:1", "list-projects"]);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr.contains("127.0.0.1:1"), "{}: {}", name, stderr);
+    }
+}
+
+#[test]
+fn signer_keypair_is_required_for_transactions() {
+    let output = solpay_affiliates(&[
+        "-C", &config_file("config.local.yml"),
+        "-k", &config_file("missing-keypair.json"),
+        "close-project",
+        "--owner", "11111111111111111111111111111111",
+        "--candy-machine", "11111111111111111111111111111111",
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("Error: Failed to read keypair"));
+}
+
+// Writes a nonce transaction registering `affiliate` as exported by --export
+fn export_transaction(name: &str, affiliate: &Pubkey) -> String {
+    let instruction = register_affiliate(affiliate, &Pubkey::new_unique(), &Pubkey::new_unique(), 0);
+    let transaction = nonce_transaction(&[instruction], affiliate, &Pubkey::new_unique(), affiliate, Hash::new_unique());
+    let file = format!("{}/{}.tx", env!("CARGO_TARGET_TMPDIR"), name);
+
+    std::fs::write(&file, encode_transaction(&transaction)).unwrap();
+
+    file
+}
+
+fn write_keypair(name: &str, keypair: &Keypair) -> String {
+    let file = format!("{}/{}.json", env!("CARGO_TARGET_TMPDIR"), name);
+
+    write_keypair_file(keypair, &file).unwrap();
+
+    file
+}
+
+#[test]
+fn exported_transaction_is_signed_offline() {
+    let affiliate = Keypair::new();
+    let transaction_file = export_transaction("sign-offline", &affiliate.pubkey());
+
+    // No config file is read, so signing works on a host with only the keypair
+    let output = solpay_affiliates(&[
+        "-C", &config_file("config.missing.yml"),
+        "-k", &write_keypair("sign-offline", &affiliate),
+        "sign", &transaction_file,
+    ]);
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("RegisterAffiliate"));
+
+    let signature = DetachedSignature::from_str(String::from_utf8(output.stdout).unwrap().trim()).unwrap();
+    let mut transaction = decode_transaction(&std::fs::read_to_string(&transaction_file).unwrap()).unwrap();
+
+    assert_eq!(signature.pubkey, affiliate.pubkey());
+    attach_signatures(&mut transaction, &[signature]).unwrap();
+    transaction.verify().unwrap();
+}
+
+#[test]
+fn sign_rejects_keypair_not_signing_the_transaction() {
+    let transaction_file = export_transaction("sign-unexpected", &Pubkey::new_unique());
+    let other = Keypair::new();
+
+    let output = solpay_affiliates(&["-k", &write_keypair("sign-unexpected", &other), "sign", &transaction_file]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains(&format!("{} is not a signer of the transaction", other.pubkey())));
+}
+
+#[test]
+fn export_requires_nonce() {
+    let output = solpay_affiliates(&[
+        "--export", "transaction.tx",
+        "close-project",
+        "--owner", "11111111111111111111111111111111",
+        "--candy-machine", "11111111111111111111111111111111",
+    ]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("--nonce"));
+}
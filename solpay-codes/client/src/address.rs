@@ -0,0 +1,144 @@
+use std::str::FromStr;
+use candy_machine_affiliates::{
+    normalize_referral_code,
+    AffiliateAccountState,
+    AffiliateIndexPageAccountState,
+    AffiliateProfileAccountState,
+    CandyMachineProjectAccountState,
+    FeeDiscountConfigAccountState,
+    ProjectAccountState,
+    PromoCodeAccountState,
+    ProtocolStatsAccountState,
+    PromoCodeUsageAccountState,
+    ReferralCodeAccountState,
+    SPAF_MINT_AUTHORITY_SEED,
+};
+use solana_sdk::pubkey::Pubkey;
+use crate::error::ClientError;
+
+// Derives the project PDA of a candy machine
+pub fn project_address(project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+// Derives the affiliate PDA of a wallet in a project
+pub fn affiliate_address(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+            affiliate.as_ref(),
+            project_owner.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+// Derives the PDA of a page of the affiliate index of a project
+pub fn affiliate_index_page_address(project_owner: &Pubkey, candy_machine_id: &Pubkey, page: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            AffiliateIndexPageAccountState::DISCRIMINATOR.as_ref(),
+            project_address(project_owner, candy_machine_id).as_ref(),
+            &page.to_le_bytes(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+// Derives the affiliate profile PDA of a wallet, listing its affiliations across projects
+pub fn affiliate_profile_address(affiliate: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            AffiliateProfileAccountState::DISCRIMINATOR.as_ref(),
+            affiliate.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+// Derives the candy machine project PDA pointing to the project registered for a candy machine
+pub fn candy_machine_project_address(candy_machine_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            CandyMachineProjectAccountState::DISCRIMINATOR.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+// Derives the referral code PDA of a project, normalizing the code as the program does
+pub fn referral_code_address(project_owner: &Pubkey, candy_machine_id: &Pubkey, code: &str) -> Result<Pubkey, ClientError> {
+    let code = normalize_referral_code(code).map_err(|_| ClientError::InvalidReferralCode(code.to_string()))?;
+
+    Ok(Pubkey::find_program_address(
+        &[
+            ReferralCodeAccountState::DISCRIMINATOR.as_ref(),
+            project_address(project_owner, candy_machine_id).as_ref(),
+            code.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0)
+}
+
+// Derives the promo code PDA of a project, normalizing the code as the program does
+pub fn promo_code_address(project_owner: &Pubkey, candy_machine_id: &Pubkey, code: &str) -> Result<Pubkey, ClientError> {
+    let code = normalize_referral_code(code).map_err(|_| ClientError::InvalidPromoCode(code.to_string()))?;
+
+    Ok(Pubkey::find_program_address(
+        &[
+            PromoCodeAccountState::DISCRIMINATOR.as_ref(),
+            project_address(project_owner, candy_machine_id).as_ref(),
+            code.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0)
+}
+
+// Derives the PDA counting the uses of a promo code by a buyer
+pub fn promo_code_usage_address(promo_code: &Pubkey, buyer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PromoCodeUsageAccountState::DISCRIMINATOR.as_ref(),
+            promo_code.as_ref(),
+            buyer.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+// Derives the protocol stats PDA
+pub fn protocol_stats_address() -> Pubkey {
+    Pubkey::find_program_address(&[ProtocolStatsAccountState::DISCRIMINATOR.as_ref()], &candy_machine_affiliates::id()).0
+}
+
+// Derives the fee discount config PDA holding the SPAF holder discount tiers
+pub fn fee_discount_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[FeeDiscountConfigAccountState::DISCRIMINATOR.as_ref()], &candy_machine_affiliates::id()).0
+}
+
+// Derives the PDA minting the SPAF milestone rewards
+pub fn spaf_mint_authority_address() -> Pubkey {
+    Pubkey::find_program_address(&[SPAF_MINT_AUTHORITY_SEED.as_ref()], &candy_machine_affiliates::id()).0
+}
+
+// SPAF mint of the cluster, None when milestone rewards are disabled
+pub fn spaf_mint_address() -> Option<Pubkey> {
+    candy_machine_affiliates::SPAF_MINT_PUBKEY.map(|mint| Pubkey::from_str(mint).expect("Pubkey conversion failed"))
+}
+
+pub fn treasury_address() -> Pubkey {
+    Pubkey::from_str(candy_machine_affiliates::SOLPAY_TREASURY_PUBKEY).expect("Pubkey conversion failed")
+}
+
+pub fn admin_address() -> Pubkey {
+    Pubkey::from_str(candy_machine_affiliates::ADMIN_PUBKEY).expect("Pubkey conversion failed")
+}
@@ -0,0 +1,45 @@
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("Account {0} is not owned by the program")]
+    IllegalOwner(Pubkey),
+    #[error("Account {0} does not hold {1} data")]
+    InvalidAccountData(Pubkey, &'static str),
+    #[error("Account {0} is not initialized")]
+    UninitializedAccount(Pubkey),
+    #[error("Invalid referral code {0:?}")]
+    InvalidReferralCode(String),
+    #[error("Referral code {0} belongs to a closed affiliate account")]
+    ReferralCodeReleased(String),
+    #[error("Invalid promo code {0:?}")]
+    InvalidPromoCode(String),
+    #[error("Milestone rewards are not enabled on this cluster")]
+    MilestoneRewardsDisabled,
+    #[error("Account {0} is not a valid nonce account: {1}")]
+    InvalidNonceAccount(Pubkey, String),
+    #[error("Invalid transaction encoding")]
+    InvalidTransaction,
+    #[error("Invalid signature {0}")]
+    InvalidSignature(String),
+    #[error("{0} is not a signer of the transaction")]
+    UnexpectedSigner(Pubkey),
+    #[error("Missing signature of {0}")]
+    MissingSignature(Pubkey),
+    #[error("Simulation failed: {0}")]
+    SimulationFailed(TransactionError),
+    #[error("Invalid view return data")]
+    InvalidReturnData,
+    #[error("Transport error: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl From<solana_rpc_client_api::client_error::Error> for ClientError {
+    fn from(e: solana_rpc_client_api::client_error::Error) -> Self {
+        ClientError::Transport(Box::new(e))
+    }
+}
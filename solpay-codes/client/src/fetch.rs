@@ -0,0 +1,347 @@
+use std::future::Future;
+use candy_machine_affiliates::{
+    affiliate_index_page,
+    AffiliateAccountState,
+    AffiliateIndexPageAccountState,
+    AffiliateProfileAccountState,
+    CandyMachineProjectAccountState,
+    FeeDiscountConfigAccountState,
+    ProjectAccountState,
+    PromoCodeAccountState,
+    PromoCodeUsageAccountState,
+    ProtocolStatsAccountState,
+    ReferralCodeAccountState,
+};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::borsh1::try_from_slice_unchecked;
+use solana_sdk::pubkey::Pubkey;
+use crate::address::{
+    affiliate_index_page_address,
+    affiliate_profile_address,
+    candy_machine_project_address,
+    fee_discount_config_address,
+    project_address,
+    promo_code_usage_address,
+    protocol_stats_address,
+    referral_code_address,
+};
+use crate::error::ClientError;
+
+// Source of on-chain accounts, implemented for the nonblocking `RpcClient`.
+// Implement it for other clients (e.g. `BanksClient` in tests) to reuse the fetch helpers.
+pub trait AccountFetcher {
+    fn get_account(&mut self, address: &Pubkey) -> impl Future<Output = Result<Option<Account>, ClientError>>;
+}
+
+impl AccountFetcher for RpcClient {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError> {
+        Ok(self.get_account_with_commitment(address, self.commitment()).await?.value)
+    }
+}
+
+pub fn decode_project(address: &Pubkey, account: &Account) -> Result<ProjectAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = ProjectAccountState::unpack_account_data(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, ProjectAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != ProjectAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, ProjectAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_affiliate(address: &Pubkey, account: &Account) -> Result<AffiliateAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = AffiliateAccountState::unpack_account_data(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, AffiliateAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != AffiliateAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, AffiliateAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_referral_code(address: &Pubkey, account: &Account) -> Result<ReferralCodeAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<ReferralCodeAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, ReferralCodeAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != ReferralCodeAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, ReferralCodeAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_promo_code(address: &Pubkey, account: &Account) -> Result<PromoCodeAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<PromoCodeAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, PromoCodeAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != PromoCodeAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, PromoCodeAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_promo_code_usage(address: &Pubkey, account: &Account) -> Result<PromoCodeUsageAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<PromoCodeUsageAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, PromoCodeUsageAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != PromoCodeUsageAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, PromoCodeUsageAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_protocol_stats(address: &Pubkey, account: &Account) -> Result<ProtocolStatsAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<ProtocolStatsAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, ProtocolStatsAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != ProtocolStatsAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, ProtocolStatsAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_affiliate_index_page(address: &Pubkey, account: &Account) -> Result<AffiliateIndexPageAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<AffiliateIndexPageAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, AffiliateIndexPageAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != AffiliateIndexPageAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, AffiliateIndexPageAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_affiliate_profile(address: &Pubkey, account: &Account) -> Result<AffiliateProfileAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<AffiliateProfileAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, AffiliateProfileAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != AffiliateProfileAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, AffiliateProfileAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub fn decode_candy_machine_project(address: &Pubkey, account: &Account) -> Result<CandyMachineProjectAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<CandyMachineProjectAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, CandyMachineProjectAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != CandyMachineProjectAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, CandyMachineProjectAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub async fn fetch_project<F: AccountFetcher>(client: &mut F, address: &Pubkey) -> Result<ProjectAccountState, ClientError> {
+    let account = client.get_account(address).await?.ok_or(ClientError::AccountNotFound(*address))?;
+
+    decode_project(address, &account)
+}
+
+pub async fn fetch_affiliate<F: AccountFetcher>(client: &mut F, address: &Pubkey) -> Result<AffiliateAccountState, ClientError> {
+    let account = client.get_account(address).await?.ok_or(ClientError::AccountNotFound(*address))?;
+
+    decode_affiliate(address, &account)
+}
+
+pub async fn fetch_referral_code<F: AccountFetcher>(client: &mut F, address: &Pubkey) -> Result<ReferralCodeAccountState, ClientError> {
+    let account = client.get_account(address).await?.ok_or(ClientError::AccountNotFound(*address))?;
+
+    decode_referral_code(address, &account)
+}
+
+// Resolves a referral code of a project to the affiliate account holding it.
+// Fails with `ReferralCodeReleased` when that affiliate account has been closed since.
+pub async fn lookup_referral_code<F: AccountFetcher>(
+    client: &mut F,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+) -> Result<(Pubkey, AffiliateAccountState), ClientError> {
+    let address = referral_code_address(project_owner, candy_machine_id, code)?;
+    let referral_code = fetch_referral_code(client, &address).await?;
+    let affiliate_address = referral_code.data.affiliate_account;
+
+    match fetch_affiliate(client, &affiliate_address).await {
+        Ok(affiliate) => Ok((affiliate_address, affiliate)),
+        Err(ClientError::AccountNotFound(_)) => Err(ClientError::ReferralCodeReleased(referral_code.data.code)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn fetch_promo_code<F: AccountFetcher>(client: &mut F, address: &Pubkey) -> Result<PromoCodeAccountState, ClientError> {
+    let account = client.get_account(address).await?.ok_or(ClientError::AccountNotFound(*address))?;
+
+    decode_promo_code(address, &account)
+}
+
+// Uses of a promo code by a buyer, 0 when the buyer never used it
+pub async fn fetch_promo_code_wallet_use_count<F: AccountFetcher>(client: &mut F, promo_code: &Pubkey, buyer: &Pubkey) -> Result<u16, ClientError> {
+    let address = promo_code_usage_address(promo_code, buyer);
+
+    match client.get_account(&address).await? {
+        Some(account) => Ok(decode_promo_code_usage(&address, &account)?.data.use_count),
+        None => Ok(0),
+    }
+}
+
+pub async fn fetch_protocol_stats<F: AccountFetcher>(client: &mut F) -> Result<ProtocolStatsAccountState, ClientError> {
+    let address = protocol_stats_address();
+    let account = client.get_account(&address).await?.ok_or(ClientError::AccountNotFound(address))?;
+
+    decode_protocol_stats(&address, &account)
+}
+
+pub fn decode_fee_discount_config(address: &Pubkey, account: &Account) -> Result<FeeDiscountConfigAccountState, ClientError> {
+    if !candy_machine_affiliates::check_id(&account.owner) {
+        return Err(ClientError::IllegalOwner(*address));
+    }
+
+    let state = try_from_slice_unchecked::<FeeDiscountConfigAccountState>(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*address, FeeDiscountConfigAccountState::DISCRIMINATOR))?;
+
+    if state.discriminator != FeeDiscountConfigAccountState::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccountData(*address, FeeDiscountConfigAccountState::DISCRIMINATOR));
+    }
+
+    if !state.is_initialized {
+        return Err(ClientError::UninitializedAccount(*address));
+    }
+
+    Ok(state)
+}
+
+pub async fn fetch_fee_discount_config<F: AccountFetcher>(client: &mut F) -> Result<FeeDiscountConfigAccountState, ClientError> {
+    let address = fee_discount_config_address();
+    let account = client.get_account(&address).await?.ok_or(ClientError::AccountNotFound(address))?;
+
+    decode_fee_discount_config(&address, &account)
+}
+
+pub async fn fetch_affiliate_index_page<F: AccountFetcher>(client: &mut F, address: &Pubkey) -> Result<AffiliateIndexPageAccountState, ClientError> {
+    let account = client.get_account(address).await?.ok_or(ClientError::AccountNotFound(*address))?;
+
+    decode_affiliate_index_page(address, &account)
+}
+
+// Affiliate accounts of a project in registration order, read from its affiliate index.
+// Affiliates registered before the index are only listed once indexed with `index_affiliate`.
+pub async fn fetch_indexed_affiliates<F: AccountFetcher>(
+    client: &mut F,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> Result<Vec<Pubkey>, ClientError> {
+    let project = fetch_project(client, &project_address(project_owner, candy_machine_id)).await?;
+    let mut affiliates = Vec::new();
+
+    if project.data.affiliate_index_length == 0 {
+        return Ok(affiliates);
+    }
+
+    for page in 0..=affiliate_index_page(project.data.affiliate_index_length - 1) {
+        let address = affiliate_index_page_address(project_owner, candy_machine_id, page);
+        let state = fetch_affiliate_index_page(client, &address).await?;
+
+        affiliates.extend(state.live_affiliate_accounts());
+    }
+
+    Ok(affiliates)
+}
+
+// Affiliate profile of a wallet, listing the project PDAs it is an affiliate of.
+// Affiliations registered before profiles are only listed once added with `add_affiliate_to_profile`.
+pub async fn fetch_affiliate_profile<F: AccountFetcher>(client: &mut F, affiliate: &Pubkey) -> Result<AffiliateProfileAccountState, ClientError> {
+    let address = affiliate_profile_address(affiliate);
+    let account = client.get_account(&address).await?.ok_or(ClientError::AccountNotFound(address))?;
+
+    decode_affiliate_profile(&address, &account)
+}
+
+// Resolves a candy machine to the project registered for it, without knowing its owner.
+// Projects registered before those lookups are only found once linked with `link_candy_machine_project`.
+pub async fn lookup_candy_machine_project<F: AccountFetcher>(client: &mut F, candy_machine_id: &Pubkey) -> Result<(Pubkey, ProjectAccountState), ClientError> {
+    let address = candy_machine_project_address(candy_machine_id);
+    let account = client.get_account(&address).await?.ok_or(ClientError::AccountNotFound(address))?;
+    let project_address = decode_candy_machine_project(&address, &account)?.data.project_account;
+
+    Ok((project_address, fetch_project(client, &project_address).await?))
+}
@@ -0,0 +1,499 @@
+// Instruction builders deriving the program addresses from the wallets and candy machine
+use candy_machine_affiliates::instruction;
+use candy_machine_affiliates::FeeDiscountTier;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::address::{
+    affiliate_address,
+    affiliate_index_page_address,
+    affiliate_profile_address,
+    candy_machine_project_address,
+    fee_discount_config_address,
+    project_address,
+    promo_code_address,
+    protocol_stats_address,
+    promo_code_usage_address,
+    referral_code_address,
+    spaf_mint_address,
+    spaf_mint_authority_address,
+    treasury_address,
+};
+use crate::error::ClientError;
+
+// Replaces the protocol stats PDA of an instruction by the program id so it skips the stats update
+// and does not take the write lock of the stats account shared by every project
+pub fn without_protocol_stats(mut instruction: Instruction) -> Instruction {
+    let stats = protocol_stats_address();
+    for account in instruction.accounts.iter_mut().filter(|account| account.pubkey == stats) {
+        *account = AccountMeta::new_readonly(candy_machine_affiliates::id(), false);
+    }
+    instruction
+}
+
+// `live_project_count` and the other starting values backfill the totals of accounts created
+// before the stats account
+pub fn initialize_protocol_stats(
+    admin: &Pubkey,
+    live_project_count: u32,
+    live_affiliate_count: u32,
+    registration_fees_collected_lamports: u64,
+    total_rewards_redeemed_lamports: u64,
+    total_referred_volume_lamports: u64,
+) -> Instruction {
+    instruction::initialize_protocol_stats(
+        &candy_machine_affiliates::id(),
+        admin,
+        &protocol_stats_address(),
+        live_project_count,
+        live_affiliate_count,
+        registration_fees_collected_lamports,
+        total_rewards_redeemed_lamports,
+        total_referred_volume_lamports,
+    )
+}
+
+// Replaces the SPAF holder discount tiers, by increasing balance
+pub fn set_fee_discount_tiers(admin: &Pubkey, tiers: Vec<FeeDiscountTier>) -> Instruction {
+    instruction::set_fee_discount_tiers(&candy_machine_affiliates::id(), admin, &fee_discount_config_address(), tiers)
+}
+
+pub fn register_project(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+) -> Instruction {
+    instruction::register_project_v2(
+        &candy_machine_affiliates::id(),
+        project_owner,
+        &project_address(project_owner, candy_machine_id),
+        &protocol_stats_address(),
+        &candy_machine_project_address(candy_machine_id),
+        *candy_machine_id,
+        affiliate_fee_percentage,
+        affiliate_target_in_sol,
+        max_affiliate_count,
+        title,
+    )
+}
+
+pub fn update_project(
+    admin: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+) -> Instruction {
+    instruction::update_project_v2(
+        &candy_machine_affiliates::id(),
+        admin,
+        &project_address(project_owner, candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+        affiliate_fee_percentage,
+        affiliate_target_in_sol,
+        max_affiliate_count,
+        title,
+    )
+}
+
+pub fn close_project(admin: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::close_project(
+        &candy_machine_affiliates::id(),
+        admin,
+        &project_address(project_owner, candy_machine_id),
+        &protocol_stats_address(),
+        &candy_machine_project_address(candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Same as `close_project`, also closing the first `affiliate_index_page_count` affiliate index pages
+pub fn close_project_with_affiliate_index(
+    admin: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page_count: u32,
+) -> Instruction {
+    let affiliate_index_pages: Vec<Pubkey> = (0..affiliate_index_page_count)
+        .map(|page| affiliate_index_page_address(project_owner, candy_machine_id, page))
+        .collect();
+
+    instruction::close_project_with_affiliate_index(
+        &candy_machine_affiliates::id(),
+        admin,
+        &project_address(project_owner, candy_machine_id),
+        &protocol_stats_address(),
+        &candy_machine_project_address(candy_machine_id),
+        &affiliate_index_pages,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Points the candy machine project PDA of a candy machine to a project registered before those lookups
+pub fn link_candy_machine_project(payer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::link_candy_machine_project(
+        &candy_machine_affiliates::id(),
+        payer,
+        &project_address(project_owner, candy_machine_id),
+        &candy_machine_project_address(candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// `affiliate_index_page` is the page the affiliate is appended to, the `affiliate_index_page` of the
+// `affiliate_index_length` of the project
+pub fn register_affiliate(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey, affiliate_index_page: u32) -> Instruction {
+    instruction::register_affiliate(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &treasury_address(),
+        &protocol_stats_address(),
+        &affiliate_index_page_address(project_owner, candy_machine_id, affiliate_index_page),
+        &affiliate_profile_address(affiliate),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// `gating_mint` must be given for projects burning the gating tokens on registration,
+// `affiliate_index_page` as in `register_affiliate`
+pub fn register_affiliate_with_gating_token(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: u32,
+    gating_token_account: &Pubkey,
+    gating_mint: Option<&Pubkey>,
+) -> Instruction {
+    instruction::register_affiliate_with_gating_token(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &treasury_address(),
+        &protocol_stats_address(),
+        &affiliate_index_page_address(project_owner, candy_machine_id, affiliate_index_page),
+        &affiliate_profile_address(affiliate),
+        gating_token_account,
+        gating_mint,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Discounts the registration fee by the SPAF balance of `spaf_token_account` following the tiers of the
+// fee discount config, gating accounts as in `register_affiliate_with_gating_token`
+pub fn register_affiliate_with_spaf_discount(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: u32,
+    gating_token_account: Option<&Pubkey>,
+    gating_mint: Option<&Pubkey>,
+    spaf_token_account: &Pubkey,
+) -> Instruction {
+    instruction::register_affiliate_with_spaf_discount(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &treasury_address(),
+        &protocol_stats_address(),
+        &affiliate_index_page_address(project_owner, candy_machine_id, affiliate_index_page),
+        &affiliate_profile_address(affiliate),
+        gating_token_account,
+        gating_mint,
+        spaf_token_account,
+        &fee_discount_config_address(),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn redeem_reward(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::redeem_reward(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &protocol_stats_address(),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Same as `redeem_reward`, also minting the SPAF milestone rewards to `reward_token_account`
+pub fn redeem_reward_with_milestone_reward(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    reward_token_account: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    let spaf_mint = spaf_mint_address().ok_or(ClientError::MilestoneRewardsDisabled)?;
+
+    Ok(instruction::redeem_reward_with_milestone_reward(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &protocol_stats_address(),
+        reward_token_account,
+        &spaf_mint,
+        &spaf_mint_authority_address(),
+        *project_owner,
+        *candy_machine_id,
+    ))
+}
+
+// `affiliate_index_page` is the page holding the affiliate, None for affiliates not in the index
+pub fn close_affiliate_account(
+    admin: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: Option<u32>,
+) -> Instruction {
+    instruction::close_affiliate_account(
+        &candy_machine_affiliates::id(),
+        admin,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &treasury_address(),
+        &protocol_stats_address(),
+        affiliate_index_page
+            .map(|page| affiliate_index_page_address(project_owner, candy_machine_id, page))
+            .as_ref(),
+        &affiliate_profile_address(affiliate),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Same as `close_affiliate_account`, also releasing the referral code claimed by the affiliate
+pub fn close_affiliate_account_with_referral_code(
+    admin: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: Option<u32>,
+    code: &str,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::close_affiliate_account_with_referral_code(
+        &candy_machine_affiliates::id(),
+        admin,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &treasury_address(),
+        &protocol_stats_address(),
+        affiliate_index_page
+            .map(|page| affiliate_index_page_address(project_owner, candy_machine_id, page))
+            .as_ref(),
+        &affiliate_profile_address(affiliate),
+        &referral_code_address(project_owner, candy_machine_id, code)?,
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    ))
+}
+
+// Appends an affiliate registered before the affiliate index to it, `affiliate_index_page` as in
+// `register_affiliate`
+pub fn index_affiliate(
+    payer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: u32,
+) -> Instruction {
+    instruction::index_affiliate(
+        &candy_machine_affiliates::id(),
+        payer,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &affiliate_index_page_address(project_owner, candy_machine_id, affiliate_index_page),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Adds a project an affiliate registered to before affiliate profiles to the profile of its wallet
+pub fn add_affiliate_to_profile(payer: &Pubkey, affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::add_affiliate_to_profile(
+        &candy_machine_affiliates::id(),
+        payer,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &affiliate_profile_address(affiliate),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// `previous_affiliate` is the wallet of the closed affiliate account still holding the code, if any
+pub fn claim_referral_code(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+    previous_affiliate: Option<&Pubkey>,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::claim_referral_code(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        &referral_code_address(project_owner, candy_machine_id, code)?,
+        previous_affiliate
+            .map(|previous_affiliate| affiliate_address(previous_affiliate, project_owner, candy_machine_id))
+            .as_ref(),
+        *project_owner,
+        *candy_machine_id,
+        code.to_string(),
+    ))
+}
+
+pub fn get_redeemable_amount(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::get_redeemable_amount(
+        &candy_machine_affiliates::id(),
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn get_project_capacity(project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::get_project_capacity(
+        &candy_machine_affiliates::id(),
+        &project_address(project_owner, candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn get_project_capacity_for_affiliate(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_token_account: Option<&Pubkey>,
+    spaf_token_account: Option<&Pubkey>,
+) -> Instruction {
+    instruction::get_project_capacity_for_affiliate(
+        &candy_machine_affiliates::id(),
+        &project_address(project_owner, candy_machine_id),
+        affiliate,
+        gating_token_account,
+        spaf_token_account,
+        &fee_discount_config_address(),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// `affiliate` is the wallet of the affiliate the code is attached to, if any
+#[allow(clippy::too_many_arguments)]
+pub fn create_promo_code(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+    affiliate: Option<&Pubkey>,
+    discount_basis_points: u16,
+    max_uses: u32,
+    max_uses_per_wallet: u16,
+    starts_at: i64,
+    ends_at: i64,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::create_promo_code(
+        &candy_machine_affiliates::id(),
+        project_owner,
+        &project_address(project_owner, candy_machine_id),
+        &promo_code_address(project_owner, candy_machine_id, code)?,
+        affiliate
+            .map(|affiliate| affiliate_address(affiliate, project_owner, candy_machine_id))
+            .as_ref(),
+        *candy_machine_id,
+        code.to_string(),
+        affiliate.copied(),
+        discount_basis_points,
+        max_uses,
+        max_uses_per_wallet,
+        starts_at,
+        ends_at,
+    ))
+}
+
+pub fn consume_promo_code(buyer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey, code: &str) -> Result<Instruction, ClientError> {
+    let promo_code = promo_code_address(project_owner, candy_machine_id, code)?;
+
+    Ok(instruction::consume_promo_code(
+        &candy_machine_affiliates::id(),
+        buyer,
+        &project_address(project_owner, candy_machine_id),
+        &promo_code,
+        &promo_code_usage_address(&promo_code, buyer),
+        *project_owner,
+        *candy_machine_id,
+        code.to_string(),
+    ))
+}
+
+pub fn set_project_gating(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_mint: Option<Pubkey>,
+    gating_min_amount: u64,
+    gating_burn: bool,
+) -> Instruction {
+    instruction::set_project_gating(
+        &candy_machine_affiliates::id(),
+        project_owner,
+        &project_address(project_owner, candy_machine_id),
+        *candy_machine_id,
+        gating_mint,
+        gating_min_amount,
+        gating_burn,
+    )
+}
+
+// `mint_accounts` and `mint_data` make up the mint instruction of `candy_machine_program`
+#[allow(clippy::too_many_arguments)]
+pub fn mint_with_referral(
+    buyer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    payment_wallet: &Pubkey,
+    candy_machine_program: &Pubkey,
+    mint_accounts: Vec<AccountMeta>,
+    mint_data: Vec<u8>,
+) -> Instruction {
+    instruction::mint_with_referral(
+        &candy_machine_affiliates::id(),
+        buyer,
+        &affiliate_address(affiliate, project_owner, candy_machine_id),
+        &project_address(project_owner, candy_machine_id),
+        payment_wallet,
+        candy_machine_program,
+        &protocol_stats_address(),
+        &affiliate_profile_address(affiliate),
+        mint_accounts,
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+        mint_data,
+    )
+}
@@ -0,0 +1,55 @@
+pub mod address;
+pub mod error;
+pub mod fetch;
+pub mod instruction;
+pub mod list;
+pub mod offline;
+pub mod transaction;
+pub mod view;
+
+pub use address::{
+    admin_address,
+    affiliate_address,
+    affiliate_index_page_address,
+    affiliate_profile_address,
+    candy_machine_project_address,
+    fee_discount_config_address,
+    project_address,
+    promo_code_address,
+    promo_code_usage_address,
+    protocol_stats_address,
+    referral_code_address,
+    spaf_mint_address,
+    spaf_mint_authority_address,
+    treasury_address,
+};
+pub use error::ClientError;
+pub use fetch::{
+    decode_affiliate,
+    decode_affiliate_index_page,
+    decode_affiliate_profile,
+    decode_candy_machine_project,
+    decode_fee_discount_config,
+    decode_project,
+    decode_promo_code,
+    decode_promo_code_usage,
+    decode_protocol_stats,
+    decode_referral_code,
+    fetch_affiliate,
+    fetch_affiliate_index_page,
+    fetch_affiliate_profile,
+    fetch_fee_discount_config,
+    fetch_indexed_affiliates,
+    fetch_project,
+    fetch_promo_code,
+    fetch_promo_code_wallet_use_count,
+    fetch_protocol_stats,
+    fetch_referral_code,
+    lookup_candy_machine_project,
+    lookup_referral_code,
+    AccountFetcher,
+};
+pub use list::{list_affiliates, list_projects};
+pub use view::{get_project_capacity, get_project_capacity_for_affiliate, get_redeemable_amount, preview_promo_code, TransactionSimulator};
+
+pub use candy_machine_affiliates as program;
@@ -0,0 +1,101 @@
+// Program account scans over `getProgramAccounts`, filtered on the Borsh layout of the state accounts
+use candy_machine_affiliates::{AffiliateAccountState, ProjectAccountState};
+use solana_account_decoder::UiAccountEncoding;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use crate::error::ClientError;
+use crate::fetch::{decode_affiliate, decode_project};
+
+// Offset of the first field after the discriminator, is_initialized and data_version
+const fn data_offset(discriminator: &str) -> usize {
+    4 + discriminator.len() + 1 + 1
+}
+
+pub const PROJECT_OWNER_OFFSET: usize = data_offset(ProjectAccountState::DISCRIMINATOR);
+pub const AFFILIATE_PUBKEY_OFFSET: usize = data_offset(AffiliateAccountState::DISCRIMINATOR);
+pub const AFFILIATE_PROJECT_OWNER_OFFSET: usize = AFFILIATE_PUBKEY_OFFSET + 32;
+pub const AFFILIATE_CANDY_MACHINE_OFFSET: usize = AFFILIATE_PROJECT_OWNER_OFFSET + 32;
+
+fn discriminator_filter(discriminator: &str) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &borsh::to_vec(discriminator).expect("Discriminator serialization failed")))
+}
+
+pub fn project_filters(project_owner: Option<&Pubkey>) -> Vec<RpcFilterType> {
+    // No size filter, projects registered before gating was added are smaller
+    let mut filters = vec![
+        discriminator_filter(ProjectAccountState::DISCRIMINATOR),
+    ];
+
+    if let Some(project_owner) = project_owner {
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(PROJECT_OWNER_OFFSET, project_owner.as_ref())));
+    }
+
+    filters
+}
+
+pub fn affiliate_filters(
+    affiliate: Option<&Pubkey>,
+    project_owner: Option<&Pubkey>,
+    candy_machine_id: Option<&Pubkey>,
+) -> Vec<RpcFilterType> {
+    // No size filter, affiliates registered before the activity counters were added are smaller
+    let mut filters = vec![
+        discriminator_filter(AffiliateAccountState::DISCRIMINATOR),
+    ];
+
+    for (offset, key) in [
+        (AFFILIATE_PUBKEY_OFFSET, affiliate),
+        (AFFILIATE_PROJECT_OWNER_OFFSET, project_owner),
+        (AFFILIATE_CANDY_MACHINE_OFFSET, candy_machine_id),
+    ] {
+        if let Some(key) = key {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, key.as_ref())));
+        }
+    }
+
+    filters
+}
+
+async fn get_program_accounts(
+    rpc_client: &RpcClient,
+    filters: Vec<RpcFilterType>,
+) -> Result<Vec<(Pubkey, solana_sdk::account::Account)>, ClientError> {
+    Ok(rpc_client.get_program_accounts_with_config(
+        &candy_machine_affiliates::id(),
+        RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(rpc_client.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    ).await?)
+}
+
+// Lists the projects of the program, optionally only those of `project_owner`
+pub async fn list_projects(
+    rpc_client: &RpcClient,
+    project_owner: Option<&Pubkey>,
+) -> Result<Vec<(Pubkey, ProjectAccountState)>, ClientError> {
+    get_program_accounts(rpc_client, project_filters(project_owner)).await?
+        .into_iter()
+        .map(|(address, account)| Ok((address, decode_project(&address, &account)?)))
+        .collect()
+}
+
+// Lists the affiliate accounts of the program matching the given wallet, project owner and candy machine
+pub async fn list_affiliates(
+    rpc_client: &RpcClient,
+    affiliate: Option<&Pubkey>,
+    project_owner: Option<&Pubkey>,
+    candy_machine_id: Option<&Pubkey>,
+) -> Result<Vec<(Pubkey, AffiliateAccountState)>, ClientError> {
+    get_program_accounts(rpc_client, affiliate_filters(affiliate, project_owner, candy_machine_id)).await?
+        .into_iter()
+        .map(|(address, account)| Ok((address, decode_affiliate(&address, &account)?)))
+        .collect()
+}
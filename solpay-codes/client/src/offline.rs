@@ -0,0 +1,128 @@
+// Offline signing with durable nonces.
+// An online host builds the unsigned transaction on top of a nonce account, the air-gapped signer
+// produces detached signatures for it and the online host attaches them and submits the transaction
+// whenever it wants, since a durable nonce does not expire like a recent blockhash.
+use std::fmt;
+use std::str::FromStr;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_rpc_client_nonce_utils::nonblocking::data_from_account;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::sanitize::Sanitize;
+use solana_sdk::transaction::Transaction;
+use crate::error::ClientError;
+use crate::fetch::AccountFetcher;
+
+// Signature of a transaction made by one of its signers, formatted as `PUBKEY=SIGNATURE`
+// like the `--signer` argument of the Solana CLI
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DetachedSignature {
+    pub pubkey: Pubkey,
+    pub signature: Signature,
+}
+
+impl fmt::Display for DetachedSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.pubkey, self.signature)
+    }
+}
+
+impl FromStr for DetachedSignature {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ClientError::InvalidSignature(s.to_string());
+        let (pubkey, signature) = s.split_once('=').ok_or_else(invalid)?;
+
+        Ok(DetachedSignature {
+            pubkey: Pubkey::from_str(pubkey).map_err(|_| invalid())?,
+            signature: Signature::from_str(signature).map_err(|_| invalid())?,
+        })
+    }
+}
+
+// Reads the blockhash currently stored in a nonce account
+pub async fn fetch_nonce_hash<F: AccountFetcher>(client: &mut F, nonce_account: &Pubkey) -> Result<Hash, ClientError> {
+    let account = client.get_account(nonce_account).await?.ok_or(ClientError::AccountNotFound(*nonce_account))?;
+    let data = data_from_account(&account).map_err(|e| ClientError::InvalidNonceAccount(*nonce_account, e.to_string()))?;
+
+    Ok(data.blockhash())
+}
+
+// Builds an unsigned transaction advancing `nonce_account` before `instructions`,
+// which stays valid until the nonce is advanced again
+pub fn nonce_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+    nonce_hash: Hash,
+) -> Transaction {
+    let message = Message::new_with_nonce(instructions.to_vec(), Some(payer), nonce_account, nonce_authority);
+    let mut transaction = Transaction::new_unsigned(message);
+
+    transaction.message.recent_blockhash = nonce_hash;
+
+    transaction
+}
+
+// Public keys that must sign the transaction, in signature order
+pub fn required_signers(transaction: &Transaction) -> &[Pubkey] {
+    &transaction.message.account_keys[..transaction.message.header.num_required_signatures as usize]
+}
+
+pub fn sign_offline<S: Signer + ?Sized>(transaction: &Transaction, signer: &S) -> Result<DetachedSignature, ClientError> {
+    let pubkey = signer.pubkey();
+
+    if !required_signers(transaction).contains(&pubkey) {
+        return Err(ClientError::UnexpectedSigner(pubkey));
+    }
+
+    let signature = signer.try_sign_message(&transaction.message_data())
+        .map_err(|e| ClientError::Transport(Box::new(e)))?;
+
+    Ok(DetachedSignature { pubkey, signature })
+}
+
+// Adds detached signatures to the transaction after verifying them against its message
+pub fn attach_signatures(transaction: &mut Transaction, signatures: &[DetachedSignature]) -> Result<(), ClientError> {
+    let message_data = transaction.message_data();
+
+    for detached in signatures {
+        let index = required_signers(transaction)
+            .iter()
+            .position(|pubkey| *pubkey == detached.pubkey)
+            .ok_or(ClientError::UnexpectedSigner(detached.pubkey))?;
+
+        if !detached.signature.verify(detached.pubkey.as_ref(), &message_data) {
+            return Err(ClientError::InvalidSignature(detached.to_string()));
+        }
+
+        transaction.signatures[index] = detached.signature;
+    }
+
+    if let Some(index) = transaction.signatures.iter().position(|signature| *signature == Signature::default()) {
+        return Err(ClientError::MissingSignature(required_signers(transaction)[index]));
+    }
+
+    Ok(())
+}
+
+// Encodes a transaction, signed or not, as base64 for exporting it to a file
+pub fn encode_transaction(transaction: &Transaction) -> String {
+    STANDARD.encode(bincode::serialize(transaction).expect("Transaction serialization failed"))
+}
+
+pub fn decode_transaction(encoded: &str) -> Result<Transaction, ClientError> {
+    let bytes = STANDARD.decode(encoded.trim()).map_err(|_| ClientError::InvalidTransaction)?;
+    let transaction: Transaction = bincode::deserialize(&bytes).map_err(|_| ClientError::InvalidTransaction)?;
+
+    if transaction.sanitize().is_err() {
+        return Err(ClientError::InvalidTransaction);
+    }
+
+    Ok(transaction)
+}
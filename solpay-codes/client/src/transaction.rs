@@ -0,0 +1,345 @@
+// Unsigned transaction builders, paid for by the signer of the instruction.
+// Sign them with `Transaction::sign` once a recent blockhash is known.
+use candy_machine_affiliates::FeeDiscountTier;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use crate::error::ClientError;
+use crate::instruction;
+
+pub fn initialize_protocol_stats(
+    admin: &Pubkey,
+    live_project_count: u32,
+    live_affiliate_count: u32,
+    registration_fees_collected_lamports: u64,
+    total_rewards_redeemed_lamports: u64,
+    total_referred_volume_lamports: u64,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::initialize_protocol_stats(
+            admin,
+            live_project_count,
+            live_affiliate_count,
+            registration_fees_collected_lamports,
+            total_rewards_redeemed_lamports,
+            total_referred_volume_lamports,
+        )],
+        Some(admin),
+    )
+}
+
+pub fn set_fee_discount_tiers(admin: &Pubkey, tiers: Vec<FeeDiscountTier>) -> Transaction {
+    Transaction::new_with_payer(&[instruction::set_fee_discount_tiers(admin, tiers)], Some(admin))
+}
+
+pub fn register_project(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::register_project(
+            project_owner,
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        )],
+        Some(project_owner),
+    )
+}
+
+pub fn update_project(
+    admin: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::update_project(
+            admin,
+            project_owner,
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        )],
+        Some(admin),
+    )
+}
+
+pub fn close_project(admin: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::close_project(admin, project_owner, candy_machine_id)],
+        Some(admin),
+    )
+}
+
+pub fn close_project_with_affiliate_index(
+    admin: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page_count: u32,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::close_project_with_affiliate_index(admin, project_owner, candy_machine_id, affiliate_index_page_count)],
+        Some(admin),
+    )
+}
+
+pub fn link_candy_machine_project(payer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::link_candy_machine_project(payer, project_owner, candy_machine_id)],
+        Some(payer),
+    )
+}
+
+pub fn register_affiliate(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey, affiliate_index_page: u32) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::register_affiliate(affiliate, project_owner, candy_machine_id, affiliate_index_page)],
+        Some(affiliate),
+    )
+}
+
+pub fn register_affiliate_with_gating_token(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: u32,
+    gating_token_account: &Pubkey,
+    gating_mint: Option<&Pubkey>,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::register_affiliate_with_gating_token(
+            affiliate,
+            project_owner,
+            candy_machine_id,
+            affiliate_index_page,
+            gating_token_account,
+            gating_mint,
+        )],
+        Some(affiliate),
+    )
+}
+
+pub fn register_affiliate_with_spaf_discount(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: u32,
+    gating_token_account: Option<&Pubkey>,
+    gating_mint: Option<&Pubkey>,
+    spaf_token_account: &Pubkey,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::register_affiliate_with_spaf_discount(
+            affiliate,
+            project_owner,
+            candy_machine_id,
+            affiliate_index_page,
+            gating_token_account,
+            gating_mint,
+            spaf_token_account,
+        )],
+        Some(affiliate),
+    )
+}
+
+pub fn redeem_reward(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::redeem_reward(affiliate, project_owner, candy_machine_id)],
+        Some(affiliate),
+    )
+}
+
+pub fn redeem_reward_with_milestone_reward(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    reward_token_account: &Pubkey,
+) -> Result<Transaction, ClientError> {
+    Ok(Transaction::new_with_payer(
+        &[instruction::redeem_reward_with_milestone_reward(affiliate, project_owner, candy_machine_id, reward_token_account)?],
+        Some(affiliate),
+    ))
+}
+
+pub fn close_affiliate_account(
+    admin: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: Option<u32>,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::close_affiliate_account(admin, affiliate, project_owner, candy_machine_id, affiliate_index_page)],
+        Some(admin),
+    )
+}
+
+pub fn close_affiliate_account_with_referral_code(
+    admin: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: Option<u32>,
+    code: &str,
+) -> Result<Transaction, ClientError> {
+    Ok(Transaction::new_with_payer(
+        &[instruction::close_affiliate_account_with_referral_code(
+            admin,
+            affiliate,
+            project_owner,
+            candy_machine_id,
+            affiliate_index_page,
+            code,
+        )?],
+        Some(admin),
+    ))
+}
+
+pub fn index_affiliate(
+    payer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_index_page: u32,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::index_affiliate(payer, affiliate, project_owner, candy_machine_id, affiliate_index_page)],
+        Some(payer),
+    )
+}
+
+pub fn add_affiliate_to_profile(payer: &Pubkey, affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::add_affiliate_to_profile(payer, affiliate, project_owner, candy_machine_id)],
+        Some(payer),
+    )
+}
+
+pub fn claim_referral_code(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+    previous_affiliate: Option<&Pubkey>,
+) -> Result<Transaction, ClientError> {
+    Ok(Transaction::new_with_payer(
+        &[instruction::claim_referral_code(affiliate, project_owner, candy_machine_id, code, previous_affiliate)?],
+        Some(affiliate),
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_promo_code(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+    affiliate: Option<&Pubkey>,
+    discount_basis_points: u16,
+    max_uses: u32,
+    max_uses_per_wallet: u16,
+    starts_at: i64,
+    ends_at: i64,
+) -> Result<Transaction, ClientError> {
+    Ok(Transaction::new_with_payer(
+        &[instruction::create_promo_code(
+            project_owner,
+            candy_machine_id,
+            code,
+            affiliate,
+            discount_basis_points,
+            max_uses,
+            max_uses_per_wallet,
+            starts_at,
+            ends_at,
+        )?],
+        Some(project_owner),
+    ))
+}
+
+// Signed by the buyer and the project owner
+pub fn consume_promo_code(buyer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey, code: &str) -> Result<Transaction, ClientError> {
+    Ok(Transaction::new_with_payer(
+        &[instruction::consume_promo_code(buyer, project_owner, candy_machine_id, code)?],
+        Some(buyer),
+    ))
+}
+
+// View transactions are only simulated, `payer` can be any funded account
+pub fn get_redeemable_amount(payer: &Pubkey, affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::get_redeemable_amount(affiliate, project_owner, candy_machine_id)],
+        Some(payer),
+    )
+}
+
+pub fn get_project_capacity(payer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::get_project_capacity(project_owner, candy_machine_id)],
+        Some(payer),
+    )
+}
+
+pub fn get_project_capacity_for_affiliate(
+    payer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_token_account: Option<&Pubkey>,
+    spaf_token_account: Option<&Pubkey>,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::get_project_capacity_for_affiliate(affiliate, project_owner, candy_machine_id, gating_token_account, spaf_token_account)],
+        Some(payer),
+    )
+}
+
+pub fn set_project_gating(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_mint: Option<Pubkey>,
+    gating_min_amount: u64,
+    gating_burn: bool,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::set_project_gating(project_owner, candy_machine_id, gating_mint, gating_min_amount, gating_burn)],
+        Some(project_owner),
+    )
+}
+
+// Signed by the buyer and the payment wallet
+#[allow(clippy::too_many_arguments)]
+pub fn mint_with_referral(
+    buyer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    payment_wallet: &Pubkey,
+    candy_machine_program: &Pubkey,
+    mint_accounts: Vec<AccountMeta>,
+    mint_data: Vec<u8>,
+) -> Transaction {
+    Transaction::new_with_payer(
+        &[instruction::mint_with_referral(
+            buyer,
+            affiliate,
+            project_owner,
+            candy_machine_id,
+            payment_wallet,
+            candy_machine_program,
+            mint_accounts,
+            mint_data,
+        )],
+        Some(buyer),
+    )
+}
@@ -0,0 +1,105 @@
+// Simulation of the read-only view instructions, which answer with Borsh encoded return data
+use std::future::Future;
+use std::str::FromStr;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use candy_machine_affiliates::{ProjectCapacity, PromoCodeDiscount, RedeemableAmount};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use crate::error::ClientError;
+use crate::transaction;
+
+// Simulator of unsigned transactions, implemented for the nonblocking `RpcClient`.
+// Returns the program that set the return data and the data itself.
+pub trait TransactionSimulator {
+    fn simulate_return_data(&mut self, transaction: Transaction) -> impl Future<Output = Result<Option<(Pubkey, Vec<u8>)>, ClientError>>;
+}
+
+impl TransactionSimulator for RpcClient {
+    async fn simulate_return_data(&mut self, transaction: Transaction) -> Result<Option<(Pubkey, Vec<u8>)>, ClientError> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.commitment()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = self.simulate_transaction_with_config(&transaction, config).await?.value;
+
+        if let Some(err) = result.err {
+            return Err(ClientError::SimulationFailed(err));
+        }
+
+        result.return_data
+            .map(|return_data| {
+                let program_id = Pubkey::from_str(&return_data.program_id).map_err(|_| ClientError::InvalidReturnData)?;
+                let data = STANDARD.decode(&return_data.data.0).map_err(|_| ClientError::InvalidReturnData)?;
+
+                Ok((program_id, data))
+            })
+            .transpose()
+    }
+}
+
+async fn simulate_view<S: TransactionSimulator, T: BorshDeserialize>(client: &mut S, transaction: Transaction) -> Result<T, ClientError> {
+    match client.simulate_return_data(transaction).await? {
+        Some((program_id, data)) if candy_machine_affiliates::check_id(&program_id) => {
+            T::try_from_slice(&data).map_err(|_| ClientError::InvalidReturnData)
+        }
+        _ => Err(ClientError::InvalidReturnData),
+    }
+}
+
+// What `RedeemReward` would pay the affiliate right now. `payer` only pays the simulated fee.
+pub async fn get_redeemable_amount<S: TransactionSimulator>(
+    client: &mut S,
+    payer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> Result<RedeemableAmount, ClientError> {
+    simulate_view(client, transaction::get_redeemable_amount(payer, affiliate, project_owner, candy_machine_id)).await
+}
+
+pub async fn get_project_capacity<S: TransactionSimulator>(
+    client: &mut S,
+    payer: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+) -> Result<ProjectCapacity, ClientError> {
+    simulate_view(client, transaction::get_project_capacity(payer, project_owner, candy_machine_id)).await
+}
+
+// Project capacity with the gating and registration fee `affiliate` would get from `RegisterAffiliate`
+pub async fn get_project_capacity_for_affiliate<S: TransactionSimulator>(
+    client: &mut S,
+    payer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_token_account: Option<&Pubkey>,
+    spaf_token_account: Option<&Pubkey>,
+) -> Result<ProjectCapacity, ClientError> {
+    let transaction = transaction::get_project_capacity_for_affiliate(
+        payer,
+        affiliate,
+        project_owner,
+        candy_machine_id,
+        gating_token_account,
+        spaf_token_account,
+    );
+
+    simulate_view(client, transaction).await
+}
+
+// Simulates `ConsumePromoCode` for the buyer, giving the discount a mint would get without using the code
+pub async fn preview_promo_code<S: TransactionSimulator>(
+    client: &mut S,
+    buyer: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+) -> Result<PromoCodeDiscount, ClientError> {
+    simulate_view(client, transaction::consume_promo_code(buyer, project_owner, candy_machine_id, code)?).await
+}
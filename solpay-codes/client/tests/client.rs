@@ -0,0 +1,332 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates_client::{
+    affiliate_address,
+    candy_machine_project_address,
+    decode_affiliate,
+    decode_project,
+    fetch_affiliate,
+    fetch_affiliate_profile,
+    fetch_fee_discount_config,
+    fetch_indexed_affiliates,
+    fetch_project,
+    get_project_capacity,
+    get_redeemable_amount,
+    fetch_promo_code,
+    fetch_promo_code_wallet_use_count,
+    fetch_protocol_stats,
+    instruction,
+    lookup_candy_machine_project,
+    lookup_referral_code,
+    preview_promo_code,
+    project_address,
+    promo_code_address,
+    protocol_stats_address,
+    referral_code_address,
+    transaction,
+    ClientError,
+};
+use candy_machine_affiliates_client::list::{affiliate_filters, project_filters};
+use candy_machine_affiliates_client::program::FeeDiscountTier;
+use solana_rpc_client_api::filter::RpcFilterType;
+use solana_sdk::account::AccountSharedData;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use common::*;
+
+#[tokio::test]
+async fn transactions_use_derived_addresses() {
+    let owner = Keypair::new();
+    let affiliate = Keypair::new();
+    let admin = admin();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner, &affiliate, &admin], &owner.pubkey()).await;
+
+    send(&mut context, transaction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 5, "Client project".to_string()), &owner).await;
+    send(&mut context, transaction::register_affiliate(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, 0), &affiliate).await;
+
+    let project_address = project_address(&owner.pubkey(), &candy_machine_id);
+    let affiliate_address = affiliate_address(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id);
+
+    let project = fetch_project(&mut Banks(&mut context.banks_client), &project_address).await.unwrap();
+
+    assert_eq!(project.data.project_owner_pubkey, owner.pubkey());
+    assert_eq!(project.data.candy_machine_id, candy_machine_id);
+    assert_eq!(project.data.affiliate_count, 1);
+    assert_eq!(project.data.title, "Client project");
+
+    let (looked_up_address, _) = lookup_candy_machine_project(&mut Banks(&mut context.banks_client), &candy_machine_id).await.unwrap();
+
+    assert_eq!(looked_up_address, project_address);
+
+    let affiliate_state = fetch_affiliate(&mut Banks(&mut context.banks_client), &affiliate_address).await.unwrap();
+
+    assert_eq!(affiliate_state.data.affiliate_pubkey, affiliate.pubkey());
+    assert_eq!(affiliate_state.data.project_owner_pubkey, owner.pubkey());
+    assert_eq!(affiliate_state.data.candy_machine_id, candy_machine_id);
+
+    let indexed_affiliates = fetch_indexed_affiliates(&mut Banks(&mut context.banks_client), &owner.pubkey(), &candy_machine_id).await.unwrap();
+
+    assert_eq!(indexed_affiliates, vec![affiliate_address]);
+
+    let affiliate_profile = fetch_affiliate_profile(&mut Banks(&mut context.banks_client), &affiliate.pubkey()).await.unwrap();
+
+    assert_eq!(affiliate_profile.data.project_accounts, vec![project_address]);
+
+    let protocol_stats = fetch_protocol_stats(&mut Banks(&mut context.banks_client)).await.unwrap();
+
+    assert_eq!(protocol_stats.data.live_project_count, 1);
+    assert_eq!(protocol_stats.data.live_affiliate_count, 1);
+
+    send(&mut context, transaction::update_project(&admin.pubkey(), &owner.pubkey(), &candy_machine_id, 12.5, 2, 6, "Renamed".to_string()), &admin).await;
+
+    let project = fetch_project(&mut Banks(&mut context.banks_client), &project_address).await.unwrap();
+
+    assert_eq!(project.data.affiliate_fee_percentage, 12.5);
+    assert_eq!(project.data.title, "Renamed");
+
+    send(&mut context, transaction::close_affiliate_account(&admin.pubkey(), &affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, Some(0)), &admin).await;
+    send(&mut context, transaction::close_project(&admin.pubkey(), &owner.pubkey(), &candy_machine_id), &admin).await;
+
+    let affiliate_profile = fetch_affiliate_profile(&mut Banks(&mut context.banks_client), &affiliate.pubkey()).await.unwrap();
+
+    assert!(affiliate_profile.data.project_accounts.is_empty());
+
+    let candy_machine_project_address = candy_machine_project_address(&candy_machine_id);
+
+    assert!(matches!(
+        lookup_candy_machine_project(&mut Banks(&mut context.banks_client), &candy_machine_id).await,
+        Err(ClientError::AccountNotFound(address)) if address == candy_machine_project_address
+    ));
+
+    assert!(matches!(
+        fetch_affiliate(&mut Banks(&mut context.banks_client), &affiliate_address).await,
+        Err(ClientError::AccountNotFound(address)) if address == affiliate_address
+    ));
+    assert!(matches!(
+        fetch_project(&mut Banks(&mut context.banks_client), &project_address).await,
+        Err(ClientError::AccountNotFound(address)) if address == project_address
+    ));
+}
+
+#[tokio::test]
+async fn fee_discount_tiers_are_set_and_fetched() {
+    let admin = admin();
+    let mut context = start(&[&admin]).await;
+    let tiers = vec![
+        FeeDiscountTier { min_spaf_balance: 1, discount_basis_points: 1_000 },
+        FeeDiscountTier { min_spaf_balance: 10, discount_basis_points: 2_500 },
+    ];
+
+    assert!(matches!(
+        fetch_fee_discount_config(&mut Banks(&mut context.banks_client)).await,
+        Err(ClientError::AccountNotFound(_))
+    ));
+
+    send(&mut context, transaction::set_fee_discount_tiers(&admin.pubkey(), tiers.clone()), &admin).await;
+
+    let fee_discount_config = fetch_fee_discount_config(&mut Banks(&mut context.banks_client)).await.unwrap();
+
+    assert_eq!(fee_discount_config.data.tiers, tiers);
+}
+
+#[tokio::test]
+async fn instructions_without_protocol_stats_skip_the_stats_update() {
+    let owner = Keypair::new();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner], &owner.pubkey()).await;
+
+    let register_project = instruction::without_protocol_stats(
+        instruction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 5, "Client project".to_string()),
+    );
+
+    assert!(register_project.accounts.iter().all(|account| account.pubkey != protocol_stats_address()));
+
+    send(&mut context, Transaction::new_with_payer(&[register_project], Some(&owner.pubkey())), &owner).await;
+
+    let project = fetch_project(&mut Banks(&mut context.banks_client), &project_address(&owner.pubkey(), &candy_machine_id)).await.unwrap();
+    let protocol_stats = fetch_protocol_stats(&mut Banks(&mut context.banks_client)).await.unwrap();
+
+    assert_eq!(project.data.title, "Client project");
+    assert_eq!(protocol_stats.data.live_project_count, 0);
+}
+
+#[tokio::test]
+async fn views_are_simulated() {
+    let owner = Keypair::new();
+    let affiliate = Keypair::new();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner, &affiliate], &owner.pubkey()).await;
+
+    send(&mut context, transaction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 2, "Client project".to_string()), &owner).await;
+    send(&mut context, transaction::register_affiliate(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, 0), &affiliate).await;
+
+    let capacity = get_project_capacity(&mut Banks(&mut context.banks_client), &owner.pubkey(), &owner.pubkey(), &candy_machine_id).await.unwrap();
+
+    assert_eq!(capacity.affiliate_count, 1);
+    assert_eq!(capacity.remaining_affiliate_slots, 1);
+    assert!(!capacity.is_full);
+
+    let amount = get_redeemable_amount(
+        &mut Banks(&mut context.banks_client),
+        &affiliate.pubkey(),
+        &affiliate.pubkey(),
+        &owner.pubkey(),
+        &candy_machine_id,
+    ).await.unwrap();
+
+    assert_eq!(amount.reward_lamports, LAMPORTS_PER_SOL);
+    assert_eq!(amount.redeemable_lamports, 0);
+
+    let unknown_candy_machine_id = Pubkey::new_unique();
+    let result = get_project_capacity(&mut Banks(&mut context.banks_client), &owner.pubkey(), &owner.pubkey(), &unknown_candy_machine_id).await;
+
+    assert!(matches!(result, Err(ClientError::SimulationFailed(_))));
+}
+
+#[tokio::test]
+async fn referral_codes_resolve_to_affiliates() {
+    let owner = Keypair::new();
+    let affiliate = Keypair::new();
+    let other_affiliate = Keypair::new();
+    let admin = admin();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner, &affiliate, &other_affiliate, &admin], &owner.pubkey()).await;
+
+    send(&mut context, transaction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 5, "Client project".to_string()), &owner).await;
+    send(&mut context, transaction::register_affiliate(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, 0), &affiliate).await;
+    send(&mut context, transaction::register_affiliate(&other_affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, 0), &other_affiliate).await;
+    send(
+        &mut context,
+        transaction::claim_referral_code(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, "SolPay", None).unwrap(),
+        &affiliate,
+    ).await;
+
+    assert_eq!(
+        referral_code_address(&owner.pubkey(), &candy_machine_id, " SOLPAY ").unwrap(),
+        referral_code_address(&owner.pubkey(), &candy_machine_id, "solpay").unwrap(),
+    );
+    assert!(matches!(
+        referral_code_address(&owner.pubkey(), &candy_machine_id, "sol pay"),
+        Err(ClientError::InvalidReferralCode(_))
+    ));
+
+    let (address, affiliate_state) = lookup_referral_code(&mut Banks(&mut context.banks_client), &owner.pubkey(), &candy_machine_id, "solpay").await.unwrap();
+
+    assert_eq!(address, affiliate_address(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id));
+    assert_eq!(affiliate_state.data.affiliate_pubkey, affiliate.pubkey());
+
+    send(
+        &mut context,
+        transaction::close_affiliate_account_with_referral_code(&admin.pubkey(), &affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, Some(0), "solpay").unwrap(),
+        &admin,
+    ).await;
+
+    let code_address = referral_code_address(&owner.pubkey(), &candy_machine_id, "solpay").unwrap();
+    assert!(matches!(
+        lookup_referral_code(&mut Banks(&mut context.banks_client), &owner.pubkey(), &candy_machine_id, "solpay").await,
+        Err(ClientError::AccountNotFound(address)) if address == code_address
+    ));
+
+    let indexed_affiliates = fetch_indexed_affiliates(&mut Banks(&mut context.banks_client), &owner.pubkey(), &candy_machine_id).await.unwrap();
+
+    assert_eq!(indexed_affiliates, vec![affiliate_address(&other_affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)]);
+
+    send(
+        &mut context,
+        transaction::claim_referral_code(&other_affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, "solpay", Some(&affiliate.pubkey())).unwrap(),
+        &other_affiliate,
+    ).await;
+
+    let (address, _) = lookup_referral_code(&mut Banks(&mut context.banks_client), &owner.pubkey(), &candy_machine_id, "solpay").await.unwrap();
+
+    assert_eq!(address, affiliate_address(&other_affiliate.pubkey(), &owner.pubkey(), &candy_machine_id));
+}
+
+#[tokio::test]
+async fn promo_codes_are_previewed_and_consumed() {
+    let owner = Keypair::new();
+    let affiliate = Keypair::new();
+    let buyer = Keypair::new();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner, &affiliate, &buyer], &owner.pubkey()).await;
+
+    send(&mut context, transaction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 5, "Client project".to_string()), &owner).await;
+    send(&mut context, transaction::register_affiliate(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, 0), &affiliate).await;
+    send(
+        &mut context,
+        transaction::create_promo_code(&owner.pubkey(), &candy_machine_id, "Summer", Some(&affiliate.pubkey()), 2_500, 10, 1, 0, 0).unwrap(),
+        &owner,
+    ).await;
+
+    let promo_code = promo_code_address(&owner.pubkey(), &candy_machine_id, "summer").unwrap();
+    let state = fetch_promo_code(&mut Banks(&mut context.banks_client), &promo_code).await.unwrap();
+
+    assert_eq!(state.data.code, "summer");
+    assert_eq!(state.data.affiliate_account, Some(affiliate_address(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)));
+
+    let discount = preview_promo_code(&mut Banks(&mut context.banks_client), &buyer.pubkey(), &owner.pubkey(), &candy_machine_id, "summer").await.unwrap();
+
+    assert_eq!(discount.discount_basis_points, 2_500);
+    assert_eq!(discount.affiliate_account, state.data.affiliate_account);
+    assert_eq!(fetch_promo_code_wallet_use_count(&mut Banks(&mut context.banks_client), &promo_code, &buyer.pubkey()).await.unwrap(), 0);
+
+    let mut transaction = transaction::consume_promo_code(&buyer.pubkey(), &owner.pubkey(), &candy_machine_id, "summer").unwrap();
+    transaction.sign(&[&buyer, &owner], context.get_new_latest_blockhash().await.unwrap());
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(fetch_promo_code_wallet_use_count(&mut Banks(&mut context.banks_client), &promo_code, &buyer.pubkey()).await.unwrap(), 1);
+    assert!(matches!(
+        preview_promo_code(&mut Banks(&mut context.banks_client), &buyer.pubkey(), &owner.pubkey(), &candy_machine_id, "summer").await,
+        Err(ClientError::SimulationFailed(_))
+    ));
+}
+
+#[tokio::test]
+async fn decode_rejects_foreign_and_mismatched_accounts() {
+    let owner = Keypair::new();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner], &owner.pubkey()).await;
+
+    send(&mut context, transaction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 5, "Client project".to_string()), &owner).await;
+
+    let address = project_address(&owner.pubkey(), &candy_machine_id);
+    let mut account = context.banks_client.get_account(address).await.unwrap().unwrap();
+
+    assert!(decode_project(&address, &account).is_ok());
+    assert!(matches!(decode_affiliate(&address, &account), Err(ClientError::InvalidAccountData(..))));
+
+    account.owner = Pubkey::new_unique();
+
+    assert!(matches!(decode_project(&address, &account), Err(ClientError::IllegalOwner(..))));
+}
+
+#[tokio::test]
+async fn list_filters_match_account_layout() {
+    let owner = Keypair::new();
+    let affiliate = Keypair::new();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner, &affiliate], &owner.pubkey()).await;
+
+    send(&mut context, transaction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 5, "Client project".to_string()), &owner).await;
+    send(&mut context, transaction::register_affiliate(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id, 0), &affiliate).await;
+
+    let project = AccountSharedData::from(
+        context.banks_client.get_account(project_address(&owner.pubkey(), &candy_machine_id)).await.unwrap().unwrap(),
+    );
+    let affiliate_account = AccountSharedData::from(
+        context.banks_client.get_account(affiliate_address(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)).await.unwrap().unwrap(),
+    );
+    let other = Pubkey::new_unique();
+
+    let allows = |filters: Vec<RpcFilterType>, account: &AccountSharedData| filters.iter().all(|filter| filter.allows(account));
+
+    assert!(allows(project_filters(None), &project));
+    assert!(allows(project_filters(Some(&owner.pubkey())), &project));
+    assert!(!allows(project_filters(Some(&other)), &project));
+    assert!(!allows(project_filters(None), &affiliate_account));
+
+    assert!(allows(affiliate_filters(None, None, None), &affiliate_account));
+    assert!(allows(affiliate_filters(Some(&affiliate.pubkey()), Some(&owner.pubkey()), Some(&candy_machine_id)), &affiliate_account));
+    assert!(!allows(affiliate_filters(Some(&other), None, None), &affiliate_account));
+    assert!(!allows(affiliate_filters(None, Some(&other), None), &affiliate_account));
+    assert!(!allows(affiliate_filters(None, None, Some(&other)), &affiliate_account));
+    assert!(!allows(affiliate_filters(None, None, None), &project));
+}
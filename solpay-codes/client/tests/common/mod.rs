@@ -0,0 +1,124 @@
+#![allow(dead_code)]
+
+use candy_machine_affiliates::candy_machine::{
+    CANDY_MACHINE_DISCRIMINATOR,
+    CANDY_MACHINE_V3_AUTHORITY_OFFSET,
+    CANDY_MACHINE_V3_PROGRAM_ID,
+};
+use candy_machine_affiliates::processor;
+use candy_machine_affiliates::{ProtocolStatsAccountState, ProtocolStatsAccountStateData};
+use candy_machine_affiliates_client::{protocol_stats_address, AccountFetcher, ClientError, TransactionSimulator};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+// Serves the fetch helpers from the program test banks
+pub struct Banks<'a>(pub &'a mut BanksClient);
+
+impl AccountFetcher for Banks<'_> {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError> {
+        self.0.get_account(*address).await.map_err(|e| ClientError::Transport(Box::new(e)))
+    }
+}
+
+impl TransactionSimulator for Banks<'_> {
+    async fn simulate_return_data(&mut self, mut transaction: Transaction) -> Result<Option<(Pubkey, Vec<u8>)>, ClientError> {
+        transaction.message.recent_blockhash = self.0.get_latest_blockhash().await.map_err(|e| ClientError::Transport(Box::new(e)))?;
+
+        let simulation = self.0.simulate_transaction(transaction).await.map_err(|e| ClientError::Transport(Box::new(e)))?;
+
+        if let Some(Err(err)) = simulation.result {
+            return Err(ClientError::SimulationFailed(err));
+        }
+
+        Ok(simulation.simulation_details
+            .and_then(|details| details.return_data)
+            .map(|return_data| (return_data.program_id, return_data.data)))
+    }
+}
+
+pub fn admin() -> Keypair {
+    read_keypair_file(concat!(env!("CARGO_MANIFEST_DIR"), "/../config/localnet/admin-keypair.json"))
+        .expect("Failed to read localnet admin keypair")
+}
+
+fn program_test(wallets: &[&Keypair]) -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "candy_machine_affiliates",
+        candy_machine_affiliates::id(),
+        processor!(processor::process_instruction),
+    );
+
+    let mut data = borsh::to_vec(&ProtocolStatsAccountState {
+        discriminator: ProtocolStatsAccountState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        data_version: 0,
+        data: ProtocolStatsAccountStateData {
+            live_project_count: 0,
+            live_affiliate_count: 0,
+            registration_fees_collected_lamports: 0,
+            total_rewards_redeemed_lamports: 0,
+            total_referred_volume_lamports: 0,
+            created_at: 0,
+            updated_at: 0,
+        },
+    }).unwrap();
+    data.resize(ProtocolStatsAccountState::LENGTH, 0);
+
+    program_test.add_account(
+        protocol_stats_address(),
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: candy_machine_affiliates::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    for wallet in wallets {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10 * LAMPORTS_PER_SOL, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    program_test
+}
+
+pub async fn start(wallets: &[&Keypair]) -> ProgramTestContext {
+    program_test(wallets).start_with_context().await
+}
+
+// Also adds a Candy Machine v3 account of `authority`, projects are registered by its authority
+pub async fn start_with_candy_machine(wallets: &[&Keypair], authority: &Pubkey) -> (ProgramTestContext, Pubkey) {
+    let mut program_test = program_test(wallets);
+    let candy_machine_id = Pubkey::new_unique();
+    let mut data = vec![0; 256];
+    data[..8].copy_from_slice(&CANDY_MACHINE_DISCRIMINATOR);
+    data[CANDY_MACHINE_V3_AUTHORITY_OFFSET..CANDY_MACHINE_V3_AUTHORITY_OFFSET + 32].copy_from_slice(authority.as_ref());
+
+    program_test.add_account(
+        candy_machine_id,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: CANDY_MACHINE_V3_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    (program_test.start_with_context().await, candy_machine_id)
+}
+
+pub async fn send(context: &mut ProgramTestContext, mut transaction: Transaction, signer: &Keypair) {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    transaction.sign(&[signer], blockhash);
+    context.banks_client.process_transaction(transaction).await.unwrap();
+}
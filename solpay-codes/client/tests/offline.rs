@@ -0,0 +1,118 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use std::str::FromStr;
+use candy_machine_affiliates_client::offline::{
+    attach_signatures,
+    decode_transaction,
+    encode_transaction,
+    fetch_nonce_hash,
+    nonce_transaction,
+    sign_offline,
+    DetachedSignature,
+};
+use candy_machine_affiliates_client::{instruction, project_address, transaction, ClientError};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::hash::Hash;
+use solana_sdk::nonce::State;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use common::*;
+
+// Creates a nonce account owned by `authority` and moves to a new blockhash so the nonce can be advanced
+async fn create_nonce_account(context: &mut ProgramTestContext, authority: &Pubkey) -> Pubkey {
+    let nonce_account = Keypair::new();
+    let payer = context.payer.insecure_clone();
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &system_instruction::create_nonce_account(
+            &payer.pubkey(),
+            &nonce_account.pubkey(),
+            authority,
+            Rent::default().minimum_balance(State::size()),
+        ),
+        Some(&payer.pubkey()),
+        &[&payer, &nonce_account],
+        blockhash,
+    );
+
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+
+    nonce_account.pubkey()
+}
+
+// Builds the close project transaction the online host exports for the air-gapped admin
+async fn export_close_project(context: &mut ProgramTestContext, admin: &Pubkey, owner: &Pubkey, candy_machine_id: &Pubkey) -> (String, Pubkey, Hash) {
+    let nonce_account = create_nonce_account(context, admin).await;
+    let nonce_hash = fetch_nonce_hash(&mut Banks(&mut context.banks_client), &nonce_account).await.unwrap();
+
+    let transaction = nonce_transaction(
+        &[instruction::close_project(admin, owner, candy_machine_id)],
+        admin,
+        &nonce_account,
+        admin,
+        nonce_hash,
+    );
+
+    (encode_transaction(&transaction), nonce_account, nonce_hash)
+}
+
+#[tokio::test]
+async fn offline_signed_nonce_transaction_is_submitted_later() {
+    let owner = Keypair::new();
+    let admin = admin();
+    let (mut context, candy_machine_id) = start_with_candy_machine(&[&owner, &admin], &owner.pubkey()).await;
+
+    send(&mut context, transaction::register_project(&owner.pubkey(), &candy_machine_id, 10.0, 1, 5, "Offline project".to_string()), &owner).await;
+
+    let (exported, nonce_account, nonce_hash) = export_close_project(&mut context, &admin.pubkey(), &owner.pubkey(), &candy_machine_id).await;
+
+    // Air-gapped host
+    let signature = sign_offline(&decode_transaction(&exported).unwrap(), &admin).unwrap().to_string();
+
+    // The nonce keeps the transaction valid after its blockhash would have expired
+    context.warp_to_slot(1_000).unwrap();
+
+    // Online host
+    let mut transaction = decode_transaction(&exported).unwrap();
+    attach_signatures(&mut transaction, &[DetachedSignature::from_str(&signature).unwrap()]).unwrap();
+
+    // `BanksClient::process_transaction` only accepts recent blockhashes, not durable nonces
+    let result = context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+
+    assert!(context.banks_client.get_account(project_address(&owner.pubkey(), &candy_machine_id)).await.unwrap().is_none());
+    assert_ne!(fetch_nonce_hash(&mut Banks(&mut context.banks_client), &nonce_account).await.unwrap(), nonce_hash);
+}
+
+#[tokio::test]
+async fn detached_signatures_are_verified() {
+    let owner = Keypair::new();
+    let admin = admin();
+    let candy_machine_id = Pubkey::new_unique();
+    let mut context = start(&[&owner, &admin]).await;
+
+    let (exported, _, _) = export_close_project(&mut context, &admin.pubkey(), &owner.pubkey(), &candy_machine_id).await;
+    let transaction = decode_transaction(&exported).unwrap();
+
+    assert!(matches!(sign_offline(&transaction, &owner), Err(ClientError::UnexpectedSigner(pubkey)) if pubkey == owner.pubkey()));
+    assert!(matches!(
+        attach_signatures(&mut transaction.clone(), &[]),
+        Err(ClientError::MissingSignature(pubkey)) if pubkey == admin.pubkey()
+    ));
+
+    let forged = DetachedSignature {
+        pubkey: admin.pubkey(),
+        signature: owner.sign_message(&transaction.message_data()),
+    };
+
+    assert!(matches!(attach_signatures(&mut transaction.clone(), &[forged]), Err(ClientError::InvalidSignature(_))));
+    assert!(matches!(DetachedSignature::from_str("not a signature"), Err(ClientError::InvalidSignature(_))));
+    assert!(matches!(decode_transaction("not a transaction"), Err(ClientError::InvalidTransaction)));
+}
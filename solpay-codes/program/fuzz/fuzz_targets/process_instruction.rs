@@ -0,0 +1,1227 @@
+#![no_main]
+
+// Drives processor::process_instruction with arbitrary instructions against an in-memory account model.
+// System program CPIs and the Clock and Rent sysvars are emulated through custom syscall stubs,
+// CPIs into other programs fail with `IncorrectProgramId`.
+// Run with `cargo fuzz run process_instruction` from the program directory.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Once, OnceLock};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::SystemInstruction,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use candy_machine_affiliates::candy_machine::{
+    CANDY_GUARD_PROGRAM_ID,
+    CANDY_MACHINE_DISCRIMINATOR,
+    CANDY_MACHINE_V2_AUTHORITY_OFFSET,
+    CANDY_MACHINE_V2_PROGRAM_ID,
+    CANDY_MACHINE_V3_AUTHORITY_OFFSET,
+    CANDY_MACHINE_V3_PROGRAM_ID,
+};
+use candy_machine_affiliates::projects::state::{
+    ProjectAccountStateDataV0,
+    ProjectAccountStateV0,
+    PROJECT_ACCOUNT_DATA_VERSION,
+};
+use candy_machine_affiliates::{
+    processor,
+    AffiliateAccountState,
+    AffiliateAccountStateData,
+    AffiliateIndexPageAccountState,
+    AffiliateIndexPageAccountStateData,
+    AffiliateProfileAccountState,
+    AffiliateProfileAccountStateData,
+    CandyMachineProjectAccountState,
+    CandyMachineProjectAccountStateData,
+    CandyMachineAffiliatesInstruction,
+    FeeDiscountConfigAccountState,
+    FeeDiscountConfigAccountStateData,
+    FeeDiscountTier,
+    ProjectAccountState,
+    ProjectAccountStateData,
+    PromoCodeAccountState,
+    PromoCodeAccountStateData,
+    PromoCodeUsageAccountState,
+    ProtocolStatsAccountState,
+    ProtocolStatsAccountStateData,
+    ReferralCodeAccountState,
+    ReferralCodeAccountStateData,
+    ADMIN_PUBKEY,
+    SOLPAY_TREASURY_PUBKEY,
+    SPAF_MINT_AUTHORITY_SEED,
+    SPAF_MINT_PUBKEY,
+    AFFILIATE_INDEX_PAGE_SIZE,
+};
+
+const WALLET_COUNT: usize = 3;
+const CANDY_MACHINE_COUNT: usize = 2;
+const MINT_COUNT: usize = 2;
+const REFERRAL_CODES: [&str; 2] = ["solpay", "mint-42"];
+const AFFILIATE_INDEX_PAGE_COUNT: usize = 2;
+// Enough for `RegisterAffiliate` with the gating, SPAF and fee discount config accounts
+const MAX_ACCOUNTS: usize = 13;
+// Keeps the lamports of all accounts below the u64 total supply limit enforced by the runtime
+const MAX_ACCOUNT_LAMPORTS: u64 = u64::MAX / MAX_ACCOUNTS as u64;
+
+static UNIX_TIMESTAMP: AtomicI64 = AtomicI64::new(0);
+
+struct Keys {
+    wallets: Vec<Pubkey>,
+    candy_machines: Vec<Pubkey>,
+    mints: Vec<Pubkey>,
+    admin: Pubkey,
+    treasury: Pubkey,
+    other_program: Pubkey,
+    spaf_mint_authority: Pubkey,
+    protocol_stats_pda: Pubkey,
+    fee_discount_config_pda: Pubkey,
+    // Indexed by [owner][candy_machine]
+    project_pdas: Vec<Vec<Pubkey>>,
+    // Indexed by [affiliate][owner][candy_machine]
+    affiliate_pdas: Vec<Vec<Vec<Pubkey>>>,
+    // Indexed by [owner][candy_machine][page]
+    affiliate_index_page_pdas: Vec<Vec<Vec<Pubkey>>>,
+    // Indexed by [affiliate]
+    affiliate_profile_pdas: Vec<Pubkey>,
+    // Indexed by [candy_machine]
+    candy_machine_project_pdas: Vec<Pubkey>,
+    // Indexed by [owner][candy_machine][code]
+    referral_code_pdas: Vec<Vec<Vec<Pubkey>>>,
+    // Indexed by [owner][candy_machine][code]
+    promo_code_pdas: Vec<Vec<Vec<Pubkey>>>,
+    // Indexed by [owner][candy_machine][code][buyer]
+    promo_code_usage_pdas: Vec<Vec<Vec<Vec<Pubkey>>>>,
+}
+
+fn keys() -> &'static Keys {
+    static KEYS: OnceLock<Keys> = OnceLock::new();
+
+    KEYS.get_or_init(|| {
+        let program_id = candy_machine_affiliates::id();
+        let wallets: Vec<Pubkey> = (0..WALLET_COUNT).map(|i| Pubkey::new_from_array([i as u8 + 1; 32])).collect();
+        let candy_machines: Vec<Pubkey> = (0..CANDY_MACHINE_COUNT).map(|i| Pubkey::new_from_array([i as u8 + 101; 32])).collect();
+        // The first mint is the SPAF mint when milestone rewards are enabled
+        let mints: Vec<Pubkey> = (0..MINT_COUNT).map(|i| match (i, SPAF_MINT_PUBKEY) {
+            (0, Some(spaf_mint)) => Pubkey::from_str(spaf_mint).unwrap(),
+            _ => Pubkey::new_from_array([i as u8 + 201; 32]),
+        }).collect();
+
+        let project_pdas: Vec<Vec<Pubkey>> = wallets.iter().map(|owner| {
+            candy_machines.iter().map(|candy_machine_id| {
+                Pubkey::find_program_address(
+                    &[ProjectAccountState::DISCRIMINATOR.as_ref(), owner.as_ref(), candy_machine_id.as_ref()],
+                    &program_id,
+                ).0
+            }).collect()
+        }).collect();
+
+        let code_pdas = |discriminator: &str| -> Vec<Vec<Vec<Pubkey>>> {
+            project_pdas.iter().map(|owner_projects| {
+                owner_projects.iter().map(|project| {
+                    REFERRAL_CODES.iter().map(|code| {
+                        Pubkey::find_program_address(
+                            &[discriminator.as_ref(), project.as_ref(), code.as_ref()],
+                            &program_id,
+                        ).0
+                    }).collect()
+                }).collect()
+            }).collect()
+        };
+
+        let affiliate_index_page_pdas = project_pdas.iter().map(|owner_projects| {
+            owner_projects.iter().map(|project| {
+                (0..AFFILIATE_INDEX_PAGE_COUNT as u32).map(|page| {
+                    Pubkey::find_program_address(
+                        &[AffiliateIndexPageAccountState::DISCRIMINATOR.as_ref(), project.as_ref(), &page.to_le_bytes()],
+                        &program_id,
+                    ).0
+                }).collect()
+            }).collect()
+        }).collect();
+
+        let affiliate_profile_pdas = wallets.iter().map(|affiliate| {
+            Pubkey::find_program_address(
+                &[AffiliateProfileAccountState::DISCRIMINATOR.as_ref(), affiliate.as_ref()],
+                &program_id,
+            ).0
+        }).collect();
+
+        let candy_machine_project_pdas = candy_machines.iter().map(|candy_machine| {
+            Pubkey::find_program_address(
+                &[CandyMachineProjectAccountState::DISCRIMINATOR.as_ref(), candy_machine.as_ref()],
+                &program_id,
+            ).0
+        }).collect();
+
+        let referral_code_pdas = code_pdas(ReferralCodeAccountState::DISCRIMINATOR);
+        let promo_code_pdas = code_pdas(PromoCodeAccountState::DISCRIMINATOR);
+
+        let promo_code_usage_pdas = promo_code_pdas.iter().map(|owner_promo_codes| {
+            owner_promo_codes.iter().map(|project_promo_codes| {
+                project_promo_codes.iter().map(|promo_code| {
+                    wallets.iter().map(|buyer| {
+                        Pubkey::find_program_address(
+                            &[PromoCodeUsageAccountState::DISCRIMINATOR.as_ref(), promo_code.as_ref(), buyer.as_ref()],
+                            &program_id,
+                        ).0
+                    }).collect()
+                }).collect()
+            }).collect()
+        }).collect();
+
+        let affiliate_pdas = wallets.iter().map(|affiliate| {
+            wallets.iter().map(|owner| {
+                candy_machines.iter().map(|candy_machine_id| {
+                    Pubkey::find_program_address(
+                        &[
+                            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+                            affiliate.as_ref(),
+                            owner.as_ref(),
+                            candy_machine_id.as_ref(),
+                        ],
+                        &program_id,
+                    ).0
+                }).collect()
+            }).collect()
+        }).collect();
+
+        Keys {
+            admin: Pubkey::from_str(ADMIN_PUBKEY).unwrap(),
+            treasury: Pubkey::from_str(SOLPAY_TREASURY_PUBKEY).unwrap(),
+            other_program: Pubkey::new_from_array([255; 32]),
+            spaf_mint_authority: Pubkey::find_program_address(&[SPAF_MINT_AUTHORITY_SEED.as_ref()], &program_id).0,
+            protocol_stats_pda: Pubkey::find_program_address(&[ProtocolStatsAccountState::DISCRIMINATOR.as_ref()], &program_id).0,
+            fee_discount_config_pda: Pubkey::find_program_address(
+                &[FeeDiscountConfigAccountState::DISCRIMINATOR.as_ref()],
+                &program_id,
+            ).0,
+            wallets,
+            candy_machines,
+            mints,
+            project_pdas,
+            affiliate_pdas,
+            affiliate_index_page_pdas,
+            affiliate_profile_pdas,
+            candy_machine_project_pdas,
+            referral_code_pdas,
+            promo_code_pdas,
+            promo_code_usage_pdas,
+        }
+    })
+}
+
+fn project_state_v0(state: ProjectAccountState) -> ProjectAccountStateV0 {
+    ProjectAccountStateV0 {
+        discriminator: state.discriminator,
+        is_initialized: state.is_initialized,
+        data_version: 0,
+        data: ProjectAccountStateDataV0 {
+            project_owner_pubkey: state.data.project_owner_pubkey,
+            candy_machine_id: state.data.candy_machine_id,
+            affiliate_fee_percentage: state.data.affiliate_fee_percentage,
+            affiliate_target_in_sol: state.data.affiliate_target_in_sol,
+            max_affiliate_count: state.data.max_affiliate_count as u8,
+            affiliate_count: state.data.affiliate_count as u8,
+            title: state.data.title,
+            created_at: state.data.created_at,
+            updated_at: state.data.updated_at,
+        },
+    }
+}
+
+// Borsh refuses to serialize NaN, raw instruction data still covers it
+fn not_nan(value: f64) -> f64 {
+    if value.is_nan() { 0.0 } else { value }
+}
+
+fn wallet_key(index: u8) -> Pubkey {
+    keys().wallets[index as usize % WALLET_COUNT]
+}
+
+fn candy_machine_key(index: u8) -> Pubkey {
+    keys().candy_machines[index as usize % CANDY_MACHINE_COUNT]
+}
+
+fn mint_key(index: u8) -> Pubkey {
+    keys().mints[index as usize % MINT_COUNT]
+}
+
+fn referral_code(index: u8) -> &'static str {
+    REFERRAL_CODES[index as usize % REFERRAL_CODES.len()]
+}
+
+fn fee_discount_tiers(tiers: &[(u64, u16)]) -> Vec<FeeDiscountTier> {
+    tiers.iter()
+        .map(|(min_spaf_balance, discount_basis_points)| FeeDiscountTier {
+            min_spaf_balance: *min_spaf_balance,
+            discount_basis_points: *discount_basis_points,
+        })
+        .collect()
+}
+
+#[derive(Arbitrary, Debug, Clone, Copy)]
+enum FuzzKey {
+    Wallet(u8),
+    Admin,
+    Treasury,
+    SystemProgram,
+    TokenProgram,
+    Program,
+    Mint(u8),
+    CandyMachine(u8),
+    // Candy Machine v2, v3 or Candy Guard program, whose mint CPIs the stubs reject
+    CandyMachineProgram(u8),
+    SpafMintAuthority,
+    ProtocolStatsPda,
+    FeeDiscountConfigPda,
+    ProjectPda { owner: u8, candy_machine: u8 },
+    AffiliatePda { affiliate: u8, owner: u8, candy_machine: u8 },
+    AffiliateIndexPagePda { owner: u8, candy_machine: u8, page: u8 },
+    AffiliateProfilePda { affiliate: u8 },
+    CandyMachineProjectPda { candy_machine: u8 },
+    ReferralCodePda { owner: u8, candy_machine: u8, code: u8 },
+    PromoCodePda { owner: u8, candy_machine: u8, code: u8 },
+    PromoCodeUsagePda { owner: u8, candy_machine: u8, code: u8, buyer: u8 },
+}
+
+impl FuzzKey {
+    fn pubkey(&self) -> Pubkey {
+        let keys = keys();
+
+        match *self {
+            FuzzKey::Wallet(index) => wallet_key(index),
+            FuzzKey::Admin => keys.admin,
+            FuzzKey::Treasury => keys.treasury,
+            FuzzKey::SystemProgram => SYSTEM_PROGRAM_ID,
+            FuzzKey::TokenProgram => spl_token::id(),
+            FuzzKey::Program => candy_machine_affiliates::id(),
+            FuzzKey::Mint(index) => mint_key(index),
+            FuzzKey::CandyMachine(index) => candy_machine_key(index),
+            FuzzKey::CandyMachineProgram(index) => {
+                [CANDY_MACHINE_V2_PROGRAM_ID, CANDY_MACHINE_V3_PROGRAM_ID, CANDY_GUARD_PROGRAM_ID][index as usize % 3]
+            }
+            FuzzKey::SpafMintAuthority => keys.spaf_mint_authority,
+            FuzzKey::ProtocolStatsPda => keys.protocol_stats_pda,
+            FuzzKey::FeeDiscountConfigPda => keys.fee_discount_config_pda,
+            FuzzKey::ProjectPda { owner, candy_machine } => {
+                keys.project_pdas[owner as usize % WALLET_COUNT][candy_machine as usize % CANDY_MACHINE_COUNT]
+            }
+            FuzzKey::AffiliatePda { affiliate, owner, candy_machine } => {
+                keys.affiliate_pdas[affiliate as usize % WALLET_COUNT][owner as usize % WALLET_COUNT]
+                    [candy_machine as usize % CANDY_MACHINE_COUNT]
+            }
+            FuzzKey::AffiliateIndexPagePda { owner, candy_machine, page } => {
+                keys.affiliate_index_page_pdas[owner as usize % WALLET_COUNT][candy_machine as usize % CANDY_MACHINE_COUNT]
+                    [page as usize % AFFILIATE_INDEX_PAGE_COUNT]
+            }
+            FuzzKey::AffiliateProfilePda { affiliate } => keys.affiliate_profile_pdas[affiliate as usize % WALLET_COUNT],
+            FuzzKey::CandyMachineProjectPda { candy_machine } => {
+                keys.candy_machine_project_pdas[candy_machine as usize % CANDY_MACHINE_COUNT]
+            }
+            FuzzKey::ReferralCodePda { owner, candy_machine, code } => {
+                keys.referral_code_pdas[owner as usize % WALLET_COUNT][candy_machine as usize % CANDY_MACHINE_COUNT]
+                    [code as usize % REFERRAL_CODES.len()]
+            }
+            FuzzKey::PromoCodePda { owner, candy_machine, code } => {
+                keys.promo_code_pdas[owner as usize % WALLET_COUNT][candy_machine as usize % CANDY_MACHINE_COUNT]
+                    [code as usize % REFERRAL_CODES.len()]
+            }
+            FuzzKey::PromoCodeUsagePda { owner, candy_machine, code, buyer } => {
+                keys.promo_code_usage_pdas[owner as usize % WALLET_COUNT][candy_machine as usize % CANDY_MACHINE_COUNT]
+                    [code as usize % REFERRAL_CODES.len()][buyer as usize % WALLET_COUNT]
+            }
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug, Clone, Copy)]
+enum FuzzOwner {
+    System,
+    Program,
+    TokenProgram,
+    CandyMachineV2,
+    CandyMachineV3,
+    Other,
+}
+
+impl FuzzOwner {
+    fn pubkey(&self) -> Pubkey {
+        match self {
+            FuzzOwner::System => SYSTEM_PROGRAM_ID,
+            FuzzOwner::Program => candy_machine_affiliates::id(),
+            FuzzOwner::TokenProgram => spl_token::id(),
+            FuzzOwner::CandyMachineV2 => CANDY_MACHINE_V2_PROGRAM_ID,
+            FuzzOwner::CandyMachineV3 => CANDY_MACHINE_V3_PROGRAM_ID,
+            FuzzOwner::Other => keys().other_program,
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum FuzzData {
+    Empty,
+    Raw(Vec<u8>),
+    Project {
+        is_initialized: bool,
+        owner: u8,
+        candy_machine: u8,
+        affiliate_fee_percentage: f64,
+        affiliate_target_in_sol: u8,
+        max_affiliate_count: u32,
+        affiliate_count: u32,
+        title: String,
+        gating_mint: Option<u8>,
+        gating_min_amount: u64,
+        gating_burn: bool,
+        total_mint_count: u32,
+        total_redemption_count: u32,
+        affiliate_index_length: u32,
+        // Data version 0 layout, with the affiliate counts truncated to u8
+        data_version_0: bool,
+        // Bytes left after a data version 0 layout by a title that was shortened
+        stale_title_bytes: bool,
+    },
+    Affiliate {
+        is_initialized: bool,
+        affiliate: u8,
+        owner: u8,
+        candy_machine: u8,
+        total_redeemed_amount_in_sol: u32,
+        mint_count: u32,
+        redemption_count: u32,
+        affiliate_index_position: Option<u32>,
+        // Size of the accounts registered before the activity counters were added
+        legacy_length: bool,
+    },
+    AffiliateIndexPage {
+        is_initialized: bool,
+        owner: u8,
+        candy_machine: u8,
+        page: u32,
+        // Entries from the first slot on, the rest of the page is empty
+        affiliate_accounts: Vec<FuzzKey>,
+    },
+    AffiliateProfile {
+        is_initialized: bool,
+        affiliate: u8,
+        total_commission_lamports: u64,
+        project_accounts: Vec<FuzzKey>,
+        // Spare room left by projects the wallet has left
+        spare_project_count: u8,
+    },
+    CandyMachineProject {
+        is_initialized: bool,
+        candy_machine: u8,
+        project_account: FuzzKey,
+        owner: u8,
+        created_at: i64,
+    },
+    ReferralCode {
+        is_initialized: bool,
+        code: u8,
+        affiliate_account: FuzzKey,
+        affiliate: u8,
+        owner: u8,
+        candy_machine: u8,
+    },
+    PromoCode {
+        is_initialized: bool,
+        code: u8,
+        owner: u8,
+        candy_machine: u8,
+        affiliate_account: Option<FuzzKey>,
+        discount_basis_points: u16,
+        max_uses: u32,
+        max_uses_per_wallet: u16,
+        use_count: u32,
+        starts_at: i64,
+        ends_at: i64,
+    },
+    ProtocolStats {
+        is_initialized: bool,
+        live_project_count: u32,
+        live_affiliate_count: u32,
+        total_rewards_redeemed_lamports: u64,
+    },
+    // Tiers as (min SPAF balance, discount basis points), validated or not
+    FeeDiscountConfig {
+        is_initialized: bool,
+        tiers: Vec<(u64, u16)>,
+    },
+    TokenAccount {
+        mint: u8,
+        owner: u8,
+        amount: u64,
+    },
+    CandyMachine {
+        v3: bool,
+        authority: u8,
+    },
+}
+
+impl FuzzData {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            FuzzData::Empty => vec![],
+            FuzzData::Raw(data) => data.clone(),
+            FuzzData::Project {
+                is_initialized,
+                owner,
+                candy_machine,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                affiliate_count,
+                title,
+                gating_mint,
+                gating_min_amount,
+                gating_burn,
+                total_mint_count,
+                total_redemption_count,
+                affiliate_index_length,
+                data_version_0,
+                stale_title_bytes,
+            } => {
+                let state = ProjectAccountState {
+                    discriminator: ProjectAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: PROJECT_ACCOUNT_DATA_VERSION,
+                    data: ProjectAccountStateData {
+                        project_owner_pubkey: wallet_key(*owner),
+                        candy_machine_id: candy_machine_key(*candy_machine),
+                        affiliate_fee_percentage: *affiliate_fee_percentage,
+                        affiliate_target_in_sol: *affiliate_target_in_sol,
+                        max_affiliate_count: *max_affiliate_count,
+                        affiliate_count: *affiliate_count,
+                        title: title.clone(),
+                        created_at: 0,
+                        updated_at: 0,
+                        gating_mint: gating_mint.map(mint_key),
+                        gating_min_amount: *gating_min_amount,
+                        gating_burn: *gating_burn,
+                        total_mint_count: *total_mint_count,
+                        total_referred_volume_lamports: 0,
+                        total_commission_lamports: 0,
+                        total_redemption_count: *total_redemption_count,
+                        peak_affiliate_count: *affiliate_count,
+                        affiliate_index_length: *affiliate_index_length,
+                    },
+                };
+                // Data version 0 accounts have the deployed layout, and are left with the end of
+                // a longer title after them when it was shortened
+                let (mut data, length) = match data_version_0 {
+                    true => (borsh::to_vec(&project_state_v0(state)).unwrap_or_default(), ProjectAccountState::V0_LENGTH),
+                    false => (borsh::to_vec(&state).unwrap_or_default(), ProjectAccountState::LENGTH),
+                };
+                let stale_byte = match *data_version_0 && *stale_title_bytes {
+                    true => b'x',
+                    false => 0,
+                };
+                data.resize(data.len().max(length), stale_byte);
+                data
+            }
+            FuzzData::Affiliate {
+                is_initialized,
+                affiliate,
+                owner,
+                candy_machine,
+                total_redeemed_amount_in_sol,
+                mint_count,
+                redemption_count,
+                affiliate_index_position,
+                legacy_length,
+            } => {
+                let mut data = borsh::to_vec(&AffiliateAccountState {
+                    discriminator: AffiliateAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: 0,
+                    data: AffiliateAccountStateData {
+                        affiliate_pubkey: wallet_key(*affiliate),
+                        project_owner_pubkey: wallet_key(*owner),
+                        candy_machine_id: candy_machine_key(*candy_machine),
+                        total_redeemed_amount_in_sol: *total_redeemed_amount_in_sol,
+                        created_at: 0,
+                        mint_count: *mint_count,
+                        referred_volume_lamports: 0,
+                        total_commission_lamports: 0,
+                        redemption_count: *redemption_count,
+                        last_activity_at: 0,
+                        affiliate_index_position: *affiliate_index_position,
+                        pending_milestone_reward: 0,
+                        referral_code: None,
+                    },
+                }).unwrap_or_default();
+                match legacy_length {
+                    true => data.truncate(AffiliateAccountState::LEGACY_LENGTH),
+                    false => data.resize(data.len().max(AffiliateAccountState::LENGTH), 0),
+                }
+                data
+            }
+            FuzzData::AffiliateIndexPage { is_initialized, owner, candy_machine, page, affiliate_accounts } => {
+                let mut state = AffiliateIndexPageAccountState {
+                    discriminator: AffiliateIndexPageAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: 0,
+                    data: AffiliateIndexPageAccountStateData {
+                        project: keys().project_pdas[*owner as usize % WALLET_COUNT][*candy_machine as usize % CANDY_MACHINE_COUNT],
+                        page: *page,
+                        affiliate_accounts: [Pubkey::default(); AFFILIATE_INDEX_PAGE_SIZE],
+                    },
+                };
+                for (slot, affiliate_account) in state.data.affiliate_accounts.iter_mut().zip(affiliate_accounts.iter()) {
+                    *slot = affiliate_account.pubkey();
+                }
+                borsh::to_vec(&state).unwrap()
+            }
+            FuzzData::AffiliateProfile { is_initialized, affiliate, total_commission_lamports, project_accounts, spare_project_count } => {
+                let mut data = borsh::to_vec(&AffiliateProfileAccountState {
+                    discriminator: AffiliateProfileAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: 0,
+                    data: AffiliateProfileAccountStateData {
+                        affiliate_pubkey: wallet_key(*affiliate),
+                        total_commission_lamports: *total_commission_lamports,
+                        project_accounts: project_accounts.iter().map(FuzzKey::pubkey).collect(),
+                    },
+                }).unwrap();
+                data.resize(data.len() + 32 * (*spare_project_count as usize % 4), 0);
+                data
+            }
+            FuzzData::CandyMachineProject { is_initialized, candy_machine, project_account, owner, created_at } => {
+                borsh::to_vec(&CandyMachineProjectAccountState {
+                    discriminator: CandyMachineProjectAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: 0,
+                    data: CandyMachineProjectAccountStateData {
+                        candy_machine_id: candy_machine_key(*candy_machine),
+                        project_account: project_account.pubkey(),
+                        project_owner_pubkey: wallet_key(*owner),
+                        created_at: *created_at,
+                    },
+                }).unwrap()
+            }
+            FuzzData::ReferralCode {
+                is_initialized,
+                code,
+                affiliate_account,
+                affiliate,
+                owner,
+                candy_machine,
+            } => {
+                let mut data = borsh::to_vec(&ReferralCodeAccountState {
+                    discriminator: ReferralCodeAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: 0,
+                    data: ReferralCodeAccountStateData {
+                        code: referral_code(*code).to_string(),
+                        affiliate_account: affiliate_account.pubkey(),
+                        affiliate_pubkey: wallet_key(*affiliate),
+                        project_owner_pubkey: wallet_key(*owner),
+                        candy_machine_id: candy_machine_key(*candy_machine),
+                        created_at: 0,
+                    },
+                }).unwrap_or_default();
+                data.resize(data.len().max(ReferralCodeAccountState::LENGTH), 0);
+                data
+            }
+            FuzzData::PromoCode {
+                is_initialized,
+                code,
+                owner,
+                candy_machine,
+                affiliate_account,
+                discount_basis_points,
+                max_uses,
+                max_uses_per_wallet,
+                use_count,
+                starts_at,
+                ends_at,
+            } => {
+                let mut data = borsh::to_vec(&PromoCodeAccountState {
+                    discriminator: PromoCodeAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: 0,
+                    data: PromoCodeAccountStateData {
+                        code: referral_code(*code).to_string(),
+                        project_owner_pubkey: wallet_key(*owner),
+                        candy_machine_id: candy_machine_key(*candy_machine),
+                        affiliate_account: affiliate_account.map(|key| key.pubkey()),
+                        discount_basis_points: *discount_basis_points,
+                        max_uses: *max_uses,
+                        max_uses_per_wallet: *max_uses_per_wallet,
+                        use_count: *use_count,
+                        starts_at: *starts_at,
+                        ends_at: *ends_at,
+                        created_at: 0,
+                    },
+                }).unwrap_or_default();
+                data.resize(data.len().max(PromoCodeAccountState::LENGTH), 0);
+                data
+            }
+            FuzzData::ProtocolStats {
+                is_initialized,
+                live_project_count,
+                live_affiliate_count,
+                total_rewards_redeemed_lamports,
+            } => borsh::to_vec(&ProtocolStatsAccountState {
+                discriminator: ProtocolStatsAccountState::DISCRIMINATOR.to_string(),
+                is_initialized: *is_initialized,
+                data_version: 0,
+                data: ProtocolStatsAccountStateData {
+                    live_project_count: *live_project_count,
+                    live_affiliate_count: *live_affiliate_count,
+                    registration_fees_collected_lamports: 0,
+                    total_rewards_redeemed_lamports: *total_rewards_redeemed_lamports,
+                    total_referred_volume_lamports: 0,
+                    created_at: 0,
+                    updated_at: 0,
+                },
+            }).unwrap(),
+            FuzzData::FeeDiscountConfig { is_initialized, tiers } => {
+                let mut data = borsh::to_vec(&FeeDiscountConfigAccountState {
+                    discriminator: FeeDiscountConfigAccountState::DISCRIMINATOR.to_string(),
+                    is_initialized: *is_initialized,
+                    data_version: 0,
+                    data: FeeDiscountConfigAccountStateData {
+                        created_at: 0,
+                        updated_at: 0,
+                        tiers: fee_discount_tiers(tiers),
+                    },
+                }).unwrap();
+                data.resize(data.len().max(FeeDiscountConfigAccountState::LENGTH), 0);
+                data
+            }
+            FuzzData::TokenAccount { mint, owner, amount } => {
+                let mut data = vec![0; spl_token::state::Account::LEN];
+                spl_token::state::Account::pack(spl_token::state::Account {
+                    mint: mint_key(*mint),
+                    owner: wallet_key(*owner),
+                    amount: *amount,
+                    delegate: COption::None,
+                    state: spl_token::state::AccountState::Initialized,
+                    is_native: COption::None,
+                    delegated_amount: 0,
+                    close_authority: COption::None,
+                }, &mut data).unwrap();
+                data
+            }
+            FuzzData::CandyMachine { v3, authority } => {
+                let authority_offset = match v3 {
+                    true => CANDY_MACHINE_V3_AUTHORITY_OFFSET,
+                    false => CANDY_MACHINE_V2_AUTHORITY_OFFSET,
+                };
+                let mut data = vec![0; authority_offset + 32];
+                data[..8].copy_from_slice(&CANDY_MACHINE_DISCRIMINATOR);
+                data[authority_offset..].copy_from_slice(wallet_key(*authority).as_ref());
+                data
+            }
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzAccount {
+    key: FuzzKey,
+    owner: FuzzOwner,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: u64,
+    data: FuzzData,
+}
+
+#[derive(Arbitrary, Debug)]
+enum FuzzInstruction {
+    Raw(Vec<u8>),
+    RegisterProject {
+        candy_machine: u8,
+        affiliate_fee_percentage: f64,
+        affiliate_target_in_sol: u8,
+        max_affiliate_count: u8,
+        title: String,
+    },
+    UpdateProject {
+        owner: u8,
+        candy_machine: u8,
+        affiliate_fee_percentage: f64,
+        affiliate_target_in_sol: u8,
+        max_affiliate_count: u8,
+        title: String,
+    },
+    CloseProject { owner: u8, candy_machine: u8 },
+    RegisterAffiliate { owner: u8, candy_machine: u8 },
+    RedeemReward { owner: u8, candy_machine: u8 },
+    CloseAffiliateAccount { affiliate: u8, owner: u8, candy_machine: u8 },
+    GetRedeemableAmount { affiliate: u8, owner: u8, candy_machine: u8 },
+    GetProjectCapacity { owner: u8, candy_machine: u8 },
+    // Picks one of `REFERRAL_CODES` unless `code` is given, which is then mostly invalid
+    ClaimReferralCode { owner: u8, candy_machine: u8, known_code: u8, code: Option<String> },
+    CreatePromoCode {
+        candy_machine: u8,
+        known_code: u8,
+        code: Option<String>,
+        affiliate: Option<u8>,
+        discount_basis_points: u16,
+        max_uses: u32,
+        max_uses_per_wallet: u16,
+        starts_at: i64,
+        ends_at: i64,
+    },
+    ConsumePromoCode { owner: u8, candy_machine: u8, known_code: u8, code: Option<String> },
+    SetProjectGating { candy_machine: u8, gating_mint: Option<u8>, gating_min_amount: u64, gating_burn: bool },
+    MintWithReferral { affiliate: u8, owner: u8, candy_machine: u8, mint_data: Vec<u8> },
+    InitializeProtocolStats {
+        live_project_count: u32,
+        live_affiliate_count: u32,
+        registration_fees_collected_lamports: u64,
+        total_rewards_redeemed_lamports: u64,
+        total_referred_volume_lamports: u64,
+    },
+    IndexAffiliate { affiliate: u8, owner: u8, candy_machine: u8 },
+    AddAffiliateToProfile { affiliate: u8, owner: u8, candy_machine: u8 },
+    LinkCandyMachineProject { owner: u8, candy_machine: u8 },
+    RegisterProjectV2 {
+        candy_machine: u8,
+        affiliate_fee_percentage: f64,
+        affiliate_target_in_sol: u8,
+        max_affiliate_count: u32,
+        title: String,
+    },
+    UpdateProjectV2 {
+        owner: u8,
+        candy_machine: u8,
+        affiliate_fee_percentage: f64,
+        affiliate_target_in_sol: u8,
+        max_affiliate_count: u32,
+        title: String,
+    },
+    SetFeeDiscountTiers { tiers: Vec<(u64, u16)> },
+}
+
+impl FuzzInstruction {
+    fn to_bytes(&self) -> Vec<u8> {
+        let instruction = match self {
+            FuzzInstruction::Raw(data) => return data.clone(),
+            FuzzInstruction::RegisterProject {
+                candy_machine,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => CandyMachineAffiliatesInstruction::RegisterProject {
+                candy_machine_id: candy_machine_key(*candy_machine),
+                affiliate_fee_percentage: not_nan(*affiliate_fee_percentage),
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            },
+            FuzzInstruction::UpdateProject {
+                owner,
+                candy_machine,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => CandyMachineAffiliatesInstruction::UpdateProject {
+                project_owner_pubkey: wallet_key(*owner),
+                candy_machine_id: candy_machine_key(*candy_machine),
+                affiliate_fee_percentage: not_nan(*affiliate_fee_percentage),
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            },
+            FuzzInstruction::CloseProject { owner, candy_machine } => CandyMachineAffiliatesInstruction::CloseProject {
+                project_owner_pubkey: wallet_key(*owner),
+                candy_machine_id: candy_machine_key(*candy_machine),
+            },
+            FuzzInstruction::RegisterAffiliate { owner, candy_machine } => CandyMachineAffiliatesInstruction::RegisterAffiliate {
+                project_owner_pubkey: wallet_key(*owner),
+                candy_machine_id: candy_machine_key(*candy_machine),
+            },
+            FuzzInstruction::RedeemReward { owner, candy_machine } => CandyMachineAffiliatesInstruction::RedeemReward {
+                project_owner_pubkey: wallet_key(*owner),
+                candy_machine_id: candy_machine_key(*candy_machine),
+            },
+            FuzzInstruction::CloseAffiliateAccount { affiliate, owner, candy_machine } => {
+                CandyMachineAffiliatesInstruction::CloseAffiliateAccount {
+                    affiliate_pubkey: wallet_key(*affiliate),
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                }
+            }
+            FuzzInstruction::GetRedeemableAmount { affiliate, owner, candy_machine } => {
+                CandyMachineAffiliatesInstruction::GetRedeemableAmount {
+                    affiliate_pubkey: wallet_key(*affiliate),
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                }
+            }
+            FuzzInstruction::GetProjectCapacity { owner, candy_machine } => CandyMachineAffiliatesInstruction::GetProjectCapacity {
+                project_owner_pubkey: wallet_key(*owner),
+                candy_machine_id: candy_machine_key(*candy_machine),
+            },
+            FuzzInstruction::ClaimReferralCode { owner, candy_machine, known_code, code } => {
+                CandyMachineAffiliatesInstruction::ClaimReferralCode {
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                    code: code.clone().unwrap_or_else(|| referral_code(*known_code).to_string()),
+                }
+            }
+            FuzzInstruction::CreatePromoCode {
+                candy_machine,
+                known_code,
+                code,
+                affiliate,
+                discount_basis_points,
+                max_uses,
+                max_uses_per_wallet,
+                starts_at,
+                ends_at,
+            } => CandyMachineAffiliatesInstruction::CreatePromoCode {
+                candy_machine_id: candy_machine_key(*candy_machine),
+                code: code.clone().unwrap_or_else(|| referral_code(*known_code).to_string()),
+                affiliate_pubkey: affiliate.map(wallet_key),
+                discount_basis_points: *discount_basis_points,
+                max_uses: *max_uses,
+                max_uses_per_wallet: *max_uses_per_wallet,
+                starts_at: *starts_at,
+                ends_at: *ends_at,
+            },
+            FuzzInstruction::ConsumePromoCode { owner, candy_machine, known_code, code } => {
+                CandyMachineAffiliatesInstruction::ConsumePromoCode {
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                    code: code.clone().unwrap_or_else(|| referral_code(*known_code).to_string()),
+                }
+            }
+            FuzzInstruction::SetProjectGating { candy_machine, gating_mint, gating_min_amount, gating_burn } => {
+                CandyMachineAffiliatesInstruction::SetProjectGating {
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                    gating_mint: gating_mint.map(mint_key),
+                    gating_min_amount: *gating_min_amount,
+                    gating_burn: *gating_burn,
+                }
+            }
+            FuzzInstruction::MintWithReferral { affiliate, owner, candy_machine, mint_data } => {
+                CandyMachineAffiliatesInstruction::MintWithReferral {
+                    affiliate_pubkey: wallet_key(*affiliate),
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                    mint_data: mint_data.clone(),
+                }
+            }
+            FuzzInstruction::InitializeProtocolStats {
+                live_project_count,
+                live_affiliate_count,
+                registration_fees_collected_lamports,
+                total_rewards_redeemed_lamports,
+                total_referred_volume_lamports,
+            } => CandyMachineAffiliatesInstruction::InitializeProtocolStats {
+                live_project_count: *live_project_count,
+                live_affiliate_count: *live_affiliate_count,
+                registration_fees_collected_lamports: *registration_fees_collected_lamports,
+                total_rewards_redeemed_lamports: *total_rewards_redeemed_lamports,
+                total_referred_volume_lamports: *total_referred_volume_lamports,
+            },
+            FuzzInstruction::IndexAffiliate { affiliate, owner, candy_machine } => {
+                CandyMachineAffiliatesInstruction::IndexAffiliate {
+                    affiliate_pubkey: wallet_key(*affiliate),
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                }
+            }
+            FuzzInstruction::AddAffiliateToProfile { affiliate, owner, candy_machine } => {
+                CandyMachineAffiliatesInstruction::AddAffiliateToProfile {
+                    affiliate_pubkey: wallet_key(*affiliate),
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                }
+            }
+            FuzzInstruction::LinkCandyMachineProject { owner, candy_machine } => {
+                CandyMachineAffiliatesInstruction::LinkCandyMachineProject {
+                    project_owner_pubkey: wallet_key(*owner),
+                    candy_machine_id: candy_machine_key(*candy_machine),
+                }
+            }
+            FuzzInstruction::RegisterProjectV2 {
+                candy_machine,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => CandyMachineAffiliatesInstruction::RegisterProjectV2 {
+                candy_machine_id: candy_machine_key(*candy_machine),
+                affiliate_fee_percentage: not_nan(*affiliate_fee_percentage),
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            },
+            FuzzInstruction::UpdateProjectV2 {
+                owner,
+                candy_machine,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => CandyMachineAffiliatesInstruction::UpdateProjectV2 {
+                project_owner_pubkey: wallet_key(*owner),
+                candy_machine_id: candy_machine_key(*candy_machine),
+                affiliate_fee_percentage: not_nan(*affiliate_fee_percentage),
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            },
+            FuzzInstruction::SetFeeDiscountTiers { tiers } => CandyMachineAffiliatesInstruction::SetFeeDiscountTiers {
+                tiers: fee_discount_tiers(tiers),
+            },
+        };
+
+        instruction.pack()
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzInput {
+    unix_timestamp: i64,
+    instruction: FuzzInstruction,
+    accounts: Vec<FuzzAccount>,
+}
+
+struct FuzzSyscallStubs;
+
+impl SyscallStubs for FuzzSyscallStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: UNIX_TIMESTAMP.load(Ordering::Relaxed),
+            ..Clock::default()
+        };
+
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Clock, clock) };
+
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Rent, Rent::default()) };
+
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id != SYSTEM_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+
+        for account_meta in instruction.accounts.iter() {
+            let account_info = account_infos
+                .iter()
+                .find(|account_info| account_info.key == &account_meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            // A CPI can only sign for accounts signing the outer instruction or for PDAs of the caller
+            let is_pda_signer = signers_seeds.iter().any(|seeds| {
+                Pubkey::create_program_address(seeds, &candy_machine_affiliates::id())
+                    .map_or(false, |pda| pda == account_meta.pubkey)
+            });
+
+            if account_meta.is_signer && !account_info.is_signer && !is_pda_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+
+            if account_meta.is_writable && !account_info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            accounts.push(account_info);
+        }
+
+        let system_instruction = limited_deserialize::<SystemInstruction>(&instruction.data, 1232)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        match system_instruction {
+            SystemInstruction::Transfer { lamports } => {
+                let [from, to] = accounts[..] else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+
+                if from.owner != &SYSTEM_PROGRAM_ID || !from.data_is_empty() {
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                debit(from, lamports)?;
+                credit(to, lamports)
+            }
+            SystemInstruction::CreateAccount { lamports, space, owner } => {
+                let [from, to] = accounts[..] else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+
+                if from.owner != &SYSTEM_PROGRAM_ID || !from.data_is_empty() {
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                if to.lamports() != 0 || !to.data_is_empty() || to.owner != &SYSTEM_PROGRAM_ID {
+                    return Err(ProgramError::AccountAlreadyInitialized);
+                }
+
+                if space as usize > MAX_PERMITTED_DATA_INCREASE {
+                    return Err(ProgramError::InvalidRealloc);
+                }
+
+                debit(from, lamports)?;
+                credit(to, lamports)?;
+
+                // Every account buffer is allocated with MAX_PERMITTED_DATA_INCREASE spare zeroed bytes
+                let mut data = to.try_borrow_mut_data()?;
+                let data_ptr = data.as_mut_ptr();
+                *data = unsafe { std::slice::from_raw_parts_mut(data_ptr, space as usize) };
+                drop(data);
+
+                to.assign(&owner);
+
+                Ok(())
+            }
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+// Mirrors the runtime input around each key, `AccountInfo::realloc` reads the original data
+// length from the four bytes before the key
+#[repr(C)]
+struct SerializedKey {
+    original_data_len: u32,
+    key: Pubkey,
+}
+
+fn debit(account_info: &AccountInfo, lamports: u64) -> ProgramResult {
+    let mut account_lamports = account_info.try_borrow_mut_lamports()?;
+    **account_lamports = account_lamports.checked_sub(lamports).ok_or(ProgramError::InsufficientFunds)?;
+
+    Ok(())
+}
+
+fn credit(account_info: &AccountInfo, lamports: u64) -> ProgramResult {
+    let mut account_lamports = account_info.try_borrow_mut_lamports()?;
+    **account_lamports = account_lamports.checked_add(lamports).ok_or(ProgramError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+fuzz_target!(|input: FuzzInput| {
+    static SET_STUBS: Once = Once::new();
+    SET_STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(FuzzSyscallStubs));
+    });
+
+    UNIX_TIMESTAMP.store(input.unix_timestamp, Ordering::Relaxed);
+
+    let instruction_data = input.instruction.to_bytes();
+    let fuzz_accounts = &input.accounts[..input.accounts.len().min(MAX_ACCOUNTS)];
+
+    // The runtime passes a single account once even when it is referenced several times,
+    // so backing storage is allocated per unique key
+    let mut unique_accounts: Vec<(&FuzzAccount, Pubkey)> = vec![];
+    let mut account_indexes = Vec::with_capacity(fuzz_accounts.len());
+
+    for fuzz_account in fuzz_accounts {
+        let key = fuzz_account.key.pubkey();
+
+        match unique_accounts.iter().position(|(_, unique_key)| *unique_key == key) {
+            Some(index) => account_indexes.push(index),
+            None => {
+                account_indexes.push(unique_accounts.len());
+                unique_accounts.push((fuzz_account, key));
+            }
+        }
+    }
+
+    let data: Vec<Vec<u8>> = unique_accounts.iter().map(|(account, _)| account.data.to_bytes()).collect();
+    let keys: Vec<SerializedKey> = unique_accounts
+        .iter()
+        .zip(data.iter())
+        .map(|((_, key), data)| SerializedKey { original_data_len: data.len() as u32, key: *key })
+        .collect();
+    let mut owners: Vec<Pubkey> = unique_accounts.iter().map(|(account, _)| account.owner.pubkey()).collect();
+    let mut lamports: Vec<u64> = unique_accounts
+        .iter()
+        .map(|(account, _)| account.lamports % MAX_ACCOUNT_LAMPORTS)
+        .collect();
+    // Like the runtime input, the data is preceded by its u64 length, which `AccountInfo::realloc`
+    // updates, and followed by MAX_PERMITTED_DATA_INCREASE spare zeroed bytes
+    let mut buffers: Vec<Vec<u64>> = data
+        .iter()
+        .map(|data| {
+            let mut buffer = vec![0u64; 1 + (data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8)];
+            buffer[0] = data.len() as u64;
+            unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr().add(1) as *mut u8, data.len()) }
+                .copy_from_slice(data);
+            buffer
+        })
+        .collect();
+
+    let program_id = candy_machine_affiliates::id();
+
+    let unique_account_infos: Vec<AccountInfo> = keys
+        .iter()
+        .zip(owners.iter_mut())
+        .zip(lamports.iter_mut())
+        .zip(buffers.iter_mut())
+        .zip(unique_accounts.iter())
+        .zip(data.iter())
+        .map(|(((((key, owner), lamports), buffer), (fuzz_account, _)), data)| AccountInfo {
+            key: &key.key,
+            is_signer: fuzz_account.is_signer,
+            is_writable: fuzz_account.is_writable,
+            lamports: Rc::new(RefCell::new(lamports)),
+            data: Rc::new(RefCell::new(unsafe {
+                std::slice::from_raw_parts_mut(buffer.as_mut_ptr().add(1) as *mut u8, data.len())
+            })),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        })
+        .collect();
+
+    let account_infos: Vec<AccountInfo> = account_indexes
+        .iter()
+        .map(|index| unique_account_infos[*index].clone())
+        .collect();
+
+    let lamports_before: u128 = unique_account_infos.iter().map(|account_info| account_info.lamports() as u128).sum();
+
+    let result = processor::process_instruction(&program_id, &account_infos, &instruction_data);
+
+    if result.is_ok() {
+        let lamports_after: u128 = unique_account_infos.iter().map(|account_info| account_info.lamports() as u128).sum();
+
+        assert_eq!(lamports_before, lamports_after, "Lamports were created or destroyed by {:?}", input);
+    }
+});
@@ -0,0 +1,144 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use borsh::BorshSerialize;
+use crate::affiliates::state::AffiliateAccountState;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{AffiliateIndexed, CandyMachineAffiliatesEvent};
+use crate::projects::state::ProjectAccountState;
+use crate::utils::{append_to_affiliate_index, grow_account, validate_client_pda};
+
+// Appends an affiliate registered before the affiliate index existed to the index of its project.
+// Anyone may index an affiliate, the signer pays for the page created and the grown accounts.
+#[allow(unused_variables)]
+pub fn index_affiliate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> ProgramResult {
+    log_debug!("Indexing affiliate...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let affiliate_pda_account = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let affiliate_index_page_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if affiliate_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let (client_affiliate_pda_is_valid, _) = validate_client_pda(
+        affiliate_pda_account,
+        &[
+            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+            affiliate_pubkey.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_affiliate_pda_is_valid {
+        log_error!("Invalid seeds for affiliate PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_project_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_project_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    log_debug!("Unpacking affiliate account state");
+    let mut affiliate_account_state = AffiliateAccountState::unpack_account_data(&affiliate_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed affiliate account data");
+
+    if !affiliate_account_state.is_initialized() {
+        log_error!("Affiliate account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    if affiliate_account_state.data.affiliate_index_position.is_some() {
+        log_error!("Affiliate already indexed");
+        return Err(CandyMachineAffiliatesError::AffiliateAlreadyIndexed.into());
+    }
+
+    log_debug!("Unpacking project state account");
+    let mut project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed project account data");
+
+    if !project_account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    let affiliate_index_position = append_to_affiliate_index(
+        affiliate_index_page_account,
+        project_pda_account,
+        &mut project_account_state,
+        affiliate_pda_account.key,
+        initializer,
+        system_program,
+        program_id,
+    )?;
+    affiliate_account_state.data.affiliate_index_position = Some(affiliate_index_position);
+
+    // Both accounts predate the index and may be smaller than the current layouts
+    grow_account(affiliate_pda_account, AffiliateAccountState::LENGTH, initializer, system_program)?;
+    grow_account(project_pda_account, ProjectAccountState::LENGTH, initializer, system_program)?;
+
+    log_debug!("Updating affiliate state account");
+    affiliate_account_state.serialize(&mut &mut affiliate_pda_account.data.borrow_mut()[..])?;
+    log_debug!("Updating project state account");
+    project_account_state.serialize(&mut &mut project_pda_account.data.borrow_mut()[..])?;
+    log_debug!("State accounts serialized");
+
+    log_info!("Affiliate indexed.");
+
+    CandyMachineAffiliatesEvent::AffiliateIndexed(AffiliateIndexed {
+        affiliate_account: *affiliate_pda_account.key,
+        project: *project_pda_account.key,
+        affiliate_index_page_account: *affiliate_index_page_account.key,
+        position: affiliate_index_position,
+    }).emit();
+
+    Ok(())
+}
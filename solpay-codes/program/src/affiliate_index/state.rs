@@ -0,0 +1,63 @@
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+
+pub const AFFILIATE_INDEX_PAGE_ACCOUNT_DATA_VERSION: u8 = 0;
+pub const AFFILIATE_INDEX_PAGE_SIZE: usize = 32;
+
+// Fixed-size page of the affiliate accounts of a project, derived from the project and the page number.
+// Affiliates are appended in registration order, entry `n` of the index lives at slot
+// `n % AFFILIATE_INDEX_PAGE_SIZE` of page `n / AFFILIATE_INDEX_PAGE_SIZE`. Closing an affiliate
+// clears its slot to the default pubkey, slots are never reused. Closing the project closes the pages
+// passed to it, the others are cleared when the project is registered again.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AffiliateIndexPageAccountStateData {
+    pub project: Pubkey,
+    pub page: u32,
+    pub affiliate_accounts: [Pubkey; AFFILIATE_INDEX_PAGE_SIZE],
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AffiliateIndexPageAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: AffiliateIndexPageAccountStateData,
+}
+
+impl Sealed for AffiliateIndexPageAccountState {}
+
+impl IsInitialized for AffiliateIndexPageAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl AffiliateIndexPageAccountState {
+    pub const DISCRIMINATOR: &'static str = "affiliate_index_page";
+    pub const LENGTH: usize = (4 + AffiliateIndexPageAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // project
+        + 32
+        // page
+        + 4
+        // affiliate_accounts
+        + 32 * AFFILIATE_INDEX_PAGE_SIZE
+    ;
+
+    // Affiliate accounts still listed on the page
+    pub fn live_affiliate_accounts(&self) -> impl Iterator<Item = &Pubkey> {
+        self.data.affiliate_accounts.iter().filter(|account| **account != Pubkey::default())
+    }
+}
+
+// Page of the affiliate index holding the entry at `position`
+pub fn affiliate_index_page(position: u32) -> u32 {
+    position / AFFILIATE_INDEX_PAGE_SIZE as u32
+}
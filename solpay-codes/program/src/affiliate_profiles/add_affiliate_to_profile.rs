@@ -0,0 +1,119 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use crate::affiliates::state::AffiliateAccountState;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{AffiliateAddedToProfile, CandyMachineAffiliatesEvent};
+use crate::projects::state::ProjectAccountState;
+use crate::utils::{add_to_affiliate_profile, validate_client_pda};
+
+// Lists the project of an affiliate account opened before affiliate profiles existed in the profile
+// of its wallet, along with the commission it earned so far.
+// Anyone may add an affiliate, the signer pays for the profile created or grown.
+#[allow(unused_variables)]
+pub fn add_affiliate_to_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> ProgramResult {
+    log_debug!("Adding affiliate to profile...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let affiliate_pda_account = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let affiliate_profile_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if affiliate_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let (client_affiliate_pda_is_valid, _) = validate_client_pda(
+        affiliate_pda_account,
+        &[
+            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+            affiliate_pubkey.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_affiliate_pda_is_valid {
+        log_error!("Invalid seeds for affiliate PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_project_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_project_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    log_debug!("Unpacking affiliate account state");
+    let affiliate_account_state = AffiliateAccountState::unpack_account_data(&affiliate_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed affiliate account data");
+
+    if !affiliate_account_state.is_initialized() {
+        log_error!("Affiliate account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    add_to_affiliate_profile(
+        affiliate_profile_account,
+        &affiliate_pubkey,
+        project_pda_account.key,
+        affiliate_account_state.data.total_commission_lamports,
+        initializer,
+        system_program,
+        program_id,
+    )?;
+
+    log_info!("Affiliate added to profile.");
+
+    CandyMachineAffiliatesEvent::AffiliateAddedToProfile(AffiliateAddedToProfile {
+        affiliate_profile_account: *affiliate_profile_account.key,
+        affiliate_pubkey,
+        affiliate_account: *affiliate_pda_account.key,
+        project: *project_pda_account.key,
+        commission_lamports: affiliate_account_state.data.total_commission_lamports,
+    }).emit();
+
+    Ok(())
+}
@@ -0,0 +1,2 @@
+pub mod state;
+pub mod add_affiliate_to_profile;
@@ -0,0 +1,57 @@
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+
+pub const AFFILIATE_PROFILE_ACCOUNT_DATA_VERSION: u8 = 0;
+
+// Affiliations of a wallet, derived from the wallet alone so it can be found without knowing its projects.
+// Lists the project PDAs the wallet has an open affiliate account in, in registration order, and the
+// commission earned across all of them, including projects it has left since.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AffiliateProfileAccountStateData {
+    pub affiliate_pubkey: Pubkey,
+    pub total_commission_lamports: u64,
+    pub project_accounts: Vec<Pubkey>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct AffiliateProfileAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: AffiliateProfileAccountStateData,
+}
+
+impl Sealed for AffiliateProfileAccountState {}
+
+impl IsInitialized for AffiliateProfileAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl AffiliateProfileAccountState {
+    pub const DISCRIMINATOR: &'static str = "affiliate_profile";
+    // Length of a profile listing no project
+    pub const LENGTH: usize = (4 + AffiliateProfileAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // affiliate_pubkey
+        + 32
+        // total_commission_lamports
+        + 8
+        // project_accounts
+        + 4
+    ;
+
+    // Length of a profile listing `project_count` projects, the account grows with each registration
+    // and keeps its size when the wallet leaves a project
+    pub fn length(project_count: usize) -> usize {
+        AffiliateProfileAccountState::LENGTH + 32 * project_count
+    }
+}
@@ -2,24 +2,27 @@ use std::str::FromStr;
 use solana_program::{
     account_info::{AccountInfo, next_account_info},
     entrypoint::ProgramResult,
-    msg,
-    system_instruction,
-    borsh::try_from_slice_unchecked,
-    program::invoke_signed,
+    borsh1::try_from_slice_unchecked,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
     program_pack::IsInitialized,
     system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{clock::Clock, Sysvar},
 };
 use borsh::BorshSerialize;
-use solana_program::native_token::LAMPORTS_PER_SOL;
-use solana_program::program::invoke;
-use crate::affiliates::state::{AFFILIATE_ACCOUNT_DATA_VERSION, AffiliateAccountState};
+use crate::affiliates::state::AffiliateAccountState;
 use crate::error::CandyMachineAffiliatesError;
+use crate::events::{AffiliateClosedV2, CandyMachineAffiliatesEvent, ReferralCodeReleased};
 use crate::{ADMIN_PUBKEY, SOLPAY_TREASURY_PUBKEY};
 use crate::projects::state::ProjectAccountState;
-use crate::utils::validate_client_pda;
+use crate::referral_codes::state::ReferralCodeAccountState;
+use crate::utils::{
+    grow_account,
+    remove_from_affiliate_index,
+    remove_from_affiliate_profile,
+    unpack_protocol_stats,
+    validate_client_pda,
+};
 
 #[allow(unused_variables)]
 pub fn close_affiliate_account(
@@ -29,7 +32,7 @@ pub fn close_affiliate_account(
     project_owner_pubkey: Pubkey,
     candy_machine_id: Pubkey,
 ) -> ProgramResult {
-    msg!("Closing affiliate account...");
+    log_debug!("Closing affiliate account...");
 
     // Get Account iterator
     let account_info_iter = &mut accounts.iter();
@@ -39,21 +42,35 @@ pub fn close_affiliate_account(
     let pda_account = next_account_info(account_info_iter)?;
     let project_pda_account = next_account_info(account_info_iter)?;
     let solpay_treasury = next_account_info(account_info_iter)?;
+    let protocol_stats_account = next_account_info(account_info_iter)?;
+    // Page of the affiliate index listing the affiliate, not used for affiliates registered before
+    // the index which may pass the program id instead
+    let affiliate_index_page_account = next_account_info(account_info_iter)?;
+    // Profile of the affiliate wallet, left as is when the wallet has none or it does not list the project
+    let affiliate_profile_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    // Referral code account of the affiliate, released along with it, required when the affiliate records
+    // its code and optional for affiliates that claimed one before codes were recorded
+    let referral_code_pda_account = next_account_info(account_info_iter).ok();
 
     // Validate accounts
 
     if !initializer.is_signer {
-        msg!("Missing required signature");
+        log_error!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
     let admin_pubkey = Pubkey::from_str(ADMIN_PUBKEY).expect("Pubkey conversion failed");
 
     if initializer.key.ne(&admin_pubkey) {
-        msg!("Action not allowed");
+        log_error!("Action not allowed");
         return Err(CandyMachineAffiliatesError::ActionNotAllowed.into());
     }
 
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
     if pda_account.owner != program_id {
         return Err(ProgramError::IllegalOwner)
     }
@@ -70,7 +87,7 @@ pub fn close_affiliate_account(
     );
 
     if !client_pda_is_valid {
-        msg!("Invalid seeds for PDA");
+        log_error!("Invalid seeds for PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
@@ -85,43 +102,165 @@ pub fn close_affiliate_account(
     );
 
     if !client_pda_is_valid {
-        msg!("Invalid seeds for PDA");
+        log_error!("Invalid seeds for PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
     // Validate treasury account
-    let solpay_treasury_pubkey = Pubkey::from_str(&SOLPAY_TREASURY_PUBKEY).expect("Pubkey conversion failed");
+    let solpay_treasury_pubkey = Pubkey::from_str(SOLPAY_TREASURY_PUBKEY).expect("Pubkey conversion failed");
 
     if solpay_treasury_pubkey.ne(solpay_treasury.key) {
-        msg!("Incorrect treasury account");
+        log_error!("Incorrect treasury account");
         return Err(CandyMachineAffiliatesError::IncorrectTreasuryAccount.into());
     }
 
-    msg!("Unpacking affiliate account state");
-    let affiliate_account_state = try_from_slice_unchecked::<AffiliateAccountState>(&pda_account.data.borrow()).unwrap();
-    msg!("Borrowed affiliate account data");
+    let mut protocol_stats_state = unpack_protocol_stats(protocol_stats_account, program_id)?;
+
+    log_debug!("Unpacking affiliate account state");
+    let affiliate_account_state = AffiliateAccountState::unpack_account_data(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed affiliate account data");
 
-    msg!("Checking if affiliate account is initialized");
+    log_debug!("Checking if affiliate account is initialized");
     if !affiliate_account_state.is_initialized() {
-        msg!("Affiliate account not initialized");
+        log_error!("Affiliate account not initialized");
         return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
     }
 
-    **solpay_treasury.try_borrow_mut_lamports()? += pda_account.lamports();
+    if affiliate_account_state.data.referral_code.is_some() && referral_code_pda_account.is_none() {
+        log_error!("Missing referral code account of the affiliate");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let referral_code_account_state = match referral_code_pda_account {
+        Some(referral_code_pda_account) => {
+            if referral_code_pda_account.owner != program_id {
+                return Err(ProgramError::IllegalOwner)
+            }
+
+            if let Some(referral_code) = &affiliate_account_state.data.referral_code {
+                let (client_pda_is_valid, _) = validate_client_pda(
+                    referral_code_pda_account,
+                    &[
+                        ReferralCodeAccountState::DISCRIMINATOR.as_ref(),
+                        project_pda_account.key.as_ref(),
+                        referral_code.as_ref(),
+                    ],
+                    program_id
+                );
+
+                if !client_pda_is_valid {
+                    log_error!("Invalid seeds for referral code PDA");
+                    return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+                }
+            }
+
+            log_debug!("Unpacking referral code account state");
+            let referral_code_account_state = try_from_slice_unchecked::<ReferralCodeAccountState>(&referral_code_pda_account.data.borrow())
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+
+            if referral_code_account_state.discriminator != ReferralCodeAccountState::DISCRIMINATOR {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if !referral_code_account_state.is_initialized() {
+                log_error!("Referral code account not initialized");
+                return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+            }
+
+            if referral_code_account_state.data.affiliate_account.ne(pda_account.key) {
+                log_error!("Referral code belongs to another affiliate");
+                return Err(CandyMachineAffiliatesError::ActionNotAllowed.into());
+            }
+
+            Some(referral_code_account_state)
+        }
+        None => None,
+    };
+
+    // Remove the affiliate from the affiliate index of the project
+    if let Some(affiliate_index_position) = affiliate_account_state.data.affiliate_index_position {
+        remove_from_affiliate_index(
+            affiliate_index_page_account,
+            project_pda_account,
+            affiliate_index_position,
+            pda_account.key,
+            program_id,
+        )?;
+    }
+
+    // Remove the project from the profile of the affiliate wallet
+    remove_from_affiliate_profile(affiliate_profile_account, &affiliate_pubkey, project_pda_account.key, program_id)?;
+
+    if let Some(protocol_stats_state) = &mut protocol_stats_state {
+        // Saturating, the starting count of a stats account may miss affiliates registered before it
+        protocol_stats_state.data.live_affiliate_count = protocol_stats_state.data.live_affiliate_count.saturating_sub(1);
+        protocol_stats_state.data.updated_at = Clock::get()?.unix_timestamp;
+
+        log_debug!("Serializing protocol stats account");
+        protocol_stats_state.serialize(&mut &mut protocol_stats_account.data.borrow_mut()[..])?;
+        log_debug!("Protocol stats account serialized");
+    }
+
+    let treasury_lamports = pda_account.lamports();
+    let solpay_treasury_lamports = solpay_treasury.lamports()
+        .checked_add(pda_account.lamports())
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    **solpay_treasury.try_borrow_mut_lamports()? = solpay_treasury_lamports;
     **pda_account.try_borrow_mut_lamports()? = 0;
     *pda_account.try_borrow_mut_data()? = &mut [];
 
-    msg!("Affiliate account closed.");
+    // Release the referral code so another affiliate of the project can claim it
+    let mut referral_code_treasury_lamports = 0;
+
+    if let Some(referral_code_pda_account) = referral_code_pda_account {
+        referral_code_treasury_lamports = referral_code_pda_account.lamports();
+        let solpay_treasury_lamports = solpay_treasury.lamports()
+            .checked_add(referral_code_treasury_lamports)
+            .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
 
-    msg!("Unpacking project state account");
-    let mut project_account_state = try_from_slice_unchecked::<ProjectAccountState>(&project_pda_account.data.borrow()).unwrap();
-    msg!("Borrowed project account data");
+        **solpay_treasury.try_borrow_mut_lamports()? = solpay_treasury_lamports;
+        **referral_code_pda_account.try_borrow_mut_lamports()? = 0;
+        *referral_code_pda_account.try_borrow_mut_data()? = &mut [];
+    }
+
+    log_info!("Affiliate account closed.");
+
+    log_debug!("Unpacking project state account");
+    let mut project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed project account data");
+
+    project_account_state.data.affiliate_count = project_account_state.data.affiliate_count
+        .checked_sub(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
 
-    project_account_state.data.affiliate_count -= 1;
+    // Projects registered before the affiliate counts were widened are smaller than the current layout,
+    // the admin pays the extra rent
+    grow_account(project_pda_account, ProjectAccountState::LENGTH, initializer, system_program)?;
 
-    msg!("Updating project state account");
+    log_debug!("Updating project state account");
     project_account_state.serialize(&mut &mut project_pda_account.data.borrow_mut()[..])?;
-    msg!("State account serialized");
+    log_debug!("State account serialized");
+
+    CandyMachineAffiliatesEvent::AffiliateClosedV2(AffiliateClosedV2 {
+        affiliate_account: *pda_account.key,
+        affiliate_pubkey: affiliate_account_state.data.affiliate_pubkey,
+        project: *project_pda_account.key,
+        treasury_lamports,
+        affiliate_count: project_account_state.data.affiliate_count,
+    }).emit();
+
+    if let (Some(referral_code_pda_account), Some(referral_code_account_state)) = (referral_code_pda_account, referral_code_account_state) {
+        CandyMachineAffiliatesEvent::ReferralCodeReleased(ReferralCodeReleased {
+            referral_code_account: *referral_code_pda_account.key,
+            code: referral_code_account_state.data.code,
+            affiliate_account: *pda_account.key,
+            project: *project_pda_account.key,
+            treasury_lamports: referral_code_treasury_lamports,
+        }).emit();
+    }
 
     Ok(())
 }
@@ -0,0 +1,98 @@
+use std::str::FromStr;
+use solana_program::account_info::AccountInfo;
+use solana_program::borsh1::try_from_slice_unchecked;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::IsInitialized;
+use solana_program::pubkey::Pubkey;
+use crate::{AFFILIATE_REGISTRATION_FEE, SPAF_MINT_PUBKEY};
+use crate::error::CandyMachineAffiliatesError;
+use crate::fee_discount_config::state::{FeeDiscountConfigAccountState, FeeDiscountTier, MAX_FEE_DISCOUNT_TIERS};
+use crate::promo_codes::state::MAX_DISCOUNT_BASIS_POINTS;
+use crate::utils::{unpack_token_account, validate_client_pda};
+
+// At most `MAX_FEE_DISCOUNT_TIERS` tiers, by strictly increasing balance, each discounting at most the whole fee
+pub fn is_valid_fee_discount_schedule(tiers: &[FeeDiscountTier]) -> bool {
+    tiers.len() <= MAX_FEE_DISCOUNT_TIERS
+        && tiers.iter().all(|tier| tier.discount_basis_points <= MAX_DISCOUNT_BASIS_POINTS)
+        && tiers.windows(2).all(|pair| pair[0].min_spaf_balance < pair[1].min_spaf_balance)
+}
+
+// Discount of the highest tier reached by a SPAF balance, 0 below the first tier
+pub fn fee_discount_basis_points(tiers: &[FeeDiscountTier], spaf_balance: u64) -> u16 {
+    tiers
+        .iter()
+        .filter(|tier| spaf_balance >= tier.min_spaf_balance)
+        .map(|tier| tier.discount_basis_points.min(MAX_DISCOUNT_BASIS_POINTS))
+        .max()
+        .unwrap_or(0)
+}
+
+// Affiliate registration fee paid by a wallet holding `spaf_balance`
+pub fn registration_fee(tiers: &[FeeDiscountTier], spaf_balance: u64) -> u64 {
+    let discount = AFFILIATE_REGISTRATION_FEE as u128 * fee_discount_basis_points(tiers, spaf_balance) as u128
+        / MAX_DISCOUNT_BASIS_POINTS as u128;
+
+    AFFILIATE_REGISTRATION_FEE - discount as u64
+}
+
+// Tiers of the fee discount config account after checking it is the config PDA, none until
+// `SetFeeDiscountTiers` creates it
+fn fee_discount_tiers(fee_discount_config_account: &AccountInfo, program_id: &Pubkey) -> Result<Vec<FeeDiscountTier>, ProgramError> {
+    let (client_pda_is_valid, _) = validate_client_pda(
+        fee_discount_config_account,
+        &[FeeDiscountConfigAccountState::DISCRIMINATOR.as_ref()],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for fee discount config PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    if fee_discount_config_account.owner != program_id {
+        log_debug!("Fee discount config account not created, no discount");
+        return Ok(Vec::new());
+    }
+
+    let config_state = try_from_slice_unchecked::<FeeDiscountConfigAccountState>(&fee_discount_config_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !config_state.is_initialized() {
+        log_error!("Fee discount config account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    Ok(config_state.data.tiers)
+}
+
+// Affiliate registration fee paid by `wallet`, discounted by the balance of `spaf_token_account` following
+// the tiers of `fee_discount_config_account` when both are given. The token account must be a SPAF token
+// account of the wallet.
+pub fn wallet_registration_fee(
+    spaf_token_account: Option<&AccountInfo>,
+    fee_discount_config_account: Option<&AccountInfo>,
+    wallet: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    if let (Some(spaf_mint_pubkey), Some(spaf_token_account)) = (SPAF_MINT_PUBKEY, spaf_token_account) {
+        let spaf_mint_pubkey = Pubkey::from_str(spaf_mint_pubkey).expect("Pubkey conversion failed");
+        let token_account = unpack_token_account(spaf_token_account)?;
+
+        if token_account.owner.ne(wallet) || token_account.mint.ne(&spaf_mint_pubkey) {
+            log_error!("Invalid SPAF token account");
+            return Err(CandyMachineAffiliatesError::InvalidSpafTokenAccount.into());
+        }
+
+        let tiers = match fee_discount_config_account {
+            Some(fee_discount_config_account) => fee_discount_tiers(fee_discount_config_account, program_id)?,
+            None => {
+                log_debug!("Fee discount config account not passed, no discount");
+                Vec::new()
+            }
+        };
+
+        return Ok(registration_fee(&tiers, token_account.amount));
+    }
+
+    Ok(AFFILIATE_REGISTRATION_FEE)
+}
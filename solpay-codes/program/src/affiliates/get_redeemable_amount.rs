@@ -0,0 +1,111 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program_pack::IsInitialized
+};
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use crate::affiliates::state::AffiliateAccountState;
+use crate::error::CandyMachineAffiliatesError;
+use crate::projects::state::ProjectAccountState;
+use crate::utils::{redeemable_affiliate_lamports, validate_client_pda};
+use crate::views::{return_view, RedeemableAmount};
+
+// Read-only counterpart of `redeem_reward`, returns what redeeming would pay right now
+pub fn get_redeemable_amount(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey
+) -> ProgramResult {
+    log_debug!("Getting redeemable amount...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let affiliate_pda_account = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+
+    if affiliate_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let (client_affiliate_pda_is_valid, _) = validate_client_pda(
+        affiliate_pda_account,
+        &[
+            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+            affiliate_pubkey.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_affiliate_pda_is_valid {
+        log_error!("Invalid seeds for affiliate PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_project_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_project_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    log_debug!("Unpacking affiliate account state");
+    let affiliate_account_state = AffiliateAccountState::unpack_account_data(&affiliate_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !affiliate_account_state.is_initialized() {
+        log_error!("Affiliate account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    log_debug!("Unpacking project account state");
+    let project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !project_account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    if affiliate_account_state.data.project_owner_pubkey.ne(&project_account_state.data.project_owner_pubkey)
+        && affiliate_account_state.data.candy_machine_id.ne(&project_account_state.data.candy_machine_id) {
+        log_error!("Mismatched accounts when redeeming reward");
+        return Err(CandyMachineAffiliatesError::RewardRedeemMismatchedAccounts.into());
+    }
+
+    let reward_lamports = project_account_state.data.affiliate_target_in_sol as u64 * LAMPORTS_PER_SOL;
+    let affiliate_balance_lamports = affiliate_pda_account.lamports();
+    let redeemable_lamports = if redeemable_affiliate_lamports(affiliate_pda_account)? < reward_lamports { 0 } else { reward_lamports };
+
+    log_info!("Redeemable amount: {} lamports.", redeemable_lamports);
+
+    return_view(&RedeemableAmount {
+        affiliate_balance_lamports,
+        reward_lamports,
+        redeemable_lamports,
+        total_redeemed_amount_in_sol: affiliate_account_state.data.total_redeemed_amount_in_sol,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use crate::SPAF_MILESTONE_REWARDS;
+
+// Seed of the PDA holding the mint authority of the SPAF mint
+pub const SPAF_MINT_AUTHORITY_SEED: &str = "spaf_mint_authority";
+
+// SPAF base units earned by raising total_redeemed_amount_in_sol from `previous_total` to `total`
+pub fn milestone_reward(previous_total: u32, total: u32) -> u64 {
+    SPAF_MILESTONE_REWARDS
+        .iter()
+        .filter(|(milestone, _)| previous_total < *milestone && *milestone <= total)
+        .fold(0, |reward, (_, amount)| reward.saturating_add(*amount))
+}
@@ -0,0 +1,315 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program_pack::IsInitialized,
+    system_instruction,
+    system_program,
+    sysvar::{clock::Clock, Sysvar},
+};
+use borsh::BorshSerialize;
+use crate::affiliates::state::AffiliateAccountState;
+use crate::candy_machine::{
+    candy_guard_mint_v2_args,
+    candy_guard_sol_payment_destination,
+    candy_machine_v2_wallet,
+    candy_machine_v3_mint_authority,
+    CANDY_GUARD_MINT_CANDY_GUARD_INDEX,
+    CANDY_GUARD_MINT_CANDY_MACHINE_INDEX,
+    CANDY_GUARD_MINT_CANDY_MACHINE_PROGRAM_INDEX,
+    CANDY_GUARD_PROGRAM_ID,
+    CANDY_MACHINE_V2_MINT_CANDY_MACHINE_INDEX,
+    CANDY_MACHINE_V2_MINT_NFT_DISCRIMINATOR,
+    CANDY_MACHINE_V2_MINT_WALLET_INDEX,
+    CANDY_MACHINE_V2_PROGRAM_ID,
+    CANDY_MACHINE_V3_PROGRAM_ID,
+};
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, MintReferred};
+use crate::projects::state::ProjectAccountState;
+use crate::utils::{credit_affiliate_profile, grow_account, unpack_protocol_stats, validate_client_pda};
+
+// Basis points of the whole price, a 100% fee
+const MAX_FEE_BASIS_POINTS: u64 = 10_000;
+
+#[allow(unused_variables)]
+pub fn mint_with_referral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    mint_data: Vec<u8>,
+) -> ProgramResult {
+    log_debug!("Minting with referral...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let buyer = next_account_info(account_info_iter)?;
+    let affiliate_pda_account = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let candy_machine_account = next_account_info(account_info_iter)?;
+    // Wallet receiving the mint price, paying the commission out of it
+    let payment_wallet = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let candy_machine_program = next_account_info(account_info_iter)?;
+    let protocol_stats_account = next_account_info(account_info_iter)?;
+    // Profile of the affiliate wallet credited with the commission when it lists the project
+    let affiliate_profile_account = next_account_info(account_info_iter)?;
+    // Accounts of the Candy Machine mint instruction, forwarded as given
+    let mint_accounts = account_info_iter.as_slice();
+
+    // Validate accounts
+
+    if !buyer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if !payment_wallet.is_signer {
+        log_error!("Missing payment wallet signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if affiliate_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    if system_program_account.key.ne(&system_program::ID) {
+        return Err(ProgramError::IncorrectProgramId)
+    }
+
+    let (client_affiliate_pda_is_valid, _) = validate_client_pda(
+        affiliate_pda_account,
+        &[
+            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+            affiliate_pubkey.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_affiliate_pda_is_valid {
+        log_error!("Invalid seeds for affiliate PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_project_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_project_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    if candy_machine_account.key.ne(&candy_machine_id) {
+        log_error!("Candy machine account does not match the project");
+        return Err(CandyMachineAffiliatesError::InvalidCandyMachineAccount.into());
+    }
+
+    let mint_account_key = |index: usize| mint_accounts.get(index).map(|account| account.key);
+
+    // v2 mints go to the candy machine itself, paying its wallet, and v3 mints through the Candy Guard
+    // set as its mint authority, paying the `solPayment` destination of the default guard set
+    let expected_payment_wallet = if candy_machine_account.owner == &CANDY_MACHINE_V2_PROGRAM_ID {
+        if candy_machine_program.key.ne(&CANDY_MACHINE_V2_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId)
+        }
+
+        if mint_data.get(..8) != Some(&CANDY_MACHINE_V2_MINT_NFT_DISCRIMINATOR[..]) {
+            log_error!("Mint data is not a Candy Machine v2 mint_nft instruction");
+            return Err(CandyMachineAffiliatesError::InvalidMintInstruction.into());
+        }
+
+        if mint_account_key(CANDY_MACHINE_V2_MINT_CANDY_MACHINE_INDEX) != Some(candy_machine_account.key) {
+            log_error!("Mint accounts do not mint from the candy machine");
+            return Err(CandyMachineAffiliatesError::InvalidMintInstruction.into());
+        }
+
+        if mint_account_key(CANDY_MACHINE_V2_MINT_WALLET_INDEX) != Some(payment_wallet.key) {
+            log_error!("Mint accounts do not pay the payment wallet");
+            return Err(CandyMachineAffiliatesError::InvalidPaymentWallet.into());
+        }
+
+        candy_machine_v2_wallet(candy_machine_account)?
+    } else if candy_machine_account.owner == &CANDY_MACHINE_V3_PROGRAM_ID {
+        if candy_machine_program.key.ne(&CANDY_GUARD_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId)
+        }
+
+        // Mints from guard groups are not supported, the label must be `None`
+        if !matches!(candy_guard_mint_v2_args(&mint_data), Some((_, None))) {
+            log_error!("Mint data is not a Candy Guard mint_v2 instruction from the default guard set");
+            return Err(CandyMachineAffiliatesError::InvalidMintInstruction.into());
+        }
+
+        if mint_account_key(CANDY_GUARD_MINT_CANDY_MACHINE_PROGRAM_INDEX) != Some(&CANDY_MACHINE_V3_PROGRAM_ID)
+            || mint_account_key(CANDY_GUARD_MINT_CANDY_MACHINE_INDEX) != Some(candy_machine_account.key)
+        {
+            log_error!("Mint accounts do not mint from the candy machine");
+            return Err(CandyMachineAffiliatesError::InvalidMintInstruction.into());
+        }
+
+        let candy_guard_account = &mint_accounts[CANDY_GUARD_MINT_CANDY_GUARD_INDEX];
+
+        if candy_guard_account.key.ne(&candy_machine_v3_mint_authority(candy_machine_account)?) {
+            log_error!("Candy guard is not the mint authority of the candy machine");
+            return Err(CandyMachineAffiliatesError::InvalidMintInstruction.into());
+        }
+
+        if !mint_accounts.iter().any(|account| account.key == payment_wallet.key) {
+            log_error!("Mint accounts do not pay the payment wallet");
+            return Err(CandyMachineAffiliatesError::InvalidPaymentWallet.into());
+        }
+
+        candy_guard_sol_payment_destination(candy_guard_account)?
+            .ok_or(CandyMachineAffiliatesError::InvalidPaymentWallet)?
+    } else {
+        return Err(ProgramError::IllegalOwner)
+    };
+
+    if payment_wallet.key.ne(&expected_payment_wallet) {
+        log_error!("Invalid mint payment wallet");
+        return Err(CandyMachineAffiliatesError::InvalidPaymentWallet.into());
+    }
+
+    log_debug!("Unpacking affiliate account state");
+    let mut affiliate_account_state = AffiliateAccountState::unpack_account_data(&affiliate_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed affiliate account data");
+
+    log_debug!("Checking if affiliate account is initialized");
+    if !affiliate_account_state.is_initialized() {
+        log_error!("Affiliate account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    log_debug!("Unpacking project account state");
+    let mut project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed project account data");
+
+    log_debug!("Checking if project account is initialized");
+    if !project_account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    let mut protocol_stats_state = unpack_protocol_stats(protocol_stats_account, program_id)?;
+
+    // Mint through the Candy Machine, measure the price paid to the wallet and pay the commission out of it
+
+    let payment_wallet_lamports = payment_wallet.lamports();
+
+    invoke(
+        &Instruction {
+            program_id: *candy_machine_program.key,
+            accounts: mint_accounts.iter()
+                .map(|account| AccountMeta {
+                    pubkey: *account.key,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: mint_data,
+        },
+        accounts,
+    )?;
+
+    let price_lamports = payment_wallet.lamports().saturating_sub(payment_wallet_lamports);
+    let commission_lamports = commission_lamports(price_lamports, project_account_state.data.affiliate_fee_percentage)?;
+
+    if commission_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payment_wallet.key, affiliate_pda_account.key, commission_lamports),
+            &[payment_wallet.clone(), affiliate_pda_account.clone(), system_program_account.clone()],
+        )?;
+    }
+
+    log_info!("Mint referred: {} lamports commission on {} lamports.", commission_lamports, price_lamports);
+
+    // Accounts created with an older layout are smaller than the current one
+    grow_account(affiliate_pda_account, AffiliateAccountState::LENGTH, buyer, system_program_account)?;
+    grow_account(project_pda_account, ProjectAccountState::LENGTH, buyer, system_program_account)?;
+
+    let clock = Clock::get()?;
+
+    affiliate_account_state.data.mint_count = affiliate_account_state.data.mint_count
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    affiliate_account_state.data.referred_volume_lamports = affiliate_account_state.data.referred_volume_lamports
+        .checked_add(price_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    affiliate_account_state.data.total_commission_lamports = affiliate_account_state.data.total_commission_lamports
+        .checked_add(commission_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    affiliate_account_state.data.last_activity_at = clock.unix_timestamp;
+
+    project_account_state.data.total_mint_count = project_account_state.data.total_mint_count
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    project_account_state.data.total_referred_volume_lamports = project_account_state.data.total_referred_volume_lamports
+        .checked_add(price_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    project_account_state.data.total_commission_lamports = project_account_state.data.total_commission_lamports
+        .checked_add(commission_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    if let Some(protocol_stats_state) = &mut protocol_stats_state {
+        protocol_stats_state.data.total_referred_volume_lamports = protocol_stats_state.data.total_referred_volume_lamports
+            .checked_add(price_lamports)
+            .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+        protocol_stats_state.data.updated_at = clock.unix_timestamp;
+    }
+
+    log_debug!("Serializing accounts");
+    affiliate_account_state.serialize(&mut &mut affiliate_pda_account.data.borrow_mut()[..])?;
+    project_account_state.serialize(&mut &mut project_pda_account.data.borrow_mut()[..])?;
+    if let Some(protocol_stats_state) = &protocol_stats_state {
+        protocol_stats_state.serialize(&mut &mut protocol_stats_account.data.borrow_mut()[..])?;
+    }
+    credit_affiliate_profile(affiliate_profile_account, &affiliate_pubkey, project_pda_account.key, commission_lamports, program_id)?;
+    log_debug!("State accounts serialized");
+
+    CandyMachineAffiliatesEvent::MintReferred(MintReferred {
+        affiliate_account: *affiliate_pda_account.key,
+        affiliate_pubkey,
+        project: *project_pda_account.key,
+        buyer_pubkey: *buyer.key,
+        price_lamports,
+        commission_lamports,
+    }).emit();
+
+    Ok(())
+}
+
+// The fee percentage is applied in basis points, rounded to the nearest one, and the commission is
+// rounded down to the lamport
+fn commission_lamports(price_lamports: u64, affiliate_fee_percentage: f64) -> Result<u64, ProgramError> {
+    // Out of range percentages saturate to 0 and 100%
+    let fee_basis_points = ((affiliate_fee_percentage * 100.0).round() as u64).min(MAX_FEE_BASIS_POINTS);
+
+    let commission_lamports = (price_lamports as u128)
+        .checked_mul(fee_basis_points as u128)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?
+        / MAX_FEE_BASIS_POINTS as u128;
+
+    Ok(commission_lamports as u64)
+}
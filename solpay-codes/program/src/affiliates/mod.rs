@@ -2,3 +2,7 @@ pub mod state;
 pub mod redeem_reward;
 pub mod register_affiliate_account;
 pub mod close_affiliate_account;
+pub mod get_redeemable_amount;
+pub mod milestones;
+pub mod fee_discounts;
+pub mod mint_with_referral;
@@ -2,18 +2,22 @@ use std::str::FromStr;
 use solana_program::{
     account_info::{AccountInfo, next_account_info},
     entrypoint::ProgramResult,
-    msg,
-    borsh::try_from_slice_unchecked,
+    program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
-    program_pack::IsInitialized
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{clock::Clock, Sysvar},
 };
 use borsh::BorshSerialize;
 use solana_program::native_token::LAMPORTS_PER_SOL;
+use crate::affiliates::milestones::{milestone_reward, SPAF_MINT_AUTHORITY_SEED};
 use crate::affiliates::state::AffiliateAccountState;
 use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, MilestoneRewardMinted, RewardRedeemed};
 use crate::projects::state::ProjectAccountState;
-use crate::utils::validate_client_pda;
+use crate::SPAF_MINT_PUBKEY;
+use crate::utils::{grow_account, redeemable_affiliate_lamports, unpack_protocol_stats, unpack_token_account, validate_client_pda};
 
 #[allow(unused_variables)]
 pub fn redeem_reward(
@@ -22,7 +26,7 @@ pub fn redeem_reward(
     project_owner_pubkey: Pubkey,
     candy_machine_id: Pubkey
 ) -> ProgramResult {
-    msg!("Redeeming reward...");
+    log_debug!("Redeeming reward...");
 
     // Get Account iterator
     let account_info_iter = &mut accounts.iter();
@@ -31,14 +35,25 @@ pub fn redeem_reward(
     let initializer = next_account_info(account_info_iter)?;
     let affiliate_pda_account = next_account_info(account_info_iter)?;
     let project_pda_account = next_account_info(account_info_iter)?;
+    let protocol_stats_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    // Receive SPAF milestone rewards when given, skipped otherwise
+    let reward_token_account = next_account_info(account_info_iter).ok();
+    let spaf_mint_account = next_account_info(account_info_iter).ok();
+    let spaf_mint_authority = next_account_info(account_info_iter).ok();
+    let token_program = next_account_info(account_info_iter).ok();
 
     // Validate accounts
 
     if !initializer.is_signer {
-        msg!("Missing required signature");
+        log_error!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature)
     }
 
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
     if affiliate_pda_account.owner != program_id {
         return Err(ProgramError::IllegalOwner)
     }
@@ -59,7 +74,7 @@ pub fn redeem_reward(
     );
 
     if !client_affiliate_pda_is_valid {
-        msg!("Invalid seeds for affiliate PDA");
+        log_error!("Invalid seeds for affiliate PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
@@ -74,51 +89,171 @@ pub fn redeem_reward(
     );
 
     if !client_project_pda_is_valid {
-        msg!("Invalid seeds for project PDA");
+        log_error!("Invalid seeds for project PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
-    msg!("Unpacking affiliate account state");
-    let mut affiliate_account_state = try_from_slice_unchecked::<AffiliateAccountState>(&affiliate_pda_account.data.borrow()).unwrap();
-    msg!("Borrowed affiliate account data");
+    log_debug!("Unpacking affiliate account state");
+    let mut affiliate_account_state = AffiliateAccountState::unpack_account_data(&affiliate_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed affiliate account data");
 
-    msg!("Checking if affiliate account is initialized");
+    log_debug!("Checking if affiliate account is initialized");
     if !affiliate_account_state.is_initialized() {
-        msg!("Affiliate account not initialized");
+        log_error!("Affiliate account not initialized");
         return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
     }
 
-    msg!("Unpacking project account state");
-    let project_account_state = try_from_slice_unchecked::<ProjectAccountState>(&project_pda_account.data.borrow()).unwrap();
-    msg!("Borrowed project account data");
+    log_debug!("Unpacking project account state");
+    let mut project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed project account data");
 
-    msg!("Checking if project account is initialized");
+    log_debug!("Checking if project account is initialized");
     if !project_account_state.is_initialized() {
-        msg!("Project account not initialized");
+        log_error!("Project account not initialized");
         return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
     }
 
     if affiliate_account_state.data.project_owner_pubkey.ne(&project_account_state.data.project_owner_pubkey)
         && affiliate_account_state.data.candy_machine_id.ne(&project_account_state.data.candy_machine_id) {
-        msg!("Mismatched accounts when redeeming reward");
+        log_error!("Mismatched accounts when redeeming reward");
         return Err(CandyMachineAffiliatesError::RewardRedeemMismatchedAccounts.into());
     }
 
-    if affiliate_pda_account.lamports() < project_account_state.data.affiliate_target_in_sol as u64 * LAMPORTS_PER_SOL {
-        msg!("Affiliate account balance has not reached the threshold");
+    let mut protocol_stats_state = unpack_protocol_stats(protocol_stats_account, program_id)?;
+
+    // Affiliates and projects registered before the activity counters and referral totals were added
+    // are smaller than the current layout, the initializer pays the extra rent
+    grow_account(affiliate_pda_account, AffiliateAccountState::LENGTH, initializer, system_program)?;
+    grow_account(project_pda_account, ProjectAccountState::LENGTH, initializer, system_program)?;
+
+    let reward_lamports = project_account_state.data.affiliate_target_in_sol as u64 * LAMPORTS_PER_SOL;
+
+    if redeemable_affiliate_lamports(affiliate_pda_account)? < reward_lamports {
+        log_error!("Affiliate account balance has not reached the threshold");
         return Err(CandyMachineAffiliatesError::AffiliateAccountBalanceNotEnough.into());
     }
 
-    **initializer.try_borrow_mut_lamports()? += project_account_state.data.affiliate_target_in_sol as u64 * LAMPORTS_PER_SOL;
-    **affiliate_pda_account.try_borrow_mut_lamports()? -= project_account_state.data.affiliate_target_in_sol as u64 * LAMPORTS_PER_SOL;
+    **affiliate_pda_account.try_borrow_mut_lamports()? -= reward_lamports;
+
+    let initializer_lamports = initializer.lamports()
+        .checked_add(reward_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    **initializer.try_borrow_mut_lamports()? = initializer_lamports;
+
+    log_info!("Reward redeemed: {} SOL.", project_account_state.data.affiliate_target_in_sol);
+
+    let previous_total_redeemed_amount_in_sol = affiliate_account_state.data.total_redeemed_amount_in_sol;
+
+    affiliate_account_state.data.total_redeemed_amount_in_sol = affiliate_account_state.data.total_redeemed_amount_in_sol
+        .checked_add(project_account_state.data.affiliate_target_in_sol as u32)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    affiliate_account_state.data.redemption_count = affiliate_account_state.data.redemption_count
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    affiliate_account_state.data.last_activity_at = Clock::get()?.unix_timestamp;
+
+    if let Some(protocol_stats_state) = &mut protocol_stats_state {
+        protocol_stats_state.data.total_rewards_redeemed_lamports = protocol_stats_state.data.total_rewards_redeemed_lamports
+            .checked_add(reward_lamports)
+            .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+        protocol_stats_state.data.updated_at = affiliate_account_state.data.last_activity_at;
+    }
+
+    project_account_state.data.total_redemption_count = project_account_state.data.total_redemption_count
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    // Mint SPAF for the milestones crossed by this redemption and the earlier ones left pending
+
+    let spaf_reward = milestone_reward(
+        previous_total_redeemed_amount_in_sol,
+        affiliate_account_state.data.total_redeemed_amount_in_sol,
+    )
+        .checked_add(affiliate_account_state.data.pending_milestone_reward)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    let mut minted_reward = None;
+
+    if SPAF_MINT_PUBKEY.is_some() && reward_token_account.is_none() && spaf_reward > 0 {
+        log_debug!("Milestone reward pending: {} SPAF", spaf_reward);
+        affiliate_account_state.data.pending_milestone_reward = spaf_reward;
+    }
+
+    if let (Some(spaf_mint_pubkey), Some(reward_token_account), true) = (SPAF_MINT_PUBKEY, reward_token_account, spaf_reward > 0) {
+        let spaf_mint_pubkey = Pubkey::from_str(spaf_mint_pubkey).expect("Pubkey conversion failed");
+        let spaf_mint_account = spaf_mint_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let spaf_mint_authority = spaf_mint_authority.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let token_program = token_program.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        if token_program.key.ne(&spl_token::id()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let (client_pda_is_valid, bump_seed) = validate_client_pda(
+            spaf_mint_authority,
+            &[SPAF_MINT_AUTHORITY_SEED.as_ref()],
+            program_id
+        );
+
+        if !client_pda_is_valid {
+            log_error!("Invalid seeds for SPAF mint authority PDA");
+            return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+        }
 
-    msg!("Reward redeemed: {} SOL.", project_account_state.data.affiliate_target_in_sol);
+        let token_account = unpack_token_account(reward_token_account)?;
 
-    affiliate_account_state.data.total_redeemed_amount_in_sol += project_account_state.data.affiliate_target_in_sol as u32;
+        if spaf_mint_account.key.ne(&spaf_mint_pubkey)
+            || token_account.mint.ne(&spaf_mint_pubkey)
+            || token_account.owner.ne(initializer.key) {
+            log_error!("Invalid reward token account");
+            return Err(CandyMachineAffiliatesError::InvalidRewardTokenAccount.into());
+        }
 
-    msg!("Serializing account");
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                token_program.key,
+                spaf_mint_account.key,
+                reward_token_account.key,
+                spaf_mint_authority.key,
+                &[],
+                spaf_reward,
+            )?,
+            &[spaf_mint_account.clone(), reward_token_account.clone(), spaf_mint_authority.clone(), token_program.clone()],
+            &[&[SPAF_MINT_AUTHORITY_SEED.as_ref(), &[bump_seed]]],
+        )?;
+
+        log_info!("Milestone reward minted: {} SPAF.", spaf_reward);
+        affiliate_account_state.data.pending_milestone_reward = 0;
+        minted_reward = Some(MilestoneRewardMinted {
+            affiliate_account: *affiliate_pda_account.key,
+            affiliate_pubkey: *initializer.key,
+            token_account: *reward_token_account.key,
+            amount: spaf_reward,
+            total_redeemed_amount_in_sol: affiliate_account_state.data.total_redeemed_amount_in_sol,
+        });
+    }
+
+    log_debug!("Serializing accounts");
     affiliate_account_state.serialize(&mut &mut affiliate_pda_account.data.borrow_mut()[..])?;
-    msg!("State account serialized");
+    project_account_state.serialize(&mut &mut project_pda_account.data.borrow_mut()[..])?;
+    if let Some(protocol_stats_state) = &protocol_stats_state {
+        protocol_stats_state.serialize(&mut &mut protocol_stats_account.data.borrow_mut()[..])?;
+    }
+    log_debug!("State accounts serialized");
+
+    CandyMachineAffiliatesEvent::RewardRedeemed(RewardRedeemed {
+        affiliate_account: *affiliate_pda_account.key,
+        affiliate_pubkey: *initializer.key,
+        project: *project_pda_account.key,
+        reward_lamports,
+        total_redeemed_amount_in_sol: affiliate_account_state.data.total_redeemed_amount_in_sol,
+    }).emit();
+
+    if let Some(minted_reward) = minted_reward {
+        CandyMachineAffiliatesEvent::MilestoneRewardMinted(minted_reward).emit();
+    }
 
     Ok(())
 }
@@ -2,9 +2,8 @@ use std::str::FromStr;
 use solana_program::{
     account_info::{AccountInfo, next_account_info},
     entrypoint::ProgramResult,
-    msg,
     system_instruction,
-    borsh::try_from_slice_unchecked,
+    borsh1::try_from_slice_unchecked,
     program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
@@ -14,15 +13,21 @@ use solana_program::{
 };
 use borsh::BorshSerialize;
 use solana_program::clock::Clock;
-use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::program::invoke;
+use crate::affiliates::fee_discounts::wallet_registration_fee;
 use crate::affiliates::state::{AFFILIATE_ACCOUNT_DATA_VERSION, AffiliateAccountState};
 use crate::error::CandyMachineAffiliatesError;
+use crate::events::{AffiliateIndexed, AffiliateRegisteredV2, CandyMachineAffiliatesEvent};
 use crate::projects::state::ProjectAccountState;
 use crate::SOLPAY_TREASURY_PUBKEY;
-use crate::utils::validate_client_pda;
-
-const AFFILIATE_REGISTRATION_FEE: u64 = (0.1 * LAMPORTS_PER_SOL as f64) as u64;
+use crate::utils::{
+    add_to_affiliate_profile,
+    append_to_affiliate_index,
+    grow_account,
+    meets_project_gating,
+    unpack_protocol_stats,
+    validate_client_pda,
+};
 
 #[allow(unused_variables)]
 pub fn register_affiliate_account(
@@ -31,7 +36,7 @@ pub fn register_affiliate_account(
     project_owner_pubkey: Pubkey,
     candy_machine_id: Pubkey,
 ) -> ProgramResult {
-    msg!("Creating affiliate account...");
+    log_debug!("Creating affiliate account...");
 
     // Get Account iterator
     let account_info_iter = &mut accounts.iter();
@@ -42,7 +47,22 @@ pub fn register_affiliate_account(
     let project_pda_account = next_account_info(account_info_iter)?;
     let solpay_treasury = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
-    let clock = Clock::get().unwrap();
+    let protocol_stats_account = next_account_info(account_info_iter)?;
+    // Page of the affiliate index the affiliate is appended to, created when it is the first entry of the page
+    let affiliate_index_page_account = next_account_info(account_info_iter)?;
+    // Profile of the affiliate wallet the project is listed in, created on its first registration
+    let affiliate_profile_account = next_account_info(account_info_iter)?;
+    // Required when the project is gated, the mint and token program only when it burns.
+    // Unused slots before the SPAF token account hold the program id.
+    let is_passed = |account: &&AccountInfo| account.key.ne(program_id);
+    let gating_token_account = next_account_info(account_info_iter).ok().filter(is_passed);
+    let gating_mint_account = next_account_info(account_info_iter).ok().filter(is_passed);
+    let token_program = next_account_info(account_info_iter).ok().filter(is_passed);
+    // Discounts the registration fee by the SPAF balance it holds when given, following the tiers of the
+    // fee discount config account after it
+    let spaf_token_account = next_account_info(account_info_iter).ok().filter(is_passed);
+    let fee_discount_config_account = next_account_info(account_info_iter).ok().filter(is_passed);
+    let clock = Clock::get()?;
 
     // Validate accounts
 
@@ -51,7 +71,7 @@ pub fn register_affiliate_account(
     }
 
     if !initializer.is_signer {
-        msg!("Missing required signature");
+        log_error!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
@@ -67,7 +87,7 @@ pub fn register_affiliate_account(
     );
 
     if !client_pda_is_valid {
-        msg!("Invalid seeds for PDA");
+        log_error!("Invalid seeds for PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
@@ -82,39 +102,88 @@ pub fn register_affiliate_account(
     );
 
     if !client_pda_is_valid {
-        msg!("Invalid seeds for PDA");
+        log_error!("Invalid seeds for PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
     // Validate treasury account
-    let solpay_treasury_pubkey = Pubkey::from_str(&SOLPAY_TREASURY_PUBKEY).expect("Pubkey conversion failed");
+    let solpay_treasury_pubkey = Pubkey::from_str(SOLPAY_TREASURY_PUBKEY).expect("Pubkey conversion failed");
 
     if solpay_treasury_pubkey.ne(solpay_treasury.key) {
-        msg!("Incorrect treasury account");
+        log_error!("Incorrect treasury account");
         return Err(CandyMachineAffiliatesError::IncorrectTreasuryAccount.into());
     }
 
-    // Pay affiliate registration fee
-    invoke(
-        &system_instruction::transfer(
-            &initializer.key,
-            &solpay_treasury.key,
-            AFFILIATE_REGISTRATION_FEE,
-        ),
-        &[initializer.clone(), solpay_treasury.clone(), system_program.clone()],
-    )?;
+    let mut protocol_stats_state = unpack_protocol_stats(protocol_stats_account, program_id)?;
 
     // Check if project max affiliates count reached
 
-    msg!("Unpacking project state account");
-    let mut project_account_state = try_from_slice_unchecked::<ProjectAccountState>(&project_pda_account.data.borrow()).unwrap();
-    msg!("Borrowed project account data");
+    log_debug!("Unpacking project state account");
+    let mut project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed project account data");
 
-    if project_account_state.data.affiliate_count == project_account_state.data.max_affiliate_count {
-        msg!("Project max affiliate count reached");
+    if project_account_state.data.affiliate_count >= project_account_state.data.max_affiliate_count {
+        log_error!("Project max affiliate count reached");
         return Err(CandyMachineAffiliatesError::ProjectMaxAffiliateCountReached.into());
     }
 
+    // Check the gating token balance of the affiliate
+
+    if !meets_project_gating(&project_account_state, gating_token_account, initializer.key)? {
+        log_error!("Gating token balance not enough");
+        return Err(CandyMachineAffiliatesError::GatingTokenBalanceNotEnough.into());
+    }
+
+    if let (Some(gating_mint), Some(gating_token_account)) = (project_account_state.data.gating_mint, gating_token_account) {
+        let gating_min_amount = project_account_state.data.gating_min_amount;
+
+        if project_account_state.data.gating_burn {
+            let gating_mint_account = gating_mint_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let token_program = token_program.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            if token_program.key.ne(&spl_token::id()) {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            if gating_mint_account.key.ne(&gating_mint) {
+                log_error!("Invalid gating mint account");
+                return Err(CandyMachineAffiliatesError::InvalidGatingTokenAccount.into());
+            }
+
+            invoke(
+                &spl_token::instruction::burn(
+                    token_program.key,
+                    gating_token_account.key,
+                    gating_mint_account.key,
+                    initializer.key,
+                    &[],
+                    gating_min_amount,
+                )?,
+                &[gating_token_account.clone(), gating_mint_account.clone(), initializer.clone(), token_program.clone()],
+            )?;
+
+            log_debug!("Gating tokens burned");
+        }
+    }
+
+    // Apply the SPAF holder discount to the registration fee
+
+    let fee = wallet_registration_fee(spaf_token_account, fee_discount_config_account, initializer.key, program_id)?;
+    log_debug!("Registration fee: {} lamports", fee);
+
+    // Pay affiliate registration fee
+    if fee > 0 {
+        invoke(
+            &system_instruction::transfer(
+                initializer.key,
+                solpay_treasury.key,
+                fee,
+            ),
+            &[initializer.clone(), solpay_treasury.clone(), system_program.clone()],
+        )?;
+    }
+
     // Calculate rent required
     let rent = Rent::get()?;
     let rent_lamports = rent.minimum_balance(AffiliateAccountState::LENGTH);
@@ -140,15 +209,16 @@ pub fn register_affiliate_account(
         ],
     )?;
 
-    msg!("PDA created");
+    log_debug!("PDA created");
 
-    msg!("Unpacking state account");
-    let mut account_state = try_from_slice_unchecked::<AffiliateAccountState>(&pda_account.data.borrow()).unwrap();
-    msg!("Borrowed account data");
+    log_debug!("Unpacking state account");
+    let mut account_state = try_from_slice_unchecked::<AffiliateAccountState>(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
 
-    msg!("Checking if affiliate account is already initialized");
+    log_debug!("Checking if affiliate account is already initialized");
     if account_state.is_initialized() {
-        msg!("Account already initialized");
+        log_error!("Account already initialized");
         return Err(ProgramError::AccountAlreadyInitialized);
     }
 
@@ -160,16 +230,79 @@ pub fn register_affiliate_account(
     account_state.data.candy_machine_id = candy_machine_id;
     account_state.data.total_redeemed_amount_in_sol = 0;
     account_state.data.created_at = clock.unix_timestamp;
+    account_state.data.last_activity_at = clock.unix_timestamp;
+
+    // Append the affiliate to the affiliate index of the project
+    let affiliate_index_position = append_to_affiliate_index(
+        affiliate_index_page_account,
+        project_pda_account,
+        &mut project_account_state,
+        pda_account.key,
+        initializer,
+        system_program,
+        program_id,
+    )?;
+    account_state.data.affiliate_index_position = Some(affiliate_index_position);
+
+    // List the project in the profile of the affiliate wallet
+    add_to_affiliate_profile(
+        affiliate_profile_account,
+        initializer.key,
+        project_pda_account.key,
+        0,
+        initializer,
+        system_program,
+        program_id,
+    )?;
 
-    msg!("Serializing account");
+    log_debug!("Serializing account");
     account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
-    msg!("State account serialized");
+    log_debug!("State account serialized");
+
+    project_account_state.data.affiliate_count = project_account_state.data.affiliate_count
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    project_account_state.data.peak_affiliate_count = project_account_state.data.peak_affiliate_count
+        .max(project_account_state.data.affiliate_count);
 
-    project_account_state.data.affiliate_count += 1;
+    // Projects registered with an older layout are smaller than the current one
+    grow_account(project_pda_account, ProjectAccountState::LENGTH, initializer, system_program)?;
 
-    msg!("Updating project state account");
+    log_debug!("Updating project state account");
     project_account_state.serialize(&mut &mut project_pda_account.data.borrow_mut()[..])?;
-    msg!("State account serialized");
+    log_debug!("State account serialized");
+
+    if let Some(protocol_stats_state) = &mut protocol_stats_state {
+        protocol_stats_state.data.live_affiliate_count = protocol_stats_state.data.live_affiliate_count
+            .checked_add(1)
+            .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+        protocol_stats_state.data.registration_fees_collected_lamports = protocol_stats_state.data.registration_fees_collected_lamports
+            .checked_add(fee)
+            .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+        protocol_stats_state.data.updated_at = clock.unix_timestamp;
+
+        log_debug!("Updating protocol stats account");
+        protocol_stats_state.serialize(&mut &mut protocol_stats_account.data.borrow_mut()[..])?;
+        log_debug!("Protocol stats account serialized");
+    }
+
+    log_info!("Affiliate registered.");
+
+    CandyMachineAffiliatesEvent::AffiliateRegisteredV2(AffiliateRegisteredV2 {
+        affiliate_account: *pda_account.key,
+        affiliate_pubkey: *initializer.key,
+        project: *project_pda_account.key,
+        registration_fee: fee,
+        affiliate_count: project_account_state.data.affiliate_count,
+        created_at: account_state.data.created_at,
+    }).emit();
+
+    CandyMachineAffiliatesEvent::AffiliateIndexed(AffiliateIndexed {
+        affiliate_account: *pda_account.key,
+        project: *project_pda_account.key,
+        affiliate_index_page_account: *affiliate_index_page_account.key,
+        position: affiliate_index_position,
+    }).emit();
 
     Ok(())
 }
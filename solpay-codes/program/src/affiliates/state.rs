@@ -2,21 +2,37 @@ use borsh::{
     BorshDeserialize,
     BorshSerialize
 };
+use solana_program::borsh1::try_from_slice_unchecked;
 use solana_program::program_pack::{IsInitialized, Sealed};
 use solana_program::pubkey::Pubkey;
+use crate::referral_codes::state::MAX_REFERRAL_CODE_LENGTH;
 
 pub const AFFILIATE_ACCOUNT_DATA_VERSION: u8 = 0;
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct AffiliateAccountStateData {
     pub affiliate_pubkey: Pubkey,
     pub project_owner_pubkey: Pubkey,
     pub candy_machine_id: Pubkey,
     pub total_redeemed_amount_in_sol: u32,
     pub created_at: i64,
+    // Mints referred with `MintWithReferral`, their price and the commission they paid
+    pub mint_count: u32,
+    pub referred_volume_lamports: u64,
+    pub total_commission_lamports: u64,
+    pub redemption_count: u32,
+    // Last referred mint or redemption, `created_at` until then
+    pub last_activity_at: i64,
+    // Entry of the affiliate in the affiliate index of the project, `None` until indexed
+    pub affiliate_index_position: Option<u32>,
+    // SPAF of the milestones crossed by redemptions made without the SPAF accounts, minted by the next
+    // redemption passing them
+    pub pending_milestone_reward: u64,
+    // Referral code claimed by the affiliate, released along with the account when it is closed
+    pub referral_code: Option<String>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct AffiliateAccountState {
     pub discriminator: String,
     pub is_initialized: bool,
@@ -45,9 +61,41 @@ impl AffiliateAccountState {
         + 32
         // candy_machine_id
         + 32
-        // mint_count
+        // total_redeemed_amount_in_sol
         + 4
         // created_at
         + 8
+        // mint_count
+        + 4
+        // referred_volume_lamports
+        + 8
+        // total_commission_lamports
+        + 8
+        // redemption_count
+        + 4
+        // last_activity_at
+        + 8
+        // affiliate_index_position
+        + (1 + 4)
+        // pending_milestone_reward
+        + 8
+        // referral_code
+        + (1 + 4 + MAX_REFERRAL_CODE_LENGTH)
     ;
+    // Accounts registered before the activity counters were added end after `created_at`
+    pub const LEGACY_LENGTH: usize = AffiliateAccountState::LENGTH - (4 + 8 + 8 + 4 + 8) - (1 + 4) - 8
+        - (1 + 4 + MAX_REFERRAL_CODE_LENGTH);
+
+    // Decodes an affiliate account of any earlier size, the counters of legacy accounts are zero,
+    // they are not indexed and have no pending milestone reward nor referral code
+    pub fn unpack_account_data(data: &[u8]) -> Result<AffiliateAccountState, borsh::io::Error> {
+        if data.len() >= AffiliateAccountState::LENGTH {
+            return try_from_slice_unchecked(data);
+        }
+
+        let mut padded_data = data.to_vec();
+        padded_data.resize(AffiliateAccountState::LENGTH, 0);
+
+        try_from_slice_unchecked(&padded_data)
+    }
 }
@@ -0,0 +1,143 @@
+// Metaplex Candy Machine accounts the projects are registered for
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+};
+use crate::error::CandyMachineAffiliatesError;
+
+pub const CANDY_MACHINE_V2_PROGRAM_ID: Pubkey = pubkey!("cndy3Z4yapfJBmL3ShUp5exZKqR3z33thTzeNMm2gRZ");
+pub const CANDY_MACHINE_V3_PROGRAM_ID: Pubkey = pubkey!("CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR");
+// Mints from v3 candy machines go through their Candy Guard
+pub const CANDY_GUARD_PROGRAM_ID: Pubkey = pubkey!("Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g");
+
+// Anchor discriminator of the `CandyMachine` account, sha256("account:CandyMachine")[..8] in v2 and v3
+pub const CANDY_MACHINE_DISCRIMINATOR: [u8; 8] = [51, 173, 177, 113, 25, 241, 109, 189];
+
+// v2: discriminator, authority, wallet
+pub const CANDY_MACHINE_V2_AUTHORITY_OFFSET: usize = 8;
+pub const CANDY_MACHINE_V2_WALLET_OFFSET: usize = 40;
+// v3: discriminator, version, token_standard and features (a u64 features before versioning), authority
+pub const CANDY_MACHINE_V3_AUTHORITY_OFFSET: usize = 16;
+// v3: authority, mint_authority (the Candy Guard minting from it)
+pub const CANDY_MACHINE_V3_MINT_AUTHORITY_OFFSET: usize = 48;
+
+// Anchor discriminator of the `CandyGuard` account, sha256("account:CandyGuard")[..8]
+pub const CANDY_GUARD_DISCRIMINATOR: [u8; 8] = [44, 207, 199, 184, 112, 103, 34, 181];
+// Candy Guard: discriminator, base, bump, authority, then the default guard set
+pub const CANDY_GUARD_GUARDS_OFFSET: usize = 73;
+// Guard set: a u64 of enabled guards, then each enabled guard in order, `botTax` (lamports, lastInstruction)
+// before `solPayment` (lamports, destination)
+pub const CANDY_GUARD_BOT_TAX_FEATURE: u64 = 1;
+pub const CANDY_GUARD_SOL_PAYMENT_FEATURE: u64 = 1 << 1;
+pub const CANDY_GUARD_BOT_TAX_LENGTH: usize = 9;
+
+// Anchor discriminators of the mint instructions, sha256("global:mint_nft")[..8] in v2 and
+// sha256("global:mint_v2")[..8] in Candy Guard
+pub const CANDY_MACHINE_V2_MINT_NFT_DISCRIMINATOR: [u8; 8] = [211, 57, 6, 167, 15, 219, 35, 251];
+pub const CANDY_GUARD_MINT_V2_DISCRIMINATOR: [u8; 8] = [120, 121, 23, 146, 173, 110, 199, 205];
+
+// Positions of the accounts in the mint instructions: v2 `mint_nft` starts with the candy machine,
+// its creator PDA, the payer and the wallet; Candy Guard `mint_v2` with the candy guard, the Candy
+// Machine v3 program and the candy machine
+pub const CANDY_MACHINE_V2_MINT_CANDY_MACHINE_INDEX: usize = 0;
+pub const CANDY_MACHINE_V2_MINT_WALLET_INDEX: usize = 3;
+pub const CANDY_GUARD_MINT_CANDY_GUARD_INDEX: usize = 0;
+pub const CANDY_GUARD_MINT_CANDY_MACHINE_PROGRAM_INDEX: usize = 1;
+pub const CANDY_GUARD_MINT_CANDY_MACHINE_INDEX: usize = 2;
+
+// Reads the authority of a candy machine account owned by a supported Candy Machine program
+pub fn candy_machine_authority(candy_machine: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    let authority_offset = if candy_machine.owner == &CANDY_MACHINE_V2_PROGRAM_ID {
+        CANDY_MACHINE_V2_AUTHORITY_OFFSET
+    } else if candy_machine.owner == &CANDY_MACHINE_V3_PROGRAM_ID {
+        CANDY_MACHINE_V3_AUTHORITY_OFFSET
+    } else {
+        return Err(ProgramError::IllegalOwner);
+    };
+
+    read_pubkey(candy_machine, authority_offset)
+}
+
+// Reads the wallet receiving the mint price of a Candy Machine v2 account
+pub fn candy_machine_v2_wallet(candy_machine: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if candy_machine.owner != &CANDY_MACHINE_V2_PROGRAM_ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    read_pubkey(candy_machine, CANDY_MACHINE_V2_WALLET_OFFSET)
+}
+
+// Reads the mint authority of a Candy Machine v3 account, the Candy Guard its mints go through
+pub fn candy_machine_v3_mint_authority(candy_machine: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if candy_machine.owner != &CANDY_MACHINE_V3_PROGRAM_ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    read_pubkey(candy_machine, CANDY_MACHINE_V3_MINT_AUTHORITY_OFFSET)
+}
+
+// Reads the `solPayment` destination of the default guard set of a Candy Guard account, if it has one
+pub fn candy_guard_sol_payment_destination(candy_guard: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+    if candy_guard.owner != &CANDY_GUARD_PROGRAM_ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let data = candy_guard.data.borrow();
+
+    let features = match (data.get(..8), data.get(CANDY_GUARD_GUARDS_OFFSET..CANDY_GUARD_GUARDS_OFFSET + 8)) {
+        (Some(discriminator), Some(features)) if discriminator == CANDY_GUARD_DISCRIMINATOR => {
+            u64::from_le_bytes(features.try_into().expect("Features slice is 8 bytes"))
+        }
+        _ => {
+            log_error!("Invalid candy guard account data");
+            return Err(CandyMachineAffiliatesError::InvalidCandyMachineAccount.into());
+        }
+    };
+
+    if features & CANDY_GUARD_SOL_PAYMENT_FEATURE == 0 {
+        return Ok(None);
+    }
+
+    let mut offset = CANDY_GUARD_GUARDS_OFFSET + 8;
+    if features & CANDY_GUARD_BOT_TAX_FEATURE != 0 {
+        offset += CANDY_GUARD_BOT_TAX_LENGTH;
+    }
+    // Skip the lamports of the guard
+    offset += 8;
+
+    match data.get(offset..offset + 32) {
+        Some(destination) => Ok(Some(Pubkey::try_from(destination).expect("Pubkey slice is 32 bytes"))),
+        None => {
+            log_error!("Invalid candy guard account data");
+            Err(CandyMachineAffiliatesError::InvalidCandyMachineAccount.into())
+        }
+    }
+}
+
+// Decodes the arguments of a Candy Guard `mint_v2` instruction, its `mint_args` and the `label` of
+// the guard group minted from, `None` for the default guard set
+pub fn candy_guard_mint_v2_args(mint_data: &[u8]) -> Option<(Vec<u8>, Option<String>)> {
+    match (mint_data.get(..8), mint_data.get(8..)) {
+        (Some(discriminator), Some(args)) if discriminator == CANDY_GUARD_MINT_V2_DISCRIMINATOR => {
+            <(Vec<u8>, Option<String>)>::try_from_slice(args).ok()
+        }
+        _ => None,
+    }
+}
+
+fn read_pubkey(candy_machine: &AccountInfo, offset: usize) -> Result<Pubkey, ProgramError> {
+    let data = candy_machine.data.borrow();
+
+    match (data.get(..8), data.get(offset..offset + 32)) {
+        (Some(discriminator), Some(pubkey)) if discriminator == CANDY_MACHINE_DISCRIMINATOR => {
+            Ok(Pubkey::try_from(pubkey).expect("Pubkey slice is 32 bytes"))
+        }
+        _ => {
+            log_error!("Invalid candy machine account data");
+            Err(CandyMachineAffiliatesError::InvalidCandyMachineAccount.into())
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::{clock::Clock, Sysvar},
+};
+use crate::candy_machine::candy_machine_authority;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, CandyMachineProjectLinked};
+use crate::projects::state::ProjectAccountState;
+use crate::utils::{create_candy_machine_project, validate_client_pda};
+
+// Creates the candy machine project PDA of a project registered before the lookup existed.
+// Anyone may link a project, the signer pays for the lookup account. Only the project of the current
+// candy machine authority can be linked, so an older project of a previous authority cannot take it.
+#[allow(unused_variables)]
+pub fn link_candy_machine_project(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> ProgramResult {
+    log_debug!("Linking candy machine project...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let candy_machine_account = next_account_info(account_info_iter)?;
+    let candy_machine_project_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let (client_project_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_project_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    if candy_machine_account.key.ne(&candy_machine_id) {
+        log_error!("Candy machine account does not match candy machine id");
+        return Err(CandyMachineAffiliatesError::InvalidCandyMachineAccount.into());
+    }
+
+    if candy_machine_authority(candy_machine_account)?.ne(&project_owner_pubkey) {
+        log_error!("Project owner is not the candy machine authority");
+        return Err(CandyMachineAffiliatesError::InvalidCandyMachineAuthority.into());
+    }
+
+    log_debug!("Unpacking project state account");
+    let project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed project account data");
+
+    if !project_account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    let created_at = Clock::get()?.unix_timestamp;
+
+    create_candy_machine_project(
+        candy_machine_project_account,
+        &candy_machine_id,
+        project_pda_account.key,
+        &project_owner_pubkey,
+        created_at,
+        initializer,
+        system_program,
+        program_id,
+    )?;
+
+    log_info!("Candy machine project linked.");
+
+    CandyMachineAffiliatesEvent::CandyMachineProjectLinked(CandyMachineProjectLinked {
+        candy_machine_project_account: *candy_machine_project_account.key,
+        candy_machine_id,
+        project: *project_pda_account.key,
+        project_owner_pubkey,
+        created_at,
+    }).emit();
+
+    Ok(())
+}
@@ -0,0 +1,2 @@
+pub mod state;
+pub mod link_candy_machine_project;
@@ -0,0 +1,52 @@
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+
+pub const CANDY_MACHINE_PROJECT_ACCOUNT_DATA_VERSION: u8 = 0;
+
+// Project registered for a candy machine, derived from the candy machine id alone so a mint page
+// can find the project without knowing its owner. A candy machine has at most one project.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CandyMachineProjectAccountStateData {
+    pub candy_machine_id: Pubkey,
+    pub project_account: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CandyMachineProjectAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: CandyMachineProjectAccountStateData,
+}
+
+impl Sealed for CandyMachineProjectAccountState {}
+
+impl IsInitialized for CandyMachineProjectAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl CandyMachineProjectAccountState {
+    pub const DISCRIMINATOR: &'static str = "candy_machine_project";
+    pub const LENGTH: usize = (4 + CandyMachineProjectAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // candy_machine_id
+        + 32
+        // project_account
+        + 32
+        // project_owner_pubkey
+        + 32
+        // created_at
+        + 8
+    ;
+}
@@ -0,0 +1,70 @@
+// Compile-time cluster profiles.
+// Exactly one of the `localnet`, `devnet` or `mainnet` features must be enabled,
+// e.g. `cargo build-sbf --features mainnet`. There is no default, a build without one fails.
+
+#[cfg(not(any(feature = "localnet", feature = "devnet", feature = "mainnet")))]
+compile_error!("No cluster profile selected, enable one of the `localnet`, `devnet` or `mainnet` features");
+
+#[cfg(any(
+    all(feature = "localnet", feature = "devnet"),
+    all(feature = "localnet", feature = "mainnet"),
+    all(feature = "devnet", feature = "mainnet"),
+))]
+compile_error!("Multiple cluster profiles selected, enable only one of the `localnet`, `devnet` or `mainnet` features");
+
+#[cfg(feature = "localnet")]
+mod profile {
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+
+    // Test-only keypairs for localnet live in config/localnet, their private keys are public
+    solana_program::declare_id!("9y8NWfi4MhumCxw7RaFpEJTNhxdJmYnBrQMcN8ViGyVt");
+
+    pub const ADMIN_PUBKEY: &str = "6oDtbbTTgFRSL6aD6baG5aFJvDhnSMx6R2ZgfN3LJA3n";
+    pub const SOLPAY_TREASURY_PUBKEY: &str = "BwKNAr2zKRA7oA2eaCfa9cdBLhnxJ4ifBLdEVKLYutpa";
+    pub const AFFILIATE_REGISTRATION_FEE: u64 = (0.1 * LAMPORTS_PER_SOL as f64) as u64;
+
+    // SPAF mint for tests, its mint authority must be the SPAF mint authority PDA of the program
+    pub const SPAF_MINT_PUBKEY: Option<&str> = Some("4nA85HictuzJMXE9oYCozXCgyeFDQFW9kZm1f2xdjLcu");
+    // (total_redeemed_amount_in_sol reached, SPAF base units minted)
+    pub const SPAF_MILESTONE_REWARDS: &[(u32, u64)] = &[(1, 10), (5, 50), (10, 100)];
+}
+
+#[cfg(feature = "devnet")]
+mod profile {
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+
+    // Pubkey of the devnet program keypair, `wallets/devnet-program-keypair.json` next to the admin wallet of
+    // config/config.dev.yml and never committed. No deployment backs this ID yet: compare it with
+    // `solana-keygen pubkey wallets/devnet-program-keypair.json` before deploying and replace it if it differs.
+    solana_program::declare_id!("43VRrrr6TLD1WM3mMroAbW4KLyLokHCY6epCroE4ha3q");
+
+    pub const ADMIN_PUBKEY: &str = "Gj9MVJ2jX2xApttsPJjb1sKoQePh1V226z3F9t3THZxD";
+    pub const SOLPAY_TREASURY_PUBKEY: &str = "ERdxYUQ5CibPsEeVKteXtLw2pNd5q9Cz36LYngme4VEf";
+    pub const AFFILIATE_REGISTRATION_FEE: u64 = (0.1 * LAMPORTS_PER_SOL as f64) as u64;
+
+    // No SPAF mint on devnet, milestone rewards are disabled
+    pub const SPAF_MINT_PUBKEY: Option<&str> = None;
+    pub const SPAF_MILESTONE_REWARDS: &[(u32, u64)] = &[];
+}
+
+#[cfg(feature = "mainnet")]
+mod profile {
+    use solana_program::native_token::LAMPORTS_PER_SOL;
+
+    // Pubkey of the mainnet program keypair, `wallets/mainnet-program-keypair.json` next to the admin wallet of
+    // config/config.main.yml and never committed. No deployment backs this ID yet: compare it with
+    // `solana-keygen pubkey wallets/mainnet-program-keypair.json` before deploying and replace it if it differs.
+    solana_program::declare_id!("FTr2v8JP4XjJofry3iv4ohxXQ1YmhCFAqjH1KQpajbjb");
+
+    pub const ADMIN_PUBKEY: &str = "Gj9MVJ2jX2xApttsPJjb1sKoQePh1V226z3F9t3THZxD";
+    pub const SOLPAY_TREASURY_PUBKEY: &str = "ERdxYUQ5CibPsEeVKteXtLw2pNd5q9Cz36LYngme4VEf";
+    pub const AFFILIATE_REGISTRATION_FEE: u64 = (0.1 * LAMPORTS_PER_SOL as f64) as u64;
+
+    // Solpay Affiliate Token, the `TokenMint` of whitelist-token/token-maintainer/src/update-metadata.ts
+    pub const SPAF_MINT_PUBKEY: Option<&str> = Some("7wXEA2xe5w1iPgvAQsdtRQ6cPFqM4fSrhwtHhEswVzj6");
+    // (total_redeemed_amount_in_sol reached, SPAF base units minted)
+    pub const SPAF_MILESTONE_REWARDS: &[(u32, u64)] = &[(10, 1), (50, 5), (100, 10), (500, 50)];
+}
+
+#[cfg(any(feature = "localnet", feature = "devnet", feature = "mainnet"))]
+pub use profile::*;
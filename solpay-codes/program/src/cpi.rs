@@ -0,0 +1,895 @@
+// Wrappers for invoking the program from other on-chain programs.
+// Pass an empty `signer_seeds` slice when the signer is not a PDA of the calling program.
+// View wrappers return the decoded return data of the program.
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::fee_discount_config::state::FeeDiscountTier;
+use crate::instruction;
+use crate::views::{ProjectCapacity, PromoCodeDiscount, RedeemableAmount};
+
+fn invoke_checked<'a>(instruction: &Instruction, program: &AccountInfo<'a>, account_infos: &[AccountInfo<'a>], signer_seeds: &[&[&[u8]]]) -> ProgramResult {
+    if !crate::check_id(program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut account_infos = account_infos.to_vec();
+    account_infos.push(program.clone());
+
+    invoke_signed(instruction, &account_infos, signer_seeds)
+}
+
+fn read_view<T: BorshDeserialize>(program: &AccountInfo) -> Result<T, ProgramError> {
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => {
+            T::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_project<'a>(
+    program: AccountInfo<'a>,
+    initializer: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    candy_machine: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    candy_machine_project_pda_account: AccountInfo<'a>,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u8,
+    title: String,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::register_project(
+            program.key,
+            initializer.key,
+            project_pda_account.key,
+            protocol_stats_pda_account.key,
+            candy_machine_project_pda_account.key,
+            *candy_machine.key,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        ),
+        &program,
+        &[
+            initializer,
+            project_pda_account,
+            system_program,
+            candy_machine,
+            protocol_stats_pda_account,
+            candy_machine_project_pda_account,
+        ],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_project_v2<'a>(
+    program: AccountInfo<'a>,
+    initializer: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    candy_machine: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    candy_machine_project_pda_account: AccountInfo<'a>,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::register_project_v2(
+            program.key,
+            initializer.key,
+            project_pda_account.key,
+            protocol_stats_pda_account.key,
+            candy_machine_project_pda_account.key,
+            *candy_machine.key,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        ),
+        &program,
+        &[
+            initializer,
+            project_pda_account,
+            system_program,
+            candy_machine,
+            protocol_stats_pda_account,
+            candy_machine_project_pda_account,
+        ],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_project<'a>(
+    program: AccountInfo<'a>,
+    admin: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u8,
+    title: String,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::update_project(
+            program.key,
+            admin.key,
+            project_pda_account.key,
+            project_owner_pubkey,
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        ),
+        &program,
+        &[admin, project_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_project_v2<'a>(
+    program: AccountInfo<'a>,
+    admin: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::update_project_v2(
+            program.key,
+            admin.key,
+            project_pda_account.key,
+            project_owner_pubkey,
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        ),
+        &program,
+        &[admin, project_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn close_project<'a>(
+    program: AccountInfo<'a>,
+    admin: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    project_owner: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    candy_machine_project_pda_account: AccountInfo<'a>,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::close_project(
+            program.key,
+            admin.key,
+            project_pda_account.key,
+            protocol_stats_pda_account.key,
+            candy_machine_project_pda_account.key,
+            *project_owner.key,
+            candy_machine_id,
+        ),
+        &program,
+        &[admin, project_pda_account, project_owner, protocol_stats_pda_account, candy_machine_project_pda_account],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn close_project_with_affiliate_index<'a>(
+    program: AccountInfo<'a>,
+    admin: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    project_owner: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    candy_machine_project_pda_account: AccountInfo<'a>,
+    affiliate_index_page_pda_accounts: Vec<AccountInfo<'a>>,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let affiliate_index_page_pdas: Vec<Pubkey> = affiliate_index_page_pda_accounts.iter().map(|account| *account.key).collect();
+    let instruction = instruction::close_project_with_affiliate_index(
+        program.key,
+        admin.key,
+        project_pda_account.key,
+        protocol_stats_pda_account.key,
+        candy_machine_project_pda_account.key,
+        &affiliate_index_page_pdas,
+        *project_owner.key,
+        candy_machine_id,
+    );
+    let mut account_infos = vec![admin, project_pda_account, project_owner, protocol_stats_pda_account, candy_machine_project_pda_account];
+    account_infos.extend(affiliate_index_page_pda_accounts);
+
+    invoke_checked(&instruction, &program, &account_infos, signer_seeds)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_affiliate<'a>(
+    program: AccountInfo<'a>,
+    affiliate: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    solpay_treasury: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    affiliate_index_page_pda_account: AccountInfo<'a>,
+    affiliate_profile_pda_account: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::register_affiliate(
+            program.key,
+            affiliate.key,
+            affiliate_pda_account.key,
+            project_pda_account.key,
+            solpay_treasury.key,
+            protocol_stats_pda_account.key,
+            affiliate_index_page_pda_account.key,
+            affiliate_profile_pda_account.key,
+            project_owner_pubkey,
+            candy_machine_id,
+        ),
+        &program,
+        &[
+            affiliate,
+            affiliate_pda_account,
+            project_pda_account,
+            solpay_treasury,
+            system_program,
+            protocol_stats_pda_account,
+            affiliate_index_page_pda_account,
+            affiliate_profile_pda_account,
+        ],
+        signer_seeds,
+    )
+}
+
+// Pass `gating_mint` and `token_program` for projects burning the gating tokens
+#[allow(clippy::too_many_arguments)]
+pub fn register_affiliate_with_gating_token<'a>(
+    program: AccountInfo<'a>,
+    affiliate: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    solpay_treasury: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    affiliate_index_page_pda_account: AccountInfo<'a>,
+    affiliate_profile_pda_account: AccountInfo<'a>,
+    gating_token_account: AccountInfo<'a>,
+    gating_mint: Option<(AccountInfo<'a>, AccountInfo<'a>)>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::register_affiliate_with_gating_token(
+        program.key,
+        affiliate.key,
+        affiliate_pda_account.key,
+        project_pda_account.key,
+        solpay_treasury.key,
+        protocol_stats_pda_account.key,
+        affiliate_index_page_pda_account.key,
+        affiliate_profile_pda_account.key,
+        gating_token_account.key,
+        gating_mint.as_ref().map(|(gating_mint, _)| gating_mint.key),
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+    let mut account_infos = vec![
+        affiliate,
+        affiliate_pda_account,
+        project_pda_account,
+        solpay_treasury,
+        system_program,
+        protocol_stats_pda_account,
+        affiliate_index_page_pda_account,
+        affiliate_profile_pda_account,
+        gating_token_account,
+    ];
+
+    if let Some((gating_mint, token_program)) = gating_mint {
+        account_infos.extend([gating_mint, token_program]);
+    }
+
+    invoke_checked(&instruction, &program, &account_infos, signer_seeds)
+}
+
+// Pass `gating` for gated projects, along with the gating mint and token program when the project burns
+#[allow(clippy::too_many_arguments)]
+pub fn register_affiliate_with_spaf_discount<'a>(
+    program: AccountInfo<'a>,
+    affiliate: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    solpay_treasury: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    affiliate_index_page_pda_account: AccountInfo<'a>,
+    affiliate_profile_pda_account: AccountInfo<'a>,
+    gating: Option<(AccountInfo<'a>, Option<(AccountInfo<'a>, AccountInfo<'a>)>)>,
+    spaf_token_account: AccountInfo<'a>,
+    fee_discount_config_pda_account: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let gating_mint = gating.as_ref().and_then(|(_, gating_mint)| gating_mint.as_ref().map(|(gating_mint, _)| gating_mint.key));
+    let instruction = instruction::register_affiliate_with_spaf_discount(
+        program.key,
+        affiliate.key,
+        affiliate_pda_account.key,
+        project_pda_account.key,
+        solpay_treasury.key,
+        protocol_stats_pda_account.key,
+        affiliate_index_page_pda_account.key,
+        affiliate_profile_pda_account.key,
+        gating.as_ref().map(|(gating_token_account, _)| gating_token_account.key),
+        gating_mint,
+        spaf_token_account.key,
+        fee_discount_config_pda_account.key,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+    let mut account_infos = vec![
+        affiliate,
+        affiliate_pda_account,
+        project_pda_account,
+        solpay_treasury,
+        system_program,
+        protocol_stats_pda_account,
+        affiliate_index_page_pda_account,
+        affiliate_profile_pda_account,
+        spaf_token_account,
+        fee_discount_config_pda_account,
+    ];
+
+    if let Some((gating_token_account, gating_mint)) = gating {
+        account_infos.push(gating_token_account);
+
+        if let Some((gating_mint, token_program)) = gating_mint {
+            account_infos.extend([gating_mint, token_program]);
+        }
+    }
+
+    invoke_checked(&instruction, &program, &account_infos, signer_seeds)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn redeem_reward<'a>(
+    program: AccountInfo<'a>,
+    affiliate: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::redeem_reward(
+            program.key,
+            affiliate.key,
+            affiliate_pda_account.key,
+            project_pda_account.key,
+            protocol_stats_pda_account.key,
+            project_owner_pubkey,
+            candy_machine_id,
+        ),
+        &program,
+        &[affiliate, affiliate_pda_account, project_pda_account, protocol_stats_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn redeem_reward_with_milestone_reward<'a>(
+    program: AccountInfo<'a>,
+    affiliate: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    reward_token_account: AccountInfo<'a>,
+    spaf_mint: AccountInfo<'a>,
+    spaf_mint_authority: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::redeem_reward_with_milestone_reward(
+            program.key,
+            affiliate.key,
+            affiliate_pda_account.key,
+            project_pda_account.key,
+            protocol_stats_pda_account.key,
+            reward_token_account.key,
+            spaf_mint.key,
+            spaf_mint_authority.key,
+            project_owner_pubkey,
+            candy_machine_id,
+        ),
+        &program,
+        &[affiliate, affiliate_pda_account, project_pda_account, protocol_stats_pda_account, system_program, reward_token_account, spaf_mint, spaf_mint_authority, token_program],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn close_affiliate_account<'a>(
+    program: AccountInfo<'a>,
+    admin: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    solpay_treasury: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    affiliate_index_page_pda_account: Option<AccountInfo<'a>>,
+    affiliate_profile_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::close_affiliate_account(
+        program.key,
+        admin.key,
+        affiliate_pda_account.key,
+        project_pda_account.key,
+        solpay_treasury.key,
+        protocol_stats_pda_account.key,
+        affiliate_index_page_pda_account.as_ref().map(|page| page.key),
+        affiliate_profile_pda_account.key,
+        affiliate_pubkey,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+    let mut account_infos = vec![admin, affiliate_pda_account, project_pda_account, solpay_treasury, protocol_stats_pda_account, affiliate_profile_pda_account, system_program];
+    account_infos.extend(affiliate_index_page_pda_account);
+
+    invoke_checked(&instruction, &program, &account_infos, signer_seeds)
+}
+
+pub fn get_redeemable_amount<'a>(
+    program: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Result<RedeemableAmount, ProgramError> {
+    invoke_checked(
+        &instruction::get_redeemable_amount(
+            program.key,
+            affiliate_pda_account.key,
+            project_pda_account.key,
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        ),
+        &program,
+        &[affiliate_pda_account, project_pda_account],
+        &[],
+    )?;
+
+    read_view(&program)
+}
+
+pub fn get_project_capacity<'a>(
+    program: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Result<ProjectCapacity, ProgramError> {
+    invoke_checked(
+        &instruction::get_project_capacity(
+            program.key,
+            project_pda_account.key,
+            project_owner_pubkey,
+            candy_machine_id,
+        ),
+        &program,
+        &[project_pda_account],
+        &[],
+    )?;
+
+    read_view(&program)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn get_project_capacity_for_affiliate<'a>(
+    program: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    affiliate: AccountInfo<'a>,
+    gating_token_account: Option<AccountInfo<'a>>,
+    spaf_token_account: Option<AccountInfo<'a>>,
+    fee_discount_config_pda_account: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Result<ProjectCapacity, ProgramError> {
+    let instruction = instruction::get_project_capacity_for_affiliate(
+        program.key,
+        project_pda_account.key,
+        affiliate.key,
+        gating_token_account.as_ref().map(|account| account.key),
+        spaf_token_account.as_ref().map(|account| account.key),
+        fee_discount_config_pda_account.key,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+    let mut account_infos = vec![project_pda_account, affiliate];
+    account_infos.extend(gating_token_account);
+    account_infos.extend(spaf_token_account);
+    account_infos.push(fee_discount_config_pda_account);
+
+    invoke_checked(&instruction, &program, &account_infos, &[])?;
+
+    read_view(&program)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn claim_referral_code<'a>(
+    program: AccountInfo<'a>,
+    affiliate: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    referral_code_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    previous_affiliate_pda_account: Option<AccountInfo<'a>>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    code: String,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::claim_referral_code(
+        program.key,
+        affiliate.key,
+        affiliate_pda_account.key,
+        project_pda_account.key,
+        referral_code_pda_account.key,
+        previous_affiliate_pda_account.as_ref().map(|account| account.key),
+        project_owner_pubkey,
+        candy_machine_id,
+        code,
+    );
+    let mut account_infos = vec![affiliate, affiliate_pda_account, project_pda_account, referral_code_pda_account, system_program];
+    account_infos.extend(previous_affiliate_pda_account);
+
+    invoke_checked(&instruction, &program, &account_infos, signer_seeds)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_promo_code<'a>(
+    program: AccountInfo<'a>,
+    project_owner: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    promo_code_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    affiliate_pda_account: Option<AccountInfo<'a>>,
+    candy_machine_id: Pubkey,
+    code: String,
+    affiliate_pubkey: Option<Pubkey>,
+    discount_basis_points: u16,
+    max_uses: u32,
+    max_uses_per_wallet: u16,
+    starts_at: i64,
+    ends_at: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::create_promo_code(
+        program.key,
+        project_owner.key,
+        project_pda_account.key,
+        promo_code_pda_account.key,
+        affiliate_pda_account.as_ref().map(|account| account.key),
+        candy_machine_id,
+        code,
+        affiliate_pubkey,
+        discount_basis_points,
+        max_uses,
+        max_uses_per_wallet,
+        starts_at,
+        ends_at,
+    );
+    let mut account_infos = vec![project_owner, project_pda_account, promo_code_pda_account, system_program];
+    account_infos.extend(affiliate_pda_account);
+
+    invoke_checked(&instruction, &program, &account_infos, signer_seeds)
+}
+
+// Called from buyer facing mint flows signed by the project owner, returns the discount to apply
+#[allow(clippy::too_many_arguments)]
+pub fn consume_promo_code<'a>(
+    program: AccountInfo<'a>,
+    buyer: AccountInfo<'a>,
+    project_owner: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    promo_code_pda_account: AccountInfo<'a>,
+    promo_code_usage_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    candy_machine_id: Pubkey,
+    code: String,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<PromoCodeDiscount, ProgramError> {
+    invoke_checked(
+        &instruction::consume_promo_code(
+            program.key,
+            buyer.key,
+            project_pda_account.key,
+            promo_code_pda_account.key,
+            promo_code_usage_pda_account.key,
+            *project_owner.key,
+            candy_machine_id,
+            code,
+        ),
+        &program,
+        &[buyer, project_owner, project_pda_account, promo_code_pda_account, promo_code_usage_pda_account, system_program],
+        signer_seeds,
+    )?;
+
+    read_view(&program)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn set_project_gating<'a>(
+    program: AccountInfo<'a>,
+    project_owner: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    candy_machine_id: Pubkey,
+    gating_mint: Option<Pubkey>,
+    gating_min_amount: u64,
+    gating_burn: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::set_project_gating(
+            program.key,
+            project_owner.key,
+            project_pda_account.key,
+            candy_machine_id,
+            gating_mint,
+            gating_min_amount,
+            gating_burn,
+        ),
+        &program,
+        &[project_owner, project_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+// `mint_accounts` are the accounts of the Candy Machine mint instruction, passed on as they are.
+// `payment_wallet` signs for the commission paid out of the mint price.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_with_referral<'a>(
+    program: AccountInfo<'a>,
+    buyer: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    candy_machine: AccountInfo<'a>,
+    payment_wallet: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    candy_machine_program: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    affiliate_profile_pda_account: AccountInfo<'a>,
+    mint_accounts: &[AccountInfo<'a>],
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    mint_data: Vec<u8>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_infos = vec![
+        buyer.clone(),
+        affiliate_pda_account.clone(),
+        project_pda_account.clone(),
+        candy_machine.clone(),
+        payment_wallet.clone(),
+        system_program,
+        candy_machine_program.clone(),
+        protocol_stats_pda_account.clone(),
+        affiliate_profile_pda_account.clone(),
+    ];
+    account_infos.extend_from_slice(mint_accounts);
+
+    invoke_checked(
+        &instruction::mint_with_referral(
+            program.key,
+            buyer.key,
+            affiliate_pda_account.key,
+            project_pda_account.key,
+            payment_wallet.key,
+            candy_machine_program.key,
+            protocol_stats_pda_account.key,
+            affiliate_profile_pda_account.key,
+            mint_accounts.iter()
+                .map(|account| AccountMeta {
+                    pubkey: *account.key,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            affiliate_pubkey,
+            project_owner_pubkey,
+            *candy_machine.key,
+            mint_data,
+        ),
+        &program,
+        &account_infos,
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_protocol_stats<'a>(
+    program: AccountInfo<'a>,
+    admin: AccountInfo<'a>,
+    protocol_stats_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    live_project_count: u32,
+    live_affiliate_count: u32,
+    registration_fees_collected_lamports: u64,
+    total_rewards_redeemed_lamports: u64,
+    total_referred_volume_lamports: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::initialize_protocol_stats(
+            program.key,
+            admin.key,
+            protocol_stats_pda_account.key,
+            live_project_count,
+            live_affiliate_count,
+            registration_fees_collected_lamports,
+            total_rewards_redeemed_lamports,
+            total_referred_volume_lamports,
+        ),
+        &program,
+        &[admin, protocol_stats_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn index_affiliate<'a>(
+    program: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    affiliate_index_page_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::index_affiliate(
+            program.key,
+            payer.key,
+            affiliate_pda_account.key,
+            project_pda_account.key,
+            affiliate_index_page_pda_account.key,
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        ),
+        &program,
+        &[payer, affiliate_pda_account, project_pda_account, affiliate_index_page_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn add_affiliate_to_profile<'a>(
+    program: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    affiliate_pda_account: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    affiliate_profile_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::add_affiliate_to_profile(
+            program.key,
+            payer.key,
+            affiliate_pda_account.key,
+            project_pda_account.key,
+            affiliate_profile_pda_account.key,
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        ),
+        &program,
+        &[payer, affiliate_pda_account, project_pda_account, affiliate_profile_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn link_candy_machine_project<'a>(
+    program: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    project_pda_account: AccountInfo<'a>,
+    candy_machine: AccountInfo<'a>,
+    candy_machine_project_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    project_owner_pubkey: Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::link_candy_machine_project(
+            program.key,
+            payer.key,
+            project_pda_account.key,
+            candy_machine_project_pda_account.key,
+            project_owner_pubkey,
+            *candy_machine.key,
+        ),
+        &program,
+        &[payer, project_pda_account, candy_machine, candy_machine_project_pda_account, system_program],
+        signer_seeds,
+    )
+}
+
+pub fn set_fee_discount_tiers<'a>(
+    program: AccountInfo<'a>,
+    admin: AccountInfo<'a>,
+    fee_discount_config_pda_account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    tiers: Vec<FeeDiscountTier>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_checked(
+        &instruction::set_fee_discount_tiers(
+            program.key,
+            admin.key,
+            fee_discount_config_pda_account.key,
+            tiers,
+        ),
+        &program,
+        &[admin, fee_discount_config_pda_account, system_program],
+        signer_seeds,
+    )
+}
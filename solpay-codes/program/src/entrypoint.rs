@@ -3,7 +3,6 @@ use solana_program::{
     entrypoint::ProgramResult,
     pubkey::Pubkey,
     account_info::AccountInfo,
-    msg,
 };
 
 use crate::processor;
@@ -16,7 +15,7 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8]
 ) -> ProgramResult {
-    msg!(
+    log_debug!(
         "Processing instruction: {}, {} accounts, {:?}",
         program_id,
         accounts.len(),
@@ -1,7 +1,7 @@
 use solana_program::{program_error::ProgramError};
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Clone, Copy, Debug, Error)]
 pub enum CandyMachineAffiliatesError {
     // 0
     #[error("Action not allowed")]
@@ -39,6 +39,78 @@ pub enum CandyMachineAffiliatesError {
     // 11
     #[error("Affiliate account balance has not reached the redeem threshold")]
     AffiliateAccountBalanceNotEnough,
+    // 12
+    #[error("Incorrect project owner account")]
+    IncorrectProjectOwnerAccount,
+    // 13
+    #[error("Invalid referral code")]
+    InvalidReferralCode,
+    // 14
+    #[error("Referral code already claimed")]
+    ReferralCodeAlreadyClaimed,
+    // 15
+    #[error("Invalid promo code")]
+    InvalidPromoCode,
+    // 16
+    #[error("Invalid promo code discount")]
+    InvalidPromoCodeDiscount,
+    // 17
+    #[error("Promo code ends before it starts")]
+    InvalidPromoCodePeriod,
+    // 18
+    #[error("Promo code already exists")]
+    PromoCodeAlreadyExists,
+    // 19
+    #[error("Promo code not active")]
+    PromoCodeNotActive,
+    // 20
+    #[error("Promo code max uses reached")]
+    PromoCodeMaxUsesReached,
+    // 21
+    #[error("Promo code max uses per wallet reached")]
+    PromoCodeWalletLimitReached,
+    // 22
+    #[error("Invalid project gating settings")]
+    InvalidProjectGating,
+    // 23
+    #[error("Invalid gating token account")]
+    InvalidGatingTokenAccount,
+    // 24
+    #[error("Gating token balance not enough")]
+    GatingTokenBalanceNotEnough,
+    // 25
+    #[error("Invalid reward token account")]
+    InvalidRewardTokenAccount,
+    // 26
+    #[error("Invalid SPAF token account")]
+    InvalidSpafTokenAccount,
+    // 27
+    #[error("Invalid candy machine account")]
+    InvalidCandyMachineAccount,
+    // 28
+    #[error("Project owner is not the candy machine authority")]
+    InvalidCandyMachineAuthority,
+    // 29
+    #[error("Invalid mint payment wallet")]
+    InvalidPaymentWallet,
+    // 30
+    #[error("Affiliate already in the affiliate index")]
+    AffiliateAlreadyIndexed,
+    // 31
+    #[error("Project already in the affiliate profile")]
+    ProjectAlreadyInAffiliateProfile,
+    // 32
+    #[error("Candy machine already registered to a project")]
+    CandyMachineAlreadyRegistered,
+    // 33
+    #[error("Affiliate already has a referral code")]
+    AffiliateReferralCodeAlreadyClaimed,
+    // 34
+    #[error("Mint instruction does not mint from the candy machine")]
+    InvalidMintInstruction,
+    // 35
+    #[error("Invalid fee discount tiers")]
+    InvalidFeeDiscountTiers,
 }
 
 impl From<CandyMachineAffiliatesError> for ProgramError {
@@ -0,0 +1,320 @@
+// Typed events emitted with `sol_log_data` so indexers do not have to parse `msg!` lines.
+// Each event is logged as two data fields: the `EVENT_DISCRIMINATOR` bytes followed by the
+// Borsh encoded `CandyMachineAffiliatesEvent`. New variants must only be appended to keep
+// the Borsh variant indices stable.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+use crate::fee_discount_config::state::FeeDiscountTier;
+
+pub const EVENT_DISCRIMINATOR: &str = "solpay_event";
+
+const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProjectRegistered {
+    pub project: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u8,
+    pub title: String,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProjectUpdated {
+    pub project: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u8,
+    pub title: String,
+    pub updated_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProjectClosed {
+    pub project: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub refunded_lamports: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AffiliateRegistered {
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project: Pubkey,
+    pub registration_fee: u64,
+    pub affiliate_count: u8,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RewardRedeemed {
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project: Pubkey,
+    pub reward_lamports: u64,
+    pub total_redeemed_amount_in_sol: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AffiliateClosed {
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project: Pubkey,
+    pub treasury_lamports: u64,
+    pub affiliate_count: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ReferralCodeClaimed {
+    pub referral_code_account: Pubkey,
+    pub code: String,
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project: Pubkey,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ReferralCodeReleased {
+    pub referral_code_account: Pubkey,
+    pub code: String,
+    pub affiliate_account: Pubkey,
+    pub project: Pubkey,
+    pub treasury_lamports: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct PromoCodeCreated {
+    pub promo_code_account: Pubkey,
+    pub code: String,
+    pub project: Pubkey,
+    pub affiliate_account: Option<Pubkey>,
+    pub discount_basis_points: u16,
+    pub max_uses: u32,
+    pub max_uses_per_wallet: u16,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct PromoCodeConsumed {
+    pub promo_code_account: Pubkey,
+    pub code: String,
+    pub project: Pubkey,
+    pub affiliate_account: Option<Pubkey>,
+    pub buyer_pubkey: Pubkey,
+    pub discount_basis_points: u16,
+    pub use_count: u32,
+    pub wallet_use_count: u16,
+    pub used_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProjectGatingUpdated {
+    pub project: Pubkey,
+    pub gating_mint: Option<Pubkey>,
+    pub gating_min_amount: u64,
+    pub gating_burn: bool,
+    pub updated_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MilestoneRewardMinted {
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+    pub total_redeemed_amount_in_sol: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MintReferred {
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project: Pubkey,
+    pub buyer_pubkey: Pubkey,
+    pub price_lamports: u64,
+    pub commission_lamports: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProtocolStatsInitialized {
+    pub protocol_stats_account: Pubkey,
+    pub live_project_count: u32,
+    pub live_affiliate_count: u32,
+    pub registration_fees_collected_lamports: u64,
+    pub total_rewards_redeemed_lamports: u64,
+    pub total_referred_volume_lamports: u64,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AffiliateIndexed {
+    pub affiliate_account: Pubkey,
+    pub project: Pubkey,
+    pub affiliate_index_page_account: Pubkey,
+    pub position: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AffiliateAddedToProfile {
+    pub affiliate_profile_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub affiliate_account: Pubkey,
+    pub project: Pubkey,
+    pub commission_lamports: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct CandyMachineProjectLinked {
+    pub candy_machine_project_account: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub project: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProjectRegisteredV2 {
+    pub project: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u32,
+    pub title: String,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProjectUpdatedV2 {
+    pub project: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u32,
+    pub title: String,
+    pub updated_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AffiliateRegisteredV2 {
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project: Pubkey,
+    pub registration_fee: u64,
+    pub affiliate_count: u32,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AffiliateClosedV2 {
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project: Pubkey,
+    pub treasury_lamports: u64,
+    pub affiliate_count: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct FeeDiscountTiersSet {
+    pub fee_discount_config_account: Pubkey,
+    pub tiers: Vec<FeeDiscountTier>,
+    pub updated_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum CandyMachineAffiliatesEvent {
+    ProjectRegistered(ProjectRegistered),
+    ProjectUpdated(ProjectUpdated),
+    ProjectClosed(ProjectClosed),
+    AffiliateRegistered(AffiliateRegistered),
+    RewardRedeemed(RewardRedeemed),
+    AffiliateClosed(AffiliateClosed),
+    ReferralCodeClaimed(ReferralCodeClaimed),
+    ReferralCodeReleased(ReferralCodeReleased),
+    PromoCodeCreated(PromoCodeCreated),
+    PromoCodeConsumed(PromoCodeConsumed),
+    ProjectGatingUpdated(ProjectGatingUpdated),
+    MilestoneRewardMinted(MilestoneRewardMinted),
+    MintReferred(MintReferred),
+    ProtocolStatsInitialized(ProtocolStatsInitialized),
+    AffiliateIndexed(AffiliateIndexed),
+    AffiliateAddedToProfile(AffiliateAddedToProfile),
+    CandyMachineProjectLinked(CandyMachineProjectLinked),
+    // Logged in place of the variants above with u8 affiliate counts
+    ProjectRegisteredV2(ProjectRegisteredV2),
+    ProjectUpdatedV2(ProjectUpdatedV2),
+    AffiliateRegisteredV2(AffiliateRegisteredV2),
+    AffiliateClosedV2(AffiliateClosedV2),
+    FeeDiscountTiersSet(FeeDiscountTiersSet),
+}
+
+impl CandyMachineAffiliatesEvent {
+    // Logs the event as a `Program data:` entry
+    pub fn emit(&self) {
+        let data = borsh::to_vec(self).expect("Event serialization failed");
+
+        sol_log_data(&[EVENT_DISCRIMINATOR.as_bytes(), &data]);
+    }
+
+    // Decodes the data fields of a single `sol_log_data` call
+    pub fn decode(fields: &[&[u8]]) -> Option<Self> {
+        match fields {
+            [discriminator, data] if *discriminator == EVENT_DISCRIMINATOR.as_bytes() => {
+                Self::try_from_slice(data).ok()
+            }
+            _ => None,
+        }
+    }
+
+    // Decodes all events logged by `program_id` in the log messages of a transaction.
+    // Invocations are tracked so data logged by other programs, including CPI callees, is skipped.
+    pub fn from_logs<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<Self> {
+        let program_id = program_id.to_string();
+        let mut invocations: Vec<&str> = Vec::new();
+        let mut events = Vec::new();
+
+        for log in logs {
+            let log = log.as_ref();
+
+            if let Some(encoded) = log.strip_prefix(PROGRAM_DATA_LOG_PREFIX) {
+                if invocations.last() != Some(&program_id.as_str()) {
+                    continue;
+                }
+
+                let fields = encoded
+                    .split_whitespace()
+                    .map(|field| STANDARD.decode(field))
+                    .collect::<Result<Vec<_>, _>>();
+
+                if let Some(event) = fields.ok().and_then(|fields| {
+                    Self::decode(&fields.iter().map(Vec::as_slice).collect::<Vec<_>>())
+                }) {
+                    events.push(event);
+                }
+            } else if let Some(rest) = log.strip_prefix("Program ") {
+                let mut words = rest.split_whitespace();
+
+                match (words.next(), words.next()) {
+                    (Some(invoked), Some("invoke")) => invocations.push(invoked),
+                    (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                        invocations.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        events
+    }
+}
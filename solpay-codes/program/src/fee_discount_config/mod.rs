@@ -0,0 +1,2 @@
+pub mod state;
+pub mod set_fee_discount_tiers;
@@ -0,0 +1,133 @@
+use std::str::FromStr;
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    borsh1::try_from_slice_unchecked,
+    entrypoint::ProgramResult,
+    system_instruction,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID
+};
+use borsh::BorshSerialize;
+use crate::ADMIN_PUBKEY;
+use crate::affiliates::fee_discounts::is_valid_fee_discount_schedule;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, FeeDiscountTiersSet};
+use crate::fee_discount_config::state::{
+    FEE_DISCOUNT_CONFIG_ACCOUNT_DATA_VERSION,
+    FeeDiscountConfigAccountState,
+    FeeDiscountTier,
+};
+use crate::utils::validate_client_pda;
+
+// Replaces the SPAF holder discount tiers of the registration fee, creating the config account the first time
+pub fn set_fee_discount_tiers(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tiers: Vec<FeeDiscountTier>,
+) -> ProgramResult {
+    log_debug!("Setting fee discount tiers...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock = Clock::get()?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let admin_pubkey = Pubkey::from_str(ADMIN_PUBKEY).expect("Pubkey conversion failed");
+
+    if initializer.key.ne(&admin_pubkey) {
+        log_error!("Action not allowed");
+        return Err(CandyMachineAffiliatesError::ActionNotAllowed.into());
+    }
+
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        pda_account,
+        &[FeeDiscountConfigAccountState::DISCRIMINATOR.as_ref()],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    // Validate tiers
+
+    if !is_valid_fee_discount_schedule(&tiers) {
+        log_error!("Invalid fee discount tiers");
+        return Err(CandyMachineAffiliatesError::InvalidFeeDiscountTiers.into());
+    }
+
+    if pda_account.owner != program_id {
+        // Calculate rent required
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(FeeDiscountConfigAccountState::LENGTH);
+
+        // Create the fee discount config account
+        invoke_signed(
+            &system_instruction::create_account(
+                initializer.key,
+                pda_account.key,
+                rent_lamports,
+                FeeDiscountConfigAccountState::LENGTH.try_into().unwrap(),
+                program_id,
+            ),
+            &[initializer.clone(), pda_account.clone(), system_program.clone()],
+            &[
+                &[
+                    FeeDiscountConfigAccountState::DISCRIMINATOR.as_ref(),
+                    &[bump_seed]
+                ]
+            ],
+        )?;
+
+        log_debug!("PDA created");
+    }
+
+    log_debug!("Unpacking state account");
+    let mut account_state = try_from_slice_unchecked::<FeeDiscountConfigAccountState>(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
+
+    if !account_state.is_initialized() {
+        account_state.discriminator = FeeDiscountConfigAccountState::DISCRIMINATOR.to_string();
+        account_state.is_initialized = true;
+        account_state.data_version = FEE_DISCOUNT_CONFIG_ACCOUNT_DATA_VERSION;
+        account_state.data.created_at = clock.unix_timestamp;
+    }
+
+    account_state.data.tiers = tiers;
+    account_state.data.updated_at = clock.unix_timestamp;
+
+    log_debug!("Serializing account");
+    account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    log_debug!("State account serialized");
+
+    log_info!("Fee discount tiers set: {} tiers.", account_state.data.tiers.len());
+
+    CandyMachineAffiliatesEvent::FeeDiscountTiersSet(FeeDiscountTiersSet {
+        fee_discount_config_account: *pda_account.key,
+        tiers: account_state.data.tiers,
+        updated_at: account_state.data.updated_at,
+    }).emit();
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program_pack::{IsInitialized, Sealed};
+
+pub const FEE_DISCOUNT_CONFIG_ACCOUNT_DATA_VERSION: u8 = 0;
+
+// Most tiers the config account has room for
+pub const MAX_FEE_DISCOUNT_TIERS: usize = 8;
+
+// Registration fee discount of wallets holding at least `min_spaf_balance` SPAF base units
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FeeDiscountTier {
+    pub min_spaf_balance: u64,
+    pub discount_basis_points: u16,
+}
+
+// SPAF holder discount schedule of the affiliate registration fee, set by the admin.
+// The single account is derived from the discriminator alone.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct FeeDiscountConfigAccountStateData {
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub tiers: Vec<FeeDiscountTier>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct FeeDiscountConfigAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: FeeDiscountConfigAccountStateData,
+}
+
+impl Sealed for FeeDiscountConfigAccountState {}
+
+impl IsInitialized for FeeDiscountConfigAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl FeeDiscountConfigAccountState {
+    pub const DISCRIMINATOR: &'static str = "fee_discount_config";
+    pub const LENGTH: usize = (4 + FeeDiscountConfigAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // created_at
+        + 8
+        // updated_at
+        + 8
+        // tiers
+        + 4 + MAX_FEE_DISCOUNT_TIERS * (8 + 2)
+    ;
+}
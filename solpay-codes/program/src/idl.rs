@@ -0,0 +1,730 @@
+// Anchor compatible JSON IDL, in the Shank flavour used for native programs: every instruction
+// carries the `u8` discriminant read by `CandyMachineAffiliatesInstruction::unpack` instead of
+// an Anchor sighash. Type layouts are built from the Borsh structs themselves, the field lists
+// below fail to compile when a field is added or removed and `tests/idl.rs` checks their order.
+// Regenerate `idl/candy_machine_affiliates.json` with `SOLPAY_UPDATE_IDL=1 cargo test --features localnet --test idl`.
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use crate::affiliate_index::state::{AffiliateIndexPageAccountState, AffiliateIndexPageAccountStateData};
+use crate::affiliate_profiles::state::{AffiliateProfileAccountState, AffiliateProfileAccountStateData};
+use crate::affiliates::state::{AffiliateAccountState, AffiliateAccountStateData};
+use crate::candy_machine_projects::state::{CandyMachineProjectAccountState, CandyMachineProjectAccountStateData};
+use crate::error::CandyMachineAffiliatesError;
+use crate::fee_discount_config::state::{FeeDiscountConfigAccountState, FeeDiscountConfigAccountStateData, FeeDiscountTier};
+use crate::events::{
+    AffiliateAddedToProfile,
+    AffiliateClosed,
+    AffiliateClosedV2,
+    AffiliateIndexed,
+    AffiliateRegistered,
+    AffiliateRegisteredV2,
+    CandyMachineProjectLinked,
+    FeeDiscountTiersSet,
+    MilestoneRewardMinted,
+    MintReferred,
+    ProjectClosed,
+    ProjectGatingUpdated,
+    ProjectRegistered,
+    ProjectRegisteredV2,
+    ProjectUpdated,
+    ProjectUpdatedV2,
+    PromoCodeConsumed,
+    PromoCodeCreated,
+    ProtocolStatsInitialized,
+    ReferralCodeClaimed,
+    ReferralCodeReleased,
+    RewardRedeemed,
+};
+use crate::instruction::{
+    AddAffiliateToProfilePayload,
+    ClaimReferralCodePayload,
+    CloseAffiliatePayload,
+    CloseProjectPayload,
+    ConsumePromoCodePayload,
+    CreatePromoCodePayload,
+    GetProjectCapacityPayload,
+    GetRedeemableAmountPayload,
+    IndexAffiliatePayload,
+    InitializeProtocolStatsPayload,
+    LinkCandyMachineProjectPayload,
+    MintWithReferralPayload,
+    RedeemRewardPayload,
+    RegisterAffiliatePayload,
+    RegisterProjectPayload,
+    RegisterProjectV2Payload,
+    SetFeeDiscountTiersPayload,
+    SetProjectGatingPayload,
+    UpdateProjectPayload,
+    UpdateProjectV2Payload,
+};
+use crate::projects::state::{ProjectAccountState, ProjectAccountStateData};
+use crate::promo_codes::state::{
+    PromoCodeAccountState,
+    PromoCodeAccountStateData,
+    PromoCodeUsageAccountState,
+    PromoCodeUsageAccountStateData,
+};
+use crate::referral_codes::state::{ReferralCodeAccountState, ReferralCodeAccountStateData};
+use crate::stats::state::{ProtocolStatsAccountState, ProtocolStatsAccountStateData};
+use crate::views::{ProjectCapacity, PromoCodeDiscount, RedeemableAmount};
+
+pub const IDL_NAME: &str = "candy_machine_affiliates";
+
+// IDL type of a Borsh encoded value
+pub trait IdlType {
+    fn idl_type() -> Value;
+}
+
+macro_rules! idl_primitive {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(impl IdlType for $ty {
+            fn idl_type() -> Value {
+                json!($name)
+            }
+        })*
+    };
+}
+
+idl_primitive! {
+    bool => "bool",
+    u8 => "u8",
+    u16 => "u16",
+    u32 => "u32",
+    u64 => "u64",
+    i64 => "i64",
+    f64 => "f64",
+    String => "string",
+    Vec<u8> => "bytes",
+    Pubkey => "publicKey",
+}
+
+impl<T: IdlType> IdlType for Option<T> {
+    fn idl_type() -> Value {
+        json!({ "option": T::idl_type() })
+    }
+}
+
+// Vec<u8> is the bytes primitive, other vectors are listed one by one
+impl IdlType for Vec<Pubkey> {
+    fn idl_type() -> Value {
+        json!({ "vec": Pubkey::idl_type() })
+    }
+}
+
+impl IdlType for Vec<FeeDiscountTier> {
+    fn idl_type() -> Value {
+        json!({ "vec": FeeDiscountTier::idl_type() })
+    }
+}
+
+impl<T: IdlType, const N: usize> IdlType for [T; N] {
+    fn idl_type() -> Value {
+        json!({ "array": [T::idl_type(), N] })
+    }
+}
+
+// Named fields of a Borsh struct, in serialization order
+pub trait IdlFields {
+    const NAME: &'static str;
+
+    fn idl_fields() -> Vec<Value>;
+}
+
+fn field_type<S, T: IdlType>(_: fn(&S) -> &T) -> Value {
+    T::idl_type()
+}
+
+// Lists the fields of a struct, the destructuring pattern makes the list exhaustive
+macro_rules! idl_fields {
+    ($ty:ident { $($field:ident),* $(,)? }) => {
+        impl IdlFields for $ty {
+            const NAME: &'static str = stringify!($ty);
+
+            fn idl_fields() -> Vec<Value> {
+                #[allow(dead_code)]
+                fn exhaustive(value: &$ty) {
+                    let $ty { $($field: _),* } = value;
+                }
+
+                vec![$(json!({
+                    "name": camel_case(stringify!($field)),
+                    "type": field_type(|value: &$ty| &value.$field),
+                })),*]
+            }
+        }
+
+        impl IdlType for $ty {
+            fn idl_type() -> Value {
+                json!({ "defined": stringify!($ty) })
+            }
+        }
+    };
+}
+
+idl_fields!(RegisterProjectPayload { candy_machine_id, affiliate_fee_percentage, affiliate_target_in_sol, max_affiliate_count, title });
+idl_fields!(UpdateProjectPayload { project_owner_pubkey, candy_machine_id, affiliate_fee_percentage, affiliate_target_in_sol, max_affiliate_count, title });
+idl_fields!(CloseProjectPayload { project_owner_pubkey, candy_machine_id });
+idl_fields!(RegisterAffiliatePayload { project_owner_pubkey, candy_machine_id });
+idl_fields!(RedeemRewardPayload { project_owner_pubkey, candy_machine_id });
+idl_fields!(CloseAffiliatePayload { affiliate_pubkey, project_owner_pubkey, candy_machine_id });
+idl_fields!(GetRedeemableAmountPayload { affiliate_pubkey, project_owner_pubkey, candy_machine_id });
+idl_fields!(GetProjectCapacityPayload { project_owner_pubkey, candy_machine_id });
+idl_fields!(ClaimReferralCodePayload { project_owner_pubkey, candy_machine_id, code });
+idl_fields!(CreatePromoCodePayload {
+    candy_machine_id,
+    code,
+    affiliate_pubkey,
+    discount_basis_points,
+    max_uses,
+    max_uses_per_wallet,
+    starts_at,
+    ends_at,
+});
+idl_fields!(ConsumePromoCodePayload { project_owner_pubkey, candy_machine_id, code });
+idl_fields!(SetProjectGatingPayload { candy_machine_id, gating_mint, gating_min_amount, gating_burn });
+idl_fields!(MintWithReferralPayload { affiliate_pubkey, project_owner_pubkey, candy_machine_id, mint_data });
+idl_fields!(InitializeProtocolStatsPayload {
+    live_project_count,
+    live_affiliate_count,
+    registration_fees_collected_lamports,
+    total_rewards_redeemed_lamports,
+    total_referred_volume_lamports,
+});
+idl_fields!(IndexAffiliatePayload { affiliate_pubkey, project_owner_pubkey, candy_machine_id });
+idl_fields!(AddAffiliateToProfilePayload { affiliate_pubkey, project_owner_pubkey, candy_machine_id });
+idl_fields!(LinkCandyMachineProjectPayload { project_owner_pubkey, candy_machine_id });
+idl_fields!(RegisterProjectV2Payload { candy_machine_id, affiliate_fee_percentage, affiliate_target_in_sol, max_affiliate_count, title });
+idl_fields!(UpdateProjectV2Payload { project_owner_pubkey, candy_machine_id, affiliate_fee_percentage, affiliate_target_in_sol, max_affiliate_count, title });
+idl_fields!(SetFeeDiscountTiersPayload { tiers });
+
+idl_fields!(ProjectAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(ProjectAccountStateData {
+    project_owner_pubkey,
+    candy_machine_id,
+    affiliate_fee_percentage,
+    affiliate_target_in_sol,
+    max_affiliate_count,
+    affiliate_count,
+    title,
+    created_at,
+    updated_at,
+    gating_mint,
+    gating_min_amount,
+    gating_burn,
+    total_mint_count,
+    total_referred_volume_lamports,
+    total_commission_lamports,
+    total_redemption_count,
+    peak_affiliate_count,
+    affiliate_index_length,
+});
+idl_fields!(AffiliateAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(AffiliateAccountStateData {
+    affiliate_pubkey,
+    project_owner_pubkey,
+    candy_machine_id,
+    total_redeemed_amount_in_sol,
+    created_at,
+    mint_count,
+    referred_volume_lamports,
+    total_commission_lamports,
+    redemption_count,
+    last_activity_at,
+    affiliate_index_position,
+    pending_milestone_reward,
+    referral_code,
+});
+idl_fields!(ReferralCodeAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(ReferralCodeAccountStateData { code, affiliate_account, affiliate_pubkey, project_owner_pubkey, candy_machine_id, created_at });
+idl_fields!(PromoCodeAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(PromoCodeAccountStateData {
+    code,
+    project_owner_pubkey,
+    candy_machine_id,
+    affiliate_account,
+    discount_basis_points,
+    max_uses,
+    max_uses_per_wallet,
+    use_count,
+    starts_at,
+    ends_at,
+    created_at,
+});
+idl_fields!(PromoCodeUsageAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(PromoCodeUsageAccountStateData { promo_code_account, buyer_pubkey, use_count, last_used_at });
+idl_fields!(ProtocolStatsAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(ProtocolStatsAccountStateData {
+    live_project_count,
+    live_affiliate_count,
+    registration_fees_collected_lamports,
+    total_rewards_redeemed_lamports,
+    total_referred_volume_lamports,
+    created_at,
+    updated_at,
+});
+idl_fields!(AffiliateIndexPageAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(AffiliateIndexPageAccountStateData { project, page, affiliate_accounts });
+idl_fields!(AffiliateProfileAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(AffiliateProfileAccountStateData { affiliate_pubkey, total_commission_lamports, project_accounts });
+idl_fields!(CandyMachineProjectAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(CandyMachineProjectAccountStateData { candy_machine_id, project_account, project_owner_pubkey, created_at });
+idl_fields!(FeeDiscountConfigAccountState { discriminator, is_initialized, data_version, data });
+idl_fields!(FeeDiscountConfigAccountStateData { created_at, updated_at, tiers });
+idl_fields!(FeeDiscountTier { min_spaf_balance, discount_basis_points });
+
+idl_fields!(ProjectRegistered {
+    project,
+    project_owner_pubkey,
+    candy_machine_id,
+    affiliate_fee_percentage,
+    affiliate_target_in_sol,
+    max_affiliate_count,
+    title,
+    created_at,
+});
+idl_fields!(ProjectUpdated { project, affiliate_fee_percentage, affiliate_target_in_sol, max_affiliate_count, title, updated_at });
+idl_fields!(ProjectClosed { project, project_owner_pubkey, candy_machine_id, refunded_lamports });
+idl_fields!(AffiliateRegistered { affiliate_account, affiliate_pubkey, project, registration_fee, affiliate_count, created_at });
+idl_fields!(RewardRedeemed { affiliate_account, affiliate_pubkey, project, reward_lamports, total_redeemed_amount_in_sol });
+idl_fields!(AffiliateClosed { affiliate_account, affiliate_pubkey, project, treasury_lamports, affiliate_count });
+idl_fields!(ReferralCodeClaimed { referral_code_account, code, affiliate_account, affiliate_pubkey, project, created_at });
+idl_fields!(ReferralCodeReleased { referral_code_account, code, affiliate_account, project, treasury_lamports });
+idl_fields!(PromoCodeCreated {
+    promo_code_account,
+    code,
+    project,
+    affiliate_account,
+    discount_basis_points,
+    max_uses,
+    max_uses_per_wallet,
+    starts_at,
+    ends_at,
+    created_at,
+});
+idl_fields!(PromoCodeConsumed {
+    promo_code_account,
+    code,
+    project,
+    affiliate_account,
+    buyer_pubkey,
+    discount_basis_points,
+    use_count,
+    wallet_use_count,
+    used_at,
+});
+idl_fields!(MilestoneRewardMinted { affiliate_account, affiliate_pubkey, token_account, amount, total_redeemed_amount_in_sol });
+idl_fields!(ProjectGatingUpdated { project, gating_mint, gating_min_amount, gating_burn, updated_at });
+idl_fields!(MintReferred { affiliate_account, affiliate_pubkey, project, buyer_pubkey, price_lamports, commission_lamports });
+idl_fields!(ProtocolStatsInitialized {
+    protocol_stats_account,
+    live_project_count,
+    live_affiliate_count,
+    registration_fees_collected_lamports,
+    total_rewards_redeemed_lamports,
+    total_referred_volume_lamports,
+    created_at,
+});
+idl_fields!(AffiliateIndexed { affiliate_account, project, affiliate_index_page_account, position });
+idl_fields!(AffiliateAddedToProfile { affiliate_profile_account, affiliate_pubkey, affiliate_account, project, commission_lamports });
+idl_fields!(CandyMachineProjectLinked { candy_machine_project_account, candy_machine_id, project, project_owner_pubkey, created_at });
+idl_fields!(ProjectRegisteredV2 {
+    project,
+    project_owner_pubkey,
+    candy_machine_id,
+    affiliate_fee_percentage,
+    affiliate_target_in_sol,
+    max_affiliate_count,
+    title,
+    created_at,
+});
+idl_fields!(ProjectUpdatedV2 { project, affiliate_fee_percentage, affiliate_target_in_sol, max_affiliate_count, title, updated_at });
+idl_fields!(AffiliateRegisteredV2 { affiliate_account, affiliate_pubkey, project, registration_fee, affiliate_count, created_at });
+idl_fields!(AffiliateClosedV2 { affiliate_account, affiliate_pubkey, project, treasury_lamports, affiliate_count });
+idl_fields!(FeeDiscountTiersSet { fee_discount_config_account, tiers, updated_at });
+
+idl_fields!(RedeemableAmount { affiliate_balance_lamports, reward_lamports, redeemable_lamports, total_redeemed_amount_in_sol });
+idl_fields!(ProjectCapacity { affiliate_count, max_affiliate_count, remaining_affiliate_slots, is_full, registration_fee, meets_gating });
+idl_fields!(PromoCodeDiscount { discount_basis_points, affiliate_account, use_count, wallet_use_count });
+
+// Account passed to an instruction, in the order the processor reads them.
+// Optional accounts come last and may be left out.
+struct IdlAccount {
+    name: &'static str,
+    is_mut: bool,
+    is_signer: bool,
+    is_optional: bool,
+    docs: &'static str,
+}
+
+const fn account(name: &'static str, is_mut: bool, is_signer: bool, docs: &'static str) -> IdlAccount {
+    IdlAccount { name, is_mut, is_signer, is_optional: false, docs }
+}
+
+const fn optional_account(name: &'static str, is_mut: bool, docs: &'static str) -> IdlAccount {
+    IdlAccount { name, is_mut, is_signer: false, is_optional: true, docs }
+}
+
+const SYSTEM_PROGRAM: IdlAccount = account("systemProgram", false, false, "System program");
+const PROTOCOL_STATS: IdlAccount = account("protocolStatsAccount", true, false, "Protocol stats PDA, updated once InitializeProtocolStats created it, the program id to skip the stats update");
+
+fn instruction<P: IdlFields>(name: &str, discriminant: u8, docs: &str, accounts: &[IdlAccount]) -> Value {
+    json!({
+        "name": name,
+        "docs": [docs],
+        "accounts": accounts.iter().map(|account| {
+            let mut idl_account = json!({
+                "name": account.name,
+                "isMut": account.is_mut,
+                "isSigner": account.is_signer,
+                "docs": [account.docs],
+            });
+
+            if account.is_optional {
+                idl_account["optional"] = json!(true);
+            }
+
+            idl_account
+        }).collect::<Vec<_>>(),
+        "args": P::idl_fields(),
+        "discriminant": { "type": "u8", "value": discriminant },
+    })
+}
+
+// View instruction returning a `T` with `set_return_data`
+fn view<P: IdlFields, T: IdlFields>(name: &str, discriminant: u8, docs: &str, accounts: &[IdlAccount]) -> Value {
+    let mut instruction = instruction::<P>(name, discriminant, docs, accounts);
+
+    instruction["returns"] = json!({ "defined": T::NAME });
+
+    instruction
+}
+
+fn instructions() -> Vec<Value> {
+    vec![
+        instruction::<RegisterProjectPayload>("registerProject", 0, "Registers a project for a candy machine, owned by the initializer", &[
+            account("initializer", true, true, "Project owner paying for the project account, the candy machine authority"),
+            account("projectAccount", true, false, "Project PDA"),
+            SYSTEM_PROGRAM,
+            account("candyMachine", false, false, "Candy Machine v2 or v3 account of candy_machine_id"),
+            PROTOCOL_STATS,
+            account("candyMachineProjectAccount", true, false, "Candy machine project PDA pointing to the project, failing the registration when the candy machine has one"),
+        ]),
+        instruction::<UpdateProjectPayload>("updateProject", 1, "Updates the settings of a project", &[
+            account("admin", true, true, "Program admin, paying the rent of projects registered before the u32 affiliate counts"),
+            account("projectAccount", true, false, "Project PDA"),
+            optional_account("systemProgram", false, "System program, only needed to grow projects registered before the u32 affiliate counts"),
+        ]),
+        instruction::<CloseProjectPayload>("closeProject", 2, "Closes a project and refunds its rent to the owner", &[
+            account("admin", false, true, "Program admin"),
+            account("projectAccount", true, false, "Project PDA"),
+            account("projectOwner", true, false, "Project owner receiving the refund"),
+            PROTOCOL_STATS,
+            account("candyMachineProjectAccount", true, false, "Candy machine project PDA, closed along with the project when it points to it"),
+            optional_account("affiliateIndexPageAccounts", true, "Affiliate index page PDAs of the project closed along with it, any number of them"),
+        ]),
+        instruction::<RegisterAffiliatePayload>("registerAffiliate", 3, "Registers the initializer as an affiliate of a project", &[
+            account("initializer", true, true, "Affiliate paying for the affiliate account and the registration fee"),
+            account("affiliateAccount", true, false, "Affiliate PDA"),
+            account("projectAccount", true, false, "Project PDA"),
+            account("solpayTreasury", true, false, "Treasury receiving the registration fee"),
+            SYSTEM_PROGRAM,
+            PROTOCOL_STATS,
+            account("affiliateIndexPageAccount", true, false, "Affiliate index page PDA the affiliate is appended to, created by its first entry"),
+            account("affiliateProfileAccount", true, false, "Affiliate profile PDA of the initializer the project is added to, created by its first entry"),
+            optional_account("gatingTokenAccount", true, "Token account of the affiliate holding the gating mint, required by gated projects"),
+            optional_account("gatingMint", true, "Gating mint, required by projects burning the gating tokens"),
+            optional_account("tokenProgram", false, "SPL token program, required by projects burning the gating tokens"),
+            optional_account("spafTokenAccount", false, "SPAF token account of the affiliate discounting the registration fee, unused gating accounts before it are the program id"),
+            optional_account("feeDiscountConfigAccount", false, "Fee discount config PDA holding the discount tiers, required with spafTokenAccount for a discount"),
+        ]),
+        instruction::<RedeemRewardPayload>("redeemReward", 4, "Pays the project affiliate target from the affiliate account to the affiliate", &[
+            account("initializer", true, true, "Affiliate, paying the extra rent of accounts older than the current layout"),
+            account("affiliateAccount", true, false, "Affiliate PDA"),
+            account("projectAccount", true, false, "Project PDA"),
+            PROTOCOL_STATS,
+            SYSTEM_PROGRAM,
+            optional_account("rewardTokenAccount", true, "SPAF token account of the affiliate receiving milestone rewards"),
+            optional_account("spafMint", true, "SPAF mint, required with rewardTokenAccount"),
+            optional_account("spafMintAuthority", false, "SPAF mint authority PDA, required with rewardTokenAccount"),
+            optional_account("tokenProgram", false, "SPL token program, required with rewardTokenAccount"),
+        ]),
+        instruction::<CloseAffiliatePayload>("closeAffiliateAccount", 5, "Closes an affiliate account and moves its balance to the treasury", &[
+            account("admin", true, true, "Program admin, paying the rent of projects registered before the u32 affiliate counts"),
+            account("affiliateAccount", true, false, "Affiliate PDA"),
+            account("projectAccount", true, false, "Project PDA"),
+            account("solpayTreasury", true, false, "Treasury receiving the balance"),
+            PROTOCOL_STATS,
+            account("affiliateIndexPageAccount", true, false, "Affiliate index page PDA listing the affiliate, the program id for affiliates registered before the index"),
+            account("affiliateProfileAccount", true, false, "Affiliate profile PDA of the affiliate wallet, left as is when it does not exist or list the project"),
+            SYSTEM_PROGRAM,
+            optional_account("referralCodeAccount", true, "Referral code PDA of the affiliate, released to the treasury, required when the affiliate records one"),
+        ]),
+        view::<GetRedeemableAmountPayload, RedeemableAmount>("getRedeemableAmount", 6, "Returns what redeeming the affiliate reward would pay right now", &[
+            account("affiliateAccount", false, false, "Affiliate PDA"),
+            account("projectAccount", false, false, "Project PDA"),
+        ]),
+        view::<GetProjectCapacityPayload, ProjectCapacity>("getProjectCapacity", 7, "Returns the remaining affiliate slots of a project and the gating and registration fee of an affiliate", &[
+            account("projectAccount", false, false, "Project PDA"),
+            optional_account("affiliate", false, "Affiliate wallet the gating and registration fee are reported for"),
+            optional_account("gatingTokenAccount", false, "Token account of the affiliate holding the gating mint, the program id when it has none"),
+            optional_account("spafTokenAccount", false, "SPAF token account of the affiliate discounting the registration fee, the program id when it has none"),
+            optional_account("feeDiscountConfigAccount", false, "Fee discount config PDA holding the discount tiers, required with spafTokenAccount for a discount"),
+        ]),
+        instruction::<ClaimReferralCodePayload>("claimReferralCode", 8, "Claims a referral code pointing to the affiliate account of the initializer", &[
+            account("initializer", true, true, "Affiliate paying for the referral code account"),
+            account("affiliateAccount", true, false, "Affiliate PDA, recording the code"),
+            account("projectAccount", false, false, "Project PDA"),
+            account("referralCodeAccount", true, false, "Referral code PDA, seeded by the project and the normalized code"),
+            SYSTEM_PROGRAM,
+            optional_account("previousAffiliateAccount", false, "Closed affiliate account the code points to, for taking it over"),
+        ]),
+        instruction::<CreatePromoCodePayload>("createPromoCode", 9, "Creates a buyer promo code for a project owned by the initializer", &[
+            account("initializer", true, true, "Project owner paying for the promo code account"),
+            account("projectAccount", false, false, "Project PDA"),
+            account("promoCodeAccount", true, false, "Promo code PDA, seeded by the project and the normalized code"),
+            SYSTEM_PROGRAM,
+            optional_account("affiliateAccount", false, "Affiliate PDA the code is attached to, required with affiliatePubkey"),
+        ]),
+        view::<ConsumePromoCodePayload, PromoCodeDiscount>("consumePromoCode", 10, "Records a use of a promo code by the buyer and returns its discount", &[
+            account("buyer", true, true, "Buyer paying for the usage account"),
+            account("projectOwner", false, true, "Project owner co-signing the use from its mint flow"),
+            account("projectAccount", false, false, "Project PDA"),
+            account("promoCodeAccount", true, false, "Promo code PDA"),
+            account("promoCodeUsageAccount", true, false, "Usage PDA, seeded by the promo code and the buyer"),
+            SYSTEM_PROGRAM,
+        ]),
+        instruction::<SetProjectGatingPayload>("setProjectGating", 11, "Sets the token an affiliate must hold or burn to register to a project owned by the initializer", &[
+            account("initializer", true, true, "Project owner paying for resizing older project accounts"),
+            account("projectAccount", true, false, "Project PDA"),
+            SYSTEM_PROGRAM,
+        ]),
+        instruction::<MintWithReferralPayload>("mintWithReferral", 12, "Mints from the candy machine of a project and credits the affiliate account with the commission on the price, followed by the accounts of the mint instruction", &[
+            account("buyer", true, true, "Buyer minting and paying for resizing older accounts"),
+            account("affiliateAccount", true, false, "Affiliate PDA receiving the commission"),
+            account("projectAccount", true, false, "Project PDA"),
+            account("candyMachine", false, false, "Candy Machine v2 or v3 account of candy_machine_id"),
+            account("paymentWallet", true, true, "Wallet receiving the mint price and paying the commission out of it, the v2 candy machine wallet or the solPayment destination of the v3 Candy Guard"),
+            SYSTEM_PROGRAM,
+            account("candyMachineProgram", false, false, "Candy Machine v2 program, or Candy Guard program for v3 candy machines"),
+            PROTOCOL_STATS,
+            account("affiliateProfileAccount", true, false, "Affiliate profile PDA of the affiliate wallet, credited with the commission when it lists the project"),
+        ]),
+        instruction::<InitializeProtocolStatsPayload>("initializeProtocolStats", 13, "Creates the protocol stats account, starting its totals from the given values", &[
+            account("admin", true, true, "Program admin paying for the protocol stats account"),
+            account("protocolStatsAccount", true, false, "Protocol stats PDA, seeded by its discriminator"),
+            SYSTEM_PROGRAM,
+        ]),
+        instruction::<IndexAffiliatePayload>("indexAffiliate", 14, "Appends an affiliate registered before the affiliate index to the index of its project", &[
+            account("payer", true, true, "Pays for the index page created and the grown accounts"),
+            account("affiliateAccount", true, false, "Affiliate PDA"),
+            account("projectAccount", true, false, "Project PDA"),
+            account("affiliateIndexPageAccount", true, false, "Affiliate index page PDA, seeded by the project and the page number"),
+            SYSTEM_PROGRAM,
+        ]),
+        instruction::<AddAffiliateToProfilePayload>("addAffiliateToProfile", 15, "Adds a project an affiliate registered to before affiliate profiles to the profile of the affiliate wallet", &[
+            account("payer", true, true, "Pays for the profile account created or grown"),
+            account("affiliateAccount", false, false, "Affiliate PDA"),
+            account("projectAccount", false, false, "Project PDA"),
+            account("affiliateProfileAccount", true, false, "Affiliate profile PDA, seeded by the affiliate wallet"),
+            SYSTEM_PROGRAM,
+        ]),
+        instruction::<LinkCandyMachineProjectPayload>("linkCandyMachineProject", 16, "Points the candy machine of a project registered before candy machine lookups to the project", &[
+            account("payer", true, true, "Pays for the candy machine project account"),
+            account("projectAccount", false, false, "Project PDA of the candy machine authority"),
+            account("candyMachine", false, false, "Candy Machine v2 or v3 account of candy_machine_id"),
+            account("candyMachineProjectAccount", true, false, "Candy machine project PDA, seeded by the candy machine id"),
+            SYSTEM_PROGRAM,
+        ]),
+        instruction::<RegisterProjectV2Payload>("registerProjectV2", 17, "Registers a project for a candy machine, owned by the initializer, with a u32 max affiliate count", &[
+            account("initializer", true, true, "Project owner paying for the project account, the candy machine authority"),
+            account("projectAccount", true, false, "Project PDA"),
+            SYSTEM_PROGRAM,
+            account("candyMachine", false, false, "Candy Machine v2 or v3 account of candy_machine_id"),
+            PROTOCOL_STATS,
+            account("candyMachineProjectAccount", true, false, "Candy machine project PDA pointing to the project, failing the registration when the candy machine has one"),
+        ]),
+        instruction::<UpdateProjectV2Payload>("updateProjectV2", 18, "Updates the settings of a project, with a u32 max affiliate count", &[
+            account("admin", true, true, "Program admin, paying the rent of projects registered before the u32 affiliate counts"),
+            account("projectAccount", true, false, "Project PDA"),
+            optional_account("systemProgram", false, "System program, only needed to grow projects registered before the u32 affiliate counts"),
+        ]),
+        instruction::<SetFeeDiscountTiersPayload>("setFeeDiscountTiers", 19, "Replaces the SPAF holder discount tiers of the affiliate registration fee", &[
+            account("admin", true, true, "Program admin paying for the fee discount config account the first time"),
+            account("feeDiscountConfigAccount", true, false, "Fee discount config PDA, seeded by its discriminator"),
+            SYSTEM_PROGRAM,
+        ]),
+    ]
+}
+
+fn struct_type<T: IdlFields>() -> Value {
+    json!({
+        "name": T::NAME,
+        "type": { "kind": "struct", "fields": T::idl_fields() },
+    })
+}
+
+fn event_type() -> Value {
+    fn variant<T: IdlFields>() -> Value {
+        json!({ "name": T::NAME, "fields": [{ "defined": T::NAME }] })
+    }
+
+    // Variants in `CandyMachineAffiliatesEvent` order, which is append-only
+    json!({
+        "name": "CandyMachineAffiliatesEvent",
+        "type": {
+            "kind": "enum",
+            "variants": [
+                variant::<ProjectRegistered>(),
+                variant::<ProjectUpdated>(),
+                variant::<ProjectClosed>(),
+                variant::<AffiliateRegistered>(),
+                variant::<RewardRedeemed>(),
+                variant::<AffiliateClosed>(),
+                variant::<ReferralCodeClaimed>(),
+                variant::<ReferralCodeReleased>(),
+                variant::<PromoCodeCreated>(),
+                variant::<PromoCodeConsumed>(),
+                variant::<ProjectGatingUpdated>(),
+                variant::<MilestoneRewardMinted>(),
+                variant::<MintReferred>(),
+                variant::<ProtocolStatsInitialized>(),
+                variant::<AffiliateIndexed>(),
+                variant::<AffiliateAddedToProfile>(),
+                variant::<CandyMachineProjectLinked>(),
+                variant::<ProjectRegisteredV2>(),
+                variant::<ProjectUpdatedV2>(),
+                variant::<AffiliateRegisteredV2>(),
+                variant::<AffiliateClosedV2>(),
+                variant::<FeeDiscountTiersSet>(),
+            ],
+        },
+    })
+}
+
+// Every `CandyMachineAffiliatesError`, in code order
+const ERRORS: &[CandyMachineAffiliatesError] = &[
+    CandyMachineAffiliatesError::ActionNotAllowed,
+    CandyMachineAffiliatesError::UninitializedAccount,
+    CandyMachineAffiliatesError::InvalidPDA,
+    CandyMachineAffiliatesError::InvalidDataLength,
+    CandyMachineAffiliatesError::AmountOverflow,
+    CandyMachineAffiliatesError::ProjectTitleTooLong,
+    CandyMachineAffiliatesError::InvalidProjectMaxAffiliateCount,
+    CandyMachineAffiliatesError::ProjectMaxAffiliateCountReached,
+    CandyMachineAffiliatesError::ProjectAffiliateCountLargerThanNewMaxAffiliateCount,
+    CandyMachineAffiliatesError::IncorrectTreasuryAccount,
+    CandyMachineAffiliatesError::RewardRedeemMismatchedAccounts,
+    CandyMachineAffiliatesError::AffiliateAccountBalanceNotEnough,
+    CandyMachineAffiliatesError::IncorrectProjectOwnerAccount,
+    CandyMachineAffiliatesError::InvalidReferralCode,
+    CandyMachineAffiliatesError::ReferralCodeAlreadyClaimed,
+    CandyMachineAffiliatesError::InvalidPromoCode,
+    CandyMachineAffiliatesError::InvalidPromoCodeDiscount,
+    CandyMachineAffiliatesError::InvalidPromoCodePeriod,
+    CandyMachineAffiliatesError::PromoCodeAlreadyExists,
+    CandyMachineAffiliatesError::PromoCodeNotActive,
+    CandyMachineAffiliatesError::PromoCodeMaxUsesReached,
+    CandyMachineAffiliatesError::PromoCodeWalletLimitReached,
+    CandyMachineAffiliatesError::InvalidProjectGating,
+    CandyMachineAffiliatesError::InvalidGatingTokenAccount,
+    CandyMachineAffiliatesError::GatingTokenBalanceNotEnough,
+    CandyMachineAffiliatesError::InvalidRewardTokenAccount,
+    CandyMachineAffiliatesError::InvalidSpafTokenAccount,
+    CandyMachineAffiliatesError::InvalidCandyMachineAccount,
+    CandyMachineAffiliatesError::InvalidCandyMachineAuthority,
+    CandyMachineAffiliatesError::InvalidPaymentWallet,
+    CandyMachineAffiliatesError::AffiliateAlreadyIndexed,
+    CandyMachineAffiliatesError::ProjectAlreadyInAffiliateProfile,
+    CandyMachineAffiliatesError::CandyMachineAlreadyRegistered,
+    CandyMachineAffiliatesError::AffiliateReferralCodeAlreadyClaimed,
+    CandyMachineAffiliatesError::InvalidMintInstruction,
+    CandyMachineAffiliatesError::InvalidFeeDiscountTiers,
+];
+
+fn errors() -> Vec<Value> {
+    ERRORS.iter().map(|error| json!({
+        "code": *error as u32,
+        "name": format!("{:?}", error),
+        "msg": error.to_string(),
+    })).collect()
+}
+
+pub fn idl() -> Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "name": IDL_NAME,
+        "instructions": instructions(),
+        "accounts": [
+            struct_type::<ProjectAccountState>(),
+            struct_type::<AffiliateAccountState>(),
+            struct_type::<ReferralCodeAccountState>(),
+            struct_type::<PromoCodeAccountState>(),
+            struct_type::<PromoCodeUsageAccountState>(),
+            struct_type::<ProtocolStatsAccountState>(),
+            struct_type::<AffiliateIndexPageAccountState>(),
+            struct_type::<AffiliateProfileAccountState>(),
+            struct_type::<CandyMachineProjectAccountState>(),
+            struct_type::<FeeDiscountConfigAccountState>(),
+        ],
+        "types": [
+            struct_type::<ProjectAccountStateData>(),
+            struct_type::<AffiliateAccountStateData>(),
+            struct_type::<ReferralCodeAccountStateData>(),
+            struct_type::<PromoCodeAccountStateData>(),
+            struct_type::<PromoCodeUsageAccountStateData>(),
+            struct_type::<ProtocolStatsAccountStateData>(),
+            struct_type::<AffiliateIndexPageAccountStateData>(),
+            struct_type::<AffiliateProfileAccountStateData>(),
+            struct_type::<CandyMachineProjectAccountStateData>(),
+            struct_type::<FeeDiscountConfigAccountStateData>(),
+            struct_type::<FeeDiscountTier>(),
+            struct_type::<RedeemableAmount>(),
+            struct_type::<ProjectCapacity>(),
+            struct_type::<PromoCodeDiscount>(),
+            struct_type::<ProjectRegistered>(),
+            struct_type::<ProjectUpdated>(),
+            struct_type::<ProjectClosed>(),
+            struct_type::<AffiliateRegistered>(),
+            struct_type::<RewardRedeemed>(),
+            struct_type::<AffiliateClosed>(),
+            struct_type::<ReferralCodeClaimed>(),
+            struct_type::<ReferralCodeReleased>(),
+            struct_type::<PromoCodeCreated>(),
+            struct_type::<PromoCodeConsumed>(),
+            struct_type::<ProjectGatingUpdated>(),
+            struct_type::<MilestoneRewardMinted>(),
+            struct_type::<MintReferred>(),
+            struct_type::<ProtocolStatsInitialized>(),
+            struct_type::<AffiliateIndexed>(),
+            struct_type::<AffiliateAddedToProfile>(),
+            struct_type::<CandyMachineProjectLinked>(),
+            struct_type::<ProjectRegisteredV2>(),
+            struct_type::<ProjectUpdatedV2>(),
+            struct_type::<AffiliateRegisteredV2>(),
+            struct_type::<AffiliateClosedV2>(),
+            struct_type::<FeeDiscountTiersSet>(),
+            event_type(),
+        ],
+        "errors": errors(),
+        "metadata": {
+            "origin": "shank",
+            "address": crate::id().to_string(),
+        },
+    })
+}
+
+fn camel_case(name: &str) -> String {
+    let mut words = name.split('_');
+    let mut camel = words.next().unwrap_or_default().to_string();
+
+    for word in words {
+        let mut chars = word.chars();
+
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+
+    camel
+}
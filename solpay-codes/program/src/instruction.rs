@@ -1,7 +1,11 @@
-use borsh::{BorshDeserialize};
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{program_error::ProgramError};
+use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::pubkey::Pubkey;
+use solana_program::system_program::ID as SYSTEM_PROGRAM_ID;
+use crate::fee_discount_config::state::FeeDiscountTier;
 
+#[derive(Debug)]
 pub enum CandyMachineAffiliatesInstruction {
     RegisterProject {
         candy_machine_id: Pubkey,
@@ -34,51 +38,250 @@ pub enum CandyMachineAffiliatesInstruction {
         affiliate_pubkey: Pubkey,
         project_owner_pubkey: Pubkey,
         candy_machine_id: Pubkey,
-    }
+    },
+    // Returns a `RedeemableAmount`
+    GetRedeemableAmount {
+        affiliate_pubkey: Pubkey,
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+    },
+    // Returns a `ProjectCapacity`
+    GetProjectCapacity {
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+    },
+    ClaimReferralCode {
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+        code: String,
+    },
+    CreatePromoCode {
+        candy_machine_id: Pubkey,
+        code: String,
+        affiliate_pubkey: Option<Pubkey>,
+        discount_basis_points: u16,
+        max_uses: u32,
+        max_uses_per_wallet: u16,
+        starts_at: i64,
+        ends_at: i64,
+    },
+    // Returns a `PromoCodeDiscount`
+    ConsumePromoCode {
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+        code: String,
+    },
+    SetProjectGating {
+        candy_machine_id: Pubkey,
+        gating_mint: Option<Pubkey>,
+        gating_min_amount: u64,
+        gating_burn: bool,
+    },
+    // Accounts after the protocol stats account are passed to the mint instruction of the Candy Machine program with `mint_data`
+    MintWithReferral {
+        affiliate_pubkey: Pubkey,
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+        mint_data: Vec<u8>,
+    },
+    // Starts the protocol totals from the given values, counted off-chain for existing accounts
+    InitializeProtocolStats {
+        live_project_count: u32,
+        live_affiliate_count: u32,
+        registration_fees_collected_lamports: u64,
+        total_rewards_redeemed_lamports: u64,
+        total_referred_volume_lamports: u64,
+    },
+    // Appends an affiliate registered before the affiliate index to the index of its project
+    IndexAffiliate {
+        affiliate_pubkey: Pubkey,
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+    },
+    // Lists the project of an affiliate opened before affiliate profiles in the profile of its wallet
+    AddAffiliateToProfile {
+        affiliate_pubkey: Pubkey,
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+    },
+    // Points the candy machine of a project registered before candy machine lookups to the project
+    LinkCandyMachineProject {
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+    },
+    // RegisterProject with a max affiliate count above 255
+    RegisterProjectV2 {
+        candy_machine_id: Pubkey,
+        affiliate_fee_percentage: f64,
+        affiliate_target_in_sol: u8,
+        max_affiliate_count: u32,
+        title: String,
+    },
+    // UpdateProject with a max affiliate count above 255
+    UpdateProjectV2 {
+        project_owner_pubkey: Pubkey,
+        candy_machine_id: Pubkey,
+        affiliate_fee_percentage: f64,
+        affiliate_target_in_sol: u8,
+        max_affiliate_count: u32,
+        title: String,
+    },
+    // Replaces the SPAF holder discount tiers of the registration fee, by increasing balance
+    SetFeeDiscountTiers {
+        tiers: Vec<FeeDiscountTier>,
+    },
 }
 
-#[derive(BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize)]
 pub struct RegisterProjectPayload {
-    candy_machine_id: Pubkey,
-    affiliate_fee_percentage: f64,
-    affiliate_target_in_sol: u8,
-    max_affiliate_count: u8,
-    title: String,
+    pub candy_machine_id: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u8,
+    pub title: String,
 }
 
-#[derive(BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize)]
 pub struct UpdateProjectPayload {
-    project_owner_pubkey: Pubkey,
-    candy_machine_id: Pubkey,
-    affiliate_fee_percentage: f64,
-    affiliate_target_in_sol: u8,
-    max_affiliate_count: u8,
-    title: String,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u8,
+    pub title: String,
 }
 
-#[derive(BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize)]
 pub struct CloseProjectPayload {
-    project_owner_pubkey: Pubkey,
-    candy_machine_id: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
 }
 
-#[derive(BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize)]
 pub struct RegisterAffiliatePayload {
-    project_owner_pubkey: Pubkey,
-    candy_machine_id: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
 }
 
-#[derive(BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize)]
 pub struct RedeemRewardPayload {
-    project_owner_pubkey: Pubkey,
-    candy_machine_id: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
 }
 
-#[derive(BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize)]
 pub struct CloseAffiliatePayload {
-    affiliate_pubkey: Pubkey,
-    project_owner_pubkey: Pubkey,
-    candy_machine_id: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct GetRedeemableAmountPayload {
+    pub affiliate_pubkey: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct GetProjectCapacityPayload {
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ClaimReferralCodePayload {
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub code: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CreatePromoCodePayload {
+    pub candy_machine_id: Pubkey,
+    pub code: String,
+    pub affiliate_pubkey: Option<Pubkey>,
+    pub discount_basis_points: u16,
+    pub max_uses: u32,
+    pub max_uses_per_wallet: u16,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ConsumePromoCodePayload {
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub code: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SetProjectGatingPayload {
+    pub candy_machine_id: Pubkey,
+    pub gating_mint: Option<Pubkey>,
+    pub gating_min_amount: u64,
+    pub gating_burn: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MintWithReferralPayload {
+    pub affiliate_pubkey: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub mint_data: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct InitializeProtocolStatsPayload {
+    pub live_project_count: u32,
+    pub live_affiliate_count: u32,
+    pub registration_fees_collected_lamports: u64,
+    pub total_rewards_redeemed_lamports: u64,
+    pub total_referred_volume_lamports: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct IndexAffiliatePayload {
+    pub affiliate_pubkey: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AddAffiliateToProfilePayload {
+    pub affiliate_pubkey: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LinkCandyMachineProjectPayload {
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RegisterProjectV2Payload {
+    pub candy_machine_id: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u32,
+    pub title: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct UpdateProjectV2Payload {
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u32,
+    pub title: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SetFeeDiscountTiersPayload {
+    pub tiers: Vec<FeeDiscountTier>,
 }
 
 impl CandyMachineAffiliatesInstruction {
@@ -90,7 +293,8 @@ impl CandyMachineAffiliatesInstruction {
 
         Ok(match variant {
             0 => {
-                let payload: RegisterProjectPayload = RegisterProjectPayload::try_from_slice(rest).unwrap();
+                let payload: RegisterProjectPayload = RegisterProjectPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
                 Self::RegisterProject {
                     candy_machine_id: payload.candy_machine_id,
@@ -101,7 +305,8 @@ impl CandyMachineAffiliatesInstruction {
                 }
             },
             1 => {
-                let payload: UpdateProjectPayload = UpdateProjectPayload::try_from_slice(rest).unwrap();
+                let payload: UpdateProjectPayload = UpdateProjectPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
                 Self::UpdateProject {
                     project_owner_pubkey: payload.project_owner_pubkey,
@@ -113,7 +318,8 @@ impl CandyMachineAffiliatesInstruction {
                 }
             },
             2 => {
-                let payload: CloseProjectPayload = CloseProjectPayload::try_from_slice(rest).unwrap();
+                let payload: CloseProjectPayload = CloseProjectPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
                 Self::CloseProject {
                     project_owner_pubkey: payload.project_owner_pubkey,
@@ -121,7 +327,8 @@ impl CandyMachineAffiliatesInstruction {
                 }
             },
             3 => {
-                let payload: RegisterAffiliatePayload = RegisterAffiliatePayload::try_from_slice(rest).unwrap();
+                let payload: RegisterAffiliatePayload = RegisterAffiliatePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
                 Self::RegisterAffiliate {
                     project_owner_pubkey: payload.project_owner_pubkey,
@@ -129,7 +336,8 @@ impl CandyMachineAffiliatesInstruction {
                 }
             },
             4 => {
-                let payload: RedeemRewardPayload = RedeemRewardPayload::try_from_slice(rest).unwrap();
+                let payload: RedeemRewardPayload = RedeemRewardPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
                 Self::RedeemReward {
                     project_owner_pubkey: payload.project_owner_pubkey,
@@ -137,15 +345,1209 @@ impl CandyMachineAffiliatesInstruction {
                 }
             },
             5 => {
-                let payload: CloseAffiliatePayload = CloseAffiliatePayload::try_from_slice(rest).unwrap();
+                let payload: CloseAffiliatePayload = CloseAffiliatePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
                 Self::CloseAffiliateAccount {
                     affiliate_pubkey: payload.affiliate_pubkey,
                     project_owner_pubkey: payload.project_owner_pubkey,
                     candy_machine_id: payload.candy_machine_id,
                 }
+            },
+            6 => {
+                let payload: GetRedeemableAmountPayload = GetRedeemableAmountPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::GetRedeemableAmount {
+                    affiliate_pubkey: payload.affiliate_pubkey,
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                }
+            },
+            7 => {
+                let payload: GetProjectCapacityPayload = GetProjectCapacityPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::GetProjectCapacity {
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                }
+            },
+            8 => {
+                let payload: ClaimReferralCodePayload = ClaimReferralCodePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::ClaimReferralCode {
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                    code: payload.code,
+                }
+            },
+            9 => {
+                let payload: CreatePromoCodePayload = CreatePromoCodePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::CreatePromoCode {
+                    candy_machine_id: payload.candy_machine_id,
+                    code: payload.code,
+                    affiliate_pubkey: payload.affiliate_pubkey,
+                    discount_basis_points: payload.discount_basis_points,
+                    max_uses: payload.max_uses,
+                    max_uses_per_wallet: payload.max_uses_per_wallet,
+                    starts_at: payload.starts_at,
+                    ends_at: payload.ends_at,
+                }
+            },
+            10 => {
+                let payload: ConsumePromoCodePayload = ConsumePromoCodePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::ConsumePromoCode {
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                    code: payload.code,
+                }
+            }
+            11 => {
+                let payload: SetProjectGatingPayload = SetProjectGatingPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::SetProjectGating {
+                    candy_machine_id: payload.candy_machine_id,
+                    gating_mint: payload.gating_mint,
+                    gating_min_amount: payload.gating_min_amount,
+                    gating_burn: payload.gating_burn,
+                }
+            }
+            12 => {
+                let payload: MintWithReferralPayload = MintWithReferralPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::MintWithReferral {
+                    affiliate_pubkey: payload.affiliate_pubkey,
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                    mint_data: payload.mint_data,
+                }
+            }
+            13 => {
+                let payload: InitializeProtocolStatsPayload = InitializeProtocolStatsPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::InitializeProtocolStats {
+                    live_project_count: payload.live_project_count,
+                    live_affiliate_count: payload.live_affiliate_count,
+                    registration_fees_collected_lamports: payload.registration_fees_collected_lamports,
+                    total_rewards_redeemed_lamports: payload.total_rewards_redeemed_lamports,
+                    total_referred_volume_lamports: payload.total_referred_volume_lamports,
+                }
+            }
+            14 => {
+                let payload: IndexAffiliatePayload = IndexAffiliatePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::IndexAffiliate {
+                    affiliate_pubkey: payload.affiliate_pubkey,
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                }
+            }
+            15 => {
+                let payload: AddAffiliateToProfilePayload = AddAffiliateToProfilePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::AddAffiliateToProfile {
+                    affiliate_pubkey: payload.affiliate_pubkey,
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                }
+            }
+            16 => {
+                let payload: LinkCandyMachineProjectPayload = LinkCandyMachineProjectPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::LinkCandyMachineProject {
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                }
+            }
+            17 => {
+                let payload: RegisterProjectV2Payload = RegisterProjectV2Payload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::RegisterProjectV2 {
+                    candy_machine_id: payload.candy_machine_id,
+                    affiliate_fee_percentage: payload.affiliate_fee_percentage,
+                    affiliate_target_in_sol: payload.affiliate_target_in_sol,
+                    max_affiliate_count: payload.max_affiliate_count,
+                    title: payload.title,
+                }
+            }
+            18 => {
+                let payload: UpdateProjectV2Payload = UpdateProjectV2Payload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::UpdateProjectV2 {
+                    project_owner_pubkey: payload.project_owner_pubkey,
+                    candy_machine_id: payload.candy_machine_id,
+                    affiliate_fee_percentage: payload.affiliate_fee_percentage,
+                    affiliate_target_in_sol: payload.affiliate_target_in_sol,
+                    max_affiliate_count: payload.max_affiliate_count,
+                    title: payload.title,
+                }
+            }
+            19 => {
+                let payload: SetFeeDiscountTiersPayload = SetFeeDiscountTiersPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Self::SetFeeDiscountTiers {
+                    tiers: payload.tiers,
+                }
             }
             _ => return Err(ProgramError::InvalidInstructionData)
         })
     }
+
+    // Pack Instruction into the inbound buffer format expected by unpack
+    pub fn pack(&self) -> Vec<u8> {
+        let (variant, payload) = match self {
+            Self::RegisterProject {
+                candy_machine_id,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => (0, borsh::to_vec(&RegisterProjectPayload {
+                candy_machine_id: *candy_machine_id,
+                affiliate_fee_percentage: *affiliate_fee_percentage,
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            })),
+            Self::UpdateProject {
+                project_owner_pubkey,
+                candy_machine_id,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => (1, borsh::to_vec(&UpdateProjectPayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+                affiliate_fee_percentage: *affiliate_fee_percentage,
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            })),
+            Self::CloseProject {
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (2, borsh::to_vec(&CloseProjectPayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::RegisterAffiliate {
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (3, borsh::to_vec(&RegisterAffiliatePayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::RedeemReward {
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (4, borsh::to_vec(&RedeemRewardPayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::CloseAffiliateAccount {
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (5, borsh::to_vec(&CloseAffiliatePayload {
+                affiliate_pubkey: *affiliate_pubkey,
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::GetRedeemableAmount {
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (6, borsh::to_vec(&GetRedeemableAmountPayload {
+                affiliate_pubkey: *affiliate_pubkey,
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::GetProjectCapacity {
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (7, borsh::to_vec(&GetProjectCapacityPayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::ClaimReferralCode {
+                project_owner_pubkey,
+                candy_machine_id,
+                code,
+            } => (8, borsh::to_vec(&ClaimReferralCodePayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+                code: code.clone(),
+            })),
+            Self::CreatePromoCode {
+                candy_machine_id,
+                code,
+                affiliate_pubkey,
+                discount_basis_points,
+                max_uses,
+                max_uses_per_wallet,
+                starts_at,
+                ends_at,
+            } => (9, borsh::to_vec(&CreatePromoCodePayload {
+                candy_machine_id: *candy_machine_id,
+                code: code.clone(),
+                affiliate_pubkey: *affiliate_pubkey,
+                discount_basis_points: *discount_basis_points,
+                max_uses: *max_uses,
+                max_uses_per_wallet: *max_uses_per_wallet,
+                starts_at: *starts_at,
+                ends_at: *ends_at,
+            })),
+            Self::ConsumePromoCode {
+                project_owner_pubkey,
+                candy_machine_id,
+                code,
+            } => (10, borsh::to_vec(&ConsumePromoCodePayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+                code: code.clone(),
+            })),
+            Self::SetProjectGating {
+                candy_machine_id,
+                gating_mint,
+                gating_min_amount,
+                gating_burn,
+            } => (11, borsh::to_vec(&SetProjectGatingPayload {
+                candy_machine_id: *candy_machine_id,
+                gating_mint: *gating_mint,
+                gating_min_amount: *gating_min_amount,
+                gating_burn: *gating_burn,
+            })),
+            Self::MintWithReferral {
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id,
+                mint_data,
+            } => (12, borsh::to_vec(&MintWithReferralPayload {
+                affiliate_pubkey: *affiliate_pubkey,
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+                mint_data: mint_data.clone(),
+            })),
+            Self::InitializeProtocolStats {
+                live_project_count,
+                live_affiliate_count,
+                registration_fees_collected_lamports,
+                total_rewards_redeemed_lamports,
+                total_referred_volume_lamports,
+            } => (13, borsh::to_vec(&InitializeProtocolStatsPayload {
+                live_project_count: *live_project_count,
+                live_affiliate_count: *live_affiliate_count,
+                registration_fees_collected_lamports: *registration_fees_collected_lamports,
+                total_rewards_redeemed_lamports: *total_rewards_redeemed_lamports,
+                total_referred_volume_lamports: *total_referred_volume_lamports,
+            })),
+            Self::IndexAffiliate {
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (14, borsh::to_vec(&IndexAffiliatePayload {
+                affiliate_pubkey: *affiliate_pubkey,
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::AddAffiliateToProfile {
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (15, borsh::to_vec(&AddAffiliateToProfilePayload {
+                affiliate_pubkey: *affiliate_pubkey,
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::LinkCandyMachineProject {
+                project_owner_pubkey,
+                candy_machine_id,
+            } => (16, borsh::to_vec(&LinkCandyMachineProjectPayload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+            })),
+            Self::RegisterProjectV2 {
+                candy_machine_id,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => (17, borsh::to_vec(&RegisterProjectV2Payload {
+                candy_machine_id: *candy_machine_id,
+                affiliate_fee_percentage: *affiliate_fee_percentage,
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            })),
+            Self::UpdateProjectV2 {
+                project_owner_pubkey,
+                candy_machine_id,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            } => (18, borsh::to_vec(&UpdateProjectV2Payload {
+                project_owner_pubkey: *project_owner_pubkey,
+                candy_machine_id: *candy_machine_id,
+                affiliate_fee_percentage: *affiliate_fee_percentage,
+                affiliate_target_in_sol: *affiliate_target_in_sol,
+                max_affiliate_count: *max_affiliate_count,
+                title: title.clone(),
+            })),
+            Self::SetFeeDiscountTiers {
+                tiers,
+            } => (19, borsh::to_vec(&SetFeeDiscountTiersPayload {
+                tiers: tiers.clone(),
+            })),
+        };
+
+        let mut buf = vec![variant];
+        buf.extend_from_slice(&payload.expect("Payload serialization failed"));
+        buf
+    }
+}
+
+fn register_project_accounts(
+    initializer: &Pubkey,
+    project_pda: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    candy_machine_project_pda: &Pubkey,
+    candy_machine_id: Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*initializer, true),
+        AccountMeta::new(*project_pda, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(candy_machine_id, false),
+        AccountMeta::new(*protocol_stats_pda, false),
+        AccountMeta::new(*candy_machine_project_pda, false),
+    ]
+}
+
+fn update_project_accounts(admin: &Pubkey, project_pda: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new(*project_pda, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ]
+}
+
+// Creates a RegisterProject instruction
+#[allow(clippy::too_many_arguments)]
+pub fn register_project(
+    program_id: &Pubkey,
+    initializer: &Pubkey,
+    project_pda: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    candy_machine_project_pda: &Pubkey,
+    candy_machine_id: Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u8,
+    title: String,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: register_project_accounts(initializer, project_pda, protocol_stats_pda, candy_machine_project_pda, candy_machine_id),
+        data: CandyMachineAffiliatesInstruction::RegisterProject {
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        }.pack(),
+    }
+}
+
+// Creates a RegisterProjectV2 instruction, for a max affiliate count above 255
+#[allow(clippy::too_many_arguments)]
+pub fn register_project_v2(
+    program_id: &Pubkey,
+    initializer: &Pubkey,
+    project_pda: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    candy_machine_project_pda: &Pubkey,
+    candy_machine_id: Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: register_project_accounts(initializer, project_pda, protocol_stats_pda, candy_machine_project_pda, candy_machine_id),
+        data: CandyMachineAffiliatesInstruction::RegisterProjectV2 {
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        }.pack(),
+    }
+}
+
+// Creates an UpdateProject instruction, signed by the admin
+#[allow(clippy::too_many_arguments)]
+pub fn update_project(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    project_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u8,
+    title: String,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: update_project_accounts(admin, project_pda),
+        data: CandyMachineAffiliatesInstruction::UpdateProject {
+            project_owner_pubkey,
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        }.pack(),
+    }
+}
+
+// Creates an UpdateProjectV2 instruction, signed by the admin, for a max affiliate count above 255
+#[allow(clippy::too_many_arguments)]
+pub fn update_project_v2(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    project_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    affiliate_fee_percentage: f64,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: String,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: update_project_accounts(admin, project_pda),
+        data: CandyMachineAffiliatesInstruction::UpdateProjectV2 {
+            project_owner_pubkey,
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        }.pack(),
+    }
+}
+
+// Creates a CloseProject instruction, signed by the admin
+pub fn close_project(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    project_pda: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    candy_machine_project_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*project_pda, false),
+            AccountMeta::new(project_owner_pubkey, false),
+            AccountMeta::new(*protocol_stats_pda, false),
+            AccountMeta::new(*candy_machine_project_pda, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::CloseProject {
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a CloseProject instruction also closing the given affiliate index pages of the project,
+// their rent going to the project owner
+#[allow(clippy::too_many_arguments)]
+pub fn close_project_with_affiliate_index(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    project_pda: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    candy_machine_project_pda: &Pubkey,
+    affiliate_index_page_pdas: &[Pubkey],
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    let mut instruction = close_project(
+        program_id,
+        admin,
+        project_pda,
+        protocol_stats_pda,
+        candy_machine_project_pda,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+
+    instruction.accounts.extend(affiliate_index_page_pdas.iter().map(|page| AccountMeta::new(*page, false)));
+
+    instruction
+}
+
+// Creates a RegisterAffiliate instruction
+#[allow(clippy::too_many_arguments)]
+pub fn register_affiliate(
+    program_id: &Pubkey,
+    affiliate: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    solpay_treasury: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    affiliate_index_page_pda: &Pubkey,
+    affiliate_profile_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*affiliate, true),
+            AccountMeta::new(*affiliate_pda, false),
+            AccountMeta::new(*project_pda, false),
+            AccountMeta::new(*solpay_treasury, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new(*protocol_stats_pda, false),
+            AccountMeta::new(*affiliate_index_page_pda, false),
+            AccountMeta::new(*affiliate_profile_pda, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::RegisterAffiliate {
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a RegisterAffiliate instruction for a gated project. `gating_mint` must be given
+// for projects burning the gating tokens on registration.
+#[allow(clippy::too_many_arguments)]
+pub fn register_affiliate_with_gating_token(
+    program_id: &Pubkey,
+    affiliate: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    solpay_treasury: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    affiliate_index_page_pda: &Pubkey,
+    affiliate_profile_pda: &Pubkey,
+    gating_token_account: &Pubkey,
+    gating_mint: Option<&Pubkey>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    let mut instruction = register_affiliate(
+        program_id,
+        affiliate,
+        affiliate_pda,
+        project_pda,
+        solpay_treasury,
+        protocol_stats_pda,
+        affiliate_index_page_pda,
+        affiliate_profile_pda,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+
+    instruction.accounts.push(AccountMeta::new(*gating_token_account, false));
+
+    if let Some(gating_mint) = gating_mint {
+        instruction.accounts.push(AccountMeta::new(*gating_mint, false));
+        instruction.accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    instruction
+}
+
+// Creates a RegisterAffiliate instruction discounting the registration fee by the SPAF balance of
+// `spaf_token_account`, following the tiers of the fee discount config PDA. Gating accounts left out
+// are replaced by the program id.
+#[allow(clippy::too_many_arguments)]
+pub fn register_affiliate_with_spaf_discount(
+    program_id: &Pubkey,
+    affiliate: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    solpay_treasury: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    affiliate_index_page_pda: &Pubkey,
+    affiliate_profile_pda: &Pubkey,
+    gating_token_account: Option<&Pubkey>,
+    gating_mint: Option<&Pubkey>,
+    spaf_token_account: &Pubkey,
+    fee_discount_config_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    let mut instruction = register_affiliate(
+        program_id,
+        affiliate,
+        affiliate_pda,
+        project_pda,
+        solpay_treasury,
+        protocol_stats_pda,
+        affiliate_index_page_pda,
+        affiliate_profile_pda,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+    let placeholder = AccountMeta::new_readonly(*program_id, false);
+
+    instruction.accounts.push(gating_token_account.map_or(placeholder.clone(), |account| AccountMeta::new(*account, false)));
+
+    match gating_mint {
+        Some(gating_mint) => instruction.accounts.extend([
+            AccountMeta::new(*gating_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]),
+        None => instruction.accounts.extend([placeholder.clone(), placeholder]),
+    }
+
+    instruction.accounts.push(AccountMeta::new_readonly(*spaf_token_account, false));
+    instruction.accounts.push(AccountMeta::new_readonly(*fee_discount_config_pda, false));
+
+    instruction
+}
+
+// Creates a RedeemReward instruction
+pub fn redeem_reward(
+    program_id: &Pubkey,
+    affiliate: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*affiliate, true),
+            AccountMeta::new(*affiliate_pda, false),
+            AccountMeta::new(*project_pda, false),
+            AccountMeta::new(*protocol_stats_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::RedeemReward {
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a RedeemReward instruction minting the SPAF milestone rewards of the redemption to
+// `reward_token_account`, a SPAF token account of the affiliate
+#[allow(clippy::too_many_arguments)]
+pub fn redeem_reward_with_milestone_reward(
+    program_id: &Pubkey,
+    affiliate: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    reward_token_account: &Pubkey,
+    spaf_mint: &Pubkey,
+    spaf_mint_authority: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    let mut instruction = redeem_reward(
+        program_id,
+        affiliate,
+        affiliate_pda,
+        project_pda,
+        protocol_stats_pda,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+
+    instruction.accounts.extend([
+        AccountMeta::new(*reward_token_account, false),
+        AccountMeta::new(*spaf_mint, false),
+        AccountMeta::new_readonly(*spaf_mint_authority, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]);
+
+    instruction
+}
+
+// Creates a CloseAffiliateAccount instruction, signed by the admin. `affiliate_index_page_pda` is the
+// index page listing the affiliate, `None` for affiliates registered before the affiliate index.
+#[allow(clippy::too_many_arguments)]
+pub fn close_affiliate_account(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    solpay_treasury: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    affiliate_index_page_pda: Option<&Pubkey>,
+    affiliate_profile_pda: &Pubkey,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(*affiliate_pda, false),
+            AccountMeta::new(*project_pda, false),
+            AccountMeta::new(*solpay_treasury, false),
+            AccountMeta::new(*protocol_stats_pda, false),
+            affiliate_index_page_pda.map_or(
+                AccountMeta::new_readonly(*program_id, false),
+                |page| AccountMeta::new(*page, false),
+            ),
+            AccountMeta::new(*affiliate_profile_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::CloseAffiliateAccount {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a CloseAffiliateAccount instruction also releasing the referral code of the affiliate
+#[allow(clippy::too_many_arguments)]
+pub fn close_affiliate_account_with_referral_code(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    solpay_treasury: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    affiliate_index_page_pda: Option<&Pubkey>,
+    affiliate_profile_pda: &Pubkey,
+    referral_code_pda: &Pubkey,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    let mut instruction = close_affiliate_account(
+        program_id,
+        admin,
+        affiliate_pda,
+        project_pda,
+        solpay_treasury,
+        protocol_stats_pda,
+        affiliate_index_page_pda,
+        affiliate_profile_pda,
+        affiliate_pubkey,
+        project_owner_pubkey,
+        candy_machine_id,
+    );
+
+    instruction.accounts.push(AccountMeta::new(*referral_code_pda, false));
+
+    instruction
+}
+
+// Creates a GetRedeemableAmount view instruction
+pub fn get_redeemable_amount(
+    program_id: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*affiliate_pda, false),
+            AccountMeta::new_readonly(*project_pda, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::GetRedeemableAmount {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a GetProjectCapacity view instruction
+pub fn get_project_capacity(
+    program_id: &Pubkey,
+    project_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*project_pda, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::GetProjectCapacity {
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a GetProjectCapacity view instruction reporting the gating and registration fee of `affiliate`.
+// Leave out the gating or SPAF token account when the affiliate has none.
+#[allow(clippy::too_many_arguments)]
+pub fn get_project_capacity_for_affiliate(
+    program_id: &Pubkey,
+    project_pda: &Pubkey,
+    affiliate: &Pubkey,
+    gating_token_account: Option<&Pubkey>,
+    spaf_token_account: Option<&Pubkey>,
+    fee_discount_config_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    let mut instruction = get_project_capacity(program_id, project_pda, project_owner_pubkey, candy_machine_id);
+
+    instruction.accounts.push(AccountMeta::new_readonly(*affiliate, false));
+    instruction.accounts.push(AccountMeta::new_readonly(*gating_token_account.unwrap_or(program_id), false));
+    instruction.accounts.push(AccountMeta::new_readonly(*spaf_token_account.unwrap_or(program_id), false));
+    instruction.accounts.push(AccountMeta::new_readonly(*fee_discount_config_pda, false));
+
+    instruction
+}
+
+// Creates a ClaimReferralCode instruction. Pass the affiliate account the code currently points to
+// as `previous_affiliate_pda` to take over the code after that affiliate was closed.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_referral_code(
+    program_id: &Pubkey,
+    affiliate: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    referral_code_pda: &Pubkey,
+    previous_affiliate_pda: Option<&Pubkey>,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    code: String,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*affiliate, true),
+        AccountMeta::new(*affiliate_pda, false),
+        AccountMeta::new_readonly(*project_pda, false),
+        AccountMeta::new(*referral_code_pda, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    if let Some(previous_affiliate_pda) = previous_affiliate_pda {
+        accounts.push(AccountMeta::new_readonly(*previous_affiliate_pda, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: CandyMachineAffiliatesInstruction::ClaimReferralCode {
+            project_owner_pubkey,
+            candy_machine_id,
+            code,
+        }.pack(),
+    }
+}
+
+// Creates a CreatePromoCode instruction, signed by the project owner. `affiliate_pda` must be
+// given along with `affiliate_pubkey` to attach the code to that affiliate.
+#[allow(clippy::too_many_arguments)]
+pub fn create_promo_code(
+    program_id: &Pubkey,
+    project_owner: &Pubkey,
+    project_pda: &Pubkey,
+    promo_code_pda: &Pubkey,
+    affiliate_pda: Option<&Pubkey>,
+    candy_machine_id: Pubkey,
+    code: String,
+    affiliate_pubkey: Option<Pubkey>,
+    discount_basis_points: u16,
+    max_uses: u32,
+    max_uses_per_wallet: u16,
+    starts_at: i64,
+    ends_at: i64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*project_owner, true),
+        AccountMeta::new_readonly(*project_pda, false),
+        AccountMeta::new(*promo_code_pda, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    if let Some(affiliate_pda) = affiliate_pda {
+        accounts.push(AccountMeta::new_readonly(*affiliate_pda, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: CandyMachineAffiliatesInstruction::CreatePromoCode {
+            candy_machine_id,
+            code,
+            affiliate_pubkey,
+            discount_basis_points,
+            max_uses,
+            max_uses_per_wallet,
+            starts_at,
+            ends_at,
+        }.pack(),
+    }
+}
+
+// Creates a ConsumePromoCode instruction, signed by the buyer paying for its usage account
+#[allow(clippy::too_many_arguments)]
+pub fn consume_promo_code(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    project_pda: &Pubkey,
+    promo_code_pda: &Pubkey,
+    promo_code_usage_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    code: String,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new_readonly(project_owner_pubkey, true),
+            AccountMeta::new_readonly(*project_pda, false),
+            AccountMeta::new(*promo_code_pda, false),
+            AccountMeta::new(*promo_code_usage_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::ConsumePromoCode {
+            project_owner_pubkey,
+            candy_machine_id,
+            code,
+        }.pack(),
+    }
+}
+
+// Creates a SetProjectGating instruction, signed by the project owner. Pass `None` to open
+// registration to everyone again.
+pub fn set_project_gating(
+    program_id: &Pubkey,
+    project_owner: &Pubkey,
+    project_pda: &Pubkey,
+    candy_machine_id: Pubkey,
+    gating_mint: Option<Pubkey>,
+    gating_min_amount: u64,
+    gating_burn: bool,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*project_owner, true),
+            AccountMeta::new(*project_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::SetProjectGating {
+            candy_machine_id,
+            gating_mint,
+            gating_min_amount,
+            gating_burn,
+        }.pack(),
+    }
+}
+
+// Creates a MintWithReferral instruction, signed by the buyer and the payment wallet paying the
+// commission. `mint_accounts` and `mint_data` make up the mint instruction of `candy_machine_program`,
+// the v2 candy machine itself or the Candy Guard of a v3 one.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_with_referral(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    payment_wallet: &Pubkey,
+    candy_machine_program: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    affiliate_profile_pda: &Pubkey,
+    mint_accounts: Vec<AccountMeta>,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    mint_data: Vec<u8>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new(*affiliate_pda, false),
+        AccountMeta::new(*project_pda, false),
+        AccountMeta::new_readonly(candy_machine_id, false),
+        AccountMeta::new(*payment_wallet, true),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*candy_machine_program, false),
+        AccountMeta::new(*protocol_stats_pda, false),
+        AccountMeta::new(*affiliate_profile_pda, false),
+    ];
+
+    accounts.extend(mint_accounts);
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: CandyMachineAffiliatesInstruction::MintWithReferral {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+            mint_data,
+        }.pack(),
+    }
+}
+
+// Creates an InitializeProtocolStats instruction, signed by the admin paying for the stats account
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_protocol_stats(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    protocol_stats_pda: &Pubkey,
+    live_project_count: u32,
+    live_affiliate_count: u32,
+    registration_fees_collected_lamports: u64,
+    total_rewards_redeemed_lamports: u64,
+    total_referred_volume_lamports: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(*protocol_stats_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::InitializeProtocolStats {
+            live_project_count,
+            live_affiliate_count,
+            registration_fees_collected_lamports,
+            total_rewards_redeemed_lamports,
+            total_referred_volume_lamports,
+        }.pack(),
+    }
+}
+
+// Creates an IndexAffiliate instruction appending an affiliate registered before the affiliate index
+// to page `affiliate_index_length / AFFILIATE_INDEX_PAGE_SIZE` of the index of its project
+#[allow(clippy::too_many_arguments)]
+pub fn index_affiliate(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    affiliate_index_page_pda: &Pubkey,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*affiliate_pda, false),
+            AccountMeta::new(*project_pda, false),
+            AccountMeta::new(*affiliate_index_page_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::IndexAffiliate {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates an AddAffiliateToProfile instruction listing the project of an affiliate opened before
+// affiliate profiles in the profile of its wallet, paid by `payer`
+#[allow(clippy::too_many_arguments)]
+pub fn add_affiliate_to_profile(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    affiliate_pda: &Pubkey,
+    project_pda: &Pubkey,
+    affiliate_profile_pda: &Pubkey,
+    affiliate_pubkey: Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*affiliate_pda, false),
+            AccountMeta::new_readonly(*project_pda, false),
+            AccountMeta::new(*affiliate_profile_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::AddAffiliateToProfile {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a LinkCandyMachineProject instruction pointing the candy machine of a project registered
+// before candy machine lookups to it, paid by `payer`
+pub fn link_candy_machine_project(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    project_pda: &Pubkey,
+    candy_machine_project_pda: &Pubkey,
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*project_pda, false),
+            AccountMeta::new_readonly(candy_machine_id, false),
+            AccountMeta::new(*candy_machine_project_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::LinkCandyMachineProject {
+            project_owner_pubkey,
+            candy_machine_id,
+        }.pack(),
+    }
+}
+
+// Creates a SetFeeDiscountTiers instruction, signed by the admin paying for the config account the first time
+pub fn set_fee_discount_tiers(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    fee_discount_config_pda: &Pubkey,
+    tiers: Vec<FeeDiscountTier>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(*fee_discount_config_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data: CandyMachineAffiliatesInstruction::SetFeeDiscountTiers {
+            tiers,
+        }.pack(),
+    }
 }
@@ -1,10 +1,68 @@
-pub const ADMIN_PUBKEY: &str = "Gj9MVJ2jX2xApttsPJjb1sKoQePh1V226z3F9t3THZxD";
-pub const SOLPAY_TREASURY_PUBKEY: &str = "ERdxYUQ5CibPsEeVKteXtLw2pNd5q9Cz36LYngme4VEf";
+#[macro_use]
+mod log;
+mod cluster;
 
+pub use cluster::{
+    id,
+    check_id,
+    ID,
+    ADMIN_PUBKEY,
+    SOLPAY_TREASURY_PUBKEY,
+    AFFILIATE_REGISTRATION_FEE,
+    SPAF_MINT_PUBKEY,
+    SPAF_MILESTONE_REWARDS,
+};
+
+#[cfg(not(feature = "no-entrypoint"))]
 mod entrypoint;
-mod processor;
-mod instruction;
-mod error;
+pub mod processor;
+pub mod instruction;
+pub mod error;
+pub mod events;
+pub mod views;
+#[cfg(not(target_os = "solana"))]
+pub mod idl;
 mod utils;
-mod affiliates;
-mod projects;
+pub mod affiliates;
+pub mod projects;
+pub mod referral_codes;
+pub mod promo_codes;
+pub mod stats;
+pub mod fee_discount_config;
+pub mod affiliate_index;
+pub mod affiliate_profiles;
+pub mod candy_machine_projects;
+pub mod candy_machine;
+#[cfg(feature = "cpi")]
+pub mod cpi;
+
+pub use error::CandyMachineAffiliatesError;
+pub use events::CandyMachineAffiliatesEvent;
+pub use views::{ProjectCapacity, PromoCodeDiscount, RedeemableAmount};
+pub use instruction::CandyMachineAffiliatesInstruction;
+pub use projects::state::{ProjectAccountState, ProjectAccountStateData};
+pub use affiliates::state::{AffiliateAccountState, AffiliateAccountStateData};
+pub use affiliates::milestones::{milestone_reward, SPAF_MINT_AUTHORITY_SEED};
+pub use affiliates::fee_discounts::{fee_discount_basis_points, is_valid_fee_discount_schedule, registration_fee};
+pub use referral_codes::state::{normalize_referral_code, ReferralCodeAccountState, ReferralCodeAccountStateData};
+pub use stats::state::{ProtocolStatsAccountState, ProtocolStatsAccountStateData};
+pub use fee_discount_config::state::{
+    FeeDiscountConfigAccountState,
+    FeeDiscountConfigAccountStateData,
+    FeeDiscountTier,
+    MAX_FEE_DISCOUNT_TIERS,
+};
+pub use affiliate_index::state::{
+    affiliate_index_page,
+    AffiliateIndexPageAccountState,
+    AffiliateIndexPageAccountStateData,
+    AFFILIATE_INDEX_PAGE_SIZE,
+};
+pub use affiliate_profiles::state::{AffiliateProfileAccountState, AffiliateProfileAccountStateData};
+pub use candy_machine_projects::state::{CandyMachineProjectAccountState, CandyMachineProjectAccountStateData};
+pub use promo_codes::state::{
+    PromoCodeAccountState,
+    PromoCodeAccountStateData,
+    PromoCodeUsageAccountState,
+    PromoCodeUsageAccountStateData,
+};
@@ -0,0 +1,32 @@
+// Leveled logging on top of `msg!`.
+// `log_info!` and `log_error!` are always logged and should be limited to one outcome line per instruction.
+// `log_debug!` is only logged when the `verbose-logs` feature is enabled, otherwise the arguments are
+// type checked but never formatted, so the step by step logs cost no compute in production builds.
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        solana_program::msg!($($arg)*)
+    };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        solana_program::msg!($($arg)*)
+    };
+}
+
+#[cfg(feature = "verbose-logs")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        solana_program::msg!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "verbose-logs"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if false {
+            solana_program::msg!($($arg)*)
+        }
+    };
+}
@@ -7,9 +7,21 @@ use crate::instruction::CandyMachineAffiliatesInstruction;
 use crate::affiliates::register_affiliate_account::register_affiliate_account;
 use crate::affiliates::redeem_reward::redeem_reward;
 use crate::affiliates::close_affiliate_account::close_affiliate_account;
+use crate::affiliates::get_redeemable_amount::get_redeemable_amount;
+use crate::affiliates::mint_with_referral::mint_with_referral;
 use crate::projects::register_project_account::register_project_account;
 use crate::projects::update_project_account::update_project_account;
 use crate::projects::close_project_account::close_project_account;
+use crate::projects::get_project_capacity::get_project_capacity;
+use crate::projects::set_project_gating::set_project_gating;
+use crate::referral_codes::claim_referral_code::claim_referral_code;
+use crate::promo_codes::create_promo_code::create_promo_code;
+use crate::promo_codes::consume_promo_code::consume_promo_code;
+use crate::stats::initialize_protocol_stats::initialize_protocol_stats;
+use crate::fee_discount_config::set_fee_discount_tiers::set_fee_discount_tiers;
+use crate::affiliate_index::index_affiliate::index_affiliate;
+use crate::affiliate_profiles::add_affiliate_to_profile::add_affiliate_to_profile;
+use crate::candy_machine_projects::link_candy_machine_project::link_candy_machine_project;
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -32,7 +44,7 @@ pub fn process_instruction(
                 candy_machine_id,
                 affiliate_fee_percentage,
                 affiliate_target_in_sol,
-                max_affiliate_count,
+                u32::from(max_affiliate_count),
                 title
             )
         }
@@ -51,7 +63,7 @@ pub fn process_instruction(
                 candy_machine_id,
                 affiliate_fee_percentage,
                 affiliate_target_in_sol,
-                max_affiliate_count,
+                u32::from(max_affiliate_count),
                 title,
             )
         }
@@ -101,5 +113,207 @@ pub fn process_instruction(
                 candy_machine_id
             )
         }
+        CandyMachineAffiliatesInstruction::GetRedeemableAmount {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        } => {
+            get_redeemable_amount(
+                program_id,
+                accounts,
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id
+            )
+        }
+        CandyMachineAffiliatesInstruction::GetProjectCapacity {
+            project_owner_pubkey,
+            candy_machine_id,
+        } => {
+            get_project_capacity(
+                program_id,
+                accounts,
+                project_owner_pubkey,
+                candy_machine_id
+            )
+        }
+        CandyMachineAffiliatesInstruction::ClaimReferralCode {
+            project_owner_pubkey,
+            candy_machine_id,
+            code,
+        } => {
+            claim_referral_code(
+                program_id,
+                accounts,
+                project_owner_pubkey,
+                candy_machine_id,
+                code
+            )
+        }
+        CandyMachineAffiliatesInstruction::CreatePromoCode {
+            candy_machine_id,
+            code,
+            affiliate_pubkey,
+            discount_basis_points,
+            max_uses,
+            max_uses_per_wallet,
+            starts_at,
+            ends_at,
+        } => {
+            create_promo_code(
+                program_id,
+                accounts,
+                candy_machine_id,
+                code,
+                affiliate_pubkey,
+                discount_basis_points,
+                max_uses,
+                max_uses_per_wallet,
+                starts_at,
+                ends_at
+            )
+        }
+        CandyMachineAffiliatesInstruction::ConsumePromoCode {
+            project_owner_pubkey,
+            candy_machine_id,
+            code,
+        } => {
+            consume_promo_code(
+                program_id,
+                accounts,
+                project_owner_pubkey,
+                candy_machine_id,
+                code
+            )
+        }
+        CandyMachineAffiliatesInstruction::SetProjectGating {
+            candy_machine_id,
+            gating_mint,
+            gating_min_amount,
+            gating_burn,
+        } => {
+            set_project_gating(
+                program_id,
+                accounts,
+                candy_machine_id,
+                gating_mint,
+                gating_min_amount,
+                gating_burn
+            )
+        }
+        CandyMachineAffiliatesInstruction::MintWithReferral {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+            mint_data,
+        } => {
+            mint_with_referral(
+                program_id,
+                accounts,
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id,
+                mint_data
+            )
+        }
+        CandyMachineAffiliatesInstruction::InitializeProtocolStats {
+            live_project_count,
+            live_affiliate_count,
+            registration_fees_collected_lamports,
+            total_rewards_redeemed_lamports,
+            total_referred_volume_lamports,
+        } => {
+            initialize_protocol_stats(
+                program_id,
+                accounts,
+                live_project_count,
+                live_affiliate_count,
+                registration_fees_collected_lamports,
+                total_rewards_redeemed_lamports,
+                total_referred_volume_lamports
+            )
+        }
+        CandyMachineAffiliatesInstruction::IndexAffiliate {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        } => {
+            index_affiliate(
+                program_id,
+                accounts,
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id
+            )
+        }
+        CandyMachineAffiliatesInstruction::AddAffiliateToProfile {
+            affiliate_pubkey,
+            project_owner_pubkey,
+            candy_machine_id,
+        } => {
+            add_affiliate_to_profile(
+                program_id,
+                accounts,
+                affiliate_pubkey,
+                project_owner_pubkey,
+                candy_machine_id
+            )
+        }
+        CandyMachineAffiliatesInstruction::LinkCandyMachineProject {
+            project_owner_pubkey,
+            candy_machine_id,
+        } => {
+            link_candy_machine_project(
+                program_id,
+                accounts,
+                project_owner_pubkey,
+                candy_machine_id
+            )
+        }
+        CandyMachineAffiliatesInstruction::RegisterProjectV2 {
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title
+        } => {
+            register_project_account(
+                program_id,
+                accounts,
+                candy_machine_id,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title
+            )
+        }
+        CandyMachineAffiliatesInstruction::UpdateProjectV2 {
+            project_owner_pubkey,
+            candy_machine_id,
+            affiliate_fee_percentage,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            title,
+        } => {
+            update_project_account(
+                program_id,
+                accounts,
+                project_owner_pubkey,
+                candy_machine_id,
+                affiliate_fee_percentage,
+                affiliate_target_in_sol,
+                max_affiliate_count,
+                title,
+            )
+        }
+        CandyMachineAffiliatesInstruction::SetFeeDiscountTiers {
+            tiers,
+        } => {
+            set_fee_discount_tiers(
+                program_id,
+                accounts,
+                tiers,
+            )
+        }
     }
 }
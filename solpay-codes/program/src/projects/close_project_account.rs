@@ -2,22 +2,17 @@ use std::str::FromStr;
 use solana_program::{
     account_info::{AccountInfo, next_account_info},
     entrypoint::ProgramResult,
-    msg,
-    system_instruction,
-    borsh::try_from_slice_unchecked,
-    program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
     program_pack::IsInitialized,
+    sysvar::{clock::Clock, Sysvar},
 };
 use borsh::BorshSerialize;
-use solana_program::native_token::LAMPORTS_PER_SOL;
-use solana_program::program::invoke;
 use crate::ADMIN_PUBKEY;
-use crate::projects::state::{PROJECT_ACCOUNT_DATA_VERSION, ProjectAccountState};
+use crate::projects::state::ProjectAccountState;
 use crate::error::CandyMachineAffiliatesError;
-use crate::utils::validate_client_pda;
+use crate::events::{CandyMachineAffiliatesEvent, ProjectClosed};
+use crate::utils::{close_affiliate_index_page, close_candy_machine_project, unpack_protocol_stats, validate_client_pda};
 
 #[allow(unused_variables)]
 pub fn close_project_account(
@@ -26,7 +21,7 @@ pub fn close_project_account(
     project_owner_pubkey: Pubkey,
     candy_machine_id: Pubkey,
 ) -> ProgramResult {
-    msg!("Closing project account...");
+    log_debug!("Closing project account...");
 
     // Get Account iterator
     let account_info_iter = &mut accounts.iter();
@@ -35,18 +30,22 @@ pub fn close_project_account(
     let initializer = next_account_info(account_info_iter)?;
     let pda_account = next_account_info(account_info_iter)?;
     let owner_account = next_account_info(account_info_iter)?;
+    let protocol_stats_account = next_account_info(account_info_iter)?;
+    let candy_machine_project_account = next_account_info(account_info_iter)?;
+    // Affiliate index pages of the project, closed along with it
+    let affiliate_index_page_accounts = account_info_iter;
 
     // Validate accounts
 
     if !initializer.is_signer {
-        msg!("Missing required signature");
+        log_error!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
     let admin_pubkey = Pubkey::from_str(ADMIN_PUBKEY).expect("Pubkey conversion failed");
 
     if initializer.key.ne(&admin_pubkey) {
-        msg!("Action not allowed");
+        log_error!("Action not allowed");
         return Err(CandyMachineAffiliatesError::ActionNotAllowed.into());
     }
 
@@ -65,25 +64,67 @@ pub fn close_project_account(
     );
 
     if !client_pda_is_valid {
-        msg!("Invalid seeds for PDA");
+        log_error!("Invalid seeds for PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
-    msg!("Unpacking project account state");
-    let project_account_state = try_from_slice_unchecked::<ProjectAccountState>(&pda_account.data.borrow()).unwrap();
-    msg!("Borrowed project account data");
+    if owner_account.key.ne(&project_owner_pubkey) {
+        log_error!("Incorrect project owner account");
+        return Err(CandyMachineAffiliatesError::IncorrectProjectOwnerAccount.into());
+    }
+
+    log_debug!("Unpacking project account state");
+    let project_account_state = ProjectAccountState::unpack_account_data(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed project account data");
 
-    msg!("Checking if project account is initialized");
+    log_debug!("Checking if project account is initialized");
     if !project_account_state.is_initialized() {
-        msg!("Project account not initialized");
+        log_error!("Project account not initialized");
         return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
     }
 
-    **owner_account.try_borrow_mut_lamports()? += pda_account.lamports();
+    let mut protocol_stats_state = unpack_protocol_stats(protocol_stats_account, program_id)?;
+
+    if let Some(protocol_stats_state) = &mut protocol_stats_state {
+        // Saturating, the starting count of a stats account may miss projects registered before it
+        protocol_stats_state.data.live_project_count = protocol_stats_state.data.live_project_count.saturating_sub(1);
+        protocol_stats_state.data.updated_at = Clock::get()?.unix_timestamp;
+
+        log_debug!("Serializing protocol stats account");
+        protocol_stats_state.serialize(&mut &mut protocol_stats_account.data.borrow_mut()[..])?;
+        log_debug!("Protocol stats account serialized");
+    }
+
+    close_candy_machine_project(
+        candy_machine_project_account,
+        &candy_machine_id,
+        pda_account.key,
+        owner_account,
+        program_id,
+    )?;
+
+    for affiliate_index_page_account in affiliate_index_page_accounts {
+        close_affiliate_index_page(affiliate_index_page_account, pda_account, owner_account, program_id)?;
+    }
+
+    let refunded_lamports = pda_account.lamports();
+    let owner_lamports = owner_account.lamports()
+        .checked_add(pda_account.lamports())
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    **owner_account.try_borrow_mut_lamports()? = owner_lamports;
     **pda_account.try_borrow_mut_lamports()? = 0;
     *pda_account.try_borrow_mut_data()? = &mut [];
 
-    msg!("Project account closed.");
+    log_info!("Project account closed.");
+
+    CandyMachineAffiliatesEvent::ProjectClosed(ProjectClosed {
+        project: *pda_account.key,
+        project_owner_pubkey,
+        candy_machine_id,
+        refunded_lamports,
+    }).emit();
 
     Ok(())
 }
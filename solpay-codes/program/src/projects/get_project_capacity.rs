@@ -0,0 +1,93 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program_pack::IsInitialized
+};
+use crate::affiliates::fee_discounts::wallet_registration_fee;
+use crate::error::CandyMachineAffiliatesError;
+use crate::projects::state::ProjectAccountState;
+use crate::utils::{meets_project_gating, validate_client_pda};
+use crate::views::{return_view, ProjectCapacity};
+
+// Read-only counterpart of the capacity, gating and fee checks in `register_affiliate_account`.
+// The affiliate wallet, its gating and SPAF token accounts and the fee discount config are optional trailing accounts.
+pub fn get_project_capacity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey
+) -> ProgramResult {
+    log_debug!("Getting project capacity...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let is_passed = |account: &&AccountInfo| account.key.ne(program_id);
+    let affiliate_wallet = next_account_info(account_info_iter).ok();
+    let gating_token_account = next_account_info(account_info_iter).ok().filter(is_passed);
+    let spaf_token_account = next_account_info(account_info_iter).ok().filter(is_passed);
+    let fee_discount_config_account = next_account_info(account_info_iter).ok().filter(is_passed);
+
+    // Validate accounts
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    log_debug!("Unpacking project state account");
+    let project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !project_account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    let affiliate_count = project_account_state.data.affiliate_count;
+    let max_affiliate_count = project_account_state.data.max_affiliate_count;
+    let remaining_affiliate_slots = max_affiliate_count.saturating_sub(affiliate_count);
+
+    let (registration_fee, meets_gating) = match affiliate_wallet {
+        Some(affiliate_wallet) => {
+            let meets_gating = match (project_account_state.data.gating_mint, gating_token_account) {
+                (Some(_), None) => false,
+                _ => meets_project_gating(&project_account_state, gating_token_account, affiliate_wallet.key)?,
+            };
+
+            (wallet_registration_fee(spaf_token_account, fee_discount_config_account, affiliate_wallet.key, program_id)?, meets_gating)
+        }
+        None => (wallet_registration_fee(None, None, program_id, program_id)?, project_account_state.data.gating_mint.is_none()),
+    };
+
+    log_info!("Project capacity: {}/{} affiliates.", affiliate_count, max_affiliate_count);
+
+    return_view(&ProjectCapacity {
+        affiliate_count,
+        max_affiliate_count,
+        remaining_affiliate_slots,
+        is_full: remaining_affiliate_slots == 0,
+        registration_fee,
+        meets_gating,
+    });
+
+    Ok(())
+}
@@ -2,3 +2,5 @@ pub mod state;
 pub mod register_project_account;
 pub mod update_project_account;
 pub mod close_project_account;
+pub mod get_project_capacity;
+pub mod set_project_gating;
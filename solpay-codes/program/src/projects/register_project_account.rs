@@ -1,10 +1,7 @@
-use std::str::FromStr;
 use solana_program::{
     account_info::{AccountInfo, next_account_info},
     entrypoint::ProgramResult,
-    msg,
     system_instruction,
-    borsh::try_from_slice_unchecked,
     program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
@@ -14,11 +11,11 @@ use solana_program::{
 };
 use borsh::BorshSerialize;
 use solana_program::clock::Clock;
-use solana_program::native_token::LAMPORTS_PER_SOL;
-use solana_program::program::invoke;
+use crate::candy_machine::candy_machine_authority;
 use crate::projects::state::{MAX_PROJECT_TITLE_LENGTH, PROJECT_ACCOUNT_DATA_VERSION, ProjectAccountState};
 use crate::error::CandyMachineAffiliatesError;
-use crate::utils::validate_client_pda;
+use crate::events::{CandyMachineAffiliatesEvent, ProjectRegisteredV2};
+use crate::utils::{create_candy_machine_project, unpack_protocol_stats, validate_client_pda};
 
 #[allow(unused_variables)]
 pub fn register_project_account(
@@ -27,10 +24,10 @@ pub fn register_project_account(
     candy_machine_id: Pubkey,
     affiliate_fee_percentage: f64,
     affiliate_target_in_sol: u8,
-    max_affiliate_count: u8,
+    max_affiliate_count: u32,
     title: String,
 ) -> ProgramResult {
-    msg!("Creating project account...");
+    log_debug!("Creating project account...");
 
     // Get Account iterator
     let account_info_iter = &mut accounts.iter();
@@ -39,7 +36,10 @@ pub fn register_project_account(
     let initializer = next_account_info(account_info_iter)?;
     let pda_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
-    let clock = Clock::get().unwrap();
+    let candy_machine_account = next_account_info(account_info_iter)?;
+    let protocol_stats_account = next_account_info(account_info_iter)?;
+    let candy_machine_project_account = next_account_info(account_info_iter)?;
+    let clock = Clock::get()?;
 
     // Validate accounts
 
@@ -48,7 +48,7 @@ pub fn register_project_account(
     }
 
     if !initializer.is_signer {
-        msg!("Missing required signature");
+        log_error!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
@@ -63,19 +63,33 @@ pub fn register_project_account(
     );
 
     if !client_pda_is_valid {
-        msg!("Invalid seeds for PDA");
+        log_error!("Invalid seeds for PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
+    // Only the candy machine authority registers a project for it
+
+    if candy_machine_account.key.ne(&candy_machine_id) {
+        log_error!("Candy machine account does not match candy machine id");
+        return Err(CandyMachineAffiliatesError::InvalidCandyMachineAccount.into());
+    }
+
+    if candy_machine_authority(candy_machine_account)?.ne(initializer.key) {
+        log_error!("Project owner is not the candy machine authority");
+        return Err(CandyMachineAffiliatesError::InvalidCandyMachineAuthority.into());
+    }
+
+    let mut protocol_stats_state = unpack_protocol_stats(protocol_stats_account, program_id)?;
+
     // Validate data
 
     if title.chars().count() > MAX_PROJECT_TITLE_LENGTH {
-        msg!("Project title too long");
+        log_error!("Project title too long");
         return Err(CandyMachineAffiliatesError::ProjectTitleTooLong.into());
     }
 
-    if max_affiliate_count <= 0 {
-        msg!("Invalid project max affiliate count");
+    if max_affiliate_count == 0 {
+        log_error!("Invalid project max affiliate count");
         return Err(CandyMachineAffiliatesError::ProjectTitleTooLong.into());
     }
 
@@ -103,15 +117,16 @@ pub fn register_project_account(
         ],
     )?;
 
-    msg!("PDA created");
+    log_debug!("PDA created");
 
-    msg!("Unpacking state account");
-    let mut account_state = try_from_slice_unchecked::<ProjectAccountState>(&pda_account.data.borrow()).unwrap();
-    msg!("Borrowed account data");
+    log_debug!("Unpacking state account");
+    let mut account_state = ProjectAccountState::unpack_account_data(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
 
-    msg!("Checking if project account is already initialized");
+    log_debug!("Checking if project account is already initialized");
     if account_state.is_initialized() {
-        msg!("Account already initialized");
+        log_error!("Account already initialized");
         return Err(ProgramError::AccountAlreadyInitialized);
     }
 
@@ -127,10 +142,48 @@ pub fn register_project_account(
     account_state.data.title = title;
     account_state.data.created_at = clock.unix_timestamp;
     account_state.data.updated_at = clock.unix_timestamp;
+    account_state.data.gating_mint = None;
+    account_state.data.gating_min_amount = 0;
+    account_state.data.gating_burn = false;
+
+    // Fails when another owner already registered a project for the candy machine
+    create_candy_machine_project(
+        candy_machine_project_account,
+        &candy_machine_id,
+        pda_account.key,
+        initializer.key,
+        clock.unix_timestamp,
+        initializer,
+        system_program,
+        program_id,
+    )?;
 
-    msg!("Serializing account");
+    if let Some(protocol_stats_state) = &mut protocol_stats_state {
+        protocol_stats_state.data.live_project_count = protocol_stats_state.data.live_project_count
+            .checked_add(1)
+            .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+        protocol_stats_state.data.updated_at = clock.unix_timestamp;
+    }
+
+    log_debug!("Serializing accounts");
     account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
-    msg!("State account serialized");
+    if let Some(protocol_stats_state) = &protocol_stats_state {
+        protocol_stats_state.serialize(&mut &mut protocol_stats_account.data.borrow_mut()[..])?;
+    }
+    log_debug!("State accounts serialized");
+
+    log_info!("Project registered.");
+
+    CandyMachineAffiliatesEvent::ProjectRegisteredV2(ProjectRegisteredV2 {
+        project: *pda_account.key,
+        project_owner_pubkey: account_state.data.project_owner_pubkey,
+        candy_machine_id: account_state.data.candy_machine_id,
+        affiliate_fee_percentage: account_state.data.affiliate_fee_percentage,
+        affiliate_target_in_sol: account_state.data.affiliate_target_in_sol,
+        max_affiliate_count: account_state.data.max_affiliate_count,
+        title: account_state.data.title,
+        created_at: account_state.data.created_at,
+    }).emit();
 
     Ok(())
 }
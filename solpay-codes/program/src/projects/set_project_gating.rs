@@ -0,0 +1,108 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use borsh::BorshSerialize;
+use solana_program::clock::Clock;
+use crate::projects::state::ProjectAccountState;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, ProjectGatingUpdated};
+use crate::utils::{grow_account, validate_client_pda};
+
+#[allow(unused_variables)]
+pub fn set_project_gating(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    candy_machine_id: Pubkey,
+    gating_mint: Option<Pubkey>,
+    gating_min_amount: u64,
+    gating_burn: bool,
+) -> ProgramResult {
+    log_debug!("Setting project gating...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock = Clock::get()?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    // Only the project owner derives the project PDA from the initializer key
+    let (client_pda_is_valid, _) = validate_client_pda(
+        pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            initializer.key.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    // Validate data
+
+    if gating_mint.is_some() != (gating_min_amount > 0) || (gating_mint.is_none() && gating_burn) {
+        log_error!("Invalid project gating settings");
+        return Err(CandyMachineAffiliatesError::InvalidProjectGating.into());
+    }
+
+    log_debug!("Unpacking state account");
+    let mut account_state = ProjectAccountState::unpack_account_data(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
+
+    if !account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    // Projects registered with an older layout are smaller than the current one
+    grow_account(pda_account, ProjectAccountState::LENGTH, initializer, system_program)?;
+
+    account_state.data.gating_mint = gating_mint;
+    account_state.data.gating_min_amount = gating_min_amount;
+    account_state.data.gating_burn = gating_burn;
+    account_state.data.updated_at = clock.unix_timestamp;
+
+    log_debug!("Serializing account");
+    account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    log_debug!("State account serialized");
+
+    log_info!("Project gating updated.");
+
+    CandyMachineAffiliatesEvent::ProjectGatingUpdated(ProjectGatingUpdated {
+        project: *pda_account.key,
+        gating_mint,
+        gating_min_amount,
+        gating_burn,
+        updated_at: account_state.data.updated_at,
+    }).emit();
+
+    Ok(())
+}
@@ -2,26 +2,40 @@ use borsh::{
     BorshDeserialize,
     BorshSerialize
 };
+use solana_program::borsh1::try_from_slice_unchecked;
 use solana_program::program_pack::{IsInitialized, Sealed};
 use solana_program::pubkey::Pubkey;
 
-pub const PROJECT_ACCOUNT_DATA_VERSION: u8 = 0;
+pub const PROJECT_ACCOUNT_DATA_VERSION: u8 = 1;
 pub const MAX_PROJECT_TITLE_LENGTH: usize = 50;
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct ProjectAccountStateData {
     pub project_owner_pubkey: Pubkey,
     pub candy_machine_id: Pubkey,
     pub affiliate_fee_percentage: f64,
     pub affiliate_target_in_sol: u8,
-    pub max_affiliate_count: u8,
-    pub affiliate_count: u8,
+    pub max_affiliate_count: u32,
+    pub affiliate_count: u32,
     pub title: String,
     pub created_at: i64,
     pub updated_at: i64,
+    // Mint affiliates must hold to register, `None` when registration is open
+    pub gating_mint: Option<Pubkey>,
+    pub gating_min_amount: u64,
+    // Burn `gating_min_amount` on registration instead of only checking the balance
+    pub gating_burn: bool,
+    // Totals over the mints referred with `MintWithReferral` and the rewards redeemed
+    pub total_mint_count: u32,
+    pub total_referred_volume_lamports: u64,
+    pub total_commission_lamports: u64,
+    pub total_redemption_count: u32,
+    pub peak_affiliate_count: u32,
+    // Entries ever appended to the affiliate index pages, closed affiliates included
+    pub affiliate_index_length: u32,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct ProjectAccountState {
     pub discriminator: String,
     pub is_initialized: bool,
@@ -53,14 +67,118 @@ impl ProjectAccountState {
         // affiliate_target_in_sol
         + 1
         // max_affiliate_count
-        + 1
+        + 4
         // affiliate_count
-        + 1
+        + 4
         // title
         + (4 + 4 * MAX_PROJECT_TITLE_LENGTH)
         // created_at
         + 8
         // updated_at
         + 8
+        // gating_mint
+        + (1 + 32)
+        // gating_min_amount
+        + 8
+        // gating_burn
+        + 1
+        // total_mint_count
+        + 4
+        // total_referred_volume_lamports
+        + 8
+        // total_commission_lamports
+        + 8
+        // total_redemption_count
+        + 4
+        // peak_affiliate_count
+        + 4
+        // affiliate_index_length
+        + 4
+    ;
+    // Data version 0 accounts have the deployed layout, ending at `updated_at` with u8 affiliate counts
+    pub const V0_LENGTH: usize = (4 + ProjectAccountState::DISCRIMINATOR.len())
+        // is_initialized, data_version
+        + 1 + 1
+        // project_owner_pubkey, candy_machine_id
+        + 32 + 32
+        // affiliate_fee_percentage, affiliate_target_in_sol
+        + 8 + 1
+        // max_affiliate_count, affiliate_count
+        + 1 + 1
+        // title
+        + (4 + 4 * MAX_PROJECT_TITLE_LENGTH)
+        // created_at, updated_at
+        + 8 + 8
     ;
+
+    // Decodes a project account of the current or of an earlier layout. Data version 0 accounts are
+    // decoded up to `updated_at` only, whatever follows the title is left from shorter titles, and
+    // converted to the current layout and version, with the fields added since then zero. The next
+    // instruction writing the account stores the conversion.
+    pub fn unpack_account_data(data: &[u8]) -> Result<ProjectAccountState, borsh::io::Error> {
+        let mut padded_data = data.to_vec();
+        padded_data.resize(data.len().max(ProjectAccountState::LENGTH), 0);
+
+        let (_, is_initialized, data_version) = <(String, bool, u8)>::deserialize(&mut &padded_data[..])?;
+
+        if is_initialized && data_version == 0 {
+            return Ok(ProjectAccountStateV0::deserialize(&mut &padded_data[..])?.into());
+        }
+
+        try_from_slice_unchecked(&padded_data)
+    }
+}
+
+// Layout of data version 0, the one deployed before the gating, totals and affiliate index fields
+// were added and the affiliate counts widened to u32
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProjectAccountStateDataV0 {
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub affiliate_fee_percentage: f64,
+    pub affiliate_target_in_sol: u8,
+    pub max_affiliate_count: u8,
+    pub affiliate_count: u8,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProjectAccountStateV0 {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: ProjectAccountStateDataV0,
+}
+
+impl From<ProjectAccountStateV0> for ProjectAccountState {
+    fn from(state: ProjectAccountStateV0) -> Self {
+        ProjectAccountState {
+            discriminator: state.discriminator,
+            is_initialized: state.is_initialized,
+            data_version: PROJECT_ACCOUNT_DATA_VERSION,
+            data: ProjectAccountStateData {
+                project_owner_pubkey: state.data.project_owner_pubkey,
+                candy_machine_id: state.data.candy_machine_id,
+                affiliate_fee_percentage: state.data.affiliate_fee_percentage,
+                affiliate_target_in_sol: state.data.affiliate_target_in_sol,
+                max_affiliate_count: state.data.max_affiliate_count.into(),
+                affiliate_count: state.data.affiliate_count.into(),
+                title: state.data.title,
+                created_at: state.data.created_at,
+                updated_at: state.data.updated_at,
+                gating_mint: None,
+                gating_min_amount: 0,
+                gating_burn: false,
+                total_mint_count: 0,
+                total_referred_volume_lamports: 0,
+                total_commission_lamports: 0,
+                total_redemption_count: 0,
+                // The peak is at least the live count
+                peak_affiliate_count: state.data.affiliate_count.into(),
+                affiliate_index_length: 0,
+            },
+        }
+    }
 }
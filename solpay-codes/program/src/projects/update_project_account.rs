@@ -2,25 +2,21 @@ use std::str::FromStr;
 use solana_program::{
     account_info::{AccountInfo, next_account_info},
     entrypoint::ProgramResult,
-    msg,
-    system_instruction,
-    borsh::try_from_slice_unchecked,
-    program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
-    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+    sysvar::Sysvar,
 };
 use borsh::BorshSerialize;
 use solana_program::clock::Clock;
-use solana_program::native_token::LAMPORTS_PER_SOL;
-use solana_program::program::invoke;
 use crate::ADMIN_PUBKEY;
-use crate::projects::state::{MAX_PROJECT_TITLE_LENGTH, PROJECT_ACCOUNT_DATA_VERSION, ProjectAccountState};
+use crate::projects::state::{MAX_PROJECT_TITLE_LENGTH, ProjectAccountState};
 use crate::error::CandyMachineAffiliatesError;
-use crate::utils::validate_client_pda;
+use crate::events::{CandyMachineAffiliatesEvent, ProjectUpdatedV2};
+use crate::utils::{grow_account, validate_client_pda};
 
 #[allow(unused_variables)]
+#[allow(clippy::too_many_arguments)]
 pub fn update_project_account(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -28,10 +24,10 @@ pub fn update_project_account(
     candy_machine_id: Pubkey,
     affiliate_fee_percentage: f64,
     affiliate_target_in_sol: u8,
-    max_affiliate_count: u8,
+    max_affiliate_count: u32,
     title: String,
 ) -> ProgramResult {
-    msg!("Updating project account...");
+    log_debug!("Updating project account...");
 
     // Get Account iterator
     let account_info_iter = &mut accounts.iter();
@@ -39,19 +35,21 @@ pub fn update_project_account(
     // Get accounts
     let initializer = next_account_info(account_info_iter)?;
     let pda_account = next_account_info(account_info_iter)?;
-    let clock = Clock::get().unwrap();
+    // Only needed to grow projects registered before the affiliate counts were widened
+    let system_program = next_account_info(account_info_iter).ok();
+    let clock = Clock::get()?;
 
     // Validate accounts
 
     if !initializer.is_signer {
-        msg!("Missing required signature");
+        log_error!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
     let admin_pubkey = Pubkey::from_str(ADMIN_PUBKEY).expect("Pubkey conversion failed");
 
     if initializer.key.ne(&admin_pubkey) {
-        msg!("Action not allowed");
+        log_error!("Action not allowed");
         return Err(CandyMachineAffiliatesError::ActionNotAllowed.into());
     }
 
@@ -70,40 +68,64 @@ pub fn update_project_account(
     );
 
     if !client_pda_is_valid {
-        msg!("Invalid seeds for PDA");
+        log_error!("Invalid seeds for PDA");
         return Err(CandyMachineAffiliatesError::InvalidPDA.into());
     }
 
     // Validate data
 
     if title.chars().count() > MAX_PROJECT_TITLE_LENGTH {
-        msg!("Project title too long");
+        log_error!("Project title too long");
         return Err(CandyMachineAffiliatesError::ProjectTitleTooLong.into());
     }
 
-    if max_affiliate_count <= 0 {
-        msg!("Invalid project max affiliate count");
+    if max_affiliate_count == 0 {
+        log_error!("Invalid project max affiliate count");
         return Err(CandyMachineAffiliatesError::ProjectTitleTooLong.into());
     }
 
-    msg!("Unpacking state account");
-    let mut account_state = try_from_slice_unchecked::<ProjectAccountState>(&pda_account.data.borrow()).unwrap();
-    msg!("Borrowed account data");
+    log_debug!("Unpacking state account");
+    let mut account_state = ProjectAccountState::unpack_account_data(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
 
     if max_affiliate_count < account_state.data.affiliate_count {
-        msg!("Project affiliate count is larger than new max affiliate count");
+        log_error!("Project affiliate count is larger than new max affiliate count");
         return Err(CandyMachineAffiliatesError::ProjectAffiliateCountLargerThanNewMaxAffiliateCount.into());
     }
 
+    // Projects registered before the affiliate counts were widened are smaller than the current layout,
+    // the admin pays the extra rent
+    if pda_account.data_len() < ProjectAccountState::LENGTH {
+        let system_program = system_program.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        grow_account(pda_account, ProjectAccountState::LENGTH, initializer, system_program)?;
+    }
+
     account_state.data.affiliate_fee_percentage = affiliate_fee_percentage;
     account_state.data.affiliate_target_in_sol = affiliate_target_in_sol;
     account_state.data.max_affiliate_count = max_affiliate_count;
     account_state.data.title = title;
     account_state.data.updated_at = clock.unix_timestamp;
 
-    msg!("Serializing account");
+    log_debug!("Serializing account");
     account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
-    msg!("State account serialized");
+    log_debug!("State account serialized");
+
+    log_info!("Project updated.");
+
+    CandyMachineAffiliatesEvent::ProjectUpdatedV2(ProjectUpdatedV2 {
+        project: *pda_account.key,
+        affiliate_fee_percentage: account_state.data.affiliate_fee_percentage,
+        affiliate_target_in_sol: account_state.data.affiliate_target_in_sol,
+        max_affiliate_count: account_state.data.max_affiliate_count,
+        title: account_state.data.title,
+        updated_at: account_state.data.updated_at,
+    }).emit();
 
     Ok(())
 }
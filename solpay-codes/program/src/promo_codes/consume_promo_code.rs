@@ -0,0 +1,236 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    system_instruction,
+    borsh1::try_from_slice_unchecked,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{rent::Rent, Sysvar},
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use borsh::BorshSerialize;
+use solana_program::clock::Clock;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, PromoCodeConsumed};
+use crate::projects::state::ProjectAccountState;
+use crate::promo_codes::state::{PROMO_CODE_USAGE_ACCOUNT_DATA_VERSION, PromoCodeAccountState, PromoCodeUsageAccountState};
+use crate::referral_codes::state::normalize_referral_code;
+use crate::utils::validate_client_pda;
+use crate::views::{return_view, PromoCodeDiscount};
+
+#[allow(unused_variables)]
+pub fn consume_promo_code(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    code: String,
+) -> ProgramResult {
+    log_debug!("Consuming promo code...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let buyer = next_account_info(account_info_iter)?;
+    // The project owner co-signs each use, so a code is only consumed by the mint flow of the project
+    let project_owner = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let promo_code_pda_account = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock = Clock::get()?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !buyer.is_signer || !project_owner.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if project_owner.key.ne(&project_owner_pubkey) {
+        log_error!("Action not allowed");
+        return Err(CandyMachineAffiliatesError::ActionNotAllowed.into());
+    }
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    if promo_code_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let code = match normalize_referral_code(&code) {
+        Ok(code) => code,
+        Err(_) => {
+            log_error!("Invalid promo code");
+            return Err(CandyMachineAffiliatesError::InvalidPromoCode.into());
+        }
+    };
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        promo_code_pda_account,
+        &[
+            PromoCodeAccountState::DISCRIMINATOR.as_ref(),
+            project_pda_account.key.as_ref(),
+            code.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for promo code PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        pda_account,
+        &[
+            PromoCodeUsageAccountState::DISCRIMINATOR.as_ref(),
+            promo_code_pda_account.key.as_ref(),
+            buyer.key.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for promo code usage PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    log_debug!("Unpacking project account state");
+    let project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !project_account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    log_debug!("Unpacking promo code account state");
+    let mut promo_code_account_state = try_from_slice_unchecked::<PromoCodeAccountState>(&promo_code_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !promo_code_account_state.is_initialized() {
+        log_error!("Promo code account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    if !promo_code_account_state.is_active(clock.unix_timestamp) {
+        log_error!("Promo code not active");
+        return Err(CandyMachineAffiliatesError::PromoCodeNotActive.into());
+    }
+
+    if promo_code_account_state.data.max_uses != 0
+        && promo_code_account_state.data.use_count >= promo_code_account_state.data.max_uses {
+        log_error!("Promo code max uses reached");
+        return Err(CandyMachineAffiliatesError::PromoCodeMaxUsesReached.into());
+    }
+
+    if pda_account.owner != program_id {
+        // Calculate rent required
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(PromoCodeUsageAccountState::LENGTH);
+
+        // Create the usage account of the buyer
+        invoke_signed(
+            &system_instruction::create_account(
+                buyer.key,
+                pda_account.key,
+                rent_lamports,
+                PromoCodeUsageAccountState::LENGTH.try_into().unwrap(),
+                program_id,
+            ),
+            &[buyer.clone(), pda_account.clone(), system_program.clone()],
+            &[
+                &[
+                    PromoCodeUsageAccountState::DISCRIMINATOR.as_ref(),
+                    promo_code_pda_account.key.as_ref(),
+                    buyer.key.as_ref(),
+                    &[bump_seed]
+                ]
+            ],
+        )?;
+
+        log_debug!("PDA created");
+    }
+
+    log_debug!("Unpacking usage account state");
+    let mut account_state = try_from_slice_unchecked::<PromoCodeUsageAccountState>(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
+
+    if !account_state.is_initialized() {
+        account_state.discriminator = PromoCodeUsageAccountState::DISCRIMINATOR.to_string();
+        account_state.is_initialized = true;
+        account_state.data_version = PROMO_CODE_USAGE_ACCOUNT_DATA_VERSION;
+        account_state.data.promo_code_account = *promo_code_pda_account.key;
+        account_state.data.buyer_pubkey = *buyer.key;
+        account_state.data.use_count = 0;
+    }
+
+    if promo_code_account_state.data.max_uses_per_wallet != 0
+        && account_state.data.use_count >= promo_code_account_state.data.max_uses_per_wallet {
+        log_error!("Promo code max uses per wallet reached");
+        return Err(CandyMachineAffiliatesError::PromoCodeWalletLimitReached.into());
+    }
+
+    account_state.data.use_count = account_state.data.use_count
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    account_state.data.last_used_at = clock.unix_timestamp;
+
+    promo_code_account_state.data.use_count = promo_code_account_state.data.use_count
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    log_debug!("Serializing accounts");
+    account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    promo_code_account_state.serialize(&mut &mut promo_code_pda_account.data.borrow_mut()[..])?;
+    log_debug!("State accounts serialized");
+
+    log_info!("Promo code consumed: {}.", promo_code_account_state.data.code);
+
+    CandyMachineAffiliatesEvent::PromoCodeConsumed(PromoCodeConsumed {
+        promo_code_account: *promo_code_pda_account.key,
+        code: promo_code_account_state.data.code,
+        project: *project_pda_account.key,
+        affiliate_account: promo_code_account_state.data.affiliate_account,
+        buyer_pubkey: *buyer.key,
+        discount_basis_points: promo_code_account_state.data.discount_basis_points,
+        use_count: promo_code_account_state.data.use_count,
+        wallet_use_count: account_state.data.use_count,
+        used_at: clock.unix_timestamp,
+    }).emit();
+
+    return_view(&PromoCodeDiscount {
+        discount_basis_points: promo_code_account_state.data.discount_basis_points,
+        affiliate_account: promo_code_account_state.data.affiliate_account,
+        use_count: promo_code_account_state.data.use_count,
+        wallet_use_count: account_state.data.use_count,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    system_instruction,
+    borsh1::try_from_slice_unchecked,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{rent::Rent, Sysvar},
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use borsh::BorshSerialize;
+use solana_program::clock::Clock;
+use crate::affiliates::state::AffiliateAccountState;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, PromoCodeCreated};
+use crate::projects::state::ProjectAccountState;
+use crate::promo_codes::state::{MAX_DISCOUNT_BASIS_POINTS, PROMO_CODE_ACCOUNT_DATA_VERSION, PromoCodeAccountState};
+use crate::referral_codes::state::normalize_referral_code;
+use crate::utils::validate_client_pda;
+
+#[allow(unused_variables)]
+#[allow(clippy::too_many_arguments)]
+pub fn create_promo_code(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    candy_machine_id: Pubkey,
+    code: String,
+    affiliate_pubkey: Option<Pubkey>,
+    discount_basis_points: u16,
+    max_uses: u32,
+    max_uses_per_wallet: u16,
+    starts_at: i64,
+    ends_at: i64,
+) -> ProgramResult {
+    log_debug!("Creating promo code...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    // Required when the code is attached to an affiliate
+    let affiliate_pda_account = next_account_info(account_info_iter).ok();
+    let clock = Clock::get()?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if project_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    // Only the project owner derives the project PDA from the initializer key
+    let (client_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            initializer.key.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let code = match normalize_referral_code(&code) {
+        Ok(code) => code,
+        Err(_) => {
+            log_error!("Invalid promo code");
+            return Err(CandyMachineAffiliatesError::InvalidPromoCode.into());
+        }
+    };
+
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        pda_account,
+        &[
+            PromoCodeAccountState::DISCRIMINATOR.as_ref(),
+            project_pda_account.key.as_ref(),
+            code.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for promo code PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    if let Some(affiliate_pubkey) = affiliate_pubkey {
+        let affiliate_pda_account = affiliate_pda_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        if affiliate_pda_account.owner != program_id {
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        let (client_pda_is_valid, _) = validate_client_pda(
+            affiliate_pda_account,
+            &[
+                AffiliateAccountState::DISCRIMINATOR.as_ref(),
+                affiliate_pubkey.as_ref(),
+                initializer.key.as_ref(),
+                candy_machine_id.as_ref(),
+            ],
+            program_id
+        );
+
+        if !client_pda_is_valid {
+            log_error!("Invalid seeds for affiliate PDA");
+            return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+        }
+
+        let affiliate_account_state = AffiliateAccountState::unpack_account_data(&affiliate_pda_account.data.borrow())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !affiliate_account_state.is_initialized() {
+            log_error!("Affiliate account not initialized");
+            return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+        }
+    }
+
+    log_debug!("Unpacking project account state");
+    let project_account_state = ProjectAccountState::unpack_account_data(&project_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !project_account_state.is_initialized() {
+        log_error!("Project account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    if pda_account.owner == program_id {
+        log_error!("Promo code already exists");
+        return Err(CandyMachineAffiliatesError::PromoCodeAlreadyExists.into());
+    }
+
+    // Validate data
+
+    if discount_basis_points == 0 || discount_basis_points > MAX_DISCOUNT_BASIS_POINTS {
+        log_error!("Invalid promo code discount");
+        return Err(CandyMachineAffiliatesError::InvalidPromoCodeDiscount.into());
+    }
+
+    if ends_at != 0 && ends_at <= starts_at {
+        log_error!("Promo code ends before it starts");
+        return Err(CandyMachineAffiliatesError::InvalidPromoCodePeriod.into());
+    }
+
+    // Calculate rent required
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(PromoCodeAccountState::LENGTH);
+
+    // Create the promo code account
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            pda_account.key,
+            rent_lamports,
+            PromoCodeAccountState::LENGTH.try_into().unwrap(),
+            program_id,
+        ),
+        &[initializer.clone(), pda_account.clone(), system_program.clone()],
+        &[
+            &[
+                PromoCodeAccountState::DISCRIMINATOR.as_ref(),
+                project_pda_account.key.as_ref(),
+                code.as_ref(),
+                &[bump_seed]
+            ]
+        ],
+    )?;
+
+    log_debug!("PDA created");
+
+    log_debug!("Unpacking state account");
+    let mut account_state = try_from_slice_unchecked::<PromoCodeAccountState>(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
+
+    account_state.discriminator = PromoCodeAccountState::DISCRIMINATOR.to_string();
+    account_state.is_initialized = true;
+    account_state.data_version = PROMO_CODE_ACCOUNT_DATA_VERSION;
+    account_state.data.code = code;
+    account_state.data.project_owner_pubkey = *initializer.key;
+    account_state.data.candy_machine_id = candy_machine_id;
+    account_state.data.affiliate_account = affiliate_pubkey.and(affiliate_pda_account.map(|account| *account.key));
+    account_state.data.discount_basis_points = discount_basis_points;
+    account_state.data.max_uses = max_uses;
+    account_state.data.max_uses_per_wallet = max_uses_per_wallet;
+    account_state.data.use_count = 0;
+    account_state.data.starts_at = starts_at;
+    account_state.data.ends_at = ends_at;
+    account_state.data.created_at = clock.unix_timestamp;
+
+    log_debug!("Serializing account");
+    account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    log_debug!("State account serialized");
+
+    log_info!("Promo code created: {}.", account_state.data.code);
+
+    CandyMachineAffiliatesEvent::PromoCodeCreated(PromoCodeCreated {
+        promo_code_account: *pda_account.key,
+        code: account_state.data.code,
+        project: *project_pda_account.key,
+        affiliate_account: account_state.data.affiliate_account,
+        discount_basis_points,
+        max_uses,
+        max_uses_per_wallet,
+        starts_at,
+        ends_at,
+        created_at: account_state.data.created_at,
+    }).emit();
+
+    Ok(())
+}
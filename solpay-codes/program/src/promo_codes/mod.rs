@@ -0,0 +1,3 @@
+pub mod state;
+pub mod create_promo_code;
+pub mod consume_promo_code;
@@ -0,0 +1,124 @@
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+use crate::referral_codes::state::MAX_REFERRAL_CODE_LENGTH;
+
+pub const PROMO_CODE_ACCOUNT_DATA_VERSION: u8 = 0;
+pub const PROMO_CODE_USAGE_ACCOUNT_DATA_VERSION: u8 = 0;
+pub const MAX_DISCOUNT_BASIS_POINTS: u16 = 10_000;
+
+// Promo codes follow the referral code format, see `normalize_referral_code`.
+// `max_uses`, `max_uses_per_wallet` and `ends_at` are unlimited when 0.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PromoCodeAccountStateData {
+    pub code: String,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    // Affiliate account credited for the sales made with the code, if any
+    pub affiliate_account: Option<Pubkey>,
+    pub discount_basis_points: u16,
+    pub max_uses: u32,
+    pub max_uses_per_wallet: u16,
+    pub use_count: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PromoCodeAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: PromoCodeAccountStateData,
+}
+
+impl Sealed for PromoCodeAccountState {}
+
+impl IsInitialized for PromoCodeAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl PromoCodeAccountState {
+    pub const DISCRIMINATOR: &'static str = "promo_code";
+    pub const LENGTH: usize = (4 + PromoCodeAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // code
+        + (4 + MAX_REFERRAL_CODE_LENGTH)
+        // project_owner_pubkey
+        + 32
+        // candy_machine_id
+        + 32
+        // affiliate_account
+        + (1 + 32)
+        // discount_basis_points
+        + 2
+        // max_uses
+        + 4
+        // max_uses_per_wallet
+        + 2
+        // use_count
+        + 4
+        // starts_at
+        + 8
+        // ends_at
+        + 8
+        // created_at
+        + 8
+    ;
+
+    pub fn is_active(&self, unix_timestamp: i64) -> bool {
+        unix_timestamp >= self.data.starts_at && (self.data.ends_at == 0 || unix_timestamp < self.data.ends_at)
+    }
+}
+
+// Uses of a promo code by a single buyer wallet
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PromoCodeUsageAccountStateData {
+    pub promo_code_account: Pubkey,
+    pub buyer_pubkey: Pubkey,
+    pub use_count: u16,
+    pub last_used_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PromoCodeUsageAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: PromoCodeUsageAccountStateData,
+}
+
+impl Sealed for PromoCodeUsageAccountState {}
+
+impl IsInitialized for PromoCodeUsageAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl PromoCodeUsageAccountState {
+    pub const DISCRIMINATOR: &'static str = "promo_code_usage";
+    pub const LENGTH: usize = (4 + PromoCodeUsageAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // promo_code_account
+        + 32
+        // buyer_pubkey
+        + 32
+        // use_count
+        + 2
+        // last_used_at
+        + 8
+    ;
+}
@@ -0,0 +1,211 @@
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    entrypoint::ProgramResult,
+    system_instruction,
+    borsh1::try_from_slice_unchecked,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{rent::Rent, Sysvar},
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use borsh::BorshSerialize;
+use solana_program::clock::Clock;
+use crate::affiliates::state::AffiliateAccountState;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, ReferralCodeClaimed};
+use crate::projects::state::ProjectAccountState;
+use crate::referral_codes::state::{normalize_referral_code, REFERRAL_CODE_ACCOUNT_DATA_VERSION, ReferralCodeAccountState};
+use crate::utils::{grow_account, validate_client_pda};
+
+#[allow(unused_variables)]
+pub fn claim_referral_code(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    project_owner_pubkey: Pubkey,
+    candy_machine_id: Pubkey,
+    code: String,
+) -> ProgramResult {
+    log_debug!("Claiming referral code...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let affiliate_pda_account = next_account_info(account_info_iter)?;
+    let project_pda_account = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    // Optional affiliate account the code points to, for taking over the code of a closed affiliate
+    let previous_affiliate_pda_account = next_account_info(account_info_iter).ok();
+    let clock = Clock::get()?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if affiliate_pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let code = match normalize_referral_code(&code) {
+        Ok(code) => code,
+        Err(e) => {
+            log_error!("Invalid referral code");
+            return Err(e.into());
+        }
+    };
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        affiliate_pda_account,
+        &[
+            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+            initializer.key.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for affiliate PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        project_pda_account,
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner_pubkey.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        pda_account,
+        &[
+            ReferralCodeAccountState::DISCRIMINATOR.as_ref(),
+            project_pda_account.key.as_ref(),
+            code.as_ref(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for referral code PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    log_debug!("Unpacking affiliate account state");
+    let mut affiliate_account_state = AffiliateAccountState::unpack_account_data(&affiliate_pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !affiliate_account_state.is_initialized() {
+        log_error!("Affiliate account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    if affiliate_account_state.data.referral_code.is_some() {
+        log_error!("Affiliate already has a referral code");
+        return Err(CandyMachineAffiliatesError::AffiliateReferralCodeAlreadyClaimed.into());
+    }
+
+    if pda_account.owner == program_id {
+        // The code was claimed before, it can only be taken over once its affiliate account is closed
+        let account_state = try_from_slice_unchecked::<ReferralCodeAccountState>(&pda_account.data.borrow())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        let previous_affiliate_is_closed = previous_affiliate_pda_account.is_some_and(|previous| {
+            previous.key.eq(&account_state.data.affiliate_account)
+                && (previous.owner != program_id || previous.data_is_empty())
+        });
+
+        if account_state.is_initialized() && !previous_affiliate_is_closed {
+            log_error!("Referral code already claimed");
+            return Err(CandyMachineAffiliatesError::ReferralCodeAlreadyClaimed.into());
+        }
+    } else {
+        // Calculate rent required
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(ReferralCodeAccountState::LENGTH);
+
+        // Create the referral code account
+        invoke_signed(
+            &system_instruction::create_account(
+                initializer.key,
+                pda_account.key,
+                rent_lamports,
+                ReferralCodeAccountState::LENGTH.try_into().unwrap(),
+                program_id,
+            ),
+            &[initializer.clone(), pda_account.clone(), system_program.clone()],
+            &[
+                &[
+                    ReferralCodeAccountState::DISCRIMINATOR.as_ref(),
+                    project_pda_account.key.as_ref(),
+                    code.as_ref(),
+                    &[bump_seed]
+                ]
+            ],
+        )?;
+
+        log_debug!("PDA created");
+    }
+
+    log_debug!("Unpacking state account");
+    let mut account_state = try_from_slice_unchecked::<ReferralCodeAccountState>(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
+
+    account_state.discriminator = ReferralCodeAccountState::DISCRIMINATOR.to_string();
+    account_state.is_initialized = true;
+    account_state.data_version = REFERRAL_CODE_ACCOUNT_DATA_VERSION;
+    account_state.data.code = code;
+    account_state.data.affiliate_account = *affiliate_pda_account.key;
+    account_state.data.affiliate_pubkey = *initializer.key;
+    account_state.data.project_owner_pubkey = project_owner_pubkey;
+    account_state.data.candy_machine_id = candy_machine_id;
+    account_state.data.created_at = clock.unix_timestamp;
+
+    log_debug!("Serializing account");
+    account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    log_debug!("State account serialized");
+
+    // Affiliates registered before the referral code was recorded are smaller than the current layout,
+    // the affiliate pays the extra rent
+    grow_account(affiliate_pda_account, AffiliateAccountState::LENGTH, initializer, system_program)?;
+
+    affiliate_account_state.data.referral_code = Some(account_state.data.code.clone());
+
+    log_debug!("Serializing affiliate account");
+    affiliate_account_state.serialize(&mut &mut affiliate_pda_account.data.borrow_mut()[..])?;
+    log_debug!("Affiliate account serialized");
+
+    log_info!("Referral code claimed: {}.", account_state.data.code);
+
+    CandyMachineAffiliatesEvent::ReferralCodeClaimed(ReferralCodeClaimed {
+        referral_code_account: *pda_account.key,
+        code: account_state.data.code,
+        affiliate_account: *affiliate_pda_account.key,
+        affiliate_pubkey: *initializer.key,
+        project: *project_pda_account.key,
+        created_at: account_state.data.created_at,
+    }).emit();
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+use crate::error::CandyMachineAffiliatesError;
+
+pub const REFERRAL_CODE_ACCOUNT_DATA_VERSION: u8 = 0;
+pub const MIN_REFERRAL_CODE_LENGTH: usize = 3;
+pub const MAX_REFERRAL_CODE_LENGTH: usize = 16;
+
+// Trims and lowercases a referral code, which may then only hold `a-z`, `0-9`, `-` and `_`.
+// Codes are PDA seeds, so clients must normalize them the same way before deriving addresses.
+pub fn normalize_referral_code(code: &str) -> Result<String, CandyMachineAffiliatesError> {
+    let code = code.trim().to_ascii_lowercase();
+
+    if code.len() < MIN_REFERRAL_CODE_LENGTH || code.len() > MAX_REFERRAL_CODE_LENGTH {
+        return Err(CandyMachineAffiliatesError::InvalidReferralCode);
+    }
+
+    if !code.bytes().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-' || c == b'_') {
+        return Err(CandyMachineAffiliatesError::InvalidReferralCode);
+    }
+
+    Ok(code)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ReferralCodeAccountStateData {
+    pub code: String,
+    pub affiliate_account: Pubkey,
+    pub affiliate_pubkey: Pubkey,
+    pub project_owner_pubkey: Pubkey,
+    pub candy_machine_id: Pubkey,
+    pub created_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ReferralCodeAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: ReferralCodeAccountStateData,
+}
+
+impl Sealed for ReferralCodeAccountState {}
+
+impl IsInitialized for ReferralCodeAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl ReferralCodeAccountState {
+    pub const DISCRIMINATOR: &'static str = "referral_code";
+    pub const LENGTH: usize = (4 + ReferralCodeAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // code
+        + (4 + MAX_REFERRAL_CODE_LENGTH)
+        // affiliate_account
+        + 32
+        // affiliate_pubkey
+        + 32
+        // project_owner_pubkey
+        + 32
+        // candy_machine_id
+        + 32
+        // created_at
+        + 8
+    ;
+}
@@ -0,0 +1,135 @@
+use std::str::FromStr;
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    borsh1::try_from_slice_unchecked,
+    entrypoint::ProgramResult,
+    system_instruction,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+    program_pack::IsInitialized,
+    system_program::ID as SYSTEM_PROGRAM_ID
+};
+use borsh::BorshSerialize;
+use crate::ADMIN_PUBKEY;
+use crate::error::CandyMachineAffiliatesError;
+use crate::events::{CandyMachineAffiliatesEvent, ProtocolStatsInitialized};
+use crate::stats::state::{PROTOCOL_STATS_ACCOUNT_DATA_VERSION, ProtocolStatsAccountState};
+use crate::utils::validate_client_pda;
+
+#[allow(unused_variables)]
+pub fn initialize_protocol_stats(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    live_project_count: u32,
+    live_affiliate_count: u32,
+    registration_fees_collected_lamports: u64,
+    total_rewards_redeemed_lamports: u64,
+    total_referred_volume_lamports: u64,
+) -> ProgramResult {
+    log_debug!("Creating protocol stats account...");
+
+    // Get Account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Get accounts
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let clock = Clock::get()?;
+
+    // Validate accounts
+
+    if system_program.key.ne(&SYSTEM_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !initializer.is_signer {
+        log_error!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let admin_pubkey = Pubkey::from_str(ADMIN_PUBKEY).expect("Pubkey conversion failed");
+
+    if initializer.key.ne(&admin_pubkey) {
+        log_error!("Action not allowed");
+        return Err(CandyMachineAffiliatesError::ActionNotAllowed.into());
+    }
+
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        pda_account,
+        &[ProtocolStatsAccountState::DISCRIMINATOR.as_ref()],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    // Calculate rent required
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(ProtocolStatsAccountState::LENGTH);
+
+    // Create the protocol stats account
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            pda_account.key,
+            rent_lamports,
+            ProtocolStatsAccountState::LENGTH.try_into().unwrap(),
+            program_id,
+        ),
+        &[initializer.clone(), pda_account.clone(), system_program.clone()],
+        &[
+            &[
+                ProtocolStatsAccountState::DISCRIMINATOR.as_ref(),
+                &[bump_seed]
+            ]
+        ],
+    )?;
+
+    log_debug!("PDA created");
+
+    log_debug!("Unpacking state account");
+    let mut account_state = try_from_slice_unchecked::<ProtocolStatsAccountState>(&pda_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    log_debug!("Borrowed account data");
+
+    log_debug!("Checking if protocol stats account is already initialized");
+    if account_state.is_initialized() {
+        log_error!("Account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Totals start from the values counted off-chain when the program already holds accounts
+    account_state.discriminator = ProtocolStatsAccountState::DISCRIMINATOR.to_string();
+    account_state.is_initialized = true;
+    account_state.data_version = PROTOCOL_STATS_ACCOUNT_DATA_VERSION;
+    account_state.data.live_project_count = live_project_count;
+    account_state.data.live_affiliate_count = live_affiliate_count;
+    account_state.data.registration_fees_collected_lamports = registration_fees_collected_lamports;
+    account_state.data.total_rewards_redeemed_lamports = total_rewards_redeemed_lamports;
+    account_state.data.total_referred_volume_lamports = total_referred_volume_lamports;
+    account_state.data.created_at = clock.unix_timestamp;
+    account_state.data.updated_at = clock.unix_timestamp;
+
+    log_debug!("Serializing account");
+    account_state.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    log_debug!("State account serialized");
+
+    log_info!("Protocol stats initialized.");
+
+    CandyMachineAffiliatesEvent::ProtocolStatsInitialized(ProtocolStatsInitialized {
+        protocol_stats_account: *pda_account.key,
+        live_project_count,
+        live_affiliate_count,
+        registration_fees_collected_lamports,
+        total_rewards_redeemed_lamports,
+        total_referred_volume_lamports,
+        created_at: account_state.data.created_at,
+    }).emit();
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program_pack::{IsInitialized, Sealed};
+
+pub const PROTOCOL_STATS_ACCOUNT_DATA_VERSION: u8 = 0;
+
+// Protocol wide totals kept by the register, close, redeem and mint processors.
+// The single account is derived from the discriminator alone.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProtocolStatsAccountStateData {
+    pub live_project_count: u32,
+    pub live_affiliate_count: u32,
+    pub registration_fees_collected_lamports: u64,
+    pub total_rewards_redeemed_lamports: u64,
+    pub total_referred_volume_lamports: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProtocolStatsAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub data_version: u8,
+    pub data: ProtocolStatsAccountStateData,
+}
+
+impl Sealed for ProtocolStatsAccountState {}
+
+impl IsInitialized for ProtocolStatsAccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl ProtocolStatsAccountState {
+    pub const DISCRIMINATOR: &'static str = "protocol_stats";
+    pub const LENGTH: usize = (4 + ProtocolStatsAccountState::DISCRIMINATOR.len())
+        // is_initialized
+        + 1
+        // data_version
+        + 1
+        // live_project_count
+        + 4
+        // live_affiliate_count
+        + 4
+        // registration_fees_collected_lamports
+        + 8
+        // total_rewards_redeemed_lamports
+        + 8
+        // total_referred_volume_lamports
+        + 8
+        // created_at
+        + 8
+        // updated_at
+        + 8
+    ;
+}
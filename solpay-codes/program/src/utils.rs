@@ -1,12 +1,611 @@
+use borsh::BorshSerialize;
 use solana_program::account_info::AccountInfo;
+use solana_program::borsh1::try_from_slice_unchecked;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack};
 use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program::sysvar::{rent::Rent, Sysvar};
+use crate::affiliate_index::state::{
+    affiliate_index_page,
+    AFFILIATE_INDEX_PAGE_ACCOUNT_DATA_VERSION,
+    AFFILIATE_INDEX_PAGE_SIZE,
+    AffiliateIndexPageAccountState,
+};
+use crate::affiliate_profiles::state::{
+    AFFILIATE_PROFILE_ACCOUNT_DATA_VERSION,
+    AffiliateProfileAccountState,
+    AffiliateProfileAccountStateData,
+};
+use crate::candy_machine_projects::state::{
+    CANDY_MACHINE_PROJECT_ACCOUNT_DATA_VERSION,
+    CandyMachineProjectAccountState,
+    CandyMachineProjectAccountStateData,
+};
+use crate::error::CandyMachineAffiliatesError;
+use crate::projects::state::ProjectAccountState;
+use crate::stats::state::ProtocolStatsAccountState;
 
 // Derive PDA and check that it matches client
 pub fn validate_client_pda(client_pda: &AccountInfo, seeds: &[&[u8]], program_id: &Pubkey) -> (bool, u8) {
     let (pda, bump_seed) = Pubkey::find_program_address(
-        &seeds,
+        seeds,
         program_id,
     );
 
     (pda == *client_pda.key, bump_seed)
 }
+
+// Unpack an SPL token account, owner and mint are left to the caller to check
+pub fn unpack_token_account(token_account: &AccountInfo) -> Result<spl_token::state::Account, ProgramError> {
+    if token_account.owner != &spl_token::id() {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    spl_token::state::Account::unpack(&token_account.data.borrow())
+}
+
+// Whether `gating_token_account`, a token account of `wallet`, holds the gating balance of the project.
+// Always true for projects without gating.
+pub fn meets_project_gating(
+    project_state: &ProjectAccountState,
+    gating_token_account: Option<&AccountInfo>,
+    wallet: &Pubkey,
+) -> Result<bool, ProgramError> {
+    let gating_mint = match project_state.data.gating_mint {
+        Some(gating_mint) => gating_mint,
+        None => return Ok(true),
+    };
+    let gating_token_account = gating_token_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let token_account = unpack_token_account(gating_token_account)?;
+
+    if token_account.owner.ne(wallet) || token_account.mint.ne(&gating_mint) {
+        log_error!("Invalid gating token account");
+        return Err(CandyMachineAffiliatesError::InvalidGatingTokenAccount.into());
+    }
+
+    Ok(token_account.amount >= project_state.data.gating_min_amount)
+}
+
+// Lamports of an affiliate account above its rent-exempt minimum, the most `RedeemReward` can pay out of it
+pub fn redeemable_affiliate_lamports(affiliate_account: &AccountInfo) -> Result<u64, ProgramError> {
+    let rent = Rent::get()?;
+
+    Ok(affiliate_account.lamports().saturating_sub(rent.minimum_balance(affiliate_account.data_len())))
+}
+
+// Unpack the protocol stats account after checking it is the stats PDA. `None` when the program id
+// is passed instead or until `InitializeProtocolStats` creates it, the caller then skips its stats updates.
+pub fn unpack_protocol_stats(
+    protocol_stats_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Option<ProtocolStatsAccountState>, ProgramError> {
+    if protocol_stats_account.key == program_id {
+        log_debug!("Protocol stats account not passed, stats not updated");
+        return Ok(None);
+    }
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        protocol_stats_account,
+        &[ProtocolStatsAccountState::DISCRIMINATOR.as_ref()],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for protocol stats PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    if protocol_stats_account.owner != program_id {
+        log_debug!("Protocol stats account not created, stats not updated");
+        return Ok(None);
+    }
+
+    let protocol_stats_state = try_from_slice_unchecked::<ProtocolStatsAccountState>(&protocol_stats_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !protocol_stats_state.is_initialized() {
+        log_error!("Protocol stats account not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    Ok(Some(protocol_stats_state))
+}
+
+// Grows an account created with an older, smaller state layout to `length`, `payer` paying the extra rent
+pub fn grow_account<'a>(
+    account: &AccountInfo<'a>,
+    length: usize,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    if account.data_len() >= length {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(length)
+        .saturating_sub(rent.minimum_balance(account.data_len()));
+
+    if rent_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, rent_lamports),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+
+    account.realloc(length, false)
+}
+
+// Appends `affiliate_account` to the affiliate index of the project, creating the page the entry
+// falls on when it is the first one of that page, `payer` paying its rent. A page left over from a
+// closed project registered again under the same PDA is cleared and reused instead.
+// Returns the position of the entry, the caller stores it along with the project state.
+#[allow(clippy::too_many_arguments)]
+pub fn append_to_affiliate_index<'a>(
+    index_page_account: &AccountInfo<'a>,
+    project_account: &AccountInfo<'a>,
+    project_state: &mut ProjectAccountState,
+    affiliate_account: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+) -> Result<u32, ProgramError> {
+    let position = project_state.data.affiliate_index_length;
+    let page = affiliate_index_page(position);
+    let slot = position as usize % AFFILIATE_INDEX_PAGE_SIZE;
+
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        index_page_account,
+        &[
+            AffiliateIndexPageAccountState::DISCRIMINATOR.as_ref(),
+            project_account.key.as_ref(),
+            &page.to_le_bytes(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for affiliate index page PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let reuses_page = slot == 0 && index_page_account.owner == program_id;
+
+    if slot == 0 && !reuses_page {
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(AffiliateIndexPageAccountState::LENGTH);
+
+        // Create the next page of the index
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                index_page_account.key,
+                rent_lamports,
+                AffiliateIndexPageAccountState::LENGTH.try_into().unwrap(),
+                program_id,
+            ),
+            &[payer.clone(), index_page_account.clone(), system_program.clone()],
+            &[
+                &[
+                    AffiliateIndexPageAccountState::DISCRIMINATOR.as_ref(),
+                    project_account.key.as_ref(),
+                    &page.to_le_bytes(),
+                    &[bump_seed]
+                ]
+            ],
+        )?;
+
+        log_debug!("Affiliate index page {} created", page);
+    } else if index_page_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut index_page_state = try_from_slice_unchecked::<AffiliateIndexPageAccountState>(&index_page_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if slot == 0 {
+        index_page_state.discriminator = AffiliateIndexPageAccountState::DISCRIMINATOR.to_string();
+        index_page_state.is_initialized = true;
+        index_page_state.data_version = AFFILIATE_INDEX_PAGE_ACCOUNT_DATA_VERSION;
+        index_page_state.data.project = *project_account.key;
+        index_page_state.data.page = page;
+        index_page_state.data.affiliate_accounts = [Pubkey::default(); AFFILIATE_INDEX_PAGE_SIZE];
+
+        if reuses_page {
+            log_debug!("Affiliate index page {} reused", page);
+        }
+    } else if !index_page_state.is_initialized() {
+        log_error!("Affiliate index page not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    index_page_state.data.affiliate_accounts[slot] = *affiliate_account;
+    index_page_state.serialize(&mut &mut index_page_account.data.borrow_mut()[..])?;
+
+    project_state.data.affiliate_index_length = position
+        .checked_add(1)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    Ok(position)
+}
+
+// Clears the entry of `affiliate_account` at `position` of the affiliate index of the project
+pub fn remove_from_affiliate_index(
+    index_page_account: &AccountInfo,
+    project_account: &AccountInfo,
+    position: u32,
+    affiliate_account: &Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let page = affiliate_index_page(position);
+    let slot = position as usize % AFFILIATE_INDEX_PAGE_SIZE;
+
+    if index_page_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        index_page_account,
+        &[
+            AffiliateIndexPageAccountState::DISCRIMINATOR.as_ref(),
+            project_account.key.as_ref(),
+            &page.to_le_bytes(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for affiliate index page PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let mut index_page_state = try_from_slice_unchecked::<AffiliateIndexPageAccountState>(&index_page_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if index_page_state.data.affiliate_accounts[slot].ne(affiliate_account) {
+        log_error!("Affiliate not found in its affiliate index page");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    index_page_state.data.affiliate_accounts[slot] = Pubkey::default();
+    index_page_state.serialize(&mut &mut index_page_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Closes an affiliate index page of `project_account` into `refund_account`, returning the refunded lamports
+pub fn close_affiliate_index_page(
+    index_page_account: &AccountInfo,
+    project_account: &AccountInfo,
+    refund_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    if index_page_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let index_page_state = try_from_slice_unchecked::<AffiliateIndexPageAccountState>(&index_page_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !index_page_state.is_initialized() {
+        log_error!("Affiliate index page not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    let (client_pda_is_valid, _) = validate_client_pda(
+        index_page_account,
+        &[
+            AffiliateIndexPageAccountState::DISCRIMINATOR.as_ref(),
+            project_account.key.as_ref(),
+            &index_page_state.data.page.to_le_bytes(),
+        ],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for affiliate index page PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    let refunded_lamports = index_page_account.lamports();
+    let refund_lamports = refund_account.lamports()
+        .checked_add(refunded_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    **refund_account.try_borrow_mut_lamports()? = refund_lamports;
+    **index_page_account.try_borrow_mut_lamports()? = 0;
+    *index_page_account.try_borrow_mut_data()? = &mut [];
+
+    log_debug!("Affiliate index page {} closed", index_page_state.data.page);
+
+    Ok(refunded_lamports)
+}
+
+// Checks that `profile_account` is the affiliate profile PDA of `affiliate_pubkey`, returning its bump seed
+fn validate_affiliate_profile_pda(
+    profile_account: &AccountInfo,
+    affiliate_pubkey: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        profile_account,
+        &[AffiliateProfileAccountState::DISCRIMINATOR.as_ref(), affiliate_pubkey.as_ref()],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for affiliate profile PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    Ok(bump_seed)
+}
+
+// Unpacks an affiliate profile PDA, None when the wallet has none yet
+fn unpack_affiliate_profile(
+    profile_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Option<AffiliateProfileAccountState>, ProgramError> {
+    if profile_account.owner != program_id {
+        return Ok(None);
+    }
+
+    let profile_state = try_from_slice_unchecked::<AffiliateProfileAccountState>(&profile_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !profile_state.is_initialized() {
+        log_error!("Affiliate profile not initialized");
+        return Err(CandyMachineAffiliatesError::UninitializedAccount.into());
+    }
+
+    Ok(Some(profile_state))
+}
+
+// Lists `project_account` in the affiliate profile of `affiliate_pubkey`, creating the profile on the
+// first registration of the wallet, `payer` paying its rent and growth.
+// `commission_lamports` earned in the project before it was listed are added to the lifetime total.
+#[allow(clippy::too_many_arguments)]
+pub fn add_to_affiliate_profile<'a>(
+    profile_account: &AccountInfo<'a>,
+    affiliate_pubkey: &Pubkey,
+    project_account: &Pubkey,
+    commission_lamports: u64,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let bump_seed = validate_affiliate_profile_pda(profile_account, affiliate_pubkey, program_id)?;
+
+    let mut profile_state = match unpack_affiliate_profile(profile_account, program_id)? {
+        Some(profile_state) => profile_state,
+        None => {
+            let rent = Rent::get()?;
+            let rent_lamports = rent.minimum_balance(AffiliateProfileAccountState::length(1));
+
+            // Create the profile of the wallet
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    profile_account.key,
+                    rent_lamports,
+                    AffiliateProfileAccountState::length(1).try_into().unwrap(),
+                    program_id,
+                ),
+                &[payer.clone(), profile_account.clone(), system_program.clone()],
+                &[
+                    &[
+                        AffiliateProfileAccountState::DISCRIMINATOR.as_ref(),
+                        affiliate_pubkey.as_ref(),
+                        &[bump_seed]
+                    ]
+                ],
+            )?;
+
+            log_debug!("Affiliate profile created");
+
+            AffiliateProfileAccountState {
+                discriminator: AffiliateProfileAccountState::DISCRIMINATOR.to_string(),
+                is_initialized: true,
+                data_version: AFFILIATE_PROFILE_ACCOUNT_DATA_VERSION,
+                data: AffiliateProfileAccountStateData {
+                    affiliate_pubkey: *affiliate_pubkey,
+                    total_commission_lamports: 0,
+                    project_accounts: vec![],
+                },
+            }
+        }
+    };
+
+    if profile_state.data.project_accounts.contains(project_account) {
+        log_error!("Project already in the affiliate profile");
+        return Err(CandyMachineAffiliatesError::ProjectAlreadyInAffiliateProfile.into());
+    }
+
+    profile_state.data.project_accounts.push(*project_account);
+    profile_state.data.total_commission_lamports = profile_state.data.total_commission_lamports
+        .checked_add(commission_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    grow_account(
+        profile_account,
+        AffiliateProfileAccountState::length(profile_state.data.project_accounts.len()),
+        payer,
+        system_program,
+    )?;
+    profile_state.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Removes `project_account` from the affiliate profile of `affiliate_pubkey`.
+// Wallets without a profile and projects not listed, joined before profiles existed, are left as is.
+pub fn remove_from_affiliate_profile(
+    profile_account: &AccountInfo,
+    affiliate_pubkey: &Pubkey,
+    project_account: &Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    validate_affiliate_profile_pda(profile_account, affiliate_pubkey, program_id)?;
+
+    let Some(mut profile_state) = unpack_affiliate_profile(profile_account, program_id)? else {
+        return Ok(());
+    };
+
+    let Some(position) = profile_state.data.project_accounts.iter().position(|account| account == project_account) else {
+        return Ok(());
+    };
+
+    profile_state.data.project_accounts.remove(position);
+
+    // The account keeps its size, zero the entry left past the shortened list
+    let mut data = profile_account.data.borrow_mut();
+    let length = AffiliateProfileAccountState::length(profile_state.data.project_accounts.len());
+    data[length..length + 32].fill(0);
+    profile_state.serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+// Adds `commission_lamports` earned in `project_account` to the lifetime total of the affiliate profile
+// of `affiliate_pubkey`, when the profile lists the project
+pub fn credit_affiliate_profile(
+    profile_account: &AccountInfo,
+    affiliate_pubkey: &Pubkey,
+    project_account: &Pubkey,
+    commission_lamports: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    validate_affiliate_profile_pda(profile_account, affiliate_pubkey, program_id)?;
+
+    let Some(mut profile_state) = unpack_affiliate_profile(profile_account, program_id)? else {
+        return Ok(());
+    };
+
+    if !profile_state.data.project_accounts.contains(project_account) {
+        return Ok(());
+    }
+
+    profile_state.data.total_commission_lamports = profile_state.data.total_commission_lamports
+        .checked_add(commission_lamports)
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+    profile_state.serialize(&mut &mut profile_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Checks that `lookup_account` is the candy machine project PDA of `candy_machine_id`, returning its bump seed
+fn validate_candy_machine_project_pda(
+    lookup_account: &AccountInfo,
+    candy_machine_id: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (client_pda_is_valid, bump_seed) = validate_client_pda(
+        lookup_account,
+        &[CandyMachineProjectAccountState::DISCRIMINATOR.as_ref(), candy_machine_id.as_ref()],
+        program_id
+    );
+
+    if !client_pda_is_valid {
+        log_error!("Invalid seeds for candy machine project PDA");
+        return Err(CandyMachineAffiliatesError::InvalidPDA.into());
+    }
+
+    Ok(bump_seed)
+}
+
+// Creates the candy machine project PDA of `candy_machine_id` pointing to `project_account`, `payer`
+// paying its rent. Fails when the candy machine already has a project.
+#[allow(clippy::too_many_arguments)]
+pub fn create_candy_machine_project<'a>(
+    lookup_account: &AccountInfo<'a>,
+    candy_machine_id: &Pubkey,
+    project_account: &Pubkey,
+    project_owner_pubkey: &Pubkey,
+    created_at: i64,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let bump_seed = validate_candy_machine_project_pda(lookup_account, candy_machine_id, program_id)?;
+
+    if lookup_account.owner == program_id {
+        log_error!("Candy machine already registered");
+        return Err(CandyMachineAffiliatesError::CandyMachineAlreadyRegistered.into());
+    }
+
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(CandyMachineProjectAccountState::LENGTH);
+
+    // Create the candy machine project account
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            lookup_account.key,
+            rent_lamports,
+            CandyMachineProjectAccountState::LENGTH.try_into().unwrap(),
+            program_id,
+        ),
+        &[payer.clone(), lookup_account.clone(), system_program.clone()],
+        &[
+            &[
+                CandyMachineProjectAccountState::DISCRIMINATOR.as_ref(),
+                candy_machine_id.as_ref(),
+                &[bump_seed]
+            ]
+        ],
+    )?;
+
+    log_debug!("Candy machine project created");
+
+    CandyMachineProjectAccountState {
+        discriminator: CandyMachineProjectAccountState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        data_version: CANDY_MACHINE_PROJECT_ACCOUNT_DATA_VERSION,
+        data: CandyMachineProjectAccountStateData {
+            candy_machine_id: *candy_machine_id,
+            project_account: *project_account,
+            project_owner_pubkey: *project_owner_pubkey,
+            created_at,
+        },
+    }.serialize(&mut &mut lookup_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Closes the candy machine project PDA of `candy_machine_id` into `refund_account` when it points to
+// `project_account`. Projects registered before the lookup existed have none and are left as is.
+pub fn close_candy_machine_project(
+    lookup_account: &AccountInfo,
+    candy_machine_id: &Pubkey,
+    project_account: &Pubkey,
+    refund_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    validate_candy_machine_project_pda(lookup_account, candy_machine_id, program_id)?;
+
+    if lookup_account.owner != program_id {
+        return Ok(());
+    }
+
+    let lookup_state = try_from_slice_unchecked::<CandyMachineProjectAccountState>(&lookup_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if lookup_state.data.project_account.ne(project_account) {
+        return Ok(());
+    }
+
+    let refund_lamports = refund_account.lamports()
+        .checked_add(lookup_account.lamports())
+        .ok_or(CandyMachineAffiliatesError::AmountOverflow)?;
+
+    **refund_account.try_borrow_mut_lamports()? = refund_lamports;
+    **lookup_account.try_borrow_mut_lamports()? = 0;
+    *lookup_account.try_borrow_mut_data()? = &mut [];
+
+    log_debug!("Candy machine project closed");
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+// Borsh encoded results of the view instructions, returned with `set_return_data`.
+// Simulate the instruction, or read `get_return_data` after a CPI, and decode the
+// returned bytes with `try_from_slice`.
+use borsh::{
+    BorshDeserialize,
+    BorshSerialize
+};
+use solana_program::program::set_return_data;
+use solana_program::pubkey::Pubkey;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RedeemableAmount {
+    pub affiliate_balance_lamports: u64,
+    pub reward_lamports: u64,
+    // `reward_lamports` when `RedeemReward` would succeed right now, 0 otherwise
+    pub redeemable_lamports: u64,
+    pub total_redeemed_amount_in_sol: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProjectCapacity {
+    pub affiliate_count: u32,
+    pub max_affiliate_count: u32,
+    pub remaining_affiliate_slots: u32,
+    pub is_full: bool,
+    // Fee the affiliate wallet would pay, SPAF discount included when its SPAF token account is passed
+    pub registration_fee: u64,
+    // Whether the affiliate wallet holds the gating balance, false for gated projects when no wallet is passed
+    pub meets_gating: bool,
+}
+
+// Returned by `ConsumePromoCode`, for the mint flow to apply the discount
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct PromoCodeDiscount {
+    pub discount_basis_points: u16,
+    pub affiliate_account: Option<Pubkey>,
+    pub use_count: u32,
+    pub wallet_use_count: u16,
+}
+
+pub(crate) fn return_view<T: BorshSerialize>(view: &T) {
+    set_return_data(&borsh::to_vec(view).expect("View serialization failed"));
+}
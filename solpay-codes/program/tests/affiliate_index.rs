@@ -0,0 +1,385 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    instruction,
+    AffiliateAccountState,
+    AffiliateIndexPageAccountState,
+    CandyMachineAffiliatesError,
+    AFFILIATE_INDEX_PAGE_SIZE,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_sdk::signature::Signer;
+use common::*;
+
+fn index_affiliate_instruction(payer: &Pubkey, affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey, page: u32) -> Instruction {
+    instruction::index_affiliate(
+        &candy_machine_affiliates::id(),
+        payer,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &affiliate_index_page_pda(project_owner, candy_machine_id, page),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+#[tokio::test]
+async fn register_affiliate_appends_to_affiliate_index() {
+    let mut program_test = program_test();
+    let first_affiliate = add_wallet(&mut program_test, 1);
+    let second_affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    for affiliate in [&first_affiliate, &second_affiliate] {
+        process(
+            &mut context,
+            &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+            &[affiliate],
+        ).await.unwrap();
+    }
+
+    let page_address = affiliate_index_page_pda(&project_owner, &candy_machine_id, 0);
+    let page = get_affiliate_index_page_state(&mut context, &page_address).await;
+    let first_address = affiliate_pda(&first_affiliate.pubkey(), &project_owner, &candy_machine_id);
+    let second_address = affiliate_pda(&second_affiliate.pubkey(), &project_owner, &candy_machine_id);
+
+    assert!(page.is_initialized);
+    assert_eq!(page.discriminator, AffiliateIndexPageAccountState::DISCRIMINATOR);
+    assert_eq!(page.data.project, project_address);
+    assert_eq!(page.data.page, 0);
+    assert_eq!(page.live_affiliate_accounts().collect::<Vec<_>>(), vec![&first_address, &second_address]);
+    assert_eq!(
+        get_balance(&mut context, &page_address).await,
+        Rent::default().minimum_balance(AffiliateIndexPageAccountState::LENGTH),
+    );
+
+    assert_eq!(get_affiliate_state(&mut context, &first_address).await.data.affiliate_index_position, Some(0));
+    assert_eq!(get_affiliate_state(&mut context, &second_address).await.data.affiliate_index_position, Some(1));
+    assert_eq!(get_project_state(&mut context, &project_address).await.data.affiliate_index_length, 2);
+}
+
+#[tokio::test]
+async fn register_affiliate_starts_next_page_when_page_is_full() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let mut state = project_state(&project_owner, &candy_machine_id, 1, 100, 0);
+    state.data.affiliate_index_length = AFFILIATE_INDEX_PAGE_SIZE as u32;
+    let project_address = add_project_account(&mut program_test, &state);
+    let mut context = program_test.start_with_context().await;
+
+    let page_address = affiliate_index_page_pda(&project_owner, &candy_machine_id, 1);
+    let mut instruction = register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[6].pubkey = page_address;
+
+    process(&mut context, &[instruction], &[&affiliate]).await.unwrap();
+
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    let page = get_affiliate_index_page_state(&mut context, &page_address).await;
+
+    assert_eq!(page.data.page, 1);
+    assert_eq!(page.data.affiliate_accounts[0], affiliate_address);
+    assert_eq!(
+        get_affiliate_state(&mut context, &affiliate_address).await.data.affiliate_index_position,
+        Some(AFFILIATE_INDEX_PAGE_SIZE as u32),
+    );
+    assert_eq!(
+        get_project_state(&mut context, &project_address).await.data.affiliate_index_length,
+        AFFILIATE_INDEX_PAGE_SIZE as u32 + 1,
+    );
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_wrong_index_page() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[6].pubkey = affiliate_index_page_pda(&project_owner, &candy_machine_id, 1);
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn close_affiliate_clears_its_index_entry() {
+    let mut program_test = program_test();
+    let first_affiliate = add_wallet(&mut program_test, 1);
+    let second_affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    for affiliate in [&first_affiliate, &second_affiliate] {
+        process(
+            &mut context,
+            &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+            &[affiliate],
+        ).await.unwrap();
+    }
+
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &first_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    let page = get_affiliate_index_page_state(&mut context, &affiliate_index_page_pda(&project_owner, &candy_machine_id, 0)).await;
+    let second_address = affiliate_pda(&second_affiliate.pubkey(), &project_owner, &candy_machine_id);
+
+    assert_eq!(page.data.affiliate_accounts[0], Pubkey::default());
+    assert_eq!(page.live_affiliate_accounts().collect::<Vec<_>>(), vec![&second_address]);
+    // Positions are never reused
+    assert_eq!(get_project_state(&mut context, &project_address).await.data.affiliate_index_length, 2);
+}
+
+#[tokio::test]
+async fn close_indexed_affiliate_requires_its_index_page() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let mut instruction = close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[5].pubkey = candy_machine_affiliates::id();
+    instruction.accounts[5].is_writable = false;
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_instruction_error(result, InstructionError::IllegalOwner);
+}
+
+#[tokio::test]
+async fn close_affiliate_registered_before_index_skips_index_page() {
+    let mut program_test = program_test();
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate, &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate, &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LEGACY_LENGTH,
+    );
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[instruction::close_affiliate_account(
+            &candy_machine_affiliates::id(),
+            &admin.pubkey(),
+            &affiliate_pda(&affiliate, &project_owner, &candy_machine_id),
+            &project_pda(&project_owner, &candy_machine_id),
+            &treasury(),
+            &protocol_stats_pda(),
+            None,
+            &affiliate_profile_pda(&affiliate),
+            affiliate,
+            project_owner,
+            candy_machine_id,
+        )],
+        &[&admin],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(affiliate_pda(&affiliate, &project_owner, &candy_machine_id)).await.unwrap();
+    assert!(account.is_none());
+}
+
+#[tokio::test]
+async fn index_affiliate_appends_affiliate_registered_before_index() {
+    let mut program_test = program_test();
+    let payer = add_wallet(&mut program_test, 1);
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let affiliate_address = affiliate_pda(&affiliate, &project_owner, &candy_machine_id);
+    add_program_account(
+        &mut program_test,
+        affiliate_address,
+        borsh::to_vec(&affiliate_state(&affiliate, &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LEGACY_LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[index_affiliate_instruction(&payer.pubkey(), &affiliate, &project_owner, &candy_machine_id, 0)],
+        &[&payer],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(affiliate_address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), AffiliateAccountState::LENGTH);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(AffiliateAccountState::LENGTH));
+    assert_eq!(get_affiliate_state(&mut context, &affiliate_address).await.data.affiliate_index_position, Some(0));
+
+    let page = get_affiliate_index_page_state(&mut context, &affiliate_index_page_pda(&project_owner, &candy_machine_id, 0)).await;
+    assert_eq!(page.live_affiliate_accounts().collect::<Vec<_>>(), vec![&affiliate_address]);
+    assert_eq!(get_project_state(&mut context, &project_address).await.data.affiliate_index_length, 1);
+}
+
+#[tokio::test]
+async fn index_affiliate_rejects_indexed_affiliate() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[index_affiliate_instruction(&affiliate.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id, 0)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::AffiliateAlreadyIndexed);
+}
+
+fn close_project_instruction(project_owner: &Pubkey, candy_machine_id: &Pubkey, affiliate_index_pages: &[Pubkey]) -> Instruction {
+    instruction::close_project_with_affiliate_index(
+        &candy_machine_affiliates::id(),
+        &admin().pubkey(),
+        &project_pda(project_owner, candy_machine_id),
+        &protocol_stats_pda(),
+        &candy_machine_project_pda(candy_machine_id),
+        affiliate_index_pages,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+#[tokio::test]
+async fn close_project_closes_its_affiliate_index_pages() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let project_address = project_pda(&project_owner, &candy_machine_id);
+    let page_address = affiliate_index_page_pda(&project_owner, &candy_machine_id, 0);
+    let refund_lamports = get_balance(&mut context, &project_address).await + get_balance(&mut context, &page_address).await;
+
+    process(
+        &mut context,
+        &[close_project_instruction(&project_owner, &candy_machine_id, &[page_address])],
+        &[&admin()],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &project_owner).await, refund_lamports);
+    assert!(context.banks_client.get_account(page_address).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn close_project_rejects_index_page_of_another_project() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let other_candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    add_project_account(&mut program_test, &project_state(&project_owner, &other_candy_machine_id, 1, 5, 0));
+    let affiliate = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &other_candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[close_project_instruction(
+            &project_owner,
+            &candy_machine_id,
+            &[affiliate_index_page_pda(&project_owner, &other_candy_machine_id, 0)],
+        )],
+        &[&admin()],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn register_affiliate_reuses_index_page_of_closed_project() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let first_affiliate = add_wallet(&mut program_test, 1);
+    let second_affiliate = add_wallet(&mut program_test, 1);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, "Project")],
+        &[&owner],
+    ).await.unwrap();
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&first_affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&first_affiliate],
+    ).await.unwrap();
+    // The index page is left out and outlives the project
+    process(
+        &mut context,
+        &[close_project_instruction(&owner.pubkey(), &candy_machine_id, &[])],
+        &[&admin()],
+    ).await.unwrap();
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, "Project")],
+        &[&owner],
+    ).await.unwrap();
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&second_affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&second_affiliate],
+    ).await.unwrap();
+
+    let page = get_affiliate_index_page_state(&mut context, &affiliate_index_page_pda(&owner.pubkey(), &candy_machine_id, 0)).await;
+    let second_address = affiliate_pda(&second_affiliate.pubkey(), &owner.pubkey(), &candy_machine_id);
+
+    assert_eq!(page.live_affiliate_accounts().collect::<Vec<_>>(), vec![&second_address]);
+    assert_eq!(get_affiliate_state(&mut context, &second_address).await.data.affiliate_index_position, Some(0));
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::events::AffiliateAddedToProfile;
+use candy_machine_affiliates::{
+    instruction,
+    AffiliateAccountState,
+    AffiliateProfileAccountState,
+    CandyMachineAffiliatesError,
+    CandyMachineAffiliatesEvent,
+};
+use solana_program::instruction::Instruction;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_sdk::signature::Signer;
+use common::*;
+
+fn add_affiliate_to_profile_instruction(payer: &Pubkey, affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::add_affiliate_to_profile(
+        &candy_machine_affiliates::id(),
+        payer,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &affiliate_profile_pda(affiliate),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+#[tokio::test]
+async fn register_affiliate_lists_projects_in_affiliate_profile() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let first_candy_machine_id = Pubkey::new_unique();
+    let second_candy_machine_id = Pubkey::new_unique();
+    let first_project = add_project_account(&mut program_test, &project_state(&project_owner, &first_candy_machine_id, 1, 5, 0));
+    let second_project = add_project_account(&mut program_test, &project_state(&project_owner, &second_candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &first_candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let profile = get_affiliate_profile_state(&mut context, &affiliate.pubkey()).await;
+    assert!(profile.is_initialized);
+    assert_eq!(profile.discriminator, AffiliateProfileAccountState::DISCRIMINATOR);
+    assert_eq!(profile.data.affiliate_pubkey, affiliate.pubkey());
+    assert_eq!(profile.data.total_commission_lamports, 0);
+    assert_eq!(profile.data.project_accounts, vec![first_project]);
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &second_candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let profile = get_affiliate_profile_state(&mut context, &affiliate.pubkey()).await;
+    assert_eq!(profile.data.project_accounts, vec![first_project, second_project]);
+
+    let account = context.banks_client.get_account(affiliate_profile_pda(&affiliate.pubkey())).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), AffiliateProfileAccountState::length(2));
+    assert_eq!(account.lamports, Rent::default().minimum_balance(AffiliateProfileAccountState::length(2)));
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_wrong_affiliate_profile() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[7].pubkey = affiliate_profile_pda(&Pubkey::new_unique());
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn close_affiliate_removes_project_from_affiliate_profile() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let first_candy_machine_id = Pubkey::new_unique();
+    let second_candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &first_candy_machine_id, 1, 5, 0));
+    let second_project = add_project_account(&mut program_test, &project_state(&project_owner, &second_candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    for candy_machine_id in [&first_candy_machine_id, &second_candy_machine_id] {
+        process(
+            &mut context,
+            &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, candy_machine_id)],
+            &[&affiliate],
+        ).await.unwrap();
+    }
+
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &project_owner, &first_candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    let profile = get_affiliate_profile_state(&mut context, &affiliate.pubkey()).await;
+    assert_eq!(profile.data.project_accounts, vec![second_project]);
+
+    // The account keeps its size for later registrations
+    let account = context.banks_client.get_account(affiliate_profile_pda(&affiliate.pubkey())).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), AffiliateProfileAccountState::length(2));
+    assert!(account.data[AffiliateProfileAccountState::length(1)..].iter().all(|byte| *byte == 0));
+}
+
+#[tokio::test]
+async fn add_affiliate_to_profile_lists_affiliate_registered_before_profiles() {
+    let mut program_test = program_test();
+    let payer = add_wallet(&mut program_test, 1);
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let mut state = affiliate_state(&affiliate, &project_owner, &candy_machine_id);
+    state.data.total_commission_lamports = LAMPORTS_PER_SOL / 10;
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate, &project_owner, &candy_machine_id),
+        borsh::to_vec(&state).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let events = process_with_events(
+        &mut context,
+        &[add_affiliate_to_profile_instruction(&payer.pubkey(), &affiliate, &project_owner, &candy_machine_id)],
+        &[&payer],
+    ).await;
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::AffiliateAddedToProfile(AffiliateAddedToProfile {
+        affiliate_profile_account: affiliate_profile_pda(&affiliate),
+        affiliate_pubkey: affiliate,
+        affiliate_account: affiliate_pda(&affiliate, &project_owner, &candy_machine_id),
+        project: project_address,
+        commission_lamports: LAMPORTS_PER_SOL / 10,
+    })]);
+
+    let profile = get_affiliate_profile_state(&mut context, &affiliate).await;
+    assert_eq!(profile.data.affiliate_pubkey, affiliate);
+    assert_eq!(profile.data.total_commission_lamports, LAMPORTS_PER_SOL / 10);
+    assert_eq!(profile.data.project_accounts, vec![project_address]);
+}
+
+#[tokio::test]
+async fn add_affiliate_to_profile_rejects_listed_project() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[add_affiliate_to_profile_instruction(&affiliate.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectAlreadyInAffiliateProfile);
+}
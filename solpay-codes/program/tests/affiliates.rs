@@ -0,0 +1,694 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    AffiliateAccountState,
+    CandyMachineAffiliatesError,
+    ProjectAccountState,
+    AFFILIATE_REGISTRATION_FEE,
+};
+use candy_machine_affiliates::projects::state::PROJECT_ACCOUNT_DATA_VERSION;
+use solana_program::borsh1::try_from_slice_unchecked;
+use solana_program::instruction::InstructionError;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program_test::ProgramTest;
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+use common::*;
+
+// Adds a project and a registered affiliate of it to the test genesis
+fn add_project_with_affiliate(
+    program_test: &mut ProgramTest,
+    affiliate_target_in_sol: u8,
+) -> (Keypair, Pubkey, Pubkey) {
+    let affiliate = add_wallet(program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+
+    add_project_account(program_test, &project_state(&project_owner, &candy_machine_id, affiliate_target_in_sol, 5, 1));
+    add_program_account(
+        program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+
+    (affiliate, project_owner, candy_machine_id)
+}
+
+#[tokio::test]
+async fn register_affiliate_initializes_state_and_pays_fee() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let state = get_affiliate_state(&mut context, &affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id)).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(state.discriminator, AffiliateAccountState::DISCRIMINATOR);
+    assert_eq!(state.data.affiliate_pubkey, affiliate.pubkey());
+    assert_eq!(state.data.project_owner_pubkey, project_owner);
+    assert_eq!(state.data.candy_machine_id, candy_machine_id);
+    assert_eq!(state.data.total_redeemed_amount_in_sol, 0);
+
+    let project_state = get_project_state(&mut context, &project_address).await;
+    assert_eq!(project_state.data.affiliate_count, 1);
+    assert_eq!(project_state.data.peak_affiliate_count, 1);
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports + AFFILIATE_REGISTRATION_FEE);
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_wrong_treasury() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[3].pubkey = Pubkey::new_unique();
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::IncorrectTreasuryAccount);
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_wrong_pda_seeds() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[1].pubkey = affiliate_pda(&Pubkey::new_unique(), &project_owner, &candy_machine_id);
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+
+    let mut instruction = register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[2].pubkey = project_pda(&project_owner, &Pubkey::new_unique());
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn register_affiliate_requires_signature() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[0].is_signer = false;
+
+    let result = process(&mut context, &[instruction], &[]).await;
+
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+}
+
+#[tokio::test]
+async fn register_affiliate_single_seat_boundary() {
+    let mut program_test = program_test();
+    let first_affiliate = add_wallet(&mut program_test, 1);
+    let second_affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 1, 0));
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&first_affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[register_affiliate_instruction(&second_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&second_affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectMaxAffiliateCountReached);
+}
+
+#[tokio::test]
+async fn register_affiliate_max_affiliate_count_boundary() {
+    let mut program_test = program_test();
+    let last_affiliate = add_wallet(&mut program_test, 1);
+    let extra_affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(
+        &mut program_test,
+        &project_state(&project_owner, &candy_machine_id, 1, u32::MAX, u32::MAX - 1),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&last_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&last_affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_project_state(&mut context, &project_address).await.data.affiliate_count, u32::MAX);
+
+    let result = process(
+        &mut context,
+        &[register_affiliate_instruction(&extra_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&extra_affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectMaxAffiliateCountReached);
+}
+
+#[tokio::test]
+async fn register_affiliate_past_former_u8_limit() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(
+        &mut program_test,
+        &project_state(&project_owner, &candy_machine_id, 1, 5000, u8::MAX as u32),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &project_address).await;
+    assert_eq!(state.data.affiliate_count, 256);
+    assert_eq!(state.data.peak_affiliate_count, 256);
+}
+
+#[tokio::test]
+async fn register_affiliate_migrates_data_version_0_projects() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = project_pda(&project_owner, &candy_machine_id);
+    add_program_account(
+        &mut program_test,
+        project_address,
+        project_data_v0_with_shortened_title(&project_owner, &candy_machine_id, u8::MAX, u8::MAX - 1),
+        ProjectAccountState::V0_LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let state = get_project_state(&mut context, &project_address).await;
+    assert_eq!(state.data.max_affiliate_count, u8::MAX as u32);
+    assert_eq!(state.data.affiliate_count, (u8::MAX - 1) as u32);
+    assert_eq!(state.data.title, DEFAULT_TITLE);
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(project_address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ProjectAccountState::LENGTH);
+
+    let state = try_from_slice_unchecked::<ProjectAccountState>(&account.data).unwrap();
+    assert_eq!(state.data_version, PROJECT_ACCOUNT_DATA_VERSION);
+    assert_eq!(state.data.max_affiliate_count, u8::MAX as u32);
+    assert_eq!(state.data.affiliate_count, u8::MAX as u32);
+    assert_eq!(state.data.peak_affiliate_count, u8::MAX as u32);
+    assert_eq!(state.data.title, DEFAULT_TITLE);
+}
+
+#[tokio::test]
+async fn closing_affiliate_frees_a_seat() {
+    let mut program_test = program_test();
+    let first_affiliate = add_wallet(&mut program_test, 1);
+    let second_affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 1, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&first_affiliate],
+    ).await.unwrap();
+
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &first_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    assert_eq!(get_project_state(&mut context, &project_address).await.data.affiliate_count, 0);
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&second_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&second_affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_project_state(&mut context, &project_address).await.data.affiliate_count, 1);
+}
+
+#[tokio::test]
+async fn redeem_reward_pays_affiliate() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    let affiliate_lamports = get_balance(&mut context, &affiliate.pubkey()).await;
+    let rent_lamports = get_balance(&mut context, &affiliate_address).await;
+
+    let payer = context.payer.pubkey();
+
+    process(
+        &mut context,
+        &[system_instruction::transfer(&payer, &affiliate_address, LAMPORTS_PER_SOL)],
+        &[],
+    ).await.unwrap();
+
+    process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &affiliate.pubkey()).await, affiliate_lamports + LAMPORTS_PER_SOL);
+    assert_eq!(get_balance(&mut context, &affiliate_address).await, rent_lamports);
+
+    let state = get_affiliate_state(&mut context, &affiliate_address).await;
+    assert_eq!(state.data.total_redeemed_amount_in_sol, 1);
+    assert_eq!(state.data.redemption_count, 1);
+    assert!(state.data.last_activity_at > 0);
+
+    let project_state = get_project_state(&mut context, &project_pda(&project_owner, &candy_machine_id)).await;
+    assert_eq!(project_state.data.total_redemption_count, 1);
+}
+
+#[tokio::test]
+async fn redeem_reward_resizes_older_accounts_at_the_expense_of_the_affiliate() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = project_pda(&project_owner, &candy_machine_id);
+    // Layout without the referral totals and the affiliate index length
+    let previous_length = ProjectAccountState::LENGTH - (4 + 8 + 8 + 4 + 4) - 4;
+    add_program_account(
+        &mut program_test,
+        project_address,
+        borsh::to_vec(&project_state(&project_owner, &candy_machine_id, 1, 5, 1)).unwrap(),
+        previous_length,
+    );
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LEGACY_LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    let affiliate_lamports = get_balance(&mut context, &affiliate.pubkey()).await;
+    let payer = context.payer.pubkey();
+
+    process(
+        &mut context,
+        &[system_instruction::transfer(&payer, &affiliate_address, 2 * LAMPORTS_PER_SOL)],
+        &[],
+    ).await.unwrap();
+
+    process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(project_address).await.unwrap().unwrap();
+    let affiliate_account = context.banks_client.get_account(affiliate_address).await.unwrap().unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let resize_rent = rent.minimum_balance(ProjectAccountState::LENGTH) - rent.minimum_balance(previous_length)
+        + rent.minimum_balance(AffiliateAccountState::LENGTH) - rent.minimum_balance(AffiliateAccountState::LEGACY_LENGTH);
+
+    assert_eq!(account.data.len(), ProjectAccountState::LENGTH);
+    assert_eq!(account.lamports, rent.minimum_balance(ProjectAccountState::LENGTH));
+    assert_eq!(affiliate_account.data.len(), AffiliateAccountState::LENGTH);
+    assert_eq!(affiliate_account.lamports, rent.minimum_balance(AffiliateAccountState::LENGTH) + LAMPORTS_PER_SOL);
+    assert_eq!(get_balance(&mut context, &affiliate.pubkey()).await, affiliate_lamports + LAMPORTS_PER_SOL - resize_rent);
+    assert_eq!(get_project_state(&mut context, &project_address).await.data.total_redemption_count, 1);
+}
+
+#[tokio::test]
+async fn redeem_reward_below_threshold_fails() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 2);
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    let rent_lamports = get_balance(&mut context, &affiliate_address).await;
+
+    let payer = context.payer.pubkey();
+
+    process(
+        &mut context,
+        &[system_instruction::transfer(&payer, &affiliate_address, 2 * LAMPORTS_PER_SOL - rent_lamports - 1)],
+        &[],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::AffiliateAccountBalanceNotEnough);
+}
+
+#[tokio::test]
+async fn redeem_reward_with_zero_threshold_transfers_nothing() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 0);
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_lamports = get_balance(&mut context, &affiliate.pubkey()).await;
+
+    process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &affiliate.pubkey()).await, affiliate_lamports);
+}
+
+#[tokio::test]
+async fn redeem_reward_rejects_other_signer() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 1);
+    let impostor = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[0].pubkey = impostor.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&impostor]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn redeem_reward_rejects_wrong_project_pda_seeds() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 1);
+    let other_candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &other_candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[2].pubkey = project_pda(&project_owner, &other_candy_machine_id);
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn redeem_reward_rejects_accounts_not_owned_by_program() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = redeem_reward_instruction(&affiliate.pubkey(), &Pubkey::new_unique(), &Pubkey::new_unique());
+    instruction.accounts[1].pubkey = affiliate.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_instruction_error(result, InstructionError::IllegalOwner);
+}
+
+#[tokio::test]
+async fn redeem_reward_rejects_uninitialized_accounts() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let other_candy_machine_id = Pubkey::new_unique();
+
+    // Uninitialized affiliate account of an existing project
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        vec![],
+        AffiliateAccountState::LENGTH,
+    );
+
+    // Initialized affiliate account of an uninitialized project
+    add_program_account(
+        &mut program_test,
+        project_pda(&project_owner, &other_candy_machine_id),
+        vec![],
+        ProjectAccountState::LENGTH,
+    );
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &other_candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &other_candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::UninitializedAccount);
+
+    let result = process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &other_candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::UninitializedAccount);
+}
+
+#[tokio::test]
+async fn redeem_reward_rejects_mismatched_accounts() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 0, 5, 1));
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &Pubkey::new_unique(), &Pubkey::new_unique())).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::RewardRedeemMismatchedAccounts);
+}
+
+#[tokio::test]
+async fn close_affiliate_moves_balance_to_treasury() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 1);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    let affiliate_account_lamports = get_balance(&mut context, &affiliate_address).await;
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    assert!(context.banks_client.get_account(affiliate_address).await.unwrap().is_none());
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports + affiliate_account_lamports);
+    assert_eq!(
+        get_project_state(&mut context, &project_pda(&project_owner, &candy_machine_id)).await.data.affiliate_count,
+        0
+    );
+}
+
+#[tokio::test]
+async fn close_affiliate_migrates_data_version_0_projects() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let project_address = project_pda(&project_owner, &candy_machine_id);
+    add_program_account(
+        &mut program_test,
+        project_address,
+        project_data_v0_with_shortened_title(&project_owner, &candy_machine_id, u8::MAX, 1),
+        ProjectAccountState::V0_LENGTH,
+    );
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+    let admin = admin();
+    program_test.add_account(
+        admin.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &solana_program::system_program::ID),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(project_address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ProjectAccountState::LENGTH);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(ProjectAccountState::LENGTH));
+
+    let state = try_from_slice_unchecked::<ProjectAccountState>(&account.data).unwrap();
+    assert_eq!(state.data_version, PROJECT_ACCOUNT_DATA_VERSION);
+    assert_eq!(state.data.max_affiliate_count, u8::MAX as u32);
+    assert_eq!(state.data.affiliate_count, 0);
+    assert_eq!(state.data.title, DEFAULT_TITLE);
+}
+
+#[tokio::test]
+async fn close_affiliate_rejects_project_without_affiliates() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::AmountOverflow);
+}
+
+#[tokio::test]
+async fn close_affiliate_rejects_non_admin() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[close_affiliate_instruction(&affiliate.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ActionNotAllowed);
+}
+
+#[tokio::test]
+async fn close_affiliate_rejects_wrong_treasury() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 1);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &project_owner, &candy_machine_id);
+    instruction.accounts[3].pubkey = admin.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::IncorrectTreasuryAccount);
+}
+
+#[tokio::test]
+async fn close_affiliate_rejects_wrong_pda_seeds() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id) = add_project_with_affiliate(&mut program_test, 1);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = close_affiliate_instruction(&admin.pubkey(), &Pubkey::new_unique(), &project_owner, &candy_machine_id);
+    instruction.accounts[1].pubkey = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn close_affiliate_rejects_uninitialized_account() {
+    let mut program_test = program_test();
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate, &project_owner, &candy_machine_id),
+        vec![],
+        AffiliateAccountState::LENGTH,
+    );
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &affiliate, &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::UninitializedAccount);
+}
@@ -0,0 +1,228 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::events::CandyMachineProjectLinked;
+use candy_machine_affiliates::{
+    instruction,
+    CandyMachineAffiliatesError,
+    CandyMachineAffiliatesEvent,
+    CandyMachineProjectAccountState,
+    CandyMachineProjectAccountStateData,
+};
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::ProgramTest;
+use solana_sdk::signature::Signer;
+use common::*;
+
+fn close_project_instruction(admin: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::close_project(
+        &candy_machine_affiliates::id(),
+        admin,
+        &project_pda(project_owner, candy_machine_id),
+        &protocol_stats_pda(),
+        &candy_machine_project_pda(candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+fn link_candy_machine_project_instruction(payer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::link_candy_machine_project(
+        &candy_machine_affiliates::id(),
+        payer,
+        &project_pda(project_owner, candy_machine_id),
+        &candy_machine_project_pda(candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Adds a candy machine project account pointing to the project of `project_owner`
+fn add_candy_machine_project_account(program_test: &mut ProgramTest, project_owner: &Pubkey, candy_machine_id: &Pubkey) {
+    add_program_account(
+        program_test,
+        candy_machine_project_pda(candy_machine_id),
+        borsh::to_vec(&CandyMachineProjectAccountState {
+            discriminator: CandyMachineProjectAccountState::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            data_version: 0,
+            data: CandyMachineProjectAccountStateData {
+                candy_machine_id: *candy_machine_id,
+                project_account: project_pda(project_owner, candy_machine_id),
+                project_owner_pubkey: *project_owner,
+                created_at: 0,
+            },
+        }).unwrap(),
+        CandyMachineProjectAccountState::LENGTH,
+    );
+}
+
+#[tokio::test]
+async fn register_project_creates_candy_machine_project() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+
+    let state = get_candy_machine_project_state(&mut context, &candy_machine_id).await;
+    let project = get_project_state(&mut context, &project_pda(&owner.pubkey(), &candy_machine_id)).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(state.discriminator, CandyMachineProjectAccountState::DISCRIMINATOR);
+    assert_eq!(state.data.candy_machine_id, candy_machine_id);
+    assert_eq!(state.data.project_account, project_pda(&owner.pubkey(), &candy_machine_id));
+    assert_eq!(state.data.project_owner_pubkey, owner.pubkey());
+    assert_eq!(state.data.created_at, project.data.created_at);
+}
+
+#[tokio::test]
+async fn register_project_rejects_candy_machine_of_another_project() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    // Registered by a previous authority of the candy machine
+    add_candy_machine_project_account(&mut program_test, &Pubkey::new_unique(), &candy_machine_id);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::CandyMachineAlreadyRegistered);
+}
+
+#[tokio::test]
+async fn register_project_rejects_wrong_candy_machine_project() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE);
+    instruction.accounts[5].pubkey = candy_machine_project_pda(&Pubkey::new_unique());
+
+    let result = process(&mut context, &[instruction], &[&owner]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn close_project_closes_candy_machine_project() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+
+    let lookup_address = candy_machine_project_pda(&candy_machine_id);
+    let owner_lamports = get_balance(&mut context, &owner.pubkey()).await;
+    let refunded_lamports = get_balance(&mut context, &project_pda(&owner.pubkey(), &candy_machine_id)).await
+        + get_balance(&mut context, &lookup_address).await;
+
+    process(
+        &mut context,
+        &[close_project_instruction(&admin.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    assert!(context.banks_client.get_account(lookup_address).await.unwrap().is_none());
+    assert_eq!(get_balance(&mut context, &owner.pubkey()).await, owner_lamports + refunded_lamports);
+
+    // The candy machine can be registered again
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+}
+
+#[tokio::test]
+async fn close_project_keeps_candy_machine_project_of_another_project() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let other_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    add_candy_machine_project_account(&mut program_test, &other_owner, &candy_machine_id);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[close_project_instruction(&admin.pubkey(), &owner, &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    let state = get_candy_machine_project_state(&mut context, &candy_machine_id).await;
+    assert_eq!(state.data.project_account, project_pda(&other_owner, &candy_machine_id));
+}
+
+#[tokio::test]
+async fn link_candy_machine_project_links_project_registered_before_lookups() {
+    let mut program_test = program_test();
+    let payer = add_wallet(&mut program_test, 1);
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner);
+    add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let events = process_with_events(
+        &mut context,
+        &[link_candy_machine_project_instruction(&payer.pubkey(), &owner, &candy_machine_id)],
+        &[&payer],
+    ).await;
+
+    let state = get_candy_machine_project_state(&mut context, &candy_machine_id).await;
+    assert_eq!(state.data.project_account, project_pda(&owner, &candy_machine_id));
+    assert_eq!(state.data.project_owner_pubkey, owner);
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::CandyMachineProjectLinked(CandyMachineProjectLinked {
+        candy_machine_project_account: candy_machine_project_pda(&candy_machine_id),
+        candy_machine_id,
+        project: project_pda(&owner, &candy_machine_id),
+        project_owner_pubkey: owner,
+        created_at: state.data.created_at,
+    })]);
+
+    let result = process(
+        &mut context,
+        &[link_candy_machine_project_instruction(&payer.pubkey(), &owner, &candy_machine_id)],
+        &[&payer],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::CandyMachineAlreadyRegistered);
+}
+
+#[tokio::test]
+async fn link_candy_machine_project_requires_candy_machine_authority() {
+    let mut program_test = program_test();
+    let payer = add_wallet(&mut program_test, 1);
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = add_candy_machine(&mut program_test, &Pubkey::new_unique());
+    add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[link_candy_machine_project_instruction(&payer.pubkey(), &owner, &candy_machine_id)],
+        &[&payer],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidCandyMachineAuthority);
+}
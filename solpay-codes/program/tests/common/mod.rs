@@ -0,0 +1,1070 @@
+#![allow(dead_code)]
+
+use std::str::FromStr;
+use std::sync::{Mutex, Once};
+use borsh::BorshSerialize;
+use candy_machine_affiliates::{
+    AffiliateAccountState,
+    AffiliateIndexPageAccountState,
+    AffiliateAccountStateData,
+    AffiliateProfileAccountState,
+    CandyMachineProjectAccountState,
+    CandyMachineAffiliatesError,
+    CandyMachineAffiliatesEvent,
+    FeeDiscountConfigAccountState,
+    FeeDiscountConfigAccountStateData,
+    FeeDiscountTier,
+    ProjectAccountState,
+    ProjectAccountStateData,
+    PromoCodeAccountState,
+    PromoCodeUsageAccountState,
+    ProtocolStatsAccountState,
+    ProtocolStatsAccountStateData,
+    ReferralCodeAccountState,
+    SPAF_MINT_AUTHORITY_SEED,
+    processor,
+    instruction,
+};
+use candy_machine_affiliates::candy_machine::{
+    CANDY_GUARD_DISCRIMINATOR,
+    CANDY_GUARD_GUARDS_OFFSET,
+    CANDY_GUARD_PROGRAM_ID,
+    CANDY_GUARD_SOL_PAYMENT_FEATURE,
+    CANDY_MACHINE_DISCRIMINATOR,
+    CANDY_MACHINE_V2_AUTHORITY_OFFSET,
+    CANDY_MACHINE_V2_PROGRAM_ID,
+    CANDY_MACHINE_V2_WALLET_OFFSET,
+    CANDY_MACHINE_V3_AUTHORITY_OFFSET,
+    CANDY_MACHINE_V3_MINT_AUTHORITY_OFFSET,
+    CANDY_MACHINE_V3_PROGRAM_ID,
+};
+use candy_machine_affiliates::projects::state::{
+    ProjectAccountStateDataV0,
+    ProjectAccountStateV0,
+    PROJECT_ACCOUNT_DATA_VERSION,
+};
+use solana_program::account_info::AccountInfo;
+use solana_program::borsh1::try_from_slice_unchecked;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::program_option::COption;
+use solana_program::program_pack::Pack;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+pub const DEFAULT_TITLE: &str = "Solpay test project";
+
+// Program test with an empty protocol stats account, which the register, close, redeem and mint
+// instructions update
+pub fn program_test() -> ProgramTest {
+    let mut program_test = program_test_without_protocol_stats();
+
+    add_protocol_stats_account(&mut program_test, &protocol_stats_state(0, 0));
+
+    program_test
+}
+
+pub fn program_test_without_protocol_stats() -> ProgramTest {
+    ProgramTest::new(
+        "candy_machine_affiliates",
+        candy_machine_affiliates::id(),
+        processor!(processor::process_instruction),
+    )
+}
+
+// Localnet admin keypair, matching the ADMIN_PUBKEY of the localnet cluster profile
+pub fn admin() -> Keypair {
+    read_keypair_file(concat!(env!("CARGO_MANIFEST_DIR"), "/../config/localnet/admin-keypair.json"))
+        .expect("Failed to read localnet admin keypair")
+}
+
+pub fn treasury() -> Pubkey {
+    Pubkey::from_str(candy_machine_affiliates::SOLPAY_TREASURY_PUBKEY).unwrap()
+}
+
+pub fn protocol_stats_pda() -> Pubkey {
+    Pubkey::find_program_address(
+        &[ProtocolStatsAccountState::DISCRIMINATOR.as_ref()],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn fee_discount_config_pda() -> Pubkey {
+    Pubkey::find_program_address(
+        &[FeeDiscountConfigAccountState::DISCRIMINATOR.as_ref()],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn project_pda(project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            ProjectAccountState::DISCRIMINATOR.as_ref(),
+            project_owner.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn affiliate_pda(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            AffiliateAccountState::DISCRIMINATOR.as_ref(),
+            affiliate.as_ref(),
+            project_owner.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn referral_code_pda(project_owner: &Pubkey, candy_machine_id: &Pubkey, code: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            ReferralCodeAccountState::DISCRIMINATOR.as_ref(),
+            project_pda(project_owner, candy_machine_id).as_ref(),
+            code.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn promo_code_pda(project_owner: &Pubkey, candy_machine_id: &Pubkey, code: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PromoCodeAccountState::DISCRIMINATOR.as_ref(),
+            project_pda(project_owner, candy_machine_id).as_ref(),
+            code.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn affiliate_index_page_pda(project_owner: &Pubkey, candy_machine_id: &Pubkey, page: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            AffiliateIndexPageAccountState::DISCRIMINATOR.as_ref(),
+            project_pda(project_owner, candy_machine_id).as_ref(),
+            &page.to_le_bytes(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn affiliate_profile_pda(affiliate: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            AffiliateProfileAccountState::DISCRIMINATOR.as_ref(),
+            affiliate.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn candy_machine_project_pda(candy_machine_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            CandyMachineProjectAccountState::DISCRIMINATOR.as_ref(),
+            candy_machine_id.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+pub fn promo_code_usage_pda(promo_code: &Pubkey, buyer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PromoCodeUsageAccountState::DISCRIMINATOR.as_ref(),
+            promo_code.as_ref(),
+            buyer.as_ref(),
+        ],
+        &candy_machine_affiliates::id(),
+    ).0
+}
+
+// Adds a system account funded with `sol` SOL to the test genesis
+pub fn add_wallet(program_test: &mut ProgramTest, sol: u64) -> Keypair {
+    let wallet = Keypair::new();
+
+    program_test.add_account(
+        wallet.pubkey(),
+        Account::new(sol * LAMPORTS_PER_SOL, 0, &solana_program::system_program::ID),
+    );
+
+    wallet
+}
+
+pub fn project_state(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    affiliate_count: u32,
+) -> ProjectAccountState {
+    ProjectAccountState {
+        discriminator: ProjectAccountState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        data_version: PROJECT_ACCOUNT_DATA_VERSION,
+        data: ProjectAccountStateData {
+            project_owner_pubkey: *project_owner,
+            candy_machine_id: *candy_machine_id,
+            affiliate_fee_percentage: 10.0,
+            affiliate_target_in_sol,
+            max_affiliate_count,
+            affiliate_count,
+            title: DEFAULT_TITLE.to_string(),
+            created_at: 0,
+            updated_at: 0,
+            gating_mint: None,
+            gating_min_amount: 0,
+            gating_burn: false,
+            total_mint_count: 0,
+            total_referred_volume_lamports: 0,
+            total_commission_lamports: 0,
+            total_redemption_count: 0,
+            peak_affiliate_count: affiliate_count,
+            affiliate_index_length: 0,
+        },
+    }
+}
+
+pub fn protocol_stats_state(live_project_count: u32, live_affiliate_count: u32) -> ProtocolStatsAccountState {
+    ProtocolStatsAccountState {
+        discriminator: ProtocolStatsAccountState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        data_version: 0,
+        data: ProtocolStatsAccountStateData {
+            live_project_count,
+            live_affiliate_count,
+            registration_fees_collected_lamports: 0,
+            total_rewards_redeemed_lamports: 0,
+            total_referred_volume_lamports: 0,
+            created_at: 0,
+            updated_at: 0,
+        },
+    }
+}
+
+// Tiers given as (SPAF balance held, discount in basis points)
+pub fn fee_discount_tiers(tiers: &[(u64, u16)]) -> Vec<FeeDiscountTier> {
+    tiers.iter()
+        .map(|(min_spaf_balance, discount_basis_points)| FeeDiscountTier {
+            min_spaf_balance: *min_spaf_balance,
+            discount_basis_points: *discount_basis_points,
+        })
+        .collect()
+}
+
+pub fn fee_discount_config_state(tiers: &[(u64, u16)]) -> FeeDiscountConfigAccountState {
+    FeeDiscountConfigAccountState {
+        discriminator: FeeDiscountConfigAccountState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        data_version: 0,
+        data: FeeDiscountConfigAccountStateData {
+            created_at: 0,
+            updated_at: 0,
+            tiers: fee_discount_tiers(tiers),
+        },
+    }
+}
+
+pub fn affiliate_state(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> AffiliateAccountState {
+    AffiliateAccountState {
+        discriminator: AffiliateAccountState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        data_version: 0,
+        data: AffiliateAccountStateData {
+            affiliate_pubkey: *affiliate,
+            project_owner_pubkey: *project_owner,
+            candy_machine_id: *candy_machine_id,
+            total_redeemed_amount_in_sol: 0,
+            created_at: 0,
+            mint_count: 0,
+            referred_volume_lamports: 0,
+            total_commission_lamports: 0,
+            redemption_count: 0,
+            last_activity_at: 0,
+            affiliate_index_position: None,
+            pending_milestone_reward: 0,
+            referral_code: None,
+        },
+    }
+}
+
+// Project account as written by the deployed program, before the affiliate counts were widened to u32
+pub fn project_state_v0(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    max_affiliate_count: u8,
+    affiliate_count: u8,
+) -> ProjectAccountStateV0 {
+    ProjectAccountStateV0 {
+        discriminator: ProjectAccountState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        data_version: 0,
+        data: ProjectAccountStateDataV0 {
+            project_owner_pubkey: *project_owner,
+            candy_machine_id: *candy_machine_id,
+            affiliate_fee_percentage: 10.0,
+            affiliate_target_in_sol: 1,
+            max_affiliate_count,
+            affiliate_count,
+            title: DEFAULT_TITLE.to_string(),
+            created_at: 0,
+            updated_at: 0,
+        },
+    }
+}
+
+// Data of a data version 0 project account whose title the deployed `UpdateProject` shortened to
+// `DEFAULT_TITLE`: the end of the longer title is left after `updated_at`
+pub fn project_data_v0_with_shortened_title(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    max_affiliate_count: u8,
+    affiliate_count: u8,
+) -> Vec<u8> {
+    let mut state = project_state_v0(project_owner, candy_machine_id, max_affiliate_count, affiliate_count);
+    let mut data = vec![0; ProjectAccountState::V0_LENGTH];
+
+    state.data.title = format!("{} {}", DEFAULT_TITLE, "x".repeat(40));
+    state.serialize(&mut &mut data[..]).unwrap();
+    state.data.title = DEFAULT_TITLE.to_string();
+    state.serialize(&mut &mut data[..]).unwrap();
+
+    data
+}
+
+// Adds a program owned, rent exempt account holding `data` padded to `length` bytes
+pub fn add_program_account(program_test: &mut ProgramTest, address: Pubkey, mut data: Vec<u8>, length: usize) {
+    data.resize(length, 0);
+
+    program_test.add_account(
+        address,
+        Account {
+            lamports: solana_sdk::rent::Rent::default().minimum_balance(length),
+            data,
+            owner: candy_machine_affiliates::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+pub fn add_protocol_stats_account(program_test: &mut ProgramTest, state: &ProtocolStatsAccountState) {
+    add_program_account(program_test, protocol_stats_pda(), borsh::to_vec(state).unwrap(), ProtocolStatsAccountState::LENGTH);
+}
+
+pub fn add_fee_discount_config_account(program_test: &mut ProgramTest, tiers: &[(u64, u16)]) {
+    add_program_account(
+        program_test,
+        fee_discount_config_pda(),
+        borsh::to_vec(&fee_discount_config_state(tiers)).unwrap(),
+        FeeDiscountConfigAccountState::LENGTH,
+    );
+}
+
+pub fn add_project_account(program_test: &mut ProgramTest, state: &ProjectAccountState) -> Pubkey {
+    let address = project_pda(&state.data.project_owner_pubkey, &state.data.candy_machine_id);
+
+    add_program_account(program_test, address, borsh::to_vec(state).unwrap(), ProjectAccountState::LENGTH);
+
+    address
+}
+
+// Adds a candy machine account of a Candy Machine program (v2 or v3) with the given authority
+pub fn add_candy_machine_of(program_test: &mut ProgramTest, candy_machine_program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    let authority_offset = match *candy_machine_program_id == CANDY_MACHINE_V2_PROGRAM_ID {
+        true => CANDY_MACHINE_V2_AUTHORITY_OFFSET,
+        false => CANDY_MACHINE_V3_AUTHORITY_OFFSET,
+    };
+    let mut data = vec![0; 256];
+    data[..8].copy_from_slice(&CANDY_MACHINE_DISCRIMINATOR);
+    data[authority_offset..authority_offset + 32].copy_from_slice(authority.as_ref());
+
+    add_candy_machine_account(program_test, candy_machine_program_id, data)
+}
+
+// Adds a Candy Machine v2 account paying the mint price to `wallet`
+pub fn add_candy_machine_v2(program_test: &mut ProgramTest, authority: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    let mut data = vec![0; 256];
+    data[..8].copy_from_slice(&CANDY_MACHINE_DISCRIMINATOR);
+    data[CANDY_MACHINE_V2_AUTHORITY_OFFSET..CANDY_MACHINE_V2_AUTHORITY_OFFSET + 32].copy_from_slice(authority.as_ref());
+    data[CANDY_MACHINE_V2_WALLET_OFFSET..CANDY_MACHINE_V2_WALLET_OFFSET + 32].copy_from_slice(wallet.as_ref());
+
+    add_candy_machine_account(program_test, &CANDY_MACHINE_V2_PROGRAM_ID, data)
+}
+
+// Adds a Candy Machine v3 account minting through a Candy Guard whose default guard set has a
+// `solPayment` of `price` lamports to `destination`, returning the candy machine and the candy guard
+pub fn add_candy_machine_v3_with_guard(
+    program_test: &mut ProgramTest,
+    authority: &Pubkey,
+    price: u64,
+    destination: &Pubkey,
+) -> (Pubkey, Pubkey) {
+    let mut guard_data = vec![0; 256];
+    guard_data[..8].copy_from_slice(&CANDY_GUARD_DISCRIMINATOR);
+    let guards = &mut guard_data[CANDY_GUARD_GUARDS_OFFSET..];
+    guards[..8].copy_from_slice(&CANDY_GUARD_SOL_PAYMENT_FEATURE.to_le_bytes());
+    guards[8..16].copy_from_slice(&price.to_le_bytes());
+    guards[16..48].copy_from_slice(destination.as_ref());
+    let candy_guard = add_candy_machine_account(program_test, &CANDY_GUARD_PROGRAM_ID, guard_data);
+
+    let mut data = vec![0; 256];
+    data[..8].copy_from_slice(&CANDY_MACHINE_DISCRIMINATOR);
+    data[CANDY_MACHINE_V3_AUTHORITY_OFFSET..CANDY_MACHINE_V3_AUTHORITY_OFFSET + 32].copy_from_slice(authority.as_ref());
+    data[CANDY_MACHINE_V3_MINT_AUTHORITY_OFFSET..CANDY_MACHINE_V3_MINT_AUTHORITY_OFFSET + 32].copy_from_slice(candy_guard.as_ref());
+    let candy_machine = add_candy_machine_account(program_test, &CANDY_MACHINE_V3_PROGRAM_ID, data);
+
+    (candy_machine, candy_guard)
+}
+
+fn add_candy_machine_account(program_test: &mut ProgramTest, candy_machine_program_id: &Pubkey, data: Vec<u8>) -> Pubkey {
+    let address = Pubkey::new_unique();
+
+    program_test.add_account(
+        address,
+        Account {
+            lamports: solana_sdk::rent::Rent::default().minimum_balance(data.len()),
+            data,
+            owner: *candy_machine_program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    address
+}
+
+pub fn add_candy_machine(program_test: &mut ProgramTest, authority: &Pubkey) -> Pubkey {
+    add_candy_machine_of(program_test, &CANDY_MACHINE_V3_PROGRAM_ID, authority)
+}
+
+// Adds an SPL token account, rent exempt and owned by the token program
+fn add_token_program_account<T: Pack>(program_test: &mut ProgramTest, address: Pubkey, state: T) -> Pubkey {
+    let mut data = vec![0; T::LEN];
+    T::pack(state, &mut data).unwrap();
+
+    program_test.add_account(
+        address,
+        Account {
+            lamports: solana_sdk::rent::Rent::default().minimum_balance(T::LEN),
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    address
+}
+
+pub fn add_mint(program_test: &mut ProgramTest, mint_authority: &Pubkey, supply: u64) -> Pubkey {
+    add_mint_at(program_test, Pubkey::new_unique(), mint_authority, supply)
+}
+
+pub fn add_mint_at(program_test: &mut ProgramTest, address: Pubkey, mint_authority: &Pubkey, supply: u64) -> Pubkey {
+    add_token_program_account(program_test, address, spl_token::state::Mint {
+        mint_authority: COption::Some(*mint_authority),
+        supply,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    })
+}
+
+pub fn add_token_account(program_test: &mut ProgramTest, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+    add_token_program_account(program_test, Pubkey::new_unique(), spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    })
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+
+    context.banks_client.process_transaction(transaction).await
+}
+
+// Processes the transaction and returns the `msg!` lines logged by the program
+pub async fn process_with_logs(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> (Result<(), TransactionError>, Vec<String>) {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+
+    let result = context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    let program_id = candy_machine_affiliates::id().to_string();
+    let mut invocations = Vec::new();
+    let mut program_logs = Vec::new();
+
+    for log in result.metadata.expect("Transaction metadata not found").log_messages {
+        let words = log.split_whitespace().collect::<Vec<_>>();
+
+        match words.as_slice() {
+            ["Program", "log:", ..] if invocations.last() == Some(&program_id) => {
+                program_logs.push(log["Program log: ".len()..].to_string());
+            }
+            ["Program", invoked, "invoke", ..] => invocations.push(invoked.to_string()),
+            ["Program", _, "success"] | ["Program", _, "failed:", ..] => {
+                invocations.pop();
+            }
+            _ => {}
+        }
+    }
+
+    (result.result, program_logs)
+}
+
+// Syscall stubs forwarding to the ones installed by `solana-program-test`, whose native
+// `sol_log_data` prints to stdout instead of the transaction log, and capturing event data
+struct CaptureEventsStubs(Box<dyn SyscallStubs>);
+
+static CAPTURED_EVENT_DATA: Mutex<Vec<Vec<Vec<u8>>>> = Mutex::new(Vec::new());
+
+impl SyscallStubs for CaptureEventsStubs {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+    fn sol_invoke_signed(&self, instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        self.0.sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        CAPTURED_EVENT_DATA.lock().unwrap().push(fields.iter().map(|field| field.to_vec()).collect());
+    }
+}
+
+// Processes the transaction and decodes the events emitted by the program.
+// Must be called after the program test has started so its syscall stubs are installed.
+pub async fn process_with_events(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Vec<CandyMachineAffiliatesEvent> {
+    static CAPTURE_EVENTS: Once = Once::new();
+
+    CAPTURE_EVENTS.call_once(|| {
+        struct PlaceholderStubs;
+        impl SyscallStubs for PlaceholderStubs {}
+
+        let program_test_stubs = set_syscall_stubs(Box::new(PlaceholderStubs));
+        set_syscall_stubs(Box::new(CaptureEventsStubs(program_test_stubs)));
+    });
+
+    CAPTURED_EVENT_DATA.lock().unwrap().clear();
+
+    process(context, instructions, signers).await.unwrap();
+
+    CAPTURED_EVENT_DATA.lock().unwrap()
+        .drain(..)
+        .filter_map(|fields| CandyMachineAffiliatesEvent::decode(&fields.iter().map(Vec::as_slice).collect::<Vec<_>>()))
+        .collect()
+}
+
+// Simulates a view instruction and decodes the data it returned
+pub async fn simulate_view<T: borsh::BorshDeserialize>(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+) -> Result<T, TransactionError> {
+    simulate_signed_view(context, instruction, &[]).await
+}
+
+// Same as `simulate_view` for views signed by more than the payer
+pub async fn simulate_signed_view<T: borsh::BorshDeserialize>(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<T, TransactionError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+
+    let simulation = context.banks_client.simulate_transaction(transaction).await.unwrap();
+    simulation.result.expect("Transaction was not simulated")?;
+
+    let return_data = simulation.simulation_details
+        .and_then(|details| details.return_data)
+        .expect("View returned no data");
+    assert_eq!(return_data.program_id, candy_machine_affiliates::id());
+
+    Ok(T::try_from_slice(&return_data.data).unwrap())
+}
+
+pub fn assert_instruction_error(result: Result<(), BanksClientError>, expected: InstructionError) {
+    match result.expect_err("Transaction should have failed").unwrap() {
+        TransactionError::InstructionError(_, error) => assert_eq!(error, expected),
+        error => panic!("Unexpected transaction error: {:?}", error),
+    }
+}
+
+pub fn assert_program_error(result: Result<(), BanksClientError>, expected: CandyMachineAffiliatesError) {
+    assert_instruction_error(result, InstructionError::Custom(expected as u32));
+}
+
+pub async fn get_project_state(context: &mut ProgramTestContext, address: &Pubkey) -> ProjectAccountState {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("Project account not found");
+
+    ProjectAccountState::unpack_account_data(&account.data).unwrap()
+}
+
+pub async fn get_fee_discount_config_state(context: &mut ProgramTestContext) -> FeeDiscountConfigAccountState {
+    let account = context.banks_client.get_account(fee_discount_config_pda()).await.unwrap().expect("Fee discount config account not found");
+
+    try_from_slice_unchecked::<FeeDiscountConfigAccountState>(&account.data).unwrap()
+}
+
+pub async fn get_protocol_stats_state(context: &mut ProgramTestContext) -> ProtocolStatsAccountState {
+    let account = context.banks_client.get_account(protocol_stats_pda()).await.unwrap().expect("Protocol stats account not found");
+
+    try_from_slice_unchecked::<ProtocolStatsAccountState>(&account.data).unwrap()
+}
+
+pub async fn get_affiliate_index_page_state(context: &mut ProgramTestContext, address: &Pubkey) -> AffiliateIndexPageAccountState {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("Affiliate index page account not found");
+
+    try_from_slice_unchecked::<AffiliateIndexPageAccountState>(&account.data).unwrap()
+}
+
+pub async fn get_affiliate_profile_state(context: &mut ProgramTestContext, affiliate: &Pubkey) -> AffiliateProfileAccountState {
+    let account = context.banks_client.get_account(affiliate_profile_pda(affiliate)).await.unwrap().expect("Affiliate profile account not found");
+
+    try_from_slice_unchecked::<AffiliateProfileAccountState>(&account.data).unwrap()
+}
+
+pub async fn get_candy_machine_project_state(context: &mut ProgramTestContext, candy_machine_id: &Pubkey) -> CandyMachineProjectAccountState {
+    let account = context.banks_client.get_account(candy_machine_project_pda(candy_machine_id)).await.unwrap().expect("Candy machine project account not found");
+
+    try_from_slice_unchecked::<CandyMachineProjectAccountState>(&account.data).unwrap()
+}
+
+pub async fn get_affiliate_state(context: &mut ProgramTestContext, address: &Pubkey) -> AffiliateAccountState {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("Affiliate account not found");
+
+    AffiliateAccountState::unpack_account_data(&account.data).unwrap()
+}
+
+pub async fn get_referral_code_state(context: &mut ProgramTestContext, address: &Pubkey) -> ReferralCodeAccountState {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("Referral code account not found");
+
+    try_from_slice_unchecked::<ReferralCodeAccountState>(&account.data).unwrap()
+}
+
+pub async fn get_promo_code_state(context: &mut ProgramTestContext, address: &Pubkey) -> PromoCodeAccountState {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("Promo code account not found");
+
+    try_from_slice_unchecked::<PromoCodeAccountState>(&account.data).unwrap()
+}
+
+pub async fn get_token_account(context: &mut ProgramTestContext, address: &Pubkey) -> spl_token::state::Account {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("Token account not found");
+
+    spl_token::state::Account::unpack(&account.data).unwrap()
+}
+
+pub async fn get_mint(context: &mut ProgramTestContext, address: &Pubkey) -> spl_token::state::Mint {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("Mint account not found");
+
+    spl_token::state::Mint::unpack(&account.data).unwrap()
+}
+
+pub async fn get_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+pub fn register_project_instruction(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    affiliate_target_in_sol: u8,
+    max_affiliate_count: u32,
+    title: &str,
+) -> Instruction {
+    instruction::register_project_v2(
+        &candy_machine_affiliates::id(),
+        project_owner,
+        &project_pda(project_owner, candy_machine_id),
+        &protocol_stats_pda(),
+        &candy_machine_project_pda(candy_machine_id),
+        *candy_machine_id,
+        10.0,
+        affiliate_target_in_sol,
+        max_affiliate_count,
+        title.to_string(),
+    )
+}
+
+pub fn register_affiliate_instruction(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::register_affiliate(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &treasury(),
+        &protocol_stats_pda(),
+        &affiliate_index_page_pda(project_owner, candy_machine_id, 0),
+        &affiliate_profile_pda(affiliate),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Passes `gating_mint` along with the token account for projects burning the gating tokens
+pub fn register_affiliate_with_gating_token_instruction(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_token_account: &Pubkey,
+    gating_mint: Option<&Pubkey>,
+) -> Instruction {
+    instruction::register_affiliate_with_gating_token(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &treasury(),
+        &protocol_stats_pda(),
+        &affiliate_index_page_pda(project_owner, candy_machine_id, 0),
+        &affiliate_profile_pda(affiliate),
+        gating_token_account,
+        gating_mint,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn register_affiliate_with_spaf_discount_instruction(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_token_account: Option<&Pubkey>,
+    gating_mint: Option<&Pubkey>,
+    spaf_token_account: &Pubkey,
+) -> Instruction {
+    instruction::register_affiliate_with_spaf_discount(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &treasury(),
+        &protocol_stats_pda(),
+        &affiliate_index_page_pda(project_owner, candy_machine_id, 0),
+        &affiliate_profile_pda(affiliate),
+        gating_token_account,
+        gating_mint,
+        spaf_token_account,
+        &fee_discount_config_pda(),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn redeem_reward_instruction(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::redeem_reward(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &protocol_stats_pda(),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn spaf_mint_authority_pda() -> Pubkey {
+    Pubkey::find_program_address(&[SPAF_MINT_AUTHORITY_SEED.as_ref()], &candy_machine_affiliates::id()).0
+}
+
+pub fn redeem_reward_with_milestone_reward_instruction(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    reward_token_account: &Pubkey,
+    spaf_mint: &Pubkey,
+) -> Instruction {
+    instruction::redeem_reward_with_milestone_reward(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &protocol_stats_pda(),
+        reward_token_account,
+        spaf_mint,
+        &spaf_mint_authority_pda(),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn close_affiliate_instruction(admin: &Pubkey, affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::close_affiliate_account(
+        &candy_machine_affiliates::id(),
+        admin,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &treasury(),
+        &protocol_stats_pda(),
+        Some(&affiliate_index_page_pda(project_owner, candy_machine_id, 0)),
+        &affiliate_profile_pda(affiliate),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn get_redeemable_amount_instruction(affiliate: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::get_redeemable_amount(
+        &candy_machine_affiliates::id(),
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn get_project_capacity_instruction(project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Instruction {
+    instruction::get_project_capacity(
+        &candy_machine_affiliates::id(),
+        &project_pda(project_owner, candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+pub fn get_project_capacity_for_affiliate_instruction(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_token_account: Option<&Pubkey>,
+    spaf_token_account: Option<&Pubkey>,
+) -> Instruction {
+    instruction::get_project_capacity_for_affiliate(
+        &candy_machine_affiliates::id(),
+        &project_pda(project_owner, candy_machine_id),
+        affiliate,
+        gating_token_account,
+        spaf_token_account,
+        &fee_discount_config_pda(),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Claims `code` as given, deriving the referral code PDA from its normalized form
+pub fn claim_referral_code_instruction(
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+    previous_affiliate: Option<&Pubkey>,
+) -> Instruction {
+    let normalized_code = candy_machine_affiliates::normalize_referral_code(code).unwrap_or_default();
+
+    instruction::claim_referral_code(
+        &candy_machine_affiliates::id(),
+        affiliate,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &referral_code_pda(project_owner, candy_machine_id, &normalized_code),
+        previous_affiliate.map(|previous| affiliate_pda(previous, project_owner, candy_machine_id)).as_ref(),
+        *project_owner,
+        *candy_machine_id,
+        code.to_string(),
+    )
+}
+
+pub fn close_affiliate_with_referral_code_instruction(
+    admin: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+) -> Instruction {
+    instruction::close_affiliate_account_with_referral_code(
+        &candy_machine_affiliates::id(),
+        admin,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        &treasury(),
+        &protocol_stats_pda(),
+        Some(&affiliate_index_page_pda(project_owner, candy_machine_id, 0)),
+        &affiliate_profile_pda(affiliate),
+        &referral_code_pda(project_owner, candy_machine_id, code),
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+// Promo code settings, defaulting to a 10% discount without affiliate, usage limits nor expiry
+pub struct PromoCodeSettings {
+    pub affiliate: Option<Pubkey>,
+    pub discount_basis_points: u16,
+    pub max_uses: u32,
+    pub max_uses_per_wallet: u16,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+impl Default for PromoCodeSettings {
+    fn default() -> Self {
+        PromoCodeSettings {
+            affiliate: None,
+            discount_basis_points: 1_000,
+            max_uses: 0,
+            max_uses_per_wallet: 0,
+            starts_at: 0,
+            ends_at: 0,
+        }
+    }
+}
+
+pub fn create_promo_code_instruction(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    code: &str,
+    settings: &PromoCodeSettings,
+) -> Instruction {
+    instruction::create_promo_code(
+        &candy_machine_affiliates::id(),
+        project_owner,
+        &project_pda(project_owner, candy_machine_id),
+        &promo_code_pda(project_owner, candy_machine_id, code),
+        settings.affiliate.map(|affiliate| affiliate_pda(&affiliate, project_owner, candy_machine_id)).as_ref(),
+        *candy_machine_id,
+        code.to_string(),
+        settings.affiliate,
+        settings.discount_basis_points,
+        settings.max_uses,
+        settings.max_uses_per_wallet,
+        settings.starts_at,
+        settings.ends_at,
+    )
+}
+
+pub fn consume_promo_code_instruction(buyer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey, code: &str) -> Instruction {
+    let promo_code = promo_code_pda(project_owner, candy_machine_id, code);
+
+    instruction::consume_promo_code(
+        &candy_machine_affiliates::id(),
+        buyer,
+        &project_pda(project_owner, candy_machine_id),
+        &promo_code,
+        &promo_code_usage_pda(&promo_code, buyer),
+        *project_owner,
+        *candy_machine_id,
+        code.to_string(),
+    )
+}
+
+pub fn set_project_gating_instruction(
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    gating_mint: Option<Pubkey>,
+    gating_min_amount: u64,
+    gating_burn: bool,
+) -> Instruction {
+    instruction::set_project_gating(
+        &candy_machine_affiliates::id(),
+        project_owner,
+        &project_pda(project_owner, candy_machine_id),
+        *candy_machine_id,
+        gating_mint,
+        gating_min_amount,
+        gating_burn,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn mint_with_referral_instruction(
+    buyer: &Pubkey,
+    affiliate: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    payment_wallet: &Pubkey,
+    candy_machine_program: &Pubkey,
+    mint_accounts: Vec<AccountMeta>,
+    mint_data: Vec<u8>,
+) -> Instruction {
+    instruction::mint_with_referral(
+        &candy_machine_affiliates::id(),
+        buyer,
+        &affiliate_pda(affiliate, project_owner, candy_machine_id),
+        &project_pda(project_owner, candy_machine_id),
+        payment_wallet,
+        candy_machine_program,
+        &protocol_stats_pda(),
+        &affiliate_profile_pda(affiliate),
+        mint_accounts,
+        *affiliate,
+        *project_owner,
+        *candy_machine_id,
+        mint_data,
+    )
+}
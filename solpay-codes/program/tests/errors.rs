@@ -0,0 +1,88 @@
+use std::fs;
+use candy_machine_affiliates::CandyMachineAffiliatesError;
+use candy_machine_affiliates::idl::idl;
+use solana_program::program_error::ProgramError;
+
+// Errors the processor never returns: `InvalidDataLength` is unused since the deployed program, and
+// a zero max affiliate count is reported as `ProjectTitleTooLong`, like the deployed program does
+const UNREACHABLE_ERRORS: &[&str] = &["InvalidDataLength", "InvalidProjectMaxAffiliateCount"];
+
+// Error codes are part of the program interface and must never be reordered
+#[test]
+fn error_codes_are_stable() {
+    let errors = [
+        (CandyMachineAffiliatesError::ActionNotAllowed, 0),
+        (CandyMachineAffiliatesError::UninitializedAccount, 1),
+        (CandyMachineAffiliatesError::InvalidPDA, 2),
+        (CandyMachineAffiliatesError::InvalidDataLength, 3),
+        (CandyMachineAffiliatesError::AmountOverflow, 4),
+        (CandyMachineAffiliatesError::ProjectTitleTooLong, 5),
+        (CandyMachineAffiliatesError::InvalidProjectMaxAffiliateCount, 6),
+        (CandyMachineAffiliatesError::ProjectMaxAffiliateCountReached, 7),
+        (CandyMachineAffiliatesError::ProjectAffiliateCountLargerThanNewMaxAffiliateCount, 8),
+        (CandyMachineAffiliatesError::IncorrectTreasuryAccount, 9),
+        (CandyMachineAffiliatesError::RewardRedeemMismatchedAccounts, 10),
+        (CandyMachineAffiliatesError::AffiliateAccountBalanceNotEnough, 11),
+        (CandyMachineAffiliatesError::IncorrectProjectOwnerAccount, 12),
+        (CandyMachineAffiliatesError::InvalidReferralCode, 13),
+        (CandyMachineAffiliatesError::ReferralCodeAlreadyClaimed, 14),
+        (CandyMachineAffiliatesError::InvalidPromoCode, 15),
+        (CandyMachineAffiliatesError::InvalidPromoCodeDiscount, 16),
+        (CandyMachineAffiliatesError::InvalidPromoCodePeriod, 17),
+        (CandyMachineAffiliatesError::PromoCodeAlreadyExists, 18),
+        (CandyMachineAffiliatesError::PromoCodeNotActive, 19),
+        (CandyMachineAffiliatesError::PromoCodeMaxUsesReached, 20),
+        (CandyMachineAffiliatesError::PromoCodeWalletLimitReached, 21),
+        (CandyMachineAffiliatesError::InvalidProjectGating, 22),
+        (CandyMachineAffiliatesError::InvalidGatingTokenAccount, 23),
+        (CandyMachineAffiliatesError::GatingTokenBalanceNotEnough, 24),
+        (CandyMachineAffiliatesError::InvalidRewardTokenAccount, 25),
+        (CandyMachineAffiliatesError::InvalidSpafTokenAccount, 26),
+        (CandyMachineAffiliatesError::InvalidCandyMachineAccount, 27),
+        (CandyMachineAffiliatesError::InvalidCandyMachineAuthority, 28),
+        (CandyMachineAffiliatesError::InvalidPaymentWallet, 29),
+        (CandyMachineAffiliatesError::AffiliateAlreadyIndexed, 30),
+        (CandyMachineAffiliatesError::ProjectAlreadyInAffiliateProfile, 31),
+        (CandyMachineAffiliatesError::CandyMachineAlreadyRegistered, 32),
+        (CandyMachineAffiliatesError::AffiliateReferralCodeAlreadyClaimed, 33),
+        (CandyMachineAffiliatesError::InvalidMintInstruction, 34),
+        (CandyMachineAffiliatesError::InvalidFeeDiscountTiers, 35),
+    ];
+
+    for (error, code) in errors {
+        assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+    }
+}
+
+// Every error the processor returns is expected as the `InstructionError::Custom` of a transaction
+// in the integration tests, through `assert_program_error` or an explicit `Custom(... as u32)`
+#[test]
+fn every_error_is_returned_by_a_processor_test() {
+    let tests_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests");
+    let sources: Vec<String> = fs::read_dir(tests_dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .filter(|path| !path.ends_with("errors.rs") && !path.ends_with("idl.rs"))
+        .map(|path| fs::read_to_string(path).unwrap())
+        .collect();
+
+    let idl = idl();
+    let names: Vec<&str> = idl["errors"].as_array().unwrap().iter()
+        .map(|error| error["name"].as_str().unwrap())
+        .collect();
+
+    for name in names {
+        let expected = [
+            format!("assert_program_error(result, CandyMachineAffiliatesError::{})", name),
+            format!("Custom(CandyMachineAffiliatesError::{} as u32)", name),
+            // Tables of settings and the error they are rejected with
+            format!("CandyMachineAffiliatesError::{}),", name),
+        ];
+        let is_tested = sources.iter().any(|source| expected.iter().any(|pattern| source.contains(pattern.as_str())));
+
+        match UNREACHABLE_ERRORS.contains(&name) {
+            true => assert!(!is_tested, "{} is listed as unreachable but tested", name),
+            false => assert!(is_tested, "No processor test returns {}", name),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::events::{
+    AffiliateClosedV2,
+    AffiliateIndexed,
+    AffiliateRegisteredV2,
+    ProjectClosed,
+    ProjectRegisteredV2,
+    ProjectUpdatedV2,
+    RewardRedeemed,
+    EVENT_DISCRIMINATOR,
+};
+use candy_machine_affiliates::{instruction, AffiliateAccountState, CandyMachineAffiliatesEvent, AFFILIATE_REGISTRATION_FEE};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_sdk::signature::Signer;
+use common::*;
+
+#[tokio::test]
+async fn instructions_emit_events() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let affiliate = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let project_address = project_pda(&owner.pubkey(), &candy_machine_id);
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id);
+
+    let events = process_with_events(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+    let created_at = get_project_state(&mut context, &project_address).await.data.created_at;
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::ProjectRegisteredV2(ProjectRegisteredV2 {
+        project: project_address,
+        project_owner_pubkey: owner.pubkey(),
+        candy_machine_id,
+        affiliate_fee_percentage: 10.0,
+        affiliate_target_in_sol: 1,
+        max_affiliate_count: 5,
+        title: DEFAULT_TITLE.to_string(),
+        created_at,
+    })]);
+
+    let events = process_with_events(
+        &mut context,
+        &[instruction::update_project(
+            &candy_machine_affiliates::id(),
+            &admin.pubkey(),
+            &project_address,
+            owner.pubkey(),
+            candy_machine_id,
+            15.0,
+            1,
+            8,
+            "Updated title".to_string(),
+        )],
+        &[&admin],
+    ).await;
+    let updated_at = get_project_state(&mut context, &project_address).await.data.updated_at;
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::ProjectUpdatedV2(ProjectUpdatedV2 {
+        project: project_address,
+        affiliate_fee_percentage: 15.0,
+        affiliate_target_in_sol: 1,
+        max_affiliate_count: 8,
+        title: "Updated title".to_string(),
+        updated_at,
+    })]);
+
+    let events = process_with_events(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&affiliate],
+    ).await;
+    let created_at = get_affiliate_state(&mut context, &affiliate_address).await.data.created_at;
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::AffiliateRegisteredV2(AffiliateRegisteredV2 {
+        affiliate_account: affiliate_address,
+        affiliate_pubkey: affiliate.pubkey(),
+        project: project_address,
+        registration_fee: AFFILIATE_REGISTRATION_FEE,
+        affiliate_count: 1,
+        created_at,
+    }), CandyMachineAffiliatesEvent::AffiliateIndexed(AffiliateIndexed {
+        affiliate_account: affiliate_address,
+        project: project_address,
+        affiliate_index_page_account: affiliate_index_page_pda(&owner.pubkey(), &candy_machine_id, 0),
+        position: 0,
+    })]);
+
+    let payer = context.payer.pubkey();
+    let events = process_with_events(
+        &mut context,
+        &[
+            system_instruction::transfer(&payer, &affiliate_address, LAMPORTS_PER_SOL),
+            redeem_reward_instruction(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id),
+        ],
+        &[&affiliate],
+    ).await;
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::RewardRedeemed(RewardRedeemed {
+        affiliate_account: affiliate_address,
+        affiliate_pubkey: affiliate.pubkey(),
+        project: project_address,
+        reward_lamports: LAMPORTS_PER_SOL,
+        total_redeemed_amount_in_sol: 1,
+    })]);
+
+    let events = process_with_events(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&admin],
+    ).await;
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::AffiliateClosedV2(AffiliateClosedV2 {
+        affiliate_account: affiliate_address,
+        affiliate_pubkey: affiliate.pubkey(),
+        project: project_address,
+        treasury_lamports: Rent::default().minimum_balance(AffiliateAccountState::LENGTH),
+        affiliate_count: 0,
+    })]);
+
+    let refunded_lamports = get_balance(&mut context, &project_address).await;
+    let events = process_with_events(
+        &mut context,
+        &[instruction::close_project(
+            &candy_machine_affiliates::id(),
+            &admin.pubkey(),
+            &project_address,
+            &protocol_stats_pda(),
+            &candy_machine_project_pda(&candy_machine_id),
+            owner.pubkey(),
+            candy_machine_id,
+        )],
+        &[&admin],
+    ).await;
+
+    assert_eq!(events, vec![CandyMachineAffiliatesEvent::ProjectClosed(ProjectClosed {
+        project: project_address,
+        project_owner_pubkey: owner.pubkey(),
+        candy_machine_id,
+        refunded_lamports,
+    })]);
+}
+
+#[test]
+fn from_logs_skips_data_logged_by_other_programs() {
+    let event = CandyMachineAffiliatesEvent::ProjectClosed(ProjectClosed {
+        project: Pubkey::new_unique(),
+        project_owner_pubkey: Pubkey::new_unique(),
+        candy_machine_id: Pubkey::new_unique(),
+        refunded_lamports: 42,
+    });
+    let data = format!(
+        "Program data: {} {}",
+        STANDARD.encode(EVENT_DISCRIMINATOR),
+        STANDARD.encode(borsh::to_vec(&event).unwrap()),
+    );
+    let program_id = candy_machine_affiliates::id();
+    let other_program_id = Pubkey::new_unique();
+
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        format!("Program {} invoke [2]", other_program_id),
+        data.clone(),
+        format!("Program {} success", other_program_id),
+        data.clone(),
+        "Program data: bm90IGFuIGV2ZW50".to_string(),
+        format!("Program {} success", program_id),
+        format!("Program {} invoke [1]", other_program_id),
+        data,
+        format!("Program {} success", other_program_id),
+    ];
+
+    assert_eq!(CandyMachineAffiliatesEvent::from_logs(&program_id, &logs), vec![event]);
+}
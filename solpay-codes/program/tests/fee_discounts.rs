@@ -0,0 +1,380 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use std::str::FromStr;
+use candy_machine_affiliates::{
+    fee_discount_basis_points,
+    instruction,
+    is_valid_fee_discount_schedule,
+    registration_fee,
+    CandyMachineAffiliatesError,
+    CandyMachineAffiliatesEvent,
+    FeeDiscountConfigAccountState,
+    ProjectCapacity,
+    AFFILIATE_REGISTRATION_FEE,
+    MAX_FEE_DISCOUNT_TIERS,
+    SPAF_MINT_PUBKEY,
+};
+use solana_program::instruction::Instruction;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program_test::ProgramTest;
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+use common::*;
+
+const TIERS: &[(u64, u16)] = &[(100, 2_500), (1_000, 5_000), (10_000, 7_500)];
+
+fn spaf_mint() -> Pubkey {
+    Pubkey::from_str(SPAF_MINT_PUBKEY.unwrap()).unwrap()
+}
+
+fn set_fee_discount_tiers_instruction(admin: &Pubkey, tiers: &[(u64, u16)]) -> Instruction {
+    instruction::set_fee_discount_tiers(
+        &candy_machine_affiliates::id(),
+        admin,
+        &fee_discount_config_pda(),
+        fee_discount_tiers(tiers),
+    )
+}
+
+fn add_admin(program_test: &mut ProgramTest) -> Keypair {
+    let admin = admin();
+    program_test.add_account(
+        admin.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &solana_program::system_program::ID),
+    );
+
+    admin
+}
+
+// Adds the SPAF mint, a project and a wallet holding `spaf_balance` SPAF
+fn add_spaf_holder_without_fee_discount_config(program_test: &mut ProgramTest, spaf_balance: u64) -> (Keypair, Pubkey, Pubkey, Pubkey) {
+    add_mint_at(program_test, spaf_mint(), &Pubkey::new_unique(), 100_000);
+    let affiliate = add_wallet(program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let spaf_token_account = add_token_account(program_test, &affiliate.pubkey(), &spaf_mint(), spaf_balance);
+
+    add_project_account(program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+
+    (affiliate, project_owner, candy_machine_id, spaf_token_account)
+}
+
+// Also adds the fee discount config account holding `TIERS`
+fn add_spaf_holder(program_test: &mut ProgramTest, spaf_balance: u64) -> (Keypair, Pubkey, Pubkey, Pubkey) {
+    add_fee_discount_config_account(program_test, TIERS);
+
+    add_spaf_holder_without_fee_discount_config(program_test, spaf_balance)
+}
+
+#[test]
+fn fee_discount_uses_highest_tier_reached() {
+    let tiers = fee_discount_tiers(TIERS);
+
+    assert_eq!(fee_discount_basis_points(&tiers, 0), 0);
+    assert_eq!(fee_discount_basis_points(&tiers, 99), 0);
+    assert_eq!(fee_discount_basis_points(&tiers, 100), 2_500);
+    assert_eq!(fee_discount_basis_points(&tiers, 9_999), 5_000);
+    assert_eq!(fee_discount_basis_points(&tiers, u64::MAX), 7_500);
+    assert_eq!(fee_discount_basis_points(&[], u64::MAX), 0);
+
+    assert_eq!(registration_fee(&tiers, 0), AFFILIATE_REGISTRATION_FEE);
+    assert_eq!(registration_fee(&tiers, 1_000), AFFILIATE_REGISTRATION_FEE / 2);
+}
+
+#[test]
+fn fee_discount_schedule_validation() {
+    let too_many_tiers: Vec<(u64, u16)> = (0..=MAX_FEE_DISCOUNT_TIERS as u64).map(|balance| (balance, 100)).collect();
+
+    assert!(is_valid_fee_discount_schedule(&fee_discount_tiers(TIERS)));
+    assert!(is_valid_fee_discount_schedule(&[]));
+    assert!(is_valid_fee_discount_schedule(&fee_discount_tiers(&[(1, 10_000)])));
+    assert!(!is_valid_fee_discount_schedule(&fee_discount_tiers(&[(1, 10_001)])));
+    assert!(!is_valid_fee_discount_schedule(&fee_discount_tiers(&[(10, 1_000), (10, 2_000)])));
+    assert!(!is_valid_fee_discount_schedule(&fee_discount_tiers(&[(10, 1_000), (1, 2_000)])));
+    assert!(!is_valid_fee_discount_schedule(&fee_discount_tiers(&too_many_tiers)));
+    assert!(is_valid_fee_discount_schedule(&fee_discount_tiers(&too_many_tiers[1..])));
+}
+
+#[tokio::test]
+async fn set_fee_discount_tiers_creates_then_updates_config() {
+    let mut program_test = program_test();
+    let admin = add_admin(&mut program_test);
+    let (affiliate, project_owner, candy_machine_id, spaf_token_account) = add_spaf_holder_without_fee_discount_config(&mut program_test, 1_000);
+    let mut context = program_test.start_with_context().await;
+
+    let events = process_with_events(
+        &mut context,
+        &[set_fee_discount_tiers_instruction(&admin.pubkey(), TIERS)],
+        &[&admin],
+    ).await;
+
+    let state = get_fee_discount_config_state(&mut context).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(state.discriminator, FeeDiscountConfigAccountState::DISCRIMINATOR);
+    assert_eq!(state.data.tiers, fee_discount_tiers(TIERS));
+    assert_eq!(state.data.created_at, state.data.updated_at);
+    assert_eq!(
+        get_balance(&mut context, &fee_discount_config_pda()).await,
+        Rent::default().minimum_balance(FeeDiscountConfigAccountState::LENGTH),
+    );
+    assert!(matches!(
+        events.first(),
+        Some(CandyMachineAffiliatesEvent::FeeDiscountTiersSet(set)) if set.tiers == fee_discount_tiers(TIERS)
+    ));
+
+    let max_tiers: Vec<(u64, u16)> = (1..=MAX_FEE_DISCOUNT_TIERS as u64).map(|balance| (balance * 100, 1_000)).collect();
+
+    process(
+        &mut context,
+        &[set_fee_discount_tiers_instruction(&admin.pubkey(), &max_tiers)],
+        &[&admin],
+    ).await.unwrap();
+
+    assert_eq!(get_fee_discount_config_state(&mut context).await.data.tiers, fee_discount_tiers(&max_tiers));
+
+    process(
+        &mut context,
+        &[set_fee_discount_tiers_instruction(&admin.pubkey(), &[(1_000, 10_000)])],
+        &[&admin],
+    ).await.unwrap();
+
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    process(
+        &mut context,
+        &[register_affiliate_with_spaf_discount_instruction(
+            &affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            None,
+            None,
+            &spaf_token_account,
+        )],
+        &[&affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports);
+}
+
+#[tokio::test]
+async fn set_fee_discount_tiers_rejects_non_admin() {
+    let mut program_test = program_test();
+    let wallet = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[set_fee_discount_tiers_instruction(&wallet.pubkey(), TIERS)],
+        &[&wallet],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ActionNotAllowed);
+}
+
+#[tokio::test]
+async fn set_fee_discount_tiers_rejects_invalid_tiers() {
+    let mut program_test = program_test();
+    let admin = add_admin(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    for tiers in [&[(1, 10_001)][..], &[(10, 1_000), (10, 2_000)][..]] {
+        let result = process(
+            &mut context,
+            &[set_fee_discount_tiers_instruction(&admin.pubkey(), tiers)],
+            &[&admin],
+        ).await;
+
+        assert_program_error(result, CandyMachineAffiliatesError::InvalidFeeDiscountTiers);
+    }
+}
+
+#[tokio::test]
+async fn register_affiliate_without_fee_discount_config_pays_full_fee() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id, spaf_token_account) = add_spaf_holder_without_fee_discount_config(&mut program_test, 10_000);
+    let mut context = program_test.start_with_context().await;
+
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    process(
+        &mut context,
+        &[register_affiliate_with_spaf_discount_instruction(
+            &affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            None,
+            None,
+            &spaf_token_account,
+        )],
+        &[&affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports + AFFILIATE_REGISTRATION_FEE);
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_wrong_fee_discount_config_account() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id, spaf_token_account) = add_spaf_holder(&mut program_test, 1_000);
+    let other_config = Pubkey::new_unique();
+    add_program_account(
+        &mut program_test,
+        other_config,
+        borsh::to_vec(&fee_discount_config_state(&[(1, 10_000)])).unwrap(),
+        FeeDiscountConfigAccountState::LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_affiliate_with_spaf_discount_instruction(
+        &affiliate.pubkey(),
+        &project_owner,
+        &candy_machine_id,
+        None,
+        None,
+        &spaf_token_account,
+    );
+    instruction.accounts.last_mut().unwrap().pubkey = other_config;
+
+    let result = process(&mut context, &[instruction], &[&affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn register_affiliate_applies_spaf_discount() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id, spaf_token_account) = add_spaf_holder(&mut program_test, 1_000);
+    let mut context = program_test.start_with_context().await;
+
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    let events = process_with_events(
+        &mut context,
+        &[register_affiliate_with_spaf_discount_instruction(
+            &affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            None,
+            None,
+            &spaf_token_account,
+        )],
+        &[&affiliate],
+    ).await;
+
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports + AFFILIATE_REGISTRATION_FEE / 2);
+    assert!(matches!(
+        events.first(),
+        Some(CandyMachineAffiliatesEvent::AffiliateRegisteredV2(registered)) if registered.registration_fee == AFFILIATE_REGISTRATION_FEE / 2
+    ));
+}
+
+#[tokio::test]
+async fn get_project_capacity_reports_spaf_discounted_fee() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id, spaf_token_account) = add_spaf_holder(&mut program_test, 1_000);
+    let mut context = program_test.start_with_context().await;
+
+    let capacity: ProjectCapacity = simulate_view(
+        &mut context,
+        get_project_capacity_for_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, None, Some(&spaf_token_account)),
+    ).await.unwrap();
+
+    assert_eq!(capacity.registration_fee, AFFILIATE_REGISTRATION_FEE / 2);
+    assert!(capacity.meets_gating);
+
+    let capacity: ProjectCapacity = simulate_view(
+        &mut context,
+        get_project_capacity_for_affiliate_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, None, None),
+    ).await.unwrap();
+
+    assert_eq!(capacity.registration_fee, AFFILIATE_REGISTRATION_FEE);
+}
+
+#[tokio::test]
+async fn register_affiliate_below_first_tier_pays_full_fee() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id, spaf_token_account) = add_spaf_holder(&mut program_test, 99);
+    let mut context = program_test.start_with_context().await;
+
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    process(
+        &mut context,
+        &[register_affiliate_with_spaf_discount_instruction(
+            &affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            None,
+            None,
+            &spaf_token_account,
+        )],
+        &[&affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports + AFFILIATE_REGISTRATION_FEE);
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_invalid_spaf_token_account() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id, _) = add_spaf_holder(&mut program_test, 1_000);
+    let other_wallet_account = add_token_account(&mut program_test, &Pubkey::new_unique(), &spaf_mint(), 10_000);
+    let other_mint_account = add_token_account(&mut program_test, &affiliate.pubkey(), &Pubkey::new_unique(), 10_000);
+    let mut context = program_test.start_with_context().await;
+
+    for spaf_token_account in [other_wallet_account, other_mint_account] {
+        let result = process(
+            &mut context,
+            &[register_affiliate_with_spaf_discount_instruction(
+                &affiliate.pubkey(),
+                &project_owner,
+                &candy_machine_id,
+                None,
+                None,
+                &spaf_token_account,
+            )],
+            &[&affiliate],
+        ).await;
+
+        assert_program_error(result, CandyMachineAffiliatesError::InvalidSpafTokenAccount);
+    }
+}
+
+#[tokio::test]
+async fn register_affiliate_applies_spaf_discount_to_gated_project() {
+    let mut program_test = program_test();
+    let (affiliate, project_owner, candy_machine_id, spaf_token_account) = add_spaf_holder(&mut program_test, 100);
+    let gating_mint = add_mint(&mut program_test, &Pubkey::new_unique(), 1_000);
+    let gating_token_account = add_token_account(&mut program_test, &affiliate.pubkey(), &gating_mint, 10);
+
+    let mut state = project_state(&project_owner, &candy_machine_id, 1, 5, 0);
+    state.data.gating_mint = Some(gating_mint);
+    state.data.gating_min_amount = 10;
+    state.data.gating_burn = true;
+    add_project_account(&mut program_test, &state);
+
+    let mut context = program_test.start_with_context().await;
+
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    process(
+        &mut context,
+        &[register_affiliate_with_spaf_discount_instruction(
+            &affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            Some(&gating_token_account),
+            Some(&gating_mint),
+            &spaf_token_account,
+        )],
+        &[&affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_token_account(&mut context, &gating_token_account).await.amount, 0);
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports + AFFILIATE_REGISTRATION_FEE * 3 / 4);
+}
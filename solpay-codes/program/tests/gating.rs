@@ -0,0 +1,294 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    CandyMachineAffiliatesError,
+    ProjectAccountState,
+    ProjectCapacity,
+};
+use solana_program::instruction::InstructionError;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::ProgramTest;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use common::*;
+
+const GATING_MIN_AMOUNT: u64 = 100;
+
+struct GatedProject {
+    project_owner: Pubkey,
+    candy_machine_id: Pubkey,
+    gating_mint: Pubkey,
+}
+
+fn add_gated_project(program_test: &mut ProgramTest, gating_burn: bool) -> GatedProject {
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let gating_mint = add_mint(program_test, &Pubkey::new_unique(), 1_000);
+
+    let mut state = project_state(&project_owner, &candy_machine_id, 1, 5, 0);
+    state.data.gating_mint = Some(gating_mint);
+    state.data.gating_min_amount = GATING_MIN_AMOUNT;
+    state.data.gating_burn = gating_burn;
+    add_project_account(program_test, &state);
+
+    GatedProject { project_owner, candy_machine_id, gating_mint }
+}
+
+fn add_gated_affiliate(program_test: &mut ProgramTest, project: &GatedProject, amount: u64) -> (Keypair, Pubkey) {
+    let affiliate = add_wallet(program_test, 1);
+    let token_account = add_token_account(program_test, &affiliate.pubkey(), &project.gating_mint, amount);
+
+    (affiliate, token_account)
+}
+
+#[tokio::test]
+async fn set_project_gating_stores_settings() {
+    let mut program_test = program_test();
+    let project_owner = add_wallet(&mut program_test, 1);
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner.pubkey(), &candy_machine_id, 1, 5, 0));
+    let gating_mint = Pubkey::new_unique();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[set_project_gating_instruction(&project_owner.pubkey(), &candy_machine_id, Some(gating_mint), 50, true)],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &project_pda(&project_owner.pubkey(), &candy_machine_id)).await;
+
+    assert_eq!(state.data.gating_mint, Some(gating_mint));
+    assert_eq!(state.data.gating_min_amount, 50);
+    assert!(state.data.gating_burn);
+
+    process(
+        &mut context,
+        &[set_project_gating_instruction(&project_owner.pubkey(), &candy_machine_id, None, 0, false)],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &project_pda(&project_owner.pubkey(), &candy_machine_id)).await;
+
+    assert_eq!(state.data.gating_mint, None);
+    assert_eq!(state.data.gating_min_amount, 0);
+}
+
+#[tokio::test]
+async fn set_project_gating_requires_project_owner() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let other_wallet = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = set_project_gating_instruction(&project_owner, &candy_machine_id, Some(Pubkey::new_unique()), 1, false);
+    instruction.accounts[0].pubkey = other_wallet.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&other_wallet]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn set_project_gating_rejects_invalid_settings() {
+    let mut program_test = program_test();
+    let project_owner = add_wallet(&mut program_test, 1);
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner.pubkey(), &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    for (gating_mint, gating_min_amount, gating_burn) in [
+        (Some(Pubkey::new_unique()), 0, false),
+        (None, 1, false),
+        (None, 0, true),
+    ] {
+        let result = process(
+            &mut context,
+            &[set_project_gating_instruction(&project_owner.pubkey(), &candy_machine_id, gating_mint, gating_min_amount, gating_burn)],
+            &[&project_owner],
+        ).await;
+
+        assert_program_error(result, CandyMachineAffiliatesError::InvalidProjectGating);
+    }
+}
+
+#[tokio::test]
+async fn set_project_gating_resizes_older_project_accounts() {
+    let mut program_test = program_test();
+    let project_owner = add_wallet(&mut program_test, 1);
+    let candy_machine_id = Pubkey::new_unique();
+    let state = project_state(&project_owner.pubkey(), &candy_machine_id, 1, 5, 0);
+    let address = project_pda(&project_owner.pubkey(), &candy_machine_id);
+    // Layout without the gating fields
+    let previous_length = ProjectAccountState::LENGTH - (1 + 32) - 8 - 1;
+    add_program_account(&mut program_test, address, borsh::to_vec(&state).unwrap(), previous_length);
+    let gating_mint = Pubkey::new_unique();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[set_project_gating_instruction(&project_owner.pubkey(), &candy_machine_id, Some(gating_mint), 1, false)],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+
+    assert_eq!(account.data.len(), ProjectAccountState::LENGTH);
+    assert_eq!(account.lamports, solana_sdk::rent::Rent::default().minimum_balance(ProjectAccountState::LENGTH));
+    assert_eq!(get_project_state(&mut context, &address).await.data.gating_mint, Some(gating_mint));
+}
+
+#[tokio::test]
+async fn register_affiliate_checks_gating_token_balance() {
+    let mut program_test = program_test();
+    let project = add_gated_project(&mut program_test, false);
+    let (affiliate, token_account) = add_gated_affiliate(&mut program_test, &project, GATING_MIN_AMOUNT);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_with_gating_token_instruction(
+            &affiliate.pubkey(),
+            &project.project_owner,
+            &project.candy_machine_id,
+            &token_account,
+            None,
+        )],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let state = get_affiliate_state(&mut context, &affiliate_pda(&affiliate.pubkey(), &project.project_owner, &project.candy_machine_id)).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(get_token_account(&mut context, &token_account).await.amount, GATING_MIN_AMOUNT);
+}
+
+#[tokio::test]
+async fn get_project_capacity_reports_gating_of_affiliate() {
+    let mut program_test = program_test();
+    let project = add_gated_project(&mut program_test, false);
+    let (affiliate, token_account) = add_gated_affiliate(&mut program_test, &project, GATING_MIN_AMOUNT);
+    let (poor_affiliate, poor_token_account) = add_gated_affiliate(&mut program_test, &project, GATING_MIN_AMOUNT - 1);
+    let mut context = program_test.start_with_context().await;
+
+    for (affiliate, token_account, meets_gating) in [
+        (affiliate.pubkey(), Some(&token_account), true),
+        (poor_affiliate.pubkey(), Some(&poor_token_account), false),
+        (affiliate.pubkey(), None, false),
+    ] {
+        let capacity: ProjectCapacity = simulate_view(
+            &mut context,
+            get_project_capacity_for_affiliate_instruction(&affiliate, &project.project_owner, &project.candy_machine_id, token_account, None),
+        ).await.unwrap();
+
+        assert_eq!(capacity.meets_gating, meets_gating);
+    }
+
+    let capacity: ProjectCapacity = simulate_view(
+        &mut context,
+        get_project_capacity_instruction(&project.project_owner, &project.candy_machine_id),
+    ).await.unwrap();
+
+    assert!(!capacity.meets_gating);
+
+    let result = simulate_view::<ProjectCapacity>(
+        &mut context,
+        get_project_capacity_for_affiliate_instruction(&poor_affiliate.pubkey(), &project.project_owner, &project.candy_machine_id, Some(&token_account), None),
+    ).await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::Custom(CandyMachineAffiliatesError::InvalidGatingTokenAccount as u32)),
+    );
+}
+
+#[tokio::test]
+async fn register_affiliate_burns_gating_tokens() {
+    let mut program_test = program_test();
+    let project = add_gated_project(&mut program_test, true);
+    let (affiliate, token_account) = add_gated_affiliate(&mut program_test, &project, 150);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_affiliate_with_gating_token_instruction(
+            &affiliate.pubkey(),
+            &project.project_owner,
+            &project.candy_machine_id,
+            &token_account,
+            Some(&project.gating_mint),
+        )],
+        &[&affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_token_account(&mut context, &token_account).await.amount, 150 - GATING_MIN_AMOUNT);
+    assert_eq!(get_mint(&mut context, &project.gating_mint).await.supply, 1_000 - GATING_MIN_AMOUNT);
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_low_gating_token_balance() {
+    let mut program_test = program_test();
+    let project = add_gated_project(&mut program_test, false);
+    let (affiliate, token_account) = add_gated_affiliate(&mut program_test, &project, GATING_MIN_AMOUNT - 1);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[register_affiliate_with_gating_token_instruction(
+            &affiliate.pubkey(),
+            &project.project_owner,
+            &project.candy_machine_id,
+            &token_account,
+            None,
+        )],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::GatingTokenBalanceNotEnough);
+}
+
+#[tokio::test]
+async fn register_affiliate_rejects_invalid_gating_token_account() {
+    let mut program_test = program_test();
+    let project = add_gated_project(&mut program_test, false);
+    let affiliate = add_wallet(&mut program_test, 1);
+    let other_wallet_account = add_token_account(&mut program_test, &Pubkey::new_unique(), &project.gating_mint, GATING_MIN_AMOUNT);
+    let other_mint_account = add_token_account(&mut program_test, &affiliate.pubkey(), &Pubkey::new_unique(), GATING_MIN_AMOUNT);
+    let mut context = program_test.start_with_context().await;
+
+    for token_account in [other_wallet_account, other_mint_account] {
+        let result = process(
+            &mut context,
+            &[register_affiliate_with_gating_token_instruction(
+                &affiliate.pubkey(),
+                &project.project_owner,
+                &project.candy_machine_id,
+                &token_account,
+                None,
+            )],
+            &[&affiliate],
+        ).await;
+
+        assert_program_error(result, CandyMachineAffiliatesError::InvalidGatingTokenAccount);
+    }
+}
+
+#[tokio::test]
+async fn register_affiliate_requires_gating_token_account() {
+    let mut program_test = program_test();
+    let project = add_gated_project(&mut program_test, false);
+    let affiliate = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &project.project_owner, &project.candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_instruction_error(result, InstructionError::NotEnoughAccountKeys);
+}
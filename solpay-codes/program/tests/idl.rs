@@ -0,0 +1,358 @@
+use std::fs;
+use borsh::BorshDeserialize;
+use candy_machine_affiliates::idl::idl;
+use candy_machine_affiliates::instruction::{
+    claim_referral_code,
+    close_affiliate_account,
+    close_affiliate_account_with_referral_code,
+    close_project,
+    close_project_with_affiliate_index,
+    consume_promo_code,
+    create_promo_code,
+    get_project_capacity,
+    get_project_capacity_for_affiliate,
+    get_redeemable_amount,
+    add_affiliate_to_profile,
+    index_affiliate,
+    initialize_protocol_stats,
+    link_candy_machine_project,
+    mint_with_referral,
+    redeem_reward,
+    redeem_reward_with_milestone_reward,
+    register_affiliate,
+    register_affiliate_with_gating_token,
+    register_affiliate_with_spaf_discount,
+    register_project,
+    register_project_v2,
+    set_fee_discount_tiers,
+    set_project_gating,
+    update_project,
+    update_project_v2,
+    CandyMachineAffiliatesInstruction,
+};
+use candy_machine_affiliates::{
+    AffiliateAccountState,
+    AffiliateIndexPageAccountState,
+    AffiliateProfileAccountState,
+    CandyMachineAffiliatesError,
+    CandyMachineAffiliatesEvent,
+    CandyMachineProjectAccountState,
+    FeeDiscountConfigAccountState,
+    ProjectAccountState,
+    ProjectCapacity,
+    PromoCodeAccountState,
+    PromoCodeDiscount,
+    PromoCodeUsageAccountState,
+    RedeemableAmount,
+    ReferralCodeAccountState,
+};
+use serde_json::Value;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+const IDL_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/idl/candy_machine_affiliates.json");
+
+fn snake_case(name: &str) -> String {
+    name.chars().fold(String::new(), |mut snake, c| {
+        if c.is_uppercase() {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+        snake
+    })
+}
+
+fn pascal_case(name: &str) -> String {
+    let mut chars = name.chars();
+
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn find<'a>(idl: &'a Value, section: &str, name: &str) -> &'a Value {
+    idl[section].as_array().unwrap()
+        .iter()
+        .find(|item| item["name"] == name)
+        .unwrap_or_else(|| panic!("{} missing from IDL {}", name, section))
+}
+
+fn defined<'a>(idl: &'a Value, name: &str) -> &'a Value {
+    idl["accounts"].as_array().unwrap()
+        .iter()
+        .chain(idl["types"].as_array().unwrap())
+        .find(|item| item["name"] == name)
+        .map(|item| &item["type"])
+        .unwrap_or_else(|| panic!("Type {} missing from IDL", name))
+}
+
+// Borsh encodes a distinct sample value of an IDL type following only the IDL, along with the
+// `Debug` output the Rust type holding that value is expected to produce
+struct Sampler<'a> {
+    idl: &'a Value,
+    counter: u8,
+}
+
+impl Sampler<'_> {
+    fn fields(&mut self, fields: &Value) -> (Vec<u8>, Vec<String>) {
+        let mut data = Vec::new();
+        let mut debug = Vec::new();
+
+        for field in fields.as_array().unwrap() {
+            let (bytes, value) = self.sample(&field["type"]);
+
+            data.extend(bytes);
+            debug.push(format!("{}: {}", snake_case(field["name"].as_str().unwrap()), value));
+        }
+
+        (data, debug)
+    }
+
+    fn sample(&mut self, ty: &Value) -> (Vec<u8>, String) {
+        self.counter += 1;
+        let n = self.counter;
+
+        if let Some(name) = ty["defined"].as_str() {
+            let definition = defined(self.idl, name);
+            assert_eq!(definition["kind"], "struct", "{}", name);
+            let (data, debug) = self.fields(&definition["fields"]);
+
+            return (data, format!("{} {{ {} }}", name, debug.join(", ")));
+        }
+
+        if !ty["option"].is_null() {
+            let (data, debug) = self.sample(&ty["option"]);
+
+            return ([vec![1], data].concat(), format!("Some({})", debug));
+        }
+
+        if !ty["array"].is_null() {
+            let length = ty["array"][1].as_u64().unwrap();
+            let (data, debug): (Vec<_>, Vec<_>) = (0..length).map(|_| self.sample(&ty["array"][0])).unzip();
+
+            return (data.concat(), format!("[{}]", debug.join(", ")));
+        }
+
+        if !ty["vec"].is_null() {
+            let (data, debug) = self.sample(&ty["vec"]);
+
+            return ([1u32.to_le_bytes().to_vec(), data].concat(), format!("[{}]", debug));
+        }
+
+        match ty.as_str().unwrap() {
+            "bool" => (vec![1], "true".to_string()),
+            "u8" => (vec![n], format!("{}", n)),
+            "u16" => ((n as u16).to_le_bytes().to_vec(), format!("{}", n)),
+            "u32" => ((n as u32).to_le_bytes().to_vec(), format!("{}", n)),
+            "u64" => ((n as u64).to_le_bytes().to_vec(), format!("{}", n)),
+            "i64" => ((n as i64).to_le_bytes().to_vec(), format!("{}", n)),
+            "f64" => ((n as f64).to_le_bytes().to_vec(), format!("{:?}", n as f64)),
+            "string" => (borsh::to_vec(&format!("s{}", n)).unwrap(), format!("{:?}", format!("s{}", n))),
+            "bytes" => (borsh::to_vec(&vec![n]).unwrap(), format!("{:?}", vec![n])),
+            "publicKey" => {
+                let pubkey = Pubkey::new_unique();
+                (pubkey.to_bytes().to_vec(), format!("{:?}", pubkey))
+            }
+            other => panic!("Unsupported IDL type {}", other),
+        }
+    }
+}
+
+#[test]
+fn idl_file_is_up_to_date() {
+    let generated = serde_json::to_string_pretty(&idl()).unwrap() + "\n";
+
+    if std::env::var_os("SOLPAY_UPDATE_IDL").is_some() {
+        fs::write(IDL_FILE, &generated).unwrap();
+    }
+
+    assert_eq!(
+        fs::read_to_string(IDL_FILE).unwrap(), generated,
+        "IDL file is stale, regenerate it with SOLPAY_UPDATE_IDL=1 cargo test --features localnet --test idl",
+    );
+}
+
+#[test]
+fn instruction_discriminants_and_args_match_unpack() {
+    let idl = idl();
+    let instructions = idl["instructions"].as_array().unwrap();
+
+    for instruction in instructions {
+        let name = instruction["name"].as_str().unwrap();
+        let mut sampler = Sampler { idl: &idl, counter: 0 };
+        let (args, debug) = sampler.fields(&instruction["args"]);
+        let mut data = vec![instruction["discriminant"]["value"].as_u64().unwrap() as u8];
+        data.extend(args);
+
+        let unpacked = CandyMachineAffiliatesInstruction::unpack(&data).unwrap();
+
+        assert_eq!(format!("{:?}", unpacked), format!("{} {{ {} }}", pascal_case(name), debug.join(", ")));
+        assert_eq!(unpacked.pack(), data, "{}", name);
+    }
+
+    // Discriminants are contiguous and nothing past them unpacks
+    let next = instructions.len() as u8;
+    for (i, instruction) in instructions.iter().enumerate() {
+        assert_eq!(instruction["discriminant"]["value"], i);
+    }
+    assert!(matches!(CandyMachineAffiliatesInstruction::unpack(&[next]), Err(ProgramError::InvalidInstructionData)));
+}
+
+#[test]
+fn instruction_accounts_match_builders() {
+    let idl = idl();
+    let program_id = candy_machine_affiliates::id();
+    let key = Pubkey::new_unique;
+
+    let builders: Vec<(&str, Instruction)> = vec![
+        ("registerProject", register_project(&program_id, &key(), &key(), &key(), &key(), key(), 1.0, 1, 1, String::new())),
+        ("updateProject", update_project(&program_id, &key(), &key(), key(), key(), 1.0, 1, 1, String::new())),
+        ("registerProjectV2", register_project_v2(&program_id, &key(), &key(), &key(), &key(), key(), 1.0, 1, 1, String::new())),
+        ("updateProjectV2", update_project_v2(&program_id, &key(), &key(), key(), key(), 1.0, 1, 1, String::new())),
+        ("closeProject", close_project(&program_id, &key(), &key(), &key(), &key(), key(), key())),
+        ("closeProject", close_project_with_affiliate_index(&program_id, &key(), &key(), &key(), &key(), &[key()], key(), key())),
+        ("registerAffiliate", register_affiliate(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), key(), key())),
+        ("registerAffiliate", register_affiliate_with_gating_token(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), &key(), None, key(), key())),
+        ("registerAffiliate", register_affiliate_with_gating_token(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), &key(), Some(&key()), key(), key())),
+        ("registerAffiliate", register_affiliate_with_spaf_discount(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), None, None, &key(), &key(), key(), key())),
+        ("registerAffiliate", register_affiliate_with_spaf_discount(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), Some(&key()), Some(&key()), &key(), &key(), key(), key())),
+        ("redeemReward", redeem_reward(&program_id, &key(), &key(), &key(), &key(), key(), key())),
+        ("redeemReward", redeem_reward_with_milestone_reward(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), key(), key())),
+        ("closeAffiliateAccount", close_affiliate_account(&program_id, &key(), &key(), &key(), &key(), &key(), Some(&key()), &key(), key(), key(), key())),
+        ("closeAffiliateAccount", close_affiliate_account(&program_id, &key(), &key(), &key(), &key(), &key(), None, &key(), key(), key(), key())),
+        ("closeAffiliateAccount", close_affiliate_account_with_referral_code(&program_id, &key(), &key(), &key(), &key(), &key(), Some(&key()), &key(), &key(), key(), key(), key())),
+        ("getRedeemableAmount", get_redeemable_amount(&program_id, &key(), &key(), key(), key(), key())),
+        ("getProjectCapacity", get_project_capacity(&program_id, &key(), key(), key())),
+        ("getProjectCapacity", get_project_capacity_for_affiliate(&program_id, &key(), &key(), None, None, &key(), key(), key())),
+        ("getProjectCapacity", get_project_capacity_for_affiliate(&program_id, &key(), &key(), Some(&key()), Some(&key()), &key(), key(), key())),
+        ("claimReferralCode", claim_referral_code(&program_id, &key(), &key(), &key(), &key(), None, key(), key(), String::new())),
+        ("claimReferralCode", claim_referral_code(&program_id, &key(), &key(), &key(), &key(), Some(&key()), key(), key(), String::new())),
+        ("createPromoCode", create_promo_code(&program_id, &key(), &key(), &key(), None, key(), String::new(), None, 1, 1, 1, 0, 0)),
+        ("createPromoCode", create_promo_code(&program_id, &key(), &key(), &key(), Some(&key()), key(), String::new(), Some(key()), 1, 1, 1, 0, 0)),
+        ("consumePromoCode", consume_promo_code(&program_id, &key(), &key(), &key(), &key(), key(), key(), String::new())),
+        ("setProjectGating", set_project_gating(&program_id, &key(), &key(), key(), Some(key()), 1, false)),
+        ("mintWithReferral", mint_with_referral(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), vec![], key(), key(), key(), vec![])),
+        ("initializeProtocolStats", initialize_protocol_stats(&program_id, &key(), &key(), 0, 0, 0, 0, 0)),
+        ("indexAffiliate", index_affiliate(&program_id, &key(), &key(), &key(), &key(), key(), key(), key())),
+        ("addAffiliateToProfile", add_affiliate_to_profile(&program_id, &key(), &key(), &key(), &key(), key(), key(), key())),
+        ("linkCandyMachineProject", link_candy_machine_project(&program_id, &key(), &key(), &key(), key(), key())),
+        ("setFeeDiscountTiers", set_fee_discount_tiers(&program_id, &key(), &key(), vec![])),
+    ];
+
+    for instruction in idl["instructions"].as_array().unwrap() {
+        assert!(builders.iter().any(|(name, _)| instruction["name"] == *name), "No builder for {}", instruction["name"]);
+    }
+
+    for (name, built) in builders {
+        let instruction = find(&idl, "instructions", name);
+        let accounts = instruction["accounts"].as_array().unwrap();
+        let required = accounts.iter().take_while(|account| account["optional"].is_null()).count();
+
+        assert_eq!(built.data[0] as u64, instruction["discriminant"]["value"].as_u64().unwrap(), "{}", name);
+        assert!(built.accounts.len() >= required && built.accounts.len() <= accounts.len(), "{}", name);
+        assert!(accounts[required..].iter().all(|account| account["optional"] == true), "{}", name);
+
+        // The program id stands in for optional accounts left out before a passed one
+        for (meta, account) in built.accounts.iter().zip(accounts).filter(|(meta, _)| meta.pubkey != program_id) {
+            assert_eq!(meta.is_writable, account["isMut"], "{} {}", name, account["name"]);
+            assert_eq!(meta.is_signer, account["isSigner"], "{} {}", name, account["name"]);
+        }
+    }
+}
+
+#[test]
+fn account_layouts_match_state() {
+    let idl = idl();
+
+    let mut sampler = Sampler { idl: &idl, counter: 0 };
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "ProjectAccountState" }));
+    let state = ProjectAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "AffiliateAccountState" }));
+    let state = AffiliateAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "ReferralCodeAccountState" }));
+    let state = ReferralCodeAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "PromoCodeAccountState" }));
+    let state = PromoCodeAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "PromoCodeUsageAccountState" }));
+    let state = PromoCodeUsageAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "AffiliateIndexPageAccountState" }));
+    let state = AffiliateIndexPageAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "AffiliateProfileAccountState" }));
+    let state = AffiliateProfileAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "CandyMachineProjectAccountState" }));
+    let state = CandyMachineProjectAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+
+    let (data, debug) = sampler.sample(&serde_json::json!({ "defined": "FeeDiscountConfigAccountState" }));
+    let state = FeeDiscountConfigAccountState::try_from_slice(&data).unwrap();
+    assert_eq!(format!("{:?}", state), debug);
+}
+
+#[test]
+fn view_layouts_match_return_data() {
+    let idl = idl();
+    let mut sampler = Sampler { idl: &idl, counter: 0 };
+
+    for instruction in idl["instructions"].as_array().unwrap() {
+        if instruction["returns"].is_null() {
+            continue;
+        }
+
+        let (data, debug) = sampler.sample(&instruction["returns"]);
+        let decoded = match instruction["returns"]["defined"].as_str().unwrap() {
+            "RedeemableAmount" => format!("{:?}", RedeemableAmount::try_from_slice(&data).unwrap()),
+            "ProjectCapacity" => format!("{:?}", ProjectCapacity::try_from_slice(&data).unwrap()),
+            "PromoCodeDiscount" => format!("{:?}", PromoCodeDiscount::try_from_slice(&data).unwrap()),
+            other => panic!("Unknown view result {}", other),
+        };
+
+        assert_eq!(decoded, debug);
+    }
+}
+
+#[test]
+fn event_layouts_match_events() {
+    let idl = idl();
+    let variants = defined(&idl, "CandyMachineAffiliatesEvent")["variants"].as_array().unwrap();
+
+    for (index, variant) in variants.iter().enumerate() {
+        let mut sampler = Sampler { idl: &idl, counter: 0 };
+        let (fields, debug) = sampler.sample(&variant["fields"][0]);
+        let mut data = vec![index as u8];
+        data.extend(fields);
+
+        let event = CandyMachineAffiliatesEvent::try_from_slice(&data).unwrap();
+        assert_eq!(format!("{:?}", event), format!("{}({})", variant["name"].as_str().unwrap(), debug));
+    }
+
+    assert!(CandyMachineAffiliatesEvent::try_from_slice(&[variants.len() as u8]).is_err());
+}
+
+#[test]
+fn errors_match_error_codes() {
+    let idl = idl();
+    let errors = idl["errors"].as_array().unwrap();
+
+    for (code, error) in errors.iter().enumerate() {
+        assert_eq!(error["code"], code);
+    }
+
+    // The last listed error has the highest code
+    let last = CandyMachineAffiliatesError::InvalidFeeDiscountTiers;
+    assert_eq!(errors.last().unwrap()["name"], format!("{:?}", last));
+    assert_eq!(errors.last().unwrap()["msg"], last.to_string());
+    assert_eq!(ProgramError::from(last), ProgramError::Custom(errors.len() as u32 - 1));
+}
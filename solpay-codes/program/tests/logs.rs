@@ -0,0 +1,180 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::instruction;
+use solana_program::instruction::Instruction;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use common::*;
+
+// SBF build of the program with the `verbose-logs` feature, next to the default one in `SBF_OUT_DIR`
+const VERBOSE_LOGS_PROGRAM_NAME: &str = "candy_machine_affiliates_verbose_logs";
+
+// Runs every instruction once and returns the program logs of each of them
+async fn process_all_instructions(
+    context: &mut ProgramTestContext,
+    owner: &Keypair,
+    affiliate: &Keypair,
+    candy_machine_id: Pubkey,
+) -> Vec<Vec<String>> {
+    let admin = admin();
+    let project_address = project_pda(&owner.pubkey(), &candy_machine_id);
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id);
+    let payer = context.payer.pubkey();
+
+    let instructions: Vec<(Vec<Instruction>, &Keypair)> = vec![
+        (vec![register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)], owner),
+        (
+            vec![instruction::update_project(
+                &candy_machine_affiliates::id(),
+                &admin.pubkey(),
+                &project_address,
+                owner.pubkey(),
+                candy_machine_id,
+                15.0,
+                1,
+                8,
+                "Updated title".to_string(),
+            )],
+            &admin,
+        ),
+        (vec![register_affiliate_instruction(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)], affiliate),
+        (
+            vec![
+                system_instruction::transfer(&payer, &affiliate_address, LAMPORTS_PER_SOL),
+                redeem_reward_instruction(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id),
+            ],
+            affiliate,
+        ),
+        (vec![close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)], &admin),
+        (
+            vec![instruction::close_project(
+                &candy_machine_affiliates::id(),
+                &admin.pubkey(),
+                &project_address,
+                &protocol_stats_pda(),
+                &candy_machine_project_pda(&candy_machine_id),
+                owner.pubkey(),
+                candy_machine_id,
+            )],
+            &admin,
+        ),
+    ];
+
+    let mut all_logs = Vec::new();
+
+    for (instructions, signer) in instructions {
+        let (result, logs) = process_with_logs(context, &instructions, &[signer]).await;
+
+        result.unwrap();
+        all_logs.push(logs);
+    }
+
+    all_logs
+}
+
+#[cfg(not(feature = "verbose-logs"))]
+#[tokio::test]
+async fn instructions_log_a_single_outcome_line() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let affiliate = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let all_logs = process_all_instructions(&mut context, &owner, &affiliate, candy_machine_id).await;
+
+    assert_eq!(all_logs, vec![
+        vec!["Project registered.".to_string()],
+        vec!["Project updated.".to_string()],
+        vec!["Affiliate registered.".to_string()],
+        vec!["Reward redeemed: 1 SOL.".to_string()],
+        vec!["Affiliate account closed.".to_string()],
+        vec!["Project account closed.".to_string()],
+    ]);
+}
+
+#[cfg(not(feature = "verbose-logs"))]
+#[tokio::test]
+async fn failed_instruction_logs_only_the_error() {
+    use candy_machine_affiliates::CandyMachineAffiliatesError;
+    use solana_program::instruction::InstructionError;
+    use solana_sdk::transaction::TransactionError;
+
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let (result, logs) = process_with_logs(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 0, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(0, InstructionError::Custom(CandyMachineAffiliatesError::ProjectTitleTooLong as u32))),
+    );
+    assert_eq!(logs, vec!["Invalid project max affiliate count".to_string()]);
+}
+
+#[cfg(feature = "verbose-logs")]
+#[tokio::test]
+async fn verbose_logs_log_every_step() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let affiliate = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    for logs in process_all_instructions(&mut context, &owner, &affiliate, candy_machine_id).await {
+        assert!(logs.len() > 1, "{:?}", logs);
+    }
+}
+
+// Compute units the SBF build `program_name` consumes registering a project
+async fn register_project_compute_units(program_name: &str) -> u64 {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program(program_name, candy_machine_affiliates::id(), None);
+    add_protocol_stats_account(&mut program_test, &protocol_stats_state(0, 0));
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &owner],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+
+    result.result.unwrap();
+    result.metadata.unwrap().compute_units_consumed
+}
+
+// Needs both SBF builds, the native processor used by the other tests is not metered:
+//   cargo build-sbf --features localnet,verbose-logs
+//   mv ../target/deploy/candy_machine_affiliates.so ../target/deploy/candy_machine_affiliates_verbose_logs.so
+//   cargo build-sbf --features localnet
+//   SBF_OUT_DIR=../target/deploy cargo test --features localnet --test logs -- --ignored
+#[tokio::test]
+#[ignore = "needs the default and verbose-logs SBF builds in SBF_OUT_DIR"]
+async fn default_build_uses_fewer_compute_units_than_verbose_logs() {
+    let default_compute_units = register_project_compute_units("candy_machine_affiliates").await;
+    let verbose_compute_units = register_project_compute_units(VERBOSE_LOGS_PROGRAM_NAME).await;
+
+    assert!(
+        default_compute_units < verbose_compute_units,
+        "default build used {} compute units, verbose-logs build {}",
+        default_compute_units,
+        verbose_compute_units,
+    );
+}
@@ -0,0 +1,216 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use std::str::FromStr;
+use candy_machine_affiliates::{
+    milestone_reward,
+    AffiliateAccountState,
+    CandyMachineAffiliatesError,
+    CandyMachineAffiliatesEvent,
+    SPAF_MINT_PUBKEY,
+};
+use candy_machine_affiliates::events::MilestoneRewardMinted;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use common::*;
+
+struct MilestoneTest {
+    affiliate: Keypair,
+    project_owner: Pubkey,
+    candy_machine_id: Pubkey,
+    spaf_mint: Pubkey,
+    reward_token_account: Pubkey,
+}
+
+// Adds the SPAF mint, owned by the mint authority PDA, and an affiliate with a SPAF token account
+fn add_milestone_test(program_test: &mut ProgramTest, total_redeemed_amount_in_sol: u32) -> MilestoneTest {
+    let spaf_mint = add_mint_at(program_test, spaf_mint(), &spaf_mint_authority_pda(), 0);
+    let affiliate = add_wallet(program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let reward_token_account = add_token_account(program_test, &affiliate.pubkey(), &spaf_mint, 0);
+
+    let mut state = affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    state.data.total_redeemed_amount_in_sol = total_redeemed_amount_in_sol;
+
+    add_project_account(program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    add_program_account(
+        program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        borsh::to_vec(&state).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+
+    MilestoneTest { affiliate, project_owner, candy_machine_id, spaf_mint, reward_token_account }
+}
+
+fn spaf_mint() -> Pubkey {
+    Pubkey::from_str(SPAF_MINT_PUBKEY.unwrap()).unwrap()
+}
+
+async fn fund_reward(context: &mut ProgramTestContext, test: &MilestoneTest) {
+    let payer = context.payer.pubkey();
+    let affiliate_address = affiliate_pda(&test.affiliate.pubkey(), &test.project_owner, &test.candy_machine_id);
+
+    process(
+        context,
+        &[system_instruction::transfer(&payer, &affiliate_address, LAMPORTS_PER_SOL)],
+        &[],
+    ).await.unwrap();
+}
+
+#[test]
+fn milestone_reward_sums_crossed_milestones() {
+    assert_eq!(milestone_reward(0, 0), 0);
+    assert_eq!(milestone_reward(0, 1), 10);
+    assert_eq!(milestone_reward(1, 4), 0);
+    assert_eq!(milestone_reward(4, 5), 50);
+    assert_eq!(milestone_reward(0, 10), 160);
+    assert_eq!(milestone_reward(10, 100), 0);
+}
+
+#[tokio::test]
+async fn redeem_reward_mints_milestone_reward() {
+    let mut program_test = program_test();
+    let test = add_milestone_test(&mut program_test, 0);
+    let mut context = program_test.start_with_context().await;
+    fund_reward(&mut context, &test).await;
+
+    let events = process_with_events(
+        &mut context,
+        &[redeem_reward_with_milestone_reward_instruction(
+            &test.affiliate.pubkey(),
+            &test.project_owner,
+            &test.candy_machine_id,
+            &test.reward_token_account,
+            &test.spaf_mint,
+        )],
+        &[&test.affiliate],
+    ).await;
+
+    assert_eq!(get_token_account(&mut context, &test.reward_token_account).await.amount, 10);
+    assert_eq!(get_mint(&mut context, &test.spaf_mint).await.supply, 10);
+    assert_eq!(events.last(), Some(&CandyMachineAffiliatesEvent::MilestoneRewardMinted(MilestoneRewardMinted {
+        affiliate_account: affiliate_pda(&test.affiliate.pubkey(), &test.project_owner, &test.candy_machine_id),
+        affiliate_pubkey: test.affiliate.pubkey(),
+        token_account: test.reward_token_account,
+        amount: 10,
+        total_redeemed_amount_in_sol: 1,
+    })));
+}
+
+#[tokio::test]
+async fn redeem_reward_mints_nothing_between_milestones() {
+    let mut program_test = program_test();
+    let test = add_milestone_test(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+    fund_reward(&mut context, &test).await;
+
+    process(
+        &mut context,
+        &[redeem_reward_with_milestone_reward_instruction(
+            &test.affiliate.pubkey(),
+            &test.project_owner,
+            &test.candy_machine_id,
+            &test.reward_token_account,
+            &test.spaf_mint,
+        )],
+        &[&test.affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_token_account(&mut context, &test.reward_token_account).await.amount, 0);
+}
+
+#[tokio::test]
+async fn redeem_reward_keeps_milestone_reward_pending_without_token_account() {
+    let mut program_test = program_test();
+    let test = add_milestone_test(&mut program_test, 0);
+    let mut context = program_test.start_with_context().await;
+    fund_reward(&mut context, &test).await;
+
+    let affiliate_address = affiliate_pda(&test.affiliate.pubkey(), &test.project_owner, &test.candy_machine_id);
+
+    process(
+        &mut context,
+        &[redeem_reward_instruction(&test.affiliate.pubkey(), &test.project_owner, &test.candy_machine_id)],
+        &[&test.affiliate],
+    ).await.unwrap();
+
+    let state = get_affiliate_state(&mut context, &affiliate_address).await;
+
+    assert_eq!(get_mint(&mut context, &test.spaf_mint).await.supply, 0);
+    assert_eq!(state.data.total_redeemed_amount_in_sol, 1);
+    assert_eq!(state.data.pending_milestone_reward, 10);
+
+    fund_reward(&mut context, &test).await;
+
+    let events = process_with_events(
+        &mut context,
+        &[redeem_reward_with_milestone_reward_instruction(
+            &test.affiliate.pubkey(),
+            &test.project_owner,
+            &test.candy_machine_id,
+            &test.reward_token_account,
+            &test.spaf_mint,
+        )],
+        &[&test.affiliate],
+    ).await;
+
+    assert_eq!(get_token_account(&mut context, &test.reward_token_account).await.amount, 10);
+    assert_eq!(get_affiliate_state(&mut context, &affiliate_address).await.data.pending_milestone_reward, 0);
+    assert!(events.iter().any(|event| matches!(
+        event,
+        CandyMachineAffiliatesEvent::MilestoneRewardMinted(minted) if minted.amount == 10 && minted.total_redeemed_amount_in_sol == 2
+    )));
+}
+
+#[tokio::test]
+async fn redeem_reward_rejects_invalid_reward_token_account() {
+    let mut program_test = program_test();
+    let test = add_milestone_test(&mut program_test, 0);
+    let other_wallet_account = add_token_account(&mut program_test, &Pubkey::new_unique(), &test.spaf_mint, 0);
+    let other_mint_account = add_token_account(&mut program_test, &test.affiliate.pubkey(), &Pubkey::new_unique(), 0);
+    let mut context = program_test.start_with_context().await;
+    fund_reward(&mut context, &test).await;
+
+    for token_account in [other_wallet_account, other_mint_account] {
+        let result = process(
+            &mut context,
+            &[redeem_reward_with_milestone_reward_instruction(
+                &test.affiliate.pubkey(),
+                &test.project_owner,
+                &test.candy_machine_id,
+                &token_account,
+                &test.spaf_mint,
+            )],
+            &[&test.affiliate],
+        ).await;
+
+        assert_program_error(result, CandyMachineAffiliatesError::InvalidRewardTokenAccount);
+    }
+}
+
+#[tokio::test]
+async fn redeem_reward_rejects_wrong_spaf_mint_authority() {
+    let mut program_test = program_test();
+    let test = add_milestone_test(&mut program_test, 0);
+    let mut context = program_test.start_with_context().await;
+    fund_reward(&mut context, &test).await;
+
+    let mut instruction = redeem_reward_with_milestone_reward_instruction(
+        &test.affiliate.pubkey(),
+        &test.project_owner,
+        &test.candy_machine_id,
+        &test.reward_token_account,
+        &test.spaf_mint,
+    );
+    instruction.accounts[7].pubkey = Pubkey::new_unique();
+
+    let result = process(&mut context, &[instruction], &[&test.affiliate]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
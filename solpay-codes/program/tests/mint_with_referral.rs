@@ -0,0 +1,543 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    AffiliateAccountState,
+    AffiliateProfileAccountState,
+    AffiliateProfileAccountStateData,
+    CandyMachineAffiliatesError,
+    CandyMachineAffiliatesEvent,
+};
+use candy_machine_affiliates::candy_machine::{
+    CANDY_GUARD_GUARDS_OFFSET,
+    CANDY_GUARD_MINT_V2_DISCRIMINATOR,
+    CANDY_GUARD_PROGRAM_ID,
+    CANDY_MACHINE_V2_MINT_NFT_DISCRIMINATOR,
+    CANDY_MACHINE_V2_PROGRAM_ID,
+    CANDY_MACHINE_V3_PROGRAM_ID,
+};
+use borsh::BorshDeserialize;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, InstructionError};
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::program::invoke;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+use common::*;
+
+// Stands in for the Candy Machine v2 `mint_nft` instruction: the payer pays the price following the
+// creator bump in the instruction data to the wallet of the candy machine
+fn candy_machine_v2_stub(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (discriminator, args) = data.split_at(8);
+    assert_eq!(discriminator, CANDY_MACHINE_V2_MINT_NFT_DISCRIMINATOR);
+    let (_creator_bump, price) = <(u8, u64)>::try_from_slice(args)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let _candy_machine = next_account_info(account_info_iter)?;
+    let _candy_machine_creator = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
+    let wallet = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    invoke(
+        &system_instruction::transfer(payer.key, wallet.key, price),
+        &[payer.clone(), wallet.clone(), system_program.clone()],
+    )
+}
+
+// Stands in for the Candy Guard `mint_v2` instruction of the default guard set: the payer pays the
+// `solPayment` lamports of the candy guard to the destination following the payer
+fn candy_guard_stub(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (discriminator, args) = data.split_at(8);
+    assert_eq!(discriminator, CANDY_GUARD_MINT_V2_DISCRIMINATOR);
+    let (_mint_args, label) = <(Vec<u8>, Option<String>)>::try_from_slice(args)?;
+    assert_eq!(label, None);
+
+    let account_info_iter = &mut accounts.iter();
+    let candy_guard = next_account_info(account_info_iter)?;
+    let _candy_machine_program = next_account_info(account_info_iter)?;
+    let _candy_machine = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let lamports_offset = CANDY_GUARD_GUARDS_OFFSET + 8;
+    let price = u64::from_le_bytes(candy_guard.data.borrow()[lamports_offset..lamports_offset + 8].try_into().unwrap());
+
+    invoke(
+        &system_instruction::transfer(payer.key, destination.key, price),
+        &[payer.clone(), destination.clone(), system_program.clone()],
+    )
+}
+
+// `mint_nft(creator_bump)`, followed by the price the stub charges
+fn mint_nft_data(price: u64) -> Vec<u8> {
+    [&CANDY_MACHINE_V2_MINT_NFT_DISCRIMINATOR[..], &borsh::to_vec(&(u8::MAX, price)).unwrap()].concat()
+}
+
+// `mint_v2(mint_args, label)` from the group `label`, `None` for the default guard set
+fn mint_v2_data(label: Option<&str>) -> Vec<u8> {
+    let args = (Vec::<u8>::new(), label.map(str::to_string));
+
+    [&CANDY_GUARD_MINT_V2_DISCRIMINATOR[..], &borsh::to_vec(&args).unwrap()].concat()
+}
+
+struct Mint {
+    buyer: Keypair,
+    affiliate: Pubkey,
+    project_owner: Pubkey,
+    candy_machine_id: Pubkey,
+    wallet: Keypair,
+}
+
+impl Mint {
+    fn instruction(&self, payment_wallet: &Pubkey, candy_machine_program: &Pubkey, price: u64) -> solana_program::instruction::Instruction {
+        self.instruction_with_data(payment_wallet, candy_machine_program, mint_nft_data(price))
+    }
+
+    fn instruction_with_data(&self, payment_wallet: &Pubkey, candy_machine_program: &Pubkey, mint_data: Vec<u8>) -> solana_program::instruction::Instruction {
+        mint_with_referral_instruction(
+            &self.buyer.pubkey(),
+            &self.affiliate,
+            &self.project_owner,
+            &self.candy_machine_id,
+            payment_wallet,
+            candy_machine_program,
+            vec![
+                AccountMeta::new(self.candy_machine_id, false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new(self.buyer.pubkey(), true),
+                AccountMeta::new(self.wallet.pubkey(), false),
+                AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            ],
+            mint_data,
+        )
+    }
+}
+
+// Adds a Candy Machine v2 with the stub program, a project for it with a 10% fee and an affiliate
+fn add_project_with_candy_machine(program_test: &mut ProgramTest) -> Mint {
+    add_project_with_candy_machine_of_length(program_test, AffiliateAccountState::LENGTH)
+}
+
+fn add_project_with_candy_machine_of_length(program_test: &mut ProgramTest, affiliate_account_length: usize) -> Mint {
+    program_test.add_program("candy_machine_v2_stub", CANDY_MACHINE_V2_PROGRAM_ID, processor!(candy_machine_v2_stub));
+
+    let buyer = add_wallet(program_test, 5);
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let wallet = Keypair::new();
+    let candy_machine_id = add_candy_machine_v2(program_test, &project_owner, &wallet.pubkey());
+
+    add_project_account(program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let mut affiliate_data = borsh::to_vec(&affiliate_state(&affiliate, &project_owner, &candy_machine_id)).unwrap();
+    affiliate_data.truncate(affiliate_account_length);
+    add_program_account(
+        program_test,
+        affiliate_pda(&affiliate, &project_owner, &candy_machine_id),
+        affiliate_data,
+        affiliate_account_length,
+    );
+
+    Mint { buyer, affiliate, project_owner, candy_machine_id, wallet }
+}
+
+struct GuardedMint {
+    mint: Mint,
+    candy_guard: Pubkey,
+}
+
+impl GuardedMint {
+    fn instruction(&self, payment_wallet: &Pubkey, mint_accounts: Vec<AccountMeta>) -> solana_program::instruction::Instruction {
+        self.instruction_with_data(payment_wallet, mint_accounts, mint_v2_data(None))
+    }
+
+    fn instruction_with_data(
+        &self,
+        payment_wallet: &Pubkey,
+        mint_accounts: Vec<AccountMeta>,
+        mint_data: Vec<u8>,
+    ) -> solana_program::instruction::Instruction {
+        mint_with_referral_instruction(
+            &self.mint.buyer.pubkey(),
+            &self.mint.affiliate,
+            &self.mint.project_owner,
+            &self.mint.candy_machine_id,
+            payment_wallet,
+            &CANDY_GUARD_PROGRAM_ID,
+            mint_accounts,
+            mint_data,
+        )
+    }
+
+    fn mint_accounts(&self, destination: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.candy_guard, false),
+            AccountMeta::new_readonly(CANDY_MACHINE_V3_PROGRAM_ID, false),
+            AccountMeta::new(self.mint.candy_machine_id, false),
+            AccountMeta::new(self.mint.buyer.pubkey(), true),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+        ]
+    }
+}
+
+// Adds a Candy Machine v3 minting through a Candy Guard stub paying 1 SOL to a destination other
+// than the project owner, a project for it with a 10% fee and an affiliate
+fn add_project_with_guarded_candy_machine(program_test: &mut ProgramTest) -> GuardedMint {
+    program_test.add_program("candy_guard_stub", CANDY_GUARD_PROGRAM_ID, processor!(candy_guard_stub));
+
+    let buyer = add_wallet(program_test, 5);
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let wallet = Keypair::new();
+    let (candy_machine_id, candy_guard) = add_candy_machine_v3_with_guard(
+        program_test,
+        &project_owner,
+        LAMPORTS_PER_SOL,
+        &wallet.pubkey(),
+    );
+
+    add_project_account(program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    add_program_account(
+        program_test,
+        affiliate_pda(&affiliate, &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate, &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+
+    GuardedMint { mint: Mint { buyer, affiliate, project_owner, candy_machine_id, wallet }, candy_guard }
+}
+
+#[tokio::test]
+async fn mint_with_referral_pays_affiliate_commission_out_of_price() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&mint.affiliate, &mint.project_owner, &mint.candy_machine_id);
+    let affiliate_lamports = get_balance(&mut context, &affiliate_address).await;
+
+    let events = process_with_events(
+        &mut context,
+        &[mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL)],
+        &[&mint.buyer, &mint.wallet],
+    ).await;
+
+    // The commission comes out of the price, the buyer pays the price only
+    assert_eq!(get_balance(&mut context, &mint.wallet.pubkey()).await, LAMPORTS_PER_SOL - LAMPORTS_PER_SOL / 10);
+    assert_eq!(get_balance(&mut context, &affiliate_address).await, affiliate_lamports + LAMPORTS_PER_SOL / 10);
+    assert_eq!(get_balance(&mut context, &mint.buyer.pubkey()).await, 5 * LAMPORTS_PER_SOL - LAMPORTS_PER_SOL);
+    assert!(matches!(
+        events.last(),
+        Some(CandyMachineAffiliatesEvent::MintReferred(referred))
+            if referred.affiliate_account == affiliate_address
+                && referred.buyer_pubkey == mint.buyer.pubkey()
+                && referred.price_lamports == LAMPORTS_PER_SOL
+                && referred.commission_lamports == LAMPORTS_PER_SOL / 10
+    ));
+
+    let state = get_affiliate_state(&mut context, &affiliate_address).await;
+    assert_eq!(state.data.mint_count, 1);
+    assert_eq!(state.data.referred_volume_lamports, LAMPORTS_PER_SOL);
+    assert_eq!(state.data.total_commission_lamports, LAMPORTS_PER_SOL / 10);
+    assert!(state.data.last_activity_at > 0);
+
+    let project_state = get_project_state(&mut context, &project_pda(&mint.project_owner, &mint.candy_machine_id)).await;
+    assert_eq!(project_state.data.total_mint_count, 1);
+    assert_eq!(project_state.data.total_referred_volume_lamports, LAMPORTS_PER_SOL);
+    assert_eq!(project_state.data.total_commission_lamports, LAMPORTS_PER_SOL / 10);
+
+    let protocol_stats_state = get_protocol_stats_state(&mut context).await;
+    assert_eq!(protocol_stats_state.data.total_referred_volume_lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn mint_with_referral_credits_affiliate_profile() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let project_address = project_pda(&mint.project_owner, &mint.candy_machine_id);
+    add_program_account(
+        &mut program_test,
+        affiliate_profile_pda(&mint.affiliate),
+        borsh::to_vec(&AffiliateProfileAccountState {
+            discriminator: AffiliateProfileAccountState::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            data_version: 0,
+            data: AffiliateProfileAccountStateData {
+                affiliate_pubkey: mint.affiliate,
+                total_commission_lamports: LAMPORTS_PER_SOL,
+                project_accounts: vec![project_address],
+            },
+        }).unwrap(),
+        AffiliateProfileAccountState::length(1),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL)],
+        &[&mint.buyer, &mint.wallet],
+    ).await.unwrap();
+
+    let profile = get_affiliate_profile_state(&mut context, &mint.affiliate).await;
+    assert_eq!(profile.data.total_commission_lamports, LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 10);
+    assert_eq!(profile.data.project_accounts, vec![project_address]);
+}
+
+#[tokio::test]
+async fn mint_with_referral_resizes_legacy_affiliate_account() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine_of_length(&mut program_test, AffiliateAccountState::LEGACY_LENGTH);
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&mint.affiliate, &mint.project_owner, &mint.candy_machine_id);
+
+    for _ in 0..2 {
+        process(
+            &mut context,
+            &[mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL)],
+            &[&mint.buyer, &mint.wallet],
+        ).await.unwrap();
+    }
+
+    let account = context.banks_client.get_account(affiliate_address).await.unwrap().unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let state = get_affiliate_state(&mut context, &affiliate_address).await;
+
+    assert_eq!(account.data.len(), AffiliateAccountState::LENGTH);
+    assert_eq!(account.lamports, rent.minimum_balance(AffiliateAccountState::LENGTH) + 2 * LAMPORTS_PER_SOL / 10);
+    assert_eq!(state.data.affiliate_pubkey, mint.affiliate);
+    assert_eq!(state.data.mint_count, 2);
+    assert_eq!(state.data.total_commission_lamports, 2 * LAMPORTS_PER_SOL / 10);
+}
+
+#[tokio::test]
+async fn mint_with_referral_without_price_credits_nothing() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&mint.affiliate, &mint.project_owner, &mint.candy_machine_id);
+    let affiliate_lamports = get_balance(&mut context, &affiliate_address).await;
+
+    process(
+        &mut context,
+        &[mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, 0)],
+        &[&mint.buyer, &mint.wallet],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &affiliate_address).await, affiliate_lamports);
+}
+
+#[tokio::test]
+async fn mint_with_referral_rejects_other_payment_wallet() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    // Paying the price to the buyer would let the commission be measured on a refund
+    let result = process(
+        &mut context,
+        &[mint.instruction(&mint.buyer.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL)],
+        &[&mint.buyer],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPaymentWallet);
+}
+
+#[tokio::test]
+async fn mint_with_referral_rejects_other_candy_machine_program() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[mint.instruction(&mint.wallet.pubkey(), &CANDY_GUARD_PROGRAM_ID, LAMPORTS_PER_SOL)],
+        &[&mint.buyer, &mint.wallet],
+    ).await;
+
+    assert_instruction_error(result, InstructionError::IncorrectProgramId);
+}
+
+#[tokio::test]
+async fn mint_with_referral_rejects_candy_machine_of_other_project() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let other_candy_machine_id = add_candy_machine_v2(&mut program_test, &mint.project_owner, &mint.wallet.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL);
+    instruction.accounts[3].pubkey = other_candy_machine_id;
+
+    let result = process(&mut context, &[instruction], &[&mint.buyer, &mint.wallet]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidCandyMachineAccount);
+}
+
+#[tokio::test]
+async fn mint_with_referral_requires_payment_wallet_signature() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL);
+    instruction.accounts[4].is_signer = false;
+
+    let result = process(&mut context, &[instruction], &[&mint.buyer]).await;
+
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+}
+
+#[tokio::test]
+async fn mint_with_referral_rejects_mint_from_other_candy_machine() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let other_candy_machine_id = add_candy_machine_v2(&mut program_test, &mint.project_owner, &mint.wallet.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    // The project's candy machine is passed, but the forwarded mint instruction mints from another one
+    let mut instruction = mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL);
+    instruction.accounts[9].pubkey = other_candy_machine_id;
+
+    let result = process(&mut context, &[instruction], &[&mint.buyer, &mint.wallet]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidMintInstruction);
+}
+
+#[tokio::test]
+async fn mint_with_referral_rejects_other_mint_instruction() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[mint.instruction_with_data(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL.to_le_bytes().to_vec())],
+        &[&mint.buyer, &mint.wallet],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidMintInstruction);
+}
+
+#[tokio::test]
+async fn mint_with_referral_through_candy_guard_pays_commission_out_of_sol_payment() {
+    let mut program_test = program_test();
+    let guarded = add_project_with_guarded_candy_machine(&mut program_test);
+    let mint = &guarded.mint;
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&mint.affiliate, &mint.project_owner, &mint.candy_machine_id);
+    let affiliate_lamports = get_balance(&mut context, &affiliate_address).await;
+
+    process(
+        &mut context,
+        &[guarded.instruction(&mint.wallet.pubkey(), guarded.mint_accounts(&mint.wallet.pubkey()))],
+        &[&mint.buyer, &mint.wallet],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &mint.wallet.pubkey()).await, LAMPORTS_PER_SOL - LAMPORTS_PER_SOL / 10);
+    assert_eq!(get_balance(&mut context, &affiliate_address).await, affiliate_lamports + LAMPORTS_PER_SOL / 10);
+    assert_eq!(get_balance(&mut context, &mint.buyer.pubkey()).await, 5 * LAMPORTS_PER_SOL - LAMPORTS_PER_SOL);
+
+    let state = get_affiliate_state(&mut context, &affiliate_address).await;
+    assert_eq!(state.data.mint_count, 1);
+    assert_eq!(state.data.referred_volume_lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn mint_with_referral_through_candy_guard_rejects_other_payment_wallet() {
+    let mut program_test = program_test();
+    let guarded = add_project_with_guarded_candy_machine(&mut program_test);
+    let mint = &guarded.mint;
+    let other_wallet = Keypair::new();
+    let mut context = program_test.start_with_context().await;
+
+    // The guard pays its configured destination, not the wallet given
+    let result = process(
+        &mut context,
+        &[guarded.instruction(&other_wallet.pubkey(), guarded.mint_accounts(&other_wallet.pubkey()))],
+        &[&mint.buyer, &other_wallet],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPaymentWallet);
+}
+
+#[tokio::test]
+async fn mint_with_referral_through_candy_guard_rejects_other_candy_guard() {
+    let mut program_test = program_test();
+    let guarded = add_project_with_guarded_candy_machine(&mut program_test);
+    let mint = &guarded.mint;
+    let (_, other_candy_guard) = add_candy_machine_v3_with_guard(
+        &mut program_test,
+        &mint.project_owner,
+        0,
+        &mint.wallet.pubkey(),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let mut mint_accounts = guarded.mint_accounts(&mint.wallet.pubkey());
+    mint_accounts[0].pubkey = other_candy_guard;
+
+    let result = process(
+        &mut context,
+        &[guarded.instruction(&mint.wallet.pubkey(), mint_accounts)],
+        &[&mint.buyer, &mint.wallet],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidMintInstruction);
+}
+
+#[test]
+fn mint_v2_data_has_candy_guard_encoding() {
+    // Discriminator, empty `mint_args` and `None` label
+    assert_eq!(mint_v2_data(None), [&CANDY_GUARD_MINT_V2_DISCRIMINATOR[..], &[0, 0, 0, 0, 0]].concat());
+}
+
+#[tokio::test]
+async fn mint_with_referral_through_candy_guard_rejects_guard_groups() {
+    let mut program_test = program_test();
+    let guarded = add_project_with_guarded_candy_machine(&mut program_test);
+    let mint = &guarded.mint;
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[guarded.instruction_with_data(
+            &mint.wallet.pubkey(),
+            guarded.mint_accounts(&mint.wallet.pubkey()),
+            mint_v2_data(Some("public")),
+        )],
+        &[&mint.buyer, &mint.wallet],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidMintInstruction);
+}
+
+#[tokio::test]
+async fn mint_with_referral_rounds_commission_down() {
+    let mut program_test = program_test();
+    let mint = add_project_with_candy_machine(&mut program_test);
+    program_test.add_account(
+        mint.wallet.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &solana_program::system_program::ID),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let affiliate_address = affiliate_pda(&mint.affiliate, &mint.project_owner, &mint.candy_machine_id);
+    let affiliate_lamports = get_balance(&mut context, &affiliate_address).await;
+
+    // 10% of 19 lamports is 1.9
+    process(
+        &mut context,
+        &[mint.instruction(&mint.wallet.pubkey(), &CANDY_MACHINE_V2_PROGRAM_ID, LAMPORTS_PER_SOL + 19)],
+        &[&mint.buyer, &mint.wallet],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &affiliate_address).await, affiliate_lamports + LAMPORTS_PER_SOL / 10 + 1);
+}
@@ -0,0 +1,630 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{instruction, CandyMachineAffiliatesError, ProjectAccountState};
+use candy_machine_affiliates::candy_machine::CANDY_MACHINE_V2_PROGRAM_ID;
+use candy_machine_affiliates::projects::state::{MAX_PROJECT_TITLE_LENGTH, PROJECT_ACCOUNT_DATA_VERSION};
+use solana_program::borsh1::try_from_slice_unchecked;
+use solana_program::instruction::InstructionError;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+use common::*;
+
+fn update_project_instruction(
+    signer: &Pubkey,
+    project_owner: &Pubkey,
+    candy_machine_id: &Pubkey,
+    max_affiliate_count: u32,
+    title: &str,
+) -> solana_program::instruction::Instruction {
+    instruction::update_project_v2(
+        &candy_machine_affiliates::id(),
+        signer,
+        &project_pda(project_owner, candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+        20.0,
+        2,
+        max_affiliate_count,
+        title.to_string(),
+    )
+}
+
+fn close_project_instruction(signer: &Pubkey, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> solana_program::instruction::Instruction {
+    instruction::close_project(
+        &candy_machine_affiliates::id(),
+        signer,
+        &project_pda(project_owner, candy_machine_id),
+        &protocol_stats_pda(),
+        &candy_machine_project_pda(candy_machine_id),
+        *project_owner,
+        *candy_machine_id,
+    )
+}
+
+#[tokio::test]
+async fn register_project_initializes_state() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &project_pda(&owner.pubkey(), &candy_machine_id)).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(state.discriminator, ProjectAccountState::DISCRIMINATOR);
+    assert_eq!(state.data.project_owner_pubkey, owner.pubkey());
+    assert_eq!(state.data.candy_machine_id, candy_machine_id);
+    assert_eq!(state.data.affiliate_fee_percentage, 10.0);
+    assert_eq!(state.data.affiliate_target_in_sol, 1);
+    assert_eq!(state.data.max_affiliate_count, 5);
+    assert_eq!(state.data.affiliate_count, 0);
+    assert_eq!(state.data.title, DEFAULT_TITLE);
+    assert_eq!(state.data.created_at, state.data.updated_at);
+}
+
+#[tokio::test]
+async fn register_project_requires_signature() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE);
+    instruction.accounts[0].is_signer = false;
+
+    let result = process(&mut context, &[instruction], &[]).await;
+
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+}
+
+#[tokio::test]
+async fn register_project_rejects_wrong_pda_seeds() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE);
+    instruction.accounts[1].pubkey = project_pda(&owner.pubkey(), &Pubkey::new_unique());
+
+    let result = process(&mut context, &[instruction], &[&owner]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn register_project_rejects_wrong_system_program() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE);
+    instruction.accounts[2].pubkey = Pubkey::new_unique();
+
+    let result = process(&mut context, &[instruction], &[&owner]).await;
+
+    assert_instruction_error(result, InstructionError::IncorrectProgramId);
+}
+
+#[tokio::test]
+async fn register_project_title_length_boundary() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_ids = [
+        add_candy_machine(&mut program_test, &owner.pubkey()),
+        add_candy_machine(&mut program_test, &owner.pubkey()),
+    ];
+    let mut context = program_test.start_with_context().await;
+
+    let max_title = "a".repeat(MAX_PROJECT_TITLE_LENGTH);
+    let too_long_title = "a".repeat(MAX_PROJECT_TITLE_LENGTH + 1);
+
+    let result = process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_ids[0], 1, 5, &too_long_title)],
+        &[&owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectTitleTooLong);
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_ids[1], 1, 5, &max_title)],
+        &[&owner],
+    ).await.unwrap();
+}
+
+#[tokio::test]
+async fn register_project_accepts_multibyte_title_at_max_length() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let title = "🍬".repeat(MAX_PROJECT_TITLE_LENGTH);
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, &title)],
+        &[&owner],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &project_pda(&owner.pubkey(), &candy_machine_id)).await;
+
+    assert_eq!(state.data.title, title);
+}
+
+#[tokio::test]
+async fn register_project_accepts_max_affiliate_count_above_u8() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 10_000, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &project_pda(&owner.pubkey(), &candy_machine_id)).await;
+
+    assert_eq!(state.data_version, PROJECT_ACCOUNT_DATA_VERSION);
+    assert_eq!(state.data.max_affiliate_count, 10_000);
+}
+
+#[tokio::test]
+async fn register_project_accepts_u8_max_affiliate_count_instruction() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[instruction::register_project(
+            &candy_machine_affiliates::id(),
+            &owner.pubkey(),
+            &project_pda(&owner.pubkey(), &candy_machine_id),
+            &protocol_stats_pda(),
+            &candy_machine_project_pda(&candy_machine_id),
+            candy_machine_id,
+            10.0,
+            1,
+            u8::MAX,
+            DEFAULT_TITLE.to_string(),
+        )],
+        &[&owner],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &project_pda(&owner.pubkey(), &candy_machine_id)).await;
+
+    assert_eq!(state.data.max_affiliate_count, u8::MAX as u32);
+}
+
+#[tokio::test]
+async fn register_project_rejects_zero_max_affiliate_count() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 0, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+
+    // Reported as `ProjectTitleTooLong`, like the deployed program does
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectTitleTooLong);
+}
+
+#[tokio::test]
+async fn register_project_twice_fails() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, "Another title")],
+        &[&owner],
+    ).await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn register_project_accepts_candy_machine_v2() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine_of(&mut program_test, &CANDY_MACHINE_V2_PROGRAM_ID, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+
+    assert!(get_project_state(&mut context, &project_pda(&owner.pubkey(), &candy_machine_id)).await.is_initialized);
+}
+
+#[tokio::test]
+async fn register_project_requires_candy_machine_authority() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_ids = [
+        add_candy_machine(&mut program_test, &Pubkey::new_unique()),
+        add_candy_machine_of(&mut program_test, &CANDY_MACHINE_V2_PROGRAM_ID, &Pubkey::new_unique()),
+    ];
+    let mut context = program_test.start_with_context().await;
+
+    for candy_machine_id in candy_machine_ids {
+        let result = process(
+            &mut context,
+            &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+            &[&owner],
+        ).await;
+
+        assert_program_error(result, CandyMachineAffiliatesError::InvalidCandyMachineAuthority);
+    }
+}
+
+#[tokio::test]
+async fn register_project_rejects_accounts_not_owned_by_candy_machine_programs() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine_of(&mut program_test, &Pubkey::new_unique(), &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+
+    assert_instruction_error(result, InstructionError::IllegalOwner);
+
+    // Wallets and unfunded addresses are owned by the system program
+    let result = process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &Pubkey::new_unique(), 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+
+    assert_instruction_error(result, InstructionError::IllegalOwner);
+}
+
+#[tokio::test]
+async fn register_project_rejects_invalid_candy_machine_account() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let other_candy_machine = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE);
+    instruction.accounts[3].pubkey = other_candy_machine;
+
+    let result = process(&mut context, &[instruction], &[&owner]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidCandyMachineAccount);
+
+    let mut account = context.banks_client.get_account(candy_machine_id).await.unwrap().unwrap();
+    account.data[0] ^= 1;
+    context.set_account(&candy_machine_id, &account.into());
+
+    let result = process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidCandyMachineAccount);
+}
+
+#[tokio::test]
+async fn update_project_by_admin() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let address = add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 3));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[update_project_instruction(&admin.pubkey(), &owner, &candy_machine_id, 10, "Updated title")],
+        &[&admin],
+    ).await.unwrap();
+
+    let state = get_project_state(&mut context, &address).await;
+
+    assert_eq!(state.data.affiliate_fee_percentage, 20.0);
+    assert_eq!(state.data.affiliate_target_in_sol, 2);
+    assert_eq!(state.data.max_affiliate_count, 10);
+    assert_eq!(state.data.affiliate_count, 3);
+    assert_eq!(state.data.title, "Updated title");
+    assert!(state.data.updated_at >= state.data.created_at);
+}
+
+#[test]
+fn data_version_0_projects_decode_despite_bytes_left_after_them() {
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let data = project_data_v0_with_shortened_title(&owner, &candy_machine_id, 5, 3);
+
+    // Deployed projects are 316 bytes, and the end of the longer title follows `updated_at`
+    assert_eq!(data.len(), 316);
+    let end = borsh::to_vec(&project_state_v0(&owner, &candy_machine_id, 5, 3)).unwrap().len();
+    assert_ne!(data[end], 0);
+
+    let state = ProjectAccountState::unpack_account_data(&data).unwrap();
+    assert_eq!(state.data_version, PROJECT_ACCOUNT_DATA_VERSION);
+    assert_eq!(state.data.project_owner_pubkey, owner);
+    assert_eq!(state.data.max_affiliate_count, 5);
+    assert_eq!(state.data.affiliate_count, 3);
+    assert_eq!(state.data.peak_affiliate_count, 3);
+    assert_eq!(state.data.title, DEFAULT_TITLE);
+    assert_eq!(state.data.gating_mint, None);
+    assert_eq!(state.data.gating_min_amount, 0);
+    assert_eq!(state.data.total_referred_volume_lamports, 0);
+}
+
+#[tokio::test]
+async fn update_project_migrates_data_version_0_projects() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let address = project_pda(&owner, &candy_machine_id);
+    add_program_account(
+        &mut program_test,
+        address,
+        project_data_v0_with_shortened_title(&owner, &candy_machine_id, 5, 3),
+        ProjectAccountState::V0_LENGTH,
+    );
+    let admin = admin();
+    program_test.add_account(
+        admin.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &solana_program::system_program::ID),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[update_project_instruction(&admin.pubkey(), &owner, &candy_machine_id, 1_000, DEFAULT_TITLE)],
+        &[&admin],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ProjectAccountState::LENGTH);
+
+    let state = try_from_slice_unchecked::<ProjectAccountState>(&account.data).unwrap();
+    assert_eq!(state.data_version, PROJECT_ACCOUNT_DATA_VERSION);
+    assert_eq!(state.data.max_affiliate_count, 1_000);
+    assert_eq!(state.data.affiliate_count, 3);
+    assert_eq!(state.data.title, DEFAULT_TITLE);
+    assert_eq!(state.data.gating_mint, None);
+    assert_eq!(state.data.total_mint_count, 0);
+    assert_eq!(state.data.affiliate_index_length, 0);
+}
+
+#[tokio::test]
+async fn update_project_rejects_non_admin() {
+    let mut program_test = program_test();
+    let owner = Keypair::new();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&owner.pubkey(), &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[update_project_instruction(&owner.pubkey(), &owner.pubkey(), &candy_machine_id, 10, DEFAULT_TITLE)],
+        &[&owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ActionNotAllowed);
+}
+
+#[tokio::test]
+async fn update_project_rejects_wrong_pda_seeds() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = update_project_instruction(&admin.pubkey(), &owner, &Pubkey::new_unique(), 10, DEFAULT_TITLE);
+    instruction.accounts[1].pubkey = project_pda(&owner, &candy_machine_id);
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn update_project_rejects_invalid_data() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[update_project_instruction(&admin.pubkey(), &owner, &candy_machine_id, 10, &"a".repeat(MAX_PROJECT_TITLE_LENGTH + 1))],
+        &[&admin],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectTitleTooLong);
+
+    let result = process(
+        &mut context,
+        &[update_project_instruction(&admin.pubkey(), &owner, &candy_machine_id, 0, DEFAULT_TITLE)],
+        &[&admin],
+    ).await;
+
+    // Reported as `ProjectTitleTooLong`, like the deployed program does
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectTitleTooLong);
+}
+
+#[tokio::test]
+async fn update_project_max_affiliate_count_boundary() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let address = add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 3));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[update_project_instruction(&admin.pubkey(), &owner, &candy_machine_id, 2, DEFAULT_TITLE)],
+        &[&admin],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ProjectAffiliateCountLargerThanNewMaxAffiliateCount);
+
+    process(
+        &mut context,
+        &[update_project_instruction(&admin.pubkey(), &owner, &candy_machine_id, 3, DEFAULT_TITLE)],
+        &[&admin],
+    ).await.unwrap();
+
+    assert_eq!(get_project_state(&mut context, &address).await.data.max_affiliate_count, 3);
+}
+
+#[tokio::test]
+async fn close_project_refunds_owner() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let address = add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let project_lamports = get_balance(&mut context, &address).await;
+
+    process(
+        &mut context,
+        &[close_project_instruction(&admin.pubkey(), &owner, &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    assert_eq!(get_balance(&mut context, &owner).await, project_lamports);
+    assert!(context.banks_client.get_account(address).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn close_project_rejects_non_admin() {
+    let mut program_test = program_test();
+    let owner = Keypair::new();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&owner.pubkey(), &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[close_project_instruction(&owner.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ActionNotAllowed);
+}
+
+#[tokio::test]
+async fn close_project_rejects_wrong_pda_seeds() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let other_candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    add_project_account(&mut program_test, &project_state(&owner, &other_candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = close_project_instruction(&admin.pubkey(), &owner, &candy_machine_id);
+    instruction.accounts[1].pubkey = project_pda(&owner, &other_candy_machine_id);
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn close_project_rejects_wrong_owner_account() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let address = add_project_account(&mut program_test, &project_state(&owner, &candy_machine_id, 1, 5, 0));
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = close_project_instruction(&admin.pubkey(), &owner, &candy_machine_id);
+    instruction.accounts[2].pubkey = admin.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::IncorrectProjectOwnerAccount);
+
+    let mut instruction = close_project_instruction(&admin.pubkey(), &owner, &candy_machine_id);
+    instruction.accounts[2].pubkey = address;
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::IncorrectProjectOwnerAccount);
+}
+
+#[tokio::test]
+async fn close_project_rejects_account_not_owned_by_program() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 1);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = close_project_instruction(&admin.pubkey(), &owner.pubkey(), &Pubkey::new_unique());
+    instruction.accounts[1].pubkey = owner.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&admin]).await;
+
+    assert_instruction_error(result, InstructionError::IllegalOwner);
+}
+
+#[tokio::test]
+async fn close_project_rejects_uninitialized_account() {
+    let mut program_test = program_test();
+    let owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_program_account(&mut program_test, project_pda(&owner, &candy_machine_id), vec![], ProjectAccountState::LENGTH);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[close_project_instruction(&admin.pubkey(), &owner, &candy_machine_id)],
+        &[&admin],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::UninitializedAccount);
+}
@@ -0,0 +1,314 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    AffiliateAccountState,
+    CandyMachineAffiliatesError,
+    PromoCodeDiscount,
+    PromoCodeUsageAccountState,
+};
+use solana_program::borsh1::try_from_slice_unchecked;
+use solana_program::clock::Clock;
+use solana_program::instruction::InstructionError;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use common::*;
+
+fn add_project(program_test: &mut ProgramTest) -> (Keypair, Pubkey) {
+    let project_owner = add_wallet(program_test, 1);
+    let candy_machine_id = Pubkey::new_unique();
+
+    add_project_account(program_test, &project_state(&project_owner.pubkey(), &candy_machine_id, 1, 5, 0));
+
+    (project_owner, candy_machine_id)
+}
+
+async fn set_unix_timestamp(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+#[tokio::test]
+async fn create_promo_code_stores_settings() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let affiliate = Pubkey::new_unique();
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate, &project_owner.pubkey(), &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate, &project_owner.pubkey(), &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let settings = PromoCodeSettings {
+        affiliate: Some(affiliate),
+        discount_basis_points: 1_500,
+        max_uses: 100,
+        max_uses_per_wallet: 2,
+        starts_at: 10,
+        ends_at: 20,
+    };
+
+    process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &settings)],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let state = get_promo_code_state(&mut context, &promo_code_pda(&project_owner.pubkey(), &candy_machine_id, "summer")).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(state.data.code, "summer");
+    assert_eq!(state.data.project_owner_pubkey, project_owner.pubkey());
+    assert_eq!(state.data.candy_machine_id, candy_machine_id);
+    assert_eq!(state.data.affiliate_account, Some(affiliate_pda(&affiliate, &project_owner.pubkey(), &candy_machine_id)));
+    assert_eq!(state.data.discount_basis_points, 1_500);
+    assert_eq!(state.data.max_uses, 100);
+    assert_eq!(state.data.max_uses_per_wallet, 2);
+    assert_eq!(state.data.use_count, 0);
+    assert_eq!((state.data.starts_at, state.data.ends_at), (10, 20));
+}
+
+#[tokio::test]
+async fn create_promo_code_requires_project_owner() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let other_wallet = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &PromoCodeSettings::default());
+    instruction.accounts[0].pubkey = other_wallet.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&other_wallet]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
+
+#[tokio::test]
+async fn create_promo_code_rejects_invalid_settings() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let invalid_settings = [
+        (PromoCodeSettings { discount_basis_points: 0, ..Default::default() }, CandyMachineAffiliatesError::InvalidPromoCodeDiscount),
+        (PromoCodeSettings { discount_basis_points: 10_001, ..Default::default() }, CandyMachineAffiliatesError::InvalidPromoCodeDiscount),
+        (PromoCodeSettings { starts_at: 20, ends_at: 20, ..Default::default() }, CandyMachineAffiliatesError::InvalidPromoCodePeriod),
+    ];
+
+    for (settings, expected) in invalid_settings {
+        let result = process(
+            &mut context,
+            &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &settings)],
+            &[&project_owner],
+        ).await;
+
+        assert_program_error(result, expected);
+    }
+
+    let result = process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "no", &PromoCodeSettings::default())],
+        &[&project_owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPromoCode);
+}
+
+#[tokio::test]
+async fn create_promo_code_rejects_existing_code() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &PromoCodeSettings::default())],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let settings = PromoCodeSettings { discount_basis_points: 5_000, ..Default::default() };
+    let result = process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &settings)],
+        &[&project_owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::PromoCodeAlreadyExists);
+}
+
+#[tokio::test]
+async fn consume_promo_code_records_usage_and_returns_discount() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &PromoCodeSettings::default())],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let buyer = context.payer.pubkey();
+    let instruction = consume_promo_code_instruction(&buyer, &project_owner.pubkey(), &candy_machine_id, "summer");
+
+    let discount: PromoCodeDiscount = simulate_signed_view(&mut context, instruction.clone(), &[&project_owner]).await.unwrap();
+
+    assert_eq!(discount, PromoCodeDiscount {
+        discount_basis_points: 1_000,
+        affiliate_account: None,
+        use_count: 1,
+        wallet_use_count: 1,
+    });
+
+    for _ in 0..2 {
+        process(&mut context, std::slice::from_ref(&instruction), &[&project_owner]).await.unwrap();
+    }
+
+    let promo_code = promo_code_pda(&project_owner.pubkey(), &candy_machine_id, "summer");
+    assert_eq!(get_promo_code_state(&mut context, &promo_code).await.data.use_count, 2);
+
+    let usage_account = context.banks_client.get_account(promo_code_usage_pda(&promo_code, &buyer)).await.unwrap().unwrap();
+    let usage = try_from_slice_unchecked::<PromoCodeUsageAccountState>(&usage_account.data).unwrap();
+
+    assert_eq!(usage.data.promo_code_account, promo_code);
+    assert_eq!(usage.data.buyer_pubkey, buyer);
+    assert_eq!(usage.data.use_count, 2);
+}
+
+#[tokio::test]
+async fn consume_promo_code_enforces_usage_limits() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let first_buyer = add_wallet(&mut program_test, 1);
+    let second_buyer = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let settings = PromoCodeSettings { max_uses: 2, max_uses_per_wallet: 1, ..Default::default() };
+
+    process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &settings)],
+        &[&project_owner],
+    ).await.unwrap();
+
+    process(
+        &mut context,
+        &[consume_promo_code_instruction(&first_buyer.pubkey(), &project_owner.pubkey(), &candy_machine_id, "summer")],
+        &[&first_buyer, &project_owner],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[consume_promo_code_instruction(&first_buyer.pubkey(), &project_owner.pubkey(), &candy_machine_id, "summer")],
+        &[&first_buyer, &project_owner],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::PromoCodeWalletLimitReached);
+
+    process(
+        &mut context,
+        &[consume_promo_code_instruction(&second_buyer.pubkey(), &project_owner.pubkey(), &candy_machine_id, "summer")],
+        &[&second_buyer, &project_owner],
+    ).await.unwrap();
+
+    let buyer = context.payer.pubkey();
+    let result = simulate_signed_view::<PromoCodeDiscount>(
+        &mut context,
+        consume_promo_code_instruction(&buyer, &project_owner.pubkey(), &candy_machine_id, "summer"),
+        &[&project_owner],
+    ).await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::Custom(CandyMachineAffiliatesError::PromoCodeMaxUsesReached as u32)),
+    );
+}
+
+#[tokio::test]
+async fn consume_promo_code_enforces_period() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let settings = PromoCodeSettings { starts_at: 1_000, ends_at: 2_000, ..Default::default() };
+
+    process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &settings)],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let buyer = context.payer.pubkey();
+    let instruction = consume_promo_code_instruction(&buyer, &project_owner.pubkey(), &candy_machine_id, "summer");
+
+    for (unix_timestamp, is_active) in [(999, false), (1_000, true), (1_999, true), (2_000, false)] {
+        set_unix_timestamp(&mut context, unix_timestamp).await;
+
+        let result = simulate_signed_view::<PromoCodeDiscount>(&mut context, instruction.clone(), &[&project_owner]).await;
+
+        if is_active {
+            assert!(result.is_ok(), "{}", unix_timestamp);
+        } else {
+            assert_eq!(
+                result.unwrap_err(),
+                TransactionError::InstructionError(0, InstructionError::Custom(CandyMachineAffiliatesError::PromoCodeNotActive as u32)),
+                "{}",
+                unix_timestamp,
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn consume_promo_code_rejects_unknown_code() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let buyer = context.payer.pubkey();
+    let result = process(
+        &mut context,
+        &[consume_promo_code_instruction(&buyer, &project_owner.pubkey(), &candy_machine_id, "summer")],
+        &[&project_owner],
+    ).await;
+
+    assert_instruction_error(result, InstructionError::IllegalOwner);
+}
+
+#[tokio::test]
+async fn consume_promo_code_requires_project_owner_signature() {
+    let mut program_test = program_test();
+    let (project_owner, candy_machine_id) = add_project(&mut program_test);
+    let other_wallet = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[create_promo_code_instruction(&project_owner.pubkey(), &candy_machine_id, "summer", &PromoCodeSettings::default())],
+        &[&project_owner],
+    ).await.unwrap();
+
+    let buyer = context.payer.pubkey();
+    let mut instruction = consume_promo_code_instruction(&buyer, &project_owner.pubkey(), &candy_machine_id, "summer");
+    instruction.accounts[1].is_signer = false;
+
+    let result = process(&mut context, &[instruction], &[]).await;
+
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+
+    let mut instruction = consume_promo_code_instruction(&buyer, &project_owner.pubkey(), &candy_machine_id, "summer");
+    instruction.accounts[1].pubkey = other_wallet.pubkey();
+
+    let result = process(&mut context, &[instruction], &[&other_wallet]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ActionNotAllowed);
+
+    let promo_code = promo_code_pda(&project_owner.pubkey(), &candy_machine_id, "summer");
+    assert_eq!(get_promo_code_state(&mut context, &promo_code).await.data.use_count, 0);
+}
@@ -0,0 +1,397 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    AffiliateAccountState,
+    CandyMachineAffiliatesError,
+};
+use solana_program::instruction::InstructionError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program_test::ProgramTest;
+use solana_sdk::signature::{Keypair, Signer};
+use common::*;
+
+fn add_affiliate_account(program_test: &mut ProgramTest, project_owner: &Pubkey, candy_machine_id: &Pubkey) -> Keypair {
+    let affiliate = add_wallet(program_test, 1);
+
+    add_program_account(
+        program_test,
+        affiliate_pda(&affiliate.pubkey(), project_owner, candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), project_owner, candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+
+    affiliate
+}
+
+#[tokio::test]
+async fn claim_referral_code_stores_normalized_code() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, "  SolPay_42 ", None)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let address = referral_code_pda(&project_owner, &candy_machine_id, "solpay_42");
+    let state = get_referral_code_state(&mut context, &address).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(state.data.code, "solpay_42");
+    assert_eq!(state.data.affiliate_account, affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id));
+    assert_eq!(state.data.affiliate_pubkey, affiliate.pubkey());
+    assert_eq!(state.data.project_owner_pubkey, project_owner);
+    assert_eq!(state.data.candy_machine_id, candy_machine_id);
+
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    assert_eq!(get_affiliate_state(&mut context, &affiliate_address).await.data.referral_code.as_deref(), Some("solpay_42"));
+}
+
+#[tokio::test]
+async fn claim_referral_code_allows_one_code_per_affiliate() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[claim_referral_code_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, "another", None)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::AffiliateReferralCodeAlreadyClaimed);
+    assert!(context.banks_client.get_account(referral_code_pda(&project_owner, &candy_machine_id, "another")).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn claim_referral_code_resizes_older_affiliate_accounts() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    add_program_account(
+        &mut program_test,
+        affiliate_address,
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LEGACY_LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let account = context.banks_client.get_account(affiliate_address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), AffiliateAccountState::LENGTH);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(AffiliateAccountState::LENGTH));
+    assert_eq!(get_affiliate_state(&mut context, &affiliate_address).await.data.referral_code.as_deref(), Some("solpay"));
+}
+
+#[tokio::test]
+async fn claim_referral_code_rejects_invalid_code() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let mut context = program_test.start_with_context().await;
+
+    for code in ["ab", "solpay!", "a-code-that-is-too-long"] {
+        let result = process(
+            &mut context,
+            &[claim_referral_code_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, code, None)],
+            &[&affiliate],
+        ).await;
+
+        assert_program_error(result, CandyMachineAffiliatesError::InvalidReferralCode);
+    }
+}
+
+#[tokio::test]
+async fn claim_referral_code_is_unique_per_project() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    let other_candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 2));
+    add_project_account(&mut program_test, &project_state(&project_owner, &other_candy_machine_id, 1, 5, 1));
+    let first_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let second_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let other_affiliate = add_affiliate_account(&mut program_test, &project_owner, &other_candy_machine_id);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&first_affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[claim_referral_code_instruction(&second_affiliate.pubkey(), &project_owner, &candy_machine_id, "SOLPAY", None)],
+        &[&second_affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ReferralCodeAlreadyClaimed);
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&other_affiliate.pubkey(), &project_owner, &other_candy_machine_id, "solpay", None)],
+        &[&other_affiliate],
+    ).await.unwrap();
+}
+
+#[tokio::test]
+async fn claim_referral_code_rejects_takeover_from_open_affiliate() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 2));
+    let first_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let second_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&first_affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[claim_referral_code_instruction(
+            &second_affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            "solpay",
+            Some(&first_affiliate.pubkey()),
+        )],
+        &[&second_affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ReferralCodeAlreadyClaimed);
+}
+
+#[tokio::test]
+async fn closing_affiliate_releases_referral_code() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 2));
+    let first_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let second_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&first_affiliate],
+    ).await.unwrap();
+
+    let address = referral_code_pda(&project_owner, &candy_machine_id, "solpay");
+    let code_lamports = get_balance(&mut context, &address).await;
+    let affiliate_lamports = get_balance(&mut context, &affiliate_pda(&first_affiliate.pubkey(), &project_owner, &candy_machine_id)).await;
+    let treasury_lamports = get_balance(&mut context, &treasury()).await;
+
+    process(
+        &mut context,
+        &[close_affiliate_with_referral_code_instruction(
+            &admin.pubkey(),
+            &first_affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            "solpay",
+        )],
+        &[&admin],
+    ).await.unwrap();
+
+    assert!(context.banks_client.get_account(address).await.unwrap().is_none());
+    assert_eq!(get_balance(&mut context, &treasury()).await, treasury_lamports + affiliate_lamports + code_lamports);
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&second_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&second_affiliate],
+    ).await.unwrap();
+
+    assert_eq!(get_referral_code_state(&mut context, &address).await.data.affiliate_pubkey, second_affiliate.pubkey());
+}
+
+#[tokio::test]
+async fn claim_referral_code_takes_over_code_of_closed_affiliate() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 2));
+    let first_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let second_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&first_affiliate],
+    ).await.unwrap();
+
+    // Claimed before codes were recorded on affiliate accounts, closed without releasing it
+    let first_affiliate_address = affiliate_pda(&first_affiliate.pubkey(), &project_owner, &candy_machine_id);
+    let mut account = context.banks_client.get_account(first_affiliate_address).await.unwrap().unwrap();
+    account.data = borsh::to_vec(&affiliate_state(&first_affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap();
+    account.data.resize(AffiliateAccountState::LENGTH, 0);
+    context.set_account(&first_affiliate_address, &account.into());
+
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &first_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[claim_referral_code_instruction(&second_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&second_affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ReferralCodeAlreadyClaimed);
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(
+            &second_affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            "solpay",
+            Some(&first_affiliate.pubkey()),
+        )],
+        &[&second_affiliate],
+    ).await.unwrap();
+
+    let state = get_referral_code_state(&mut context, &referral_code_pda(&project_owner, &candy_machine_id, "solpay")).await;
+
+    assert_eq!(state.data.affiliate_pubkey, second_affiliate.pubkey());
+    assert_eq!(state.data.affiliate_account, affiliate_pda(&second_affiliate.pubkey(), &project_owner, &candy_machine_id));
+}
+
+#[tokio::test]
+async fn close_affiliate_rejects_referral_code_of_another_affiliate() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 2));
+    let first_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let second_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&first_affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[close_affiliate_with_referral_code_instruction(
+            &admin.pubkey(),
+            &second_affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            "solpay",
+        )],
+        &[&admin],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ActionNotAllowed);
+
+    let affiliate_address = affiliate_pda(&second_affiliate.pubkey(), &project_owner, &candy_machine_id);
+    assert!(context.banks_client.get_account(affiliate_address).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn close_affiliate_requires_recorded_referral_code() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 2));
+    let first_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let second_affiliate = add_affiliate_account(&mut program_test, &project_owner, &candy_machine_id);
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&first_affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&first_affiliate],
+    ).await.unwrap();
+    process(
+        &mut context,
+        &[claim_referral_code_instruction(&second_affiliate.pubkey(), &project_owner, &candy_machine_id, "another", None)],
+        &[&second_affiliate],
+    ).await.unwrap();
+
+    let result = process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &first_affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&admin],
+    ).await;
+
+    assert_instruction_error(result, InstructionError::NotEnoughAccountKeys);
+
+    let result = process(
+        &mut context,
+        &[close_affiliate_with_referral_code_instruction(
+            &admin.pubkey(),
+            &first_affiliate.pubkey(),
+            &project_owner,
+            &candy_machine_id,
+            "another",
+        )],
+        &[&admin],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+
+    let affiliate_address = affiliate_pda(&first_affiliate.pubkey(), &project_owner, &candy_machine_id);
+    assert!(context.banks_client.get_account(affiliate_address).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn claim_referral_code_requires_affiliate_account() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[claim_referral_code_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id, "solpay", None)],
+        &[&affiliate],
+    ).await;
+
+    assert_instruction_error(result, InstructionError::IllegalOwner);
+}
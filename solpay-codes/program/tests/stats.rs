@@ -0,0 +1,282 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    instruction,
+    AffiliateAccountState,
+    CandyMachineAffiliatesError,
+    ProtocolStatsAccountState,
+    AFFILIATE_REGISTRATION_FEE,
+};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signer;
+use common::*;
+
+fn initialize_protocol_stats_instruction(admin: &Pubkey) -> Instruction {
+    instruction::initialize_protocol_stats(
+        &candy_machine_affiliates::id(),
+        admin,
+        &protocol_stats_pda(),
+        2,
+        3,
+        4 * AFFILIATE_REGISTRATION_FEE,
+        LAMPORTS_PER_SOL,
+        10 * LAMPORTS_PER_SOL,
+    )
+}
+
+#[tokio::test]
+async fn initialize_protocol_stats_by_admin() {
+    let mut program_test = program_test_without_protocol_stats();
+    let admin = admin();
+    program_test.add_account(
+        admin.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &solana_program::system_program::ID),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[initialize_protocol_stats_instruction(&admin.pubkey())],
+        &[&admin],
+    ).await.unwrap();
+
+    let state = get_protocol_stats_state(&mut context).await;
+
+    assert!(state.is_initialized);
+    assert_eq!(state.discriminator, ProtocolStatsAccountState::DISCRIMINATOR);
+    assert_eq!(state.data.live_project_count, 2);
+    assert_eq!(state.data.live_affiliate_count, 3);
+    assert_eq!(state.data.registration_fees_collected_lamports, 4 * AFFILIATE_REGISTRATION_FEE);
+    assert_eq!(state.data.total_rewards_redeemed_lamports, LAMPORTS_PER_SOL);
+    assert_eq!(state.data.total_referred_volume_lamports, 10 * LAMPORTS_PER_SOL);
+    assert_eq!(state.data.created_at, state.data.updated_at);
+
+    assert_eq!(
+        get_balance(&mut context, &protocol_stats_pda()).await,
+        Rent::default().minimum_balance(ProtocolStatsAccountState::LENGTH),
+    );
+}
+
+#[tokio::test]
+async fn initialize_protocol_stats_rejects_non_admin() {
+    let mut program_test = program_test_without_protocol_stats();
+    let wallet = add_wallet(&mut program_test, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[initialize_protocol_stats_instruction(&wallet.pubkey())],
+        &[&wallet],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::ActionNotAllowed);
+}
+
+#[tokio::test]
+async fn initialize_protocol_stats_twice_fails() {
+    let mut program_test = program_test();
+    let admin = admin();
+    program_test.add_account(
+        admin.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &solana_program::system_program::ID),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let result = process(
+        &mut context,
+        &[initialize_protocol_stats_instruction(&admin.pubkey())],
+        &[&admin],
+    ).await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn register_and_close_update_live_counts() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let affiliate = add_wallet(&mut program_test, 1);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let state = get_protocol_stats_state(&mut context).await;
+    assert_eq!(state.data.live_project_count, 1);
+    assert_eq!(state.data.live_affiliate_count, 1);
+    assert_eq!(state.data.registration_fees_collected_lamports, AFFILIATE_REGISTRATION_FEE);
+    assert!(state.data.updated_at > 0);
+
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&admin],
+    ).await.unwrap();
+
+    process(
+        &mut context,
+        &[instruction::close_project(
+            &candy_machine_affiliates::id(),
+            &admin.pubkey(),
+            &project_pda(&owner.pubkey(), &candy_machine_id),
+            &protocol_stats_pda(),
+            &candy_machine_project_pda(&candy_machine_id),
+            owner.pubkey(),
+            candy_machine_id,
+        )],
+        &[&admin],
+    ).await.unwrap();
+
+    let state = get_protocol_stats_state(&mut context).await;
+    assert_eq!(state.data.live_project_count, 0);
+    assert_eq!(state.data.live_affiliate_count, 0);
+    assert_eq!(state.data.registration_fees_collected_lamports, AFFILIATE_REGISTRATION_FEE);
+}
+
+#[tokio::test]
+async fn redeem_reward_updates_total_rewards_redeemed() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    add_program_account(
+        &mut program_test,
+        affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+        borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap(),
+        AffiliateAccountState::LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let payer = context.payer.pubkey();
+
+    process(
+        &mut context,
+        &[system_instruction::transfer(
+            &payer,
+            &affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+            LAMPORTS_PER_SOL,
+        )],
+        &[],
+    ).await.unwrap();
+
+    process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let state = get_protocol_stats_state(&mut context).await;
+    assert_eq!(state.data.total_rewards_redeemed_lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn instructions_skip_protocol_stats_before_initialization() {
+    let mut program_test = program_test_without_protocol_stats();
+    let owner = add_wallet(&mut program_test, 10);
+    let affiliate = add_wallet(&mut program_test, 1);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE)],
+        &[&owner],
+    ).await.unwrap();
+    process(
+        &mut context,
+        &[register_affiliate_instruction(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&affiliate],
+    ).await.unwrap();
+    process(
+        &mut context,
+        &[close_affiliate_instruction(&admin().pubkey(), &affiliate.pubkey(), &owner.pubkey(), &candy_machine_id)],
+        &[&admin()],
+    ).await.unwrap();
+
+    assert!(context.banks_client.get_account(protocol_stats_pda()).await.unwrap().is_none());
+}
+
+// Passes the program id in place of the stats PDA, as clients skipping the stats update do
+fn without_protocol_stats(mut instruction: Instruction) -> Instruction {
+    for account in instruction.accounts.iter_mut().filter(|account| account.pubkey == protocol_stats_pda()) {
+        *account = AccountMeta::new_readonly(candy_machine_affiliates::id(), false);
+    }
+    instruction
+}
+
+#[tokio::test]
+async fn instructions_without_protocol_stats_account_skip_stats() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let affiliate = add_wallet(&mut program_test, 1);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let admin = admin();
+    let mut context = program_test.start_with_context().await;
+
+    process(
+        &mut context,
+        &[without_protocol_stats(register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE))],
+        &[&owner],
+    ).await.unwrap();
+    process(
+        &mut context,
+        &[without_protocol_stats(register_affiliate_instruction(&affiliate.pubkey(), &owner.pubkey(), &candy_machine_id))],
+        &[&affiliate],
+    ).await.unwrap();
+
+    let state = get_protocol_stats_state(&mut context).await;
+    assert_eq!(state.data.live_project_count, 0);
+    assert_eq!(state.data.live_affiliate_count, 0);
+    assert_eq!(state.data.registration_fees_collected_lamports, 0);
+
+    process(
+        &mut context,
+        &[without_protocol_stats(close_affiliate_instruction(&admin.pubkey(), &affiliate.pubkey(), &owner.pubkey(), &candy_machine_id))],
+        &[&admin],
+    ).await.unwrap();
+
+    let state = get_protocol_stats_state(&mut context).await;
+    assert_eq!(state.data.live_affiliate_count, 0);
+    assert_eq!(state.data.updated_at, 0);
+}
+
+#[tokio::test]
+async fn register_project_rejects_wrong_protocol_stats_account() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test, 10);
+    let candy_machine_id = add_candy_machine(&mut program_test, &owner.pubkey());
+    let other_stats = Pubkey::new_unique();
+    add_program_account(
+        &mut program_test,
+        other_stats,
+        borsh::to_vec(&protocol_stats_state(0, 0)).unwrap(),
+        ProtocolStatsAccountState::LENGTH,
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = register_project_instruction(&owner.pubkey(), &candy_machine_id, 1, 5, DEFAULT_TITLE);
+    instruction.accounts[4].pubkey = other_stats;
+
+    let result = process(&mut context, &[instruction], &[&owner]).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::InvalidPDA);
+}
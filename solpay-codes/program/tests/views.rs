@@ -0,0 +1,184 @@
+#![cfg(feature = "localnet")]
+
+mod common;
+
+use candy_machine_affiliates::{
+    AffiliateAccountState,
+    CandyMachineAffiliatesError,
+    ProjectCapacity,
+    RedeemableAmount,
+    AFFILIATE_REGISTRATION_FEE,
+};
+use solana_program::instruction::InstructionError;
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::TransactionError;
+use common::*;
+
+fn assert_view_error<T: std::fmt::Debug>(result: Result<T, TransactionError>, expected: InstructionError) {
+    assert_eq!(result.unwrap_err(), TransactionError::InstructionError(0, expected));
+}
+
+#[tokio::test]
+async fn get_redeemable_amount_matches_redeem_threshold() {
+    let mut program_test = program_test();
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let affiliate_address = affiliate_pda(&affiliate, &project_owner, &candy_machine_id);
+    let mut affiliate_account_state = affiliate_state(&affiliate, &project_owner, &candy_machine_id);
+    affiliate_account_state.data.total_redeemed_amount_in_sol = 3;
+    add_program_account(&mut program_test, affiliate_address, borsh::to_vec(&affiliate_account_state).unwrap(), AffiliateAccountState::LENGTH);
+    let mut context = program_test.start_with_context().await;
+
+    let rent_lamports = get_balance(&mut context, &affiliate_address).await;
+
+    let amount: RedeemableAmount = simulate_view(
+        &mut context,
+        get_redeemable_amount_instruction(&affiliate, &project_owner, &candy_machine_id),
+    ).await.unwrap();
+
+    assert_eq!(amount, RedeemableAmount {
+        affiliate_balance_lamports: rent_lamports,
+        reward_lamports: LAMPORTS_PER_SOL,
+        redeemable_lamports: 0,
+        total_redeemed_amount_in_sol: 3,
+    });
+
+    let payer = context.payer.pubkey();
+
+    process(
+        &mut context,
+        &[system_instruction::transfer(&payer, &affiliate_address, LAMPORTS_PER_SOL)],
+        &[],
+    ).await.unwrap();
+
+    let amount: RedeemableAmount = simulate_view(
+        &mut context,
+        get_redeemable_amount_instruction(&affiliate, &project_owner, &candy_machine_id),
+    ).await.unwrap();
+
+    assert_eq!(amount.affiliate_balance_lamports, rent_lamports + LAMPORTS_PER_SOL);
+    assert_eq!(amount.redeemable_lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn get_redeemable_amount_keeps_affiliate_account_rent_exempt() {
+    let mut program_test = program_test();
+    let affiliate = add_wallet(&mut program_test, 1);
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let affiliate_address = affiliate_pda(&affiliate.pubkey(), &project_owner, &candy_machine_id);
+    add_program_account(&mut program_test, affiliate_address, borsh::to_vec(&affiliate_state(&affiliate.pubkey(), &project_owner, &candy_machine_id)).unwrap(), AffiliateAccountState::LENGTH);
+    let mut context = program_test.start_with_context().await;
+
+    let payer = context.payer.pubkey();
+
+    process(
+        &mut context,
+        &[system_instruction::transfer(&payer, &affiliate_address, LAMPORTS_PER_SOL - 1)],
+        &[],
+    ).await.unwrap();
+
+    let amount: RedeemableAmount = simulate_view(
+        &mut context,
+        get_redeemable_amount_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id),
+    ).await.unwrap();
+
+    assert!(amount.affiliate_balance_lamports > amount.reward_lamports);
+    assert_eq!(amount.redeemable_lamports, 0);
+
+    let result = process(
+        &mut context,
+        &[redeem_reward_instruction(&affiliate.pubkey(), &project_owner, &candy_machine_id)],
+        &[&affiliate],
+    ).await;
+
+    assert_program_error(result, CandyMachineAffiliatesError::AffiliateAccountBalanceNotEnough);
+}
+
+#[tokio::test]
+async fn get_redeemable_amount_rejects_wrong_pda_seeds() {
+    let mut program_test = program_test();
+    let affiliate = Pubkey::new_unique();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 1));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = get_redeemable_amount_instruction(&affiliate, &project_owner, &candy_machine_id);
+    instruction.accounts[0].pubkey = project_pda(&project_owner, &candy_machine_id);
+
+    let result = simulate_view::<RedeemableAmount>(&mut context, instruction).await;
+
+    assert_view_error(result, InstructionError::Custom(CandyMachineAffiliatesError::InvalidPDA as u32));
+}
+
+#[tokio::test]
+async fn get_redeemable_amount_rejects_missing_affiliate_account() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let result = simulate_view::<RedeemableAmount>(
+        &mut context,
+        get_redeemable_amount_instruction(&Pubkey::new_unique(), &project_owner, &candy_machine_id),
+    ).await;
+
+    assert_view_error(result, InstructionError::IllegalOwner);
+}
+
+#[tokio::test]
+async fn get_project_capacity_reports_remaining_slots() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let open_candy_machine_id = Pubkey::new_unique();
+    let full_candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &open_candy_machine_id, 1, 5, 2));
+    add_project_account(&mut program_test, &project_state(&project_owner, &full_candy_machine_id, 1, 2, 2));
+    let mut context = program_test.start_with_context().await;
+
+    let capacity: ProjectCapacity = simulate_view(
+        &mut context,
+        get_project_capacity_instruction(&project_owner, &open_candy_machine_id),
+    ).await.unwrap();
+
+    assert_eq!(capacity, ProjectCapacity {
+        affiliate_count: 2,
+        max_affiliate_count: 5,
+        remaining_affiliate_slots: 3,
+        is_full: false,
+        registration_fee: AFFILIATE_REGISTRATION_FEE,
+        meets_gating: true,
+    });
+
+    let capacity: ProjectCapacity = simulate_view(
+        &mut context,
+        get_project_capacity_instruction(&project_owner, &full_candy_machine_id),
+    ).await.unwrap();
+
+    assert_eq!(capacity.remaining_affiliate_slots, 0);
+    assert!(capacity.is_full);
+}
+
+#[tokio::test]
+async fn get_project_capacity_rejects_wrong_pda_seeds() {
+    let mut program_test = program_test();
+    let project_owner = Pubkey::new_unique();
+    let candy_machine_id = Pubkey::new_unique();
+    add_project_account(&mut program_test, &project_state(&project_owner, &candy_machine_id, 1, 5, 0));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = get_project_capacity_instruction(&project_owner, &candy_machine_id);
+    instruction.data = get_project_capacity_instruction(&project_owner, &Pubkey::new_unique()).data;
+
+    let result = simulate_view::<ProjectCapacity>(&mut context, instruction).await;
+
+    assert_view_error(result, InstructionError::Custom(CandyMachineAffiliatesError::InvalidPDA as u32));
+}